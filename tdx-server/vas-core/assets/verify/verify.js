@@ -0,0 +1,166 @@
+// Client-side checks for a TDX v4 quote: layout, measurements, agent-key binding and MRTD.
+// Offsets follow the TD report body (TD10ReportBody) after the 48-byte quote header, as in
+// contracts/src/types/TDXStructs.sol.
+"use strict";
+
+const HEADER_LEN = 48;
+const TEE_TYPE_TDX = 0x81;
+const FIELDS = [
+  ["TEE TCB SVN", 0, 16],
+  ["MRSEAM", 16, 48],
+  ["MRSIGNERSEAM", 64, 48],
+  ["TD attributes", 120, 8],
+  ["XFAM", 128, 8],
+  ["MRTD", 136, 48],
+  ["MRCONFIGID", 184, 48],
+  ["MROWNER", 232, 48],
+  ["MROWNERCONFIG", 280, 48],
+  ["RTMR0", 328, 48],
+  ["RTMR1", 376, 48],
+  ["RTMR2", 424, 48],
+  ["RTMR3", 472, 48],
+  ["Report data", 520, 64],
+];
+const REPORT_BODY_LEN = 584;
+const REPORT_DATA = 520;
+// reportData = 32 bytes (unused) || "HYPERLIQUID\0" || agent address
+const PROTOCOL_ID = "48595045524c495155494400";
+const PROTOCOL_ID_OFFSET = 32;
+const AGENT_ADDRESS_OFFSET = 44;
+
+const $ = (id) => document.getElementById(id);
+
+// Agent address the server claims alongside the live quote; compared, never trusted
+let servedAgent = null;
+
+function toBytes(hex) {
+  const clean = hex.trim().replace(/^0x/, "").replace(/\s+/g, "");
+  if (clean.length % 2 !== 0 || /[^0-9a-fA-F]/.test(clean)) {
+    throw new Error("The quote is not valid hex");
+  }
+  const bytes = new Uint8Array(clean.length / 2);
+  for (let i = 0; i < bytes.length; i++) {
+    bytes[i] = parseInt(clean.substr(i * 2, 2), 16);
+  }
+  return bytes;
+}
+
+function toHex(bytes) {
+  return Array.from(bytes, (b) => b.toString(16).padStart(2, "0")).join("");
+}
+
+function normalizeHex(value) {
+  return value.trim().toLowerCase().replace(/^0x/, "");
+}
+
+function parseQuote(bytes) {
+  if (bytes.length < HEADER_LEN + REPORT_BODY_LEN) {
+    throw new Error(`Quote is ${bytes.length} bytes; a TDX quote carries at least ${HEADER_LEN + REPORT_BODY_LEN}`);
+  }
+  const view = new DataView(bytes.buffer);
+  const body = bytes.subarray(HEADER_LEN, HEADER_LEN + REPORT_BODY_LEN);
+  const fields = FIELDS.map(([name, offset, len]) => [name, toHex(body.subarray(offset, offset + len))]);
+  const reportData = body.subarray(REPORT_DATA, REPORT_DATA + 64);
+  return {
+    version: view.getUint16(0, true),
+    teeType: view.getUint32(4, true),
+    fields,
+    mrtd: toHex(body.subarray(136, 184)),
+    protocolId: toHex(reportData.subarray(PROTOCOL_ID_OFFSET, AGENT_ADDRESS_OFFSET)),
+    agentAddress: "0x" + toHex(reportData.subarray(AGENT_ADDRESS_OFFSET, 64)),
+  };
+}
+
+function row(label, value, verdict) {
+  const tr = document.createElement("tr");
+  const name = document.createElement("td");
+  name.textContent = label;
+  const cell = document.createElement("td");
+  const code = document.createElement("code");
+  code.textContent = value;
+  cell.appendChild(code);
+  if (verdict !== undefined) {
+    const mark = document.createElement("span");
+    mark.className = verdict ? "pass" : "fail";
+    mark.textContent = verdict ? "  ✓" : "  ✗";
+    cell.appendChild(mark);
+  }
+  tr.append(name, cell);
+  return tr;
+}
+
+function verify() {
+  const result = $("result");
+  result.replaceChildren();
+  let quote;
+  try {
+    quote = parseQuote(toBytes($("quote").value));
+  } catch (e) {
+    const p = document.createElement("p");
+    p.className = "fail";
+    p.textContent = e.message;
+    result.appendChild(p);
+    return;
+  }
+
+  const expectedAgent = normalizeHex($("agent").value);
+  const published = $("expected").value.split(/\s+/).map(normalizeHex).filter(Boolean);
+  const table = document.createElement("table");
+  table.appendChild(row("Quote version", String(quote.version), quote.version === 4 || quote.version === 5));
+  table.appendChild(row("TEE type", "0x" + quote.teeType.toString(16), quote.teeType === TEE_TYPE_TDX));
+  for (const [name, value] of quote.fields) {
+    const verdict = name === "MRTD" && published.length > 0 ? published.includes(quote.mrtd) : undefined;
+    table.appendChild(row(name, value, verdict));
+  }
+  table.appendChild(row("Protocol id", quote.protocolId, quote.protocolId === PROTOCOL_ID));
+  table.appendChild(row(
+    "Bound agent",
+    quote.agentAddress,
+    expectedAgent ? normalizeHex(quote.agentAddress) === expectedAgent : undefined,
+  ));
+  if (servedAgent) {
+    table.appendChild(row(
+      "Agent the server claims",
+      servedAgent,
+      normalizeHex(servedAgent) === normalizeHex(quote.agentAddress),
+    ));
+  }
+  result.appendChild(table);
+
+  const summary = document.createElement("p");
+  const failed = table.querySelectorAll(".fail").length;
+  summary.className = failed ? "fail" : "pass";
+  summary.textContent = failed
+    ? `${failed} check(s) failed`
+    : published.length && expectedAgent
+      ? "The quote binds the expected agent and reports a published MRTD"
+      : "The quote parses; enter the expected agent and published MRTDs to check them";
+  result.prepend(summary);
+}
+
+async function fetchQuote() {
+  const response = await fetch("/agents/quote");
+  const body = await response.json();
+  $("quote").value = body.tdx_quote_hex || "";
+  servedAgent = body.agent_address || null;
+  verify();
+}
+
+async function loadExpected() {
+  const fromUrl = new URLSearchParams(location.search).getAll("mrtd");
+  let published = [];
+  try {
+    published = (await (await fetch("/verify/expected")).json()).mrtd || [];
+  } catch (_) {
+    // The operator published none
+  }
+  $("expected").value = [...fromUrl, ...published].join("\n");
+}
+
+$("fetch").addEventListener("click", () => fetchQuote().catch((e) => alert(e.message)));
+$("verify").addEventListener("click", verify);
+// A pasted quote is not the one the server served
+$("quote").addEventListener("input", () => {
+  servedAgent = null;
+});
+loadExpected();
@@ -0,0 +1,46 @@
+//! /info relay cost: decoding to `serde_json::Value` and re-encoding, as the proxy used to,
+//! against the passthrough's `type` scan. Run with `cargo bench -p vas-core`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde_json::{json, Value};
+use vas_core::proxy;
+
+/// An l2Book-shaped body with `levels` price levels a side
+fn l2_book(levels: usize) -> Vec<u8> {
+    let side = |sign: f64| -> Vec<Value> {
+        (0..levels)
+            .map(|i| json!({"px": format!("{:.1}", 65000.0 + sign * i as f64 * 0.5), "sz": "1.2345", "n": 3}))
+            .collect()
+    };
+    serde_json::to_vec(&json!({"coin": "BTC", "time": 1700000000000u64, "levels": [side(-1.0), side(1.0)]})).unwrap()
+}
+
+fn relay(c: &mut Criterion) {
+    let request = br#"{"type":"l2Book","coin":"BTC","nSigFigs":5}"#;
+    let mut group = c.benchmark_group("info_relay");
+
+    for levels in [20, 1_000, 20_000] {
+        let response = l2_book(levels);
+        group.throughput(Throughput::Bytes((request.len() + response.len()) as u64));
+
+        group.bench_with_input(BenchmarkId::new("value_roundtrip", levels), &response, |b, response| {
+            b.iter(|| {
+                let payload: Value = serde_json::from_slice(request).unwrap();
+                let forwarded = serde_json::to_vec(&payload).unwrap();
+                let body: Value = serde_json::from_slice(response).unwrap();
+                (forwarded, serde_json::to_vec(&body).unwrap())
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("passthrough", levels), &response, |b, response| {
+            b.iter(|| {
+                let info_type = proxy::info_type(request).unwrap();
+                (info_type.len(), response.len())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, relay);
+criterion_main!(benches);
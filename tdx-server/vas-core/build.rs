@@ -0,0 +1,31 @@
+//! Embeds build provenance served (and signed) by GET /version.
+//!
+//! VAS_GIT_COMMIT overrides `git rev-parse HEAD` for builds without a checkout, and
+//! SOURCE_DATE_EPOCH pins the timestamp so reproducible (measured) builds stay identical.
+
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let commit = std::env::var("VAS_GIT_COMMIT").ok().or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|commit| commit.trim().to_string())
+    });
+
+    let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+
+    println!("cargo:rustc-env=VAS_GIT_COMMIT={}", commit.unwrap_or_else(|| "unknown".to_string()));
+    println!("cargo:rustc-env=VAS_BUILD_TIMESTAMP={}", timestamp);
+    println!("cargo:rerun-if-env-changed=VAS_GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/refs/heads");
+}
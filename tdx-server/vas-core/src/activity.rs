@@ -0,0 +1,152 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::audit::{AuditEntry, AUDIT_EXCHANGE_ACTION};
+use crate::auth;
+use crate::events::EVENT_SESSION_LOGIN;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+/// Maximum items returned per page
+const MAX_PAGE_SIZE: usize = 200;
+
+/// Activity types, also accepted by `?types=`
+const LOGIN: &str = "login";
+const KEY_ROTATION: &str = "key_rotation";
+const POLICY_CHANGE: &str = "policy_change";
+const ORDER: &str = "order";
+const CANCEL: &str = "cancel";
+const TRANSFER: &str = "transfer";
+
+/// One entry of the account timeline
+#[derive(Debug, Serialize)]
+pub struct ActivityItem {
+    /// Opaque, stable cursor: pass as `before` to continue after this item
+    pub id: String,
+    pub timestamp_ms: u64,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// False for signing attempts that failed
+    pub ok: bool,
+    pub details: Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ActivityQuery {
+    /// Cursor of the last item already seen
+    pub before: Option<String>,
+    pub limit: Option<usize>,
+    /// Comma-separated types to include; all when absent
+    pub types: Option<String>,
+}
+
+/// Timeline type of a signed exchange action, if it belongs in the feed
+fn action_kind(action_type: &str) -> Option<&'static str> {
+    match action_type {
+        "order" | "modify" | "batchModify" | "twapOrder" => Some(ORDER),
+        "cancel" | "cancelByCloid" | "scheduleCancel" | "twapCancel" => Some(CANCEL),
+        "usdSend" | "spotSend" | "usdClassTransfer" | "withdraw3" | "sendAsset" | "subAccountTransfer" | "vaultTransfer" => Some(TRANSFER),
+        _ => None,
+    }
+}
+
+fn exchange_item(entry: &AuditEntry) -> Option<ActivityItem> {
+    let action = entry.subject.get("action")?;
+    let kind = action_kind(action.get("type")?.as_str()?)?;
+    Some(ActivityItem {
+        id: cursor(entry.timestamp_ms, 'a', entry.seq),
+        timestamp_ms: entry.timestamp_ms,
+        kind,
+        ok: entry.error.is_none(),
+        details: serde_json::json!({
+            "action": action,
+            "action_hash": entry.subject_hash,
+            "error": entry.error
+        }),
+    })
+}
+
+/// Cursors sort like the feed: by time, then source, then sequence within the source
+fn cursor(timestamp_ms: u64, source: char, seq: u64) -> String {
+    format!("{:013}{}{:012}", timestamp_ms, source, seq)
+}
+
+/// GET /me/activity?before=&limit=&types= - The caller's account timeline, newest first.
+///
+/// Combines logins (event store), signed orders, cancels and transfers (audit log), and the
+/// deployment-wide agent key rotations and policy changes that applied to the account.
+/// History goes back as far as the stores' retention.
+pub async fn me_activity(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ActivityQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let shared_view = api_key.starts_with(SHARE_TOKEN_PREFIX);
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    let limit = query.limit.unwrap_or(50).clamp(1, MAX_PAGE_SIZE);
+    let wanted: Option<Vec<&str>> = query.types.as_deref().map(|t| t.split(',').map(str::trim).collect());
+
+    let mut items: Vec<ActivityItem> = Vec::new();
+    {
+        let audit = state.audit.read().await;
+        items.extend(audit.signatures_for(&user_address, 0, u64::MAX).into_iter()
+            .filter(|entry| entry.kind == AUDIT_EXCHANGE_ACTION)
+            .filter_map(exchange_item));
+        items.extend(audit.policies_between(0, u64::MAX).into_iter().map(|entry| ActivityItem {
+            id: cursor(entry.timestamp_ms, 'a', entry.seq),
+            timestamp_ms: entry.timestamp_ms,
+            kind: POLICY_CHANGE,
+            ok: true,
+            details: serde_json::json!({"policy": entry.subject, "entry_hash": entry.entry_hash}),
+        }));
+    }
+    {
+        let events = state.event_store.read().await;
+        items.extend(events.of_kind_since(&user_address, EVENT_SESSION_LOGIN, 0).map(|event| {
+            let mut details = event.payload.clone();
+            if shared_view {
+                if let Some(details) = details.as_object_mut() {
+                    details.remove("client_ip");
+                }
+            }
+            ActivityItem {
+                id: cursor(event.timestamp_ms, 'e', event.seq),
+                timestamp_ms: event.timestamp_ms,
+                kind: LOGIN,
+                ok: true,
+                details,
+            }
+        }));
+    }
+    {
+        // The first quote is the deployment's initial key, not a rotation
+        let archive = state.quote_archive.read().await;
+        items.extend(archive.records().iter().enumerate().skip(1).map(|(i, record)| ActivityItem {
+            id: cursor(record.active_from_ms, 'q', i as u64),
+            timestamp_ms: record.active_from_ms,
+            kind: KEY_ROTATION,
+            ok: true,
+            details: serde_json::json!({"quote_id": record.quote_id, "agent_address": record.agent_address}),
+        }));
+    }
+
+    items.retain(|item| wanted.as_ref().is_none_or(|types| types.contains(&item.kind)));
+    if let Some(before) = &query.before {
+        items.retain(|item| item.id.as_str() < before.as_str());
+    }
+    items.sort_by(|a, b| b.id.cmp(&a.id));
+    let has_more = items.len() > limit;
+    items.truncate(limit);
+
+    Ok(Json(serde_json::json!({
+        "items": items,
+        "next_cursor": if has_more { items.last().map(|item| item.id.clone()) } else { None },
+        "has_more": has_more
+    })))
+}
@@ -0,0 +1,168 @@
+//! Ethereum addresses as the service compares and stores them.
+//!
+//! SIWE recovery yields lowercase hex while operators configure checksummed (EIP-55) addresses,
+//! so raw string comparison misses matches. [`Address`] compares by bytes, stores as lowercase
+//! hex (the key every per-user store already uses) and displays checksummed.
+//!
+//! Addresses of secp256k1 keys are derived here too, and only here.
+
+use alloy::primitives::keccak256;
+use secp256k1::{PublicKey, SecretKey, SECP256K1};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// A 20-byte account address; equality ignores how it was cased
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Address(alloy::primitives::Address);
+
+impl Address {
+    /// Account of a public key: the last 20 bytes of the Keccak-256 of its uncompressed x || y
+    pub fn from_public_key(public_key: &PublicKey) -> Self {
+        let uncompressed = public_key.serialize_uncompressed();
+        let hash = keccak256(&uncompressed[1..]);
+        Self(alloy::primitives::Address::from_slice(&hash[12..]))
+    }
+
+    /// Lowercase `0x` hex, the form used as a storage and lookup key
+    pub fn to_lower_hex(&self) -> String {
+        format!("{:#x}", self.0)
+    }
+
+    /// EIP-55 mixed-case form, for display
+    pub fn to_checksum(&self) -> String {
+        self.0.to_checksum(None)
+    }
+
+    /// Whether `other` names this address in any casing
+    pub fn matches(&self, other: &str) -> bool {
+        other.parse::<Address>().is_ok_and(|other| other == *self)
+    }
+}
+
+impl FromStr for Address {
+    type Err = String;
+
+    /// Parse `0x`-prefixed hex. All-lowercase and all-uppercase are taken as is; mixed case
+    /// must be a valid EIP-55 checksum, so a mistyped checksummed address is rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))
+            .ok_or_else(|| format!("Address {} must start with 0x", s))?;
+        let address: alloy::primitives::Address = s.parse()
+            .map_err(|e| format!("Invalid address {}: {}", s, e))?;
+
+        let mixed_case = hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+        if mixed_case && address.to_checksum(None)[2..] != *hex {
+            return Err(format!("Address {} has an invalid EIP-55 checksum", s));
+        }
+        Ok(Self(address))
+    }
+}
+
+impl From<alloy::primitives::Address> for Address {
+    fn from(address: alloy::primitives::Address) -> Self {
+        Self(address)
+    }
+}
+
+impl From<[u8; 20]> for Address {
+    fn from(bytes: [u8; 20]) -> Self {
+        Self(alloy::primitives::Address::from(bytes))
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_checksum())
+    }
+}
+
+/// Serialized as lowercase hex, matching addresses already persisted by the stores
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_lower_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Lowercase hex address of a public key
+pub fn public_key_to_address(public_key: &PublicKey) -> String {
+    Address::from_public_key(public_key).to_lower_hex()
+}
+
+/// Lowercase hex address of the key pair holding `secret_key`
+pub fn secret_key_to_address(secret_key: &SecretKey) -> String {
+    public_key_to_address(&secret_key.public_key(SECP256K1))
+}
+
+/// Storage key for an address given as a string: lowercase hex when it parses, otherwise the
+/// input lowercased, so lookups by malformed input still miss rather than fail
+pub fn normalize(address: &str) -> String {
+    address.parse::<Address>()
+        .map(|address| address.to_lower_hex())
+        .unwrap_or_else(|_| address.to_lowercase())
+}
+
+/// Whether two address strings name the same account, whatever their casing
+pub fn same(a: &str, b: &str) -> bool {
+    normalize(a) == normalize(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECKSUMMED: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+    #[test]
+    fn parses_any_consistent_casing() {
+        let checksummed: Address = CHECKSUMMED.parse().unwrap();
+        let lower: Address = CHECKSUMMED.to_lowercase().parse().unwrap();
+        let upper: Address = format!("0x{}", &CHECKSUMMED[2..].to_uppercase()).parse().unwrap();
+        assert_eq!(checksummed, lower);
+        assert_eq!(checksummed, upper);
+        assert_eq!(checksummed.to_checksum(), CHECKSUMMED);
+        assert_eq!(checksummed.to_lower_hex(), CHECKSUMMED.to_lowercase());
+    }
+
+    #[test]
+    fn rejects_bad_checksum() {
+        let mistyped = CHECKSUMMED.replace("aAeb", "aaEb");
+        assert!(mistyped.parse::<Address>().is_err());
+        assert!("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".parse::<Address>().is_err());
+    }
+
+    /// Well-known key/address pairs: secret keys 1 and 2, and the first Hardhat/Anvil account
+    const KEY_VECTORS: [(&str, &str); 3] = [
+        ("0000000000000000000000000000000000000000000000000000000000000001", "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"),
+        ("0000000000000000000000000000000000000000000000000000000000000002", "0x2B5AD5c4795c026514f8317c7a215E218DcCD6cF"),
+        ("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80", "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"),
+    ];
+
+    #[test]
+    fn derives_known_addresses() {
+        for (secret_hex, expected) in KEY_VECTORS {
+            let secret_key = SecretKey::from_slice(&hex::decode(secret_hex).unwrap()).unwrap();
+            let public_key = secret_key.public_key(SECP256K1);
+            assert_eq!(Address::from_public_key(&public_key).to_checksum(), expected);
+            assert_eq!(public_key_to_address(&public_key), expected.to_lowercase());
+            assert_eq!(secret_key_to_address(&secret_key), expected.to_lowercase());
+        }
+    }
+
+    #[test]
+    fn normalizes_for_comparison() {
+        assert!(same(CHECKSUMMED, &CHECKSUMMED.to_lowercase()));
+        assert_eq!(normalize(CHECKSUMMED), CHECKSUMMED.to_lowercase());
+        assert_eq!(normalize("Not-An-Address"), "not-an-address");
+        let address: Address = CHECKSUMMED.parse().unwrap();
+        assert!(address.matches(&CHECKSUMMED.to_lowercase()));
+        assert_eq!(serde_json::to_value(address).unwrap(), serde_json::json!(CHECKSUMMED.to_lowercase()));
+    }
+}
@@ -0,0 +1,322 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::audit::Requester;
+use crate::client_ip;
+use crate::config::Config;
+use crate::universal_signing::recover_personal_sign;
+use crate::AppState;
+
+/// Key id (HMAC) or admin address (EIP-191) the request is signed by; exactly one is sent
+pub const ADMIN_KEY_HEADER: &str = "X-Admin-Key";
+pub const ADMIN_ADDRESS_HEADER: &str = "X-Admin-Address";
+/// Unix ms at signing
+pub const ADMIN_TIMESTAMP_HEADER: &str = "X-Admin-Timestamp";
+/// Single-use value chosen by the caller; reuse inside the skew window is refused
+pub const ADMIN_NONCE_HEADER: &str = "X-Admin-Nonce";
+/// Hex HMAC-SHA256 under the key's secret, or a 65-byte personal_sign signature
+pub const ADMIN_SIGNATURE_HEADER: &str = "X-Admin-Signature";
+
+/// Key id ADMIN_TOKEN signs as
+pub const DEFAULT_KEY_ID: &str = "admin";
+/// Largest admin request body that is buffered for hashing
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+const MAX_NONCE_LEN: usize = 128;
+
+/// Exact text an admin request signs; the body enters as its hex sha256
+pub fn signing_message(method: &str, path_and_query: &str, timestamp_ms: u64, nonce: &str, body: &[u8]) -> String {
+    format!(
+        "vas-admin\n{}\n{}\n{}\n{}\n{}",
+        method.to_uppercase(),
+        path_and_query,
+        timestamp_ms,
+        nonce,
+        hex::encode(Sha256::digest(body))
+    )
+}
+
+/// Hex HMAC-SHA256 of `message` under an admin key secret
+pub fn hmac_signature(secret: &str, message: &str) -> String {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(message.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Who may call /admin endpoints, and the nonces they already used.
+///
+/// Every request is signed over its method, path, body and a timestamp, either with a shared
+/// admin key (HMAC) or by an admin wallet (EIP-191). Requests outside the skew window or
+/// reusing a nonce are refused, so a captured request can't be replayed.
+#[derive(Debug)]
+pub struct AdminGate {
+    /// Key id -> HMAC secret
+    keys: HashMap<String, String>,
+    /// Lowercased admin wallet addresses
+    addresses: HashSet<String>,
+    max_skew_ms: u64,
+    /// Nonce -> unix ms after which it may be forgotten
+    nonces: Mutex<HashMap<String, u64>>,
+}
+
+impl AdminGate {
+    pub fn from_config(config: &Config) -> Self {
+        let mut keys: HashMap<String, String> = config.admin_key_secrets.iter()
+            .filter_map(|entry| entry.split_once('='))
+            .map(|(id, secret)| (id.trim().to_string(), secret.trim().to_string()))
+            .filter(|(id, secret)| !id.is_empty() && !secret.is_empty())
+            .collect();
+        if let Some(token) = &config.admin_token {
+            keys.entry(DEFAULT_KEY_ID.to_string()).or_insert_with(|| token.clone());
+        }
+        let addresses = config.admin_addresses.iter().map(|a| a.to_lowercase()).collect();
+
+        Self {
+            keys,
+            addresses,
+            max_skew_ms: config.admin_request_max_skew_secs * 1000,
+            nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.keys.is_empty() || !self.addresses.is_empty()
+    }
+
+    /// Check a request's signature, timestamp and nonce; the actor it was signed by on success
+    fn authenticate(&self, headers: &HeaderMap, method: &str, path_and_query: &str, body: &[u8], now_ms: u64) -> Result<String, String> {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let timestamp_ms: u64 = header(ADMIN_TIMESTAMP_HEADER)
+            .and_then(|t| t.parse().ok())
+            .ok_or(format!("missing or invalid {}", ADMIN_TIMESTAMP_HEADER))?;
+        if timestamp_ms.abs_diff(now_ms) > self.max_skew_ms {
+            return Err(format!("timestamp {} is outside the {}s window", timestamp_ms, self.max_skew_ms / 1000));
+        }
+        let nonce = header(ADMIN_NONCE_HEADER)
+            .filter(|n| !n.is_empty() && n.len() <= MAX_NONCE_LEN)
+            .ok_or(format!("missing or invalid {}", ADMIN_NONCE_HEADER))?;
+        let signature = header(ADMIN_SIGNATURE_HEADER).ok_or(format!("missing {}", ADMIN_SIGNATURE_HEADER))?;
+        let message = signing_message(method, path_and_query, timestamp_ms, nonce, body);
+
+        let actor = match (header(ADMIN_KEY_HEADER), header(ADMIN_ADDRESS_HEADER)) {
+            (Some(key_id), None) => {
+                let secret = self.keys.get(key_id).ok_or_else(|| format!("unknown admin key '{}'", key_id))?;
+                let expected = hmac_signature(secret, &message);
+                if !constant_time_eq(signature.to_lowercase().as_bytes(), expected.as_bytes()) {
+                    return Err("HMAC signature does not match".to_string());
+                }
+                format!("key:{}", key_id)
+            }
+            (None, Some(address)) => {
+                let address = address.to_lowercase();
+                if !self.addresses.contains(&address) {
+                    return Err(format!("{} is not an admin address", address));
+                }
+                let recovered = recover_personal_sign(&message, signature).map_err(|e| format!("invalid signature: {}", e))?;
+                if recovered != address {
+                    return Err(format!("signature recovers to {}, not {}", recovered, address));
+                }
+                format!("address:{}", address)
+            }
+            _ => return Err(format!("send exactly one of {} or {}", ADMIN_KEY_HEADER, ADMIN_ADDRESS_HEADER)),
+        };
+
+        // Only a correctly signed request may consume its nonce
+        let mut nonces = self.nonces.lock().unwrap();
+        nonces.retain(|_, expires_at| *expires_at > now_ms);
+        let key = format!("{}|{}", actor, nonce);
+        if nonces.contains_key(&key) {
+            return Err(format!("nonce '{}' was already used", nonce));
+        }
+        nonces.insert(key, timestamp_ms + self.max_skew_ms);
+        Ok(actor)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Gate /admin endpoints on a signed, timestamped request, and record every state-changing
+/// call in the audit log under the actor that signed it. Disabled (404) without credentials.
+pub async fn admin_auth(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if !state.admin.enabled() {
+        warn!("Admin endpoint requested but no ADMIN_TOKEN, ADMIN_KEYS or ADMIN_ADDRESSES is set");
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let client = client_ip::describe(&request);
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_BODY_BYTES).await.map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
+    let path_and_query = parts.uri.path_and_query().map(|p| p.as_str()).unwrap_or_else(|| parts.uri.path()).to_string();
+    let method = parts.method.clone();
+
+    let actor = match state.admin.authenticate(&parts.headers, method.as_str(), &path_and_query, &body, now_ms()) {
+        Ok(actor) => actor,
+        Err(reason) => {
+            warn!("Rejected admin request {} {}: {} (from {})", method, path_and_query, reason, client);
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    };
+
+    let subject = serde_json::json!({
+        "actor": actor,
+        "method": method.as_str(),
+        "path": path_and_query,
+        "body_sha256": hex::encode(Sha256::digest(&body)),
+        "timestamp_ms": parts.headers.get(ADMIN_TIMESTAMP_HEADER).and_then(|v| v.to_str().ok()),
+        "nonce": parts.headers.get(ADMIN_NONCE_HEADER).and_then(|v| v.to_str().ok()),
+        "signature": parts.headers.get(ADMIN_SIGNATURE_HEADER).and_then(|v| v.to_str().ok())
+    });
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    // Reads are not actions; everything else is recorded, refused or not
+    if method != Method::GET && method != Method::HEAD {
+        let mut subject = subject;
+        subject["status"] = serde_json::json!(response.status().as_u16());
+        let requester = Requester { user_address: None, client_ip: client_ip::current() };
+        let entry = state.audit.write().await.record_admin_action(&requester, subject);
+        info!("🛂 Admin action by {}: {} {} -> {} (audit seq {})", actor, method, path_and_query, response.status(), entry.seq);
+    }
+    Ok(response)
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address;
+    use crate::universal_signing::sign_hash_with_key;
+    use alloy::primitives::eip191_hash_message;
+    use secp256k1::SecretKey;
+
+    const NOW_MS: u64 = 1_700_000_000_000;
+    const SECRET: &str = "s3cret";
+
+    fn gate(addresses: &[String]) -> AdminGate {
+        AdminGate {
+            keys: HashMap::from([(DEFAULT_KEY_ID.to_string(), SECRET.to_string())]),
+            addresses: addresses.iter().cloned().collect(),
+            max_skew_ms: 30_000,
+            nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    fn hmac_headers(timestamp_ms: u64, nonce: &str, body: &[u8]) -> HeaderMap {
+        let message = signing_message("POST", "/admin/policy", timestamp_ms, nonce, body);
+        headers(&[
+            (ADMIN_KEY_HEADER, DEFAULT_KEY_ID),
+            (ADMIN_TIMESTAMP_HEADER, &timestamp_ms.to_string()),
+            (ADMIN_NONCE_HEADER, nonce),
+            (ADMIN_SIGNATURE_HEADER, &hmac_signature(SECRET, &message)),
+        ])
+    }
+
+    #[test]
+    fn signing_message_format() {
+        assert_eq!(
+            signing_message("post", "/admin/replay?dry=1", NOW_MS, "n-1", b""),
+            "vas-admin\nPOST\n/admin/replay?dry=1\n1700000000000\nn-1\ne3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        assert_eq!(
+            hmac_signature("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn hmac_request_is_accepted_once() {
+        let gate = gate(&[]);
+        let headers = hmac_headers(NOW_MS, "n-1", b"{}");
+        assert_eq!(gate.authenticate(&headers, "POST", "/admin/policy", b"{}", NOW_MS).unwrap(), "key:admin");
+        let replay = gate.authenticate(&headers, "POST", "/admin/policy", b"{}", NOW_MS + 1).unwrap_err();
+        assert!(replay.contains("already used"), "{}", replay);
+    }
+
+    #[test]
+    fn hmac_request_covers_path_and_body() {
+        let gate = gate(&[]);
+        let headers = hmac_headers(NOW_MS, "n-1", b"{}");
+        assert!(gate.authenticate(&headers, "POST", "/admin/tcb-recovery", b"{}", NOW_MS).is_err());
+        assert!(gate.authenticate(&headers, "POST", "/admin/policy", b"{\"x\":1}", NOW_MS).is_err());
+        assert!(gate.authenticate(&headers, "PUT", "/admin/policy", b"{}", NOW_MS).is_err());
+        // A refused request must not burn the nonce
+        assert!(gate.authenticate(&headers, "POST", "/admin/policy", b"{}", NOW_MS).is_ok());
+    }
+
+    #[test]
+    fn timestamps_outside_skew_window_are_refused() {
+        let gate = gate(&[]);
+        for timestamp_ms in [NOW_MS - 30_001, NOW_MS + 30_001] {
+            let headers = hmac_headers(timestamp_ms, "n-1", b"");
+            let err = gate.authenticate(&headers, "POST", "/admin/policy", b"", NOW_MS).unwrap_err();
+            assert!(err.contains("outside"), "{}", err);
+        }
+        let headers = hmac_headers(NOW_MS - 30_000, "n-2", b"");
+        assert!(gate.authenticate(&headers, "POST", "/admin/policy", b"", NOW_MS).is_ok());
+    }
+
+    #[test]
+    fn eip191_request_from_admin_wallet() {
+        let key = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let admin = address::secret_key_to_address(&key);
+        let gate = gate(std::slice::from_ref(&admin));
+
+        let message = signing_message("DELETE", "/admin/status/1", NOW_MS, "n-1", b"");
+        let signature = sign_hash_with_key(&key, &eip191_hash_message(&message));
+        let signature_hex = format!("{}{}{:02x}", signature.r, &signature.s[2..], signature.v);
+        let signed = |address: &str| headers(&[
+            (ADMIN_ADDRESS_HEADER, address),
+            (ADMIN_TIMESTAMP_HEADER, &NOW_MS.to_string()),
+            (ADMIN_NONCE_HEADER, "n-1"),
+            (ADMIN_SIGNATURE_HEADER, &signature_hex),
+        ]);
+
+        let other = address::secret_key_to_address(&SecretKey::from_slice(&[8u8; 32]).unwrap());
+        assert!(gate.authenticate(&signed(&other), "DELETE", "/admin/status/1", b"", NOW_MS).is_err());
+        assert_eq!(
+            gate.authenticate(&signed(&admin.to_uppercase().replace("0X", "0x")), "DELETE", "/admin/status/1", b"", NOW_MS).unwrap(),
+            format!("address:{}", admin)
+        );
+        assert!(gate.authenticate(&signed(&admin), "DELETE", "/admin/status/1", b"", NOW_MS).is_err());
+    }
+
+    #[test]
+    fn exactly_one_credential_header() {
+        let gate = gate(&[]);
+        let mut headers = hmac_headers(NOW_MS, "n-1", b"");
+        headers.insert(ADMIN_ADDRESS_HEADER, "0x0000000000000000000000000000000000000001".parse().unwrap());
+        assert!(gate.authenticate(&headers, "POST", "/admin/policy", b"", NOW_MS).is_err());
+    }
+}
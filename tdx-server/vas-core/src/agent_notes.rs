@@ -0,0 +1,237 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use tracing::{info, error, warn};
+
+use crate::agent_stats::{approved_agents, find_agent};
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::ha::SealedBox;
+use crate::jsonl;
+use crate::preset_tdx::PresetTDXData;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+const MAX_FIELDS: usize = 16;
+const MAX_FIELD_NAME_LEN: usize = 64;
+const MAX_FIELD_VALUE_LEN: usize = 256;
+
+/// A user's note on one of their agents, sealed under a key derived from the agent key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredNote {
+    user_address: String,
+    agent_address: String,
+    /// Enclave agent whose key sealed the note; notes sealed before a key rotation can't be opened
+    sealed_by: String,
+    sealed: SealedBox,
+    updated_at: u64,
+}
+
+/// Label, bot version, environment and the like, as string fields
+pub type Notes = BTreeMap<String, String>;
+
+/// Notes users keep on their agents, encrypted at rest
+#[derive(Debug)]
+pub struct AgentNotes {
+    /// (user, agent) -> sealed note, both lowercased
+    notes: HashMap<(String, String), StoredNote>,
+    path: Option<PathBuf>,
+}
+
+/// Key sealing notes, and the agent it derives from
+fn notes_key() -> Option<([u8; 32], String)> {
+    let preset_data = PresetTDXData::get()?;
+    let key = Sha256::new()
+        .chain_update(b"vas-agent-notes")
+        .chain_update(preset_data.agent_private_key.secret_bytes())
+        .finalize()
+        .into();
+    Some((key, preset_data.agent_address.to_lowercase()))
+}
+
+impl AgentNotes {
+    /// Open the store, replaying notes already persisted at `path`
+    pub fn open(path: Option<PathBuf>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let stored: Vec<StoredNote> = match &path {
+            Some(path) => jsonl::load(path)?,
+            None => Vec::new(),
+        };
+        let notes: HashMap<(String, String), StoredNote> = stored.into_iter()
+            .map(|note| ((note.user_address.clone(), note.agent_address.clone()), note))
+            .collect();
+
+        info!("🏷️ Agent notes opened with {} notes", notes.len());
+        Ok(Self { notes, path })
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        let notes: Vec<&StoredNote> = self.notes.values().collect();
+        if let Err(e) = jsonl::rewrite(path, &notes) {
+            error!("❌ Failed to persist agent notes: {}", e);
+        }
+    }
+
+    /// Decrypted notes on an agent; Err when they were sealed under an earlier agent key
+    pub fn get(&self, user_address: &str, agent_address: &str) -> Option<Result<Notes, String>> {
+        let note = self.notes.get(&(user_address.to_string(), agent_address.to_string()))?;
+        Some(open_note(note))
+    }
+
+    fn set(&mut self, user_address: &str, agent_address: &str, notes: &Notes) -> Result<(), String> {
+        let key = (user_address.to_string(), agent_address.to_string());
+        if notes.is_empty() {
+            self.notes.remove(&key);
+        } else {
+            let (secret, sealed_by) = notes_key().ok_or("Preset TDX data not initialized")?;
+            let plaintext = serde_json::to_vec(notes).map_err(|e| e.to_string())?;
+            self.notes.insert(key, StoredNote {
+                user_address: user_address.to_string(),
+                agent_address: agent_address.to_string(),
+                sealed_by,
+                sealed: SealedBox::seal(&secret, &plaintext),
+                updated_at: now_secs(),
+            });
+        }
+        self.persist();
+        Ok(())
+    }
+
+    /// A user's notes by agent, decrypted where the current agent key can open them
+    pub fn export_user(&self, user_address: &str) -> Vec<Value> {
+        self.notes.values()
+            .filter(|note| note.user_address == user_address)
+            .map(|note| match open_note(note) {
+                Ok(notes) => serde_json::json!({"agent_address": note.agent_address, "notes": notes, "updated_at": note.updated_at}),
+                Err(e) => serde_json::json!({"agent_address": note.agent_address, "error": e, "updated_at": note.updated_at}),
+            })
+            .collect()
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> usize {
+        let before = self.notes.len();
+        self.notes.retain(|(user, _), _| user != user_address);
+        let removed = before - self.notes.len();
+        if removed > 0 {
+            self.persist();
+        }
+        removed
+    }
+}
+
+fn open_note(note: &StoredNote) -> Result<Notes, String> {
+    let (secret, current) = notes_key().ok_or("Preset TDX data not initialized")?;
+    if note.sealed_by != current {
+        return Err(format!("Sealed under earlier agent key {}", note.sealed_by));
+    }
+    let plaintext = note.sealed.open(&secret).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&plaintext).map_err(|e| e.to_string())
+}
+
+/// Apply a PATCH body: string values set a field, null removes it
+fn merge(notes: &mut Notes, patch: &serde_json::Map<String, Value>) -> Result<(), String> {
+    for (field, value) in patch {
+        if field.is_empty() || field.len() > MAX_FIELD_NAME_LEN {
+            return Err(format!("Field names must be 1 to {} characters", MAX_FIELD_NAME_LEN));
+        }
+        match value {
+            Value::Null => {
+                notes.remove(field);
+            }
+            Value::String(text) if text.len() <= MAX_FIELD_VALUE_LEN => {
+                notes.insert(field.clone(), text.clone());
+            }
+            Value::String(_) => return Err(format!("'{}' is longer than {} characters", field, MAX_FIELD_VALUE_LEN)),
+            _ => return Err(format!("'{}' must be a string, or null to remove it", field)),
+        }
+    }
+    if notes.len() > MAX_FIELDS {
+        return Err(format!("At most {} note fields per agent", MAX_FIELDS));
+    }
+    Ok(())
+}
+
+/// GET /agents - The caller's approved agents with their notes.
+///
+/// Share tokens see the agents but not the notes.
+pub async fn list_agents(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let shared_view = api_key.starts_with(SHARE_TOKEN_PREFIX);
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?.to_lowercase();
+    let agents = approved_agents(&state, &user_address).await?;
+    let current_agent = PresetTDXData::get().map(|p| p.agent_address.to_lowercase()).unwrap_or_default();
+
+    let store = state.agent_notes.read().await;
+    let agents: Vec<Value> = agents.into_iter().map(|mut agent| {
+        let address = agent.get("address").and_then(|a| a.as_str()).unwrap_or_default().to_lowercase();
+        agent["held_by_enclave"] = serde_json::json!(address == current_agent);
+        if !shared_view {
+            match store.get(&user_address, &address) {
+                Some(Ok(notes)) => agent["notes"] = serde_json::json!(notes),
+                Some(Err(e)) => agent["notes_error"] = serde_json::json!(e),
+                None => agent["notes"] = serde_json::json!({}),
+            }
+        }
+        agent
+    }).collect();
+
+    Ok(Json(serde_json::json!({"status": "ok", "response": agents})))
+}
+
+/// PATCH /agents/:name - Set or remove note fields on one of the caller's agents, by approveAgent
+/// name or address. Notes sealed under an earlier agent key are replaced, not merged.
+pub async fn patch_agent(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(patch): Json<serde_json::Map<String, Value>>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?.to_lowercase();
+    let agents = approved_agents(&state, &user_address).await?;
+    let agent = find_agent(&agents, &name).ok_or(StatusCode::NOT_FOUND)?;
+    let agent_address = agent.get("address").and_then(|a| a.as_str()).unwrap_or_default().to_lowercase();
+
+    let mut store = state.agent_notes.write().await;
+    let mut notes = match store.get(&user_address, &agent_address) {
+        Some(Ok(notes)) => notes,
+        Some(Err(e)) => {
+            warn!("⚠️ Replacing unreadable notes on {} for {}: {}", agent_address, user_address, e);
+            Notes::new()
+        }
+        None => Notes::new(),
+    };
+    if let Err(e) = merge(&mut notes, &patch) {
+        return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, e)));
+    }
+    if let Err(e) = store.set(&user_address, &agent_address, &notes) {
+        error!("❌ Failed to seal agent notes: {}", e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    info!("🏷️ Notes on agent {} for {} updated ({} fields)", agent_address, user_address, notes.len());
+    let mut agent = agent.clone();
+    agent["notes"] = serde_json::json!(notes);
+    Ok(Json(serde_json::json!({"status": "ok", "response": agent})))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tracing::{info, error};
+
+use crate::audit::AuditEntry;
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::preset_tdx::PresetTDXData;
+use crate::AppState;
+
+/// Signing activity of one agent for one user, derived from the audit log
+#[derive(Debug, Default, Serialize)]
+pub struct AgentUsage {
+    pub signatures: u64,
+    pub failures: u64,
+    pub last_signature_ms: Option<u64>,
+    pub last_attempt_ms: Option<u64>,
+    /// Action type -> successful signatures
+    pub action_types: BTreeMap<String, u64>,
+}
+
+impl AgentUsage {
+    fn add(&mut self, entry: &AuditEntry) {
+        self.last_attempt_ms = self.last_attempt_ms.max(Some(entry.timestamp_ms));
+        if entry.error.is_some() {
+            self.failures += 1;
+            return;
+        }
+        self.signatures += 1;
+        self.last_signature_ms = self.last_signature_ms.max(Some(entry.timestamp_ms));
+        *self.action_types.entry(action_type(entry)).or_default() += 1;
+    }
+}
+
+/// L1 actions are labelled by their action type, everything else by audit kind
+fn action_type(entry: &AuditEntry) -> String {
+    entry.subject.pointer("/action/type")
+        .and_then(|t| t.as_str())
+        .unwrap_or(&entry.kind)
+        .to_string()
+}
+
+/// The user's approved agents, as listed by `extraAgents`
+pub(crate) async fn approved_agents(state: &AppState, user_address: &str) -> Result<Vec<Value>, StatusCode> {
+    let agents = state.proxy
+        .proxy_info_request(&serde_json::json!({"type": "extraAgents", "user": user_address}))
+        .await
+        .map_err(|e| {
+            error!("❌ Failed to load approved agents: {}", e);
+            StatusCode::BAD_GATEWAY
+        })?;
+    Ok(agents.as_array().cloned().unwrap_or_default())
+}
+
+/// An approved agent by its approveAgent name or address
+pub(crate) fn find_agent<'a>(agents: &'a [Value], name: &str) -> Option<&'a Value> {
+    agents.iter().find(|a| {
+        a.get("name").and_then(|n| n.as_str()) == Some(name)
+            || a.get("address").and_then(|n| n.as_str()).is_some_and(|addr| addr.eq_ignore_ascii_case(name))
+    })
+}
+
+/// GET /agents/:name/stats - Signing counts, last signature and action types for one of the
+/// caller's approved agents, looked up by its approveAgent name or address.
+///
+/// Only agents held by this enclave (current or earlier quote generations) have activity
+/// here; counts cover the audit log's retained history.
+pub async fn agent_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    let agents = approved_agents(&state, &user_address).await?;
+    let agent = find_agent(&agents, &name).cloned().ok_or(StatusCode::NOT_FOUND)?;
+    let agent_address = agent.get("address").and_then(|a| a.as_str()).unwrap_or_default().to_lowercase();
+
+    // Which agent key signed an entry follows from the quote being served at the time
+    let current_agent = PresetTDXData::get().map(|p| p.agent_address.to_lowercase()).unwrap_or_default();
+    let archive = state.quote_archive.read().await;
+    let held_here = current_agent == agent_address
+        || archive.records().iter().any(|r| r.agent_address.eq_ignore_ascii_case(&agent_address));
+    if !held_here {
+        return Ok(Json(error_codes::err_body(
+            ErrorCode::NotFound,
+            format!("Agent {} is not held by this enclave, so its activity is not visible here", agent_address),
+        )));
+    }
+
+    let audit = state.audit.read().await;
+    let mut usage = AgentUsage::default();
+    for entry in audit.signatures_for(&user_address, 0, u64::MAX) {
+        let signer = archive.active_at(entry.timestamp_ms)
+            .map(|r| r.agent_address.to_lowercase())
+            .unwrap_or_else(|| current_agent.clone());
+        if signer == agent_address {
+            usage.add(entry);
+        }
+    }
+
+    info!("📈 Agent stats for {} ({}): {} signatures", name, agent_address, usage.signatures);
+
+    Ok(Json(serde_json::json!({
+        "agent": agent,
+        "stats": usage,
+        "distinct_action_types": usage.action_types.len(),
+        "coverage_from_ms": audit.started_at_ms()
+    })))
+}
@@ -0,0 +1,676 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{info, warn, error};
+use dashmap::DashMap;
+use std::sync::Arc;
+
+use crate::address::{self, Address};
+use crate::siwe_auth::{ResourceGrants, SiweLoginRequest, SiweLoginResponse, SiweLoginError, verify_siwe_message};
+use crate::api_keys;
+use crate::preset_tdx::PresetTDXData;
+use crate::locale::Locale;
+use crate::market::MarketCache;
+use crate::onboarding::OnboardingState;
+
+/// Scope allowing order placement, cancels and account settings
+pub const SCOPE_TRADE: &str = "trade";
+/// Scope allowing agent-signed balance moves (vaultTransfer)
+pub const SCOPE_TRANSFER: &str = "transfer";
+/// Scope allowing allowlisted HyperEVM transactions
+pub const SCOPE_EVM: &str = "evm";
+/// Scope allowing allowlisted EIP-712 typed-data signatures
+pub const SCOPE_TYPED_DATA: &str = "typed-data";
+
+/// All scopes a session may be granted
+pub const KNOWN_SCOPES: [&str; 4] = [SCOPE_TRADE, SCOPE_TRANSFER, SCOPE_EVM, SCOPE_TYPED_DATA];
+
+/// Shortest session lifetime a login may ask for
+const MIN_SESSION_TTL_SECS: u64 = 60;
+/// Sliding sessions are extended at most this often, so most requests only take a read lock
+const SLIDE_GRANULARITY_SECS: u64 = 60;
+
+/// Scope an exchange action type requires
+pub fn required_scope(action_type: &str) -> &'static str {
+    match action_type {
+        "vaultTransfer" => SCOPE_TRANSFER,
+        _ => SCOPE_TRADE,
+    }
+}
+
+/// Coins an action places, changes or cancels orders on, or adjusts margin for
+fn action_asset_indices(action: &Value) -> Vec<u64> {
+    // Order and cancel lists, a modified order or TWAP, and the action itself (updateLeverage)
+    let mut indices: Vec<u64> = ["orders", "cancels", "modifies"].iter()
+        .filter_map(|field| action.get(*field).and_then(|v| v.as_array()))
+        .flatten()
+        .chain(["/order", "/twap", ""].iter().filter_map(|pointer| action.pointer(pointer)))
+        .filter_map(|entry| {
+            ["a", "asset"].iter().find_map(|field| entry.get(*field))
+                .or_else(|| entry.pointer("/order/a"))
+                .and_then(|a| a.as_u64())
+        })
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+/// Coins an action touches that are outside a session's asset allowlist.
+///
+/// Indices the market metadata can't name are reported as `#<index>` and count as outside.
+pub async fn disallowed_assets(market: &MarketCache, allowed: &[String], action: &Value) -> Vec<String> {
+    let mut outside = Vec::new();
+    for index in action_asset_indices(action) {
+        match market.asset(index).await {
+            Ok(Some(asset)) if allowed.contains(&asset.name) => {}
+            Ok(Some(asset)) => outside.push(asset.name),
+            _ => outside.push(format!("#{}", index)),
+        }
+    }
+    outside
+}
+
+/// What a login asks its API key to be allowed
+#[derive(Debug, Clone)]
+pub struct LoginGrants {
+    pub scopes: Vec<String>,
+    pub allowed_assets: Option<Vec<String>>,
+    /// The grants were signed into the SIWE message, so a live session must carry exactly them
+    pub signed: bool,
+}
+
+impl LoginGrants {
+    /// Whether a session already carries these grants, in any order
+    fn matches(&self, session: &AgentSession) -> bool {
+        fn sorted(values: &[String]) -> Vec<&String> {
+            let mut values: Vec<&String> = values.iter().collect();
+            values.sort();
+            values
+        }
+        sorted(&self.scopes) == sorted(&session.scopes)
+            && self.allowed_assets.as_deref().map(sorted) == session.allowed_assets.as_deref().map(sorted)
+    }
+}
+
+/// Agent session manager for tracking authenticated users
+#[derive(Debug, Clone)]
+pub struct AgentSession {
+    pub user_address: String,
+    pub agent_address: String,
+    pub api_key: String,
+    pub created_at: u64,
+    pub expires_at: u64,
+    /// Per-session referrer code overriding the server default
+    pub referrer_code: Option<String>,
+    /// User opted out of automatic referrer assignment
+    pub referrer_opt_out: bool,
+    /// setReferrer has already been attempted for this session
+    pub referrer_applied: bool,
+    /// Capabilities granted to this session's API key
+    pub scopes: Vec<String>,
+    /// Coins the key may trade, when the SIWE message signed an asset allowlist
+    pub allowed_assets: Option<Vec<String>>,
+    /// User-held co-signer key that must approve every `/exchange` action
+    pub cosigner_address: Option<String>,
+    /// Progress through login -> quote registration -> agent approval
+    pub onboarding: OnboardingState,
+    /// Reverse-ENS name of the SIWE address, resolved in the background after login
+    pub ens_name: Option<String>,
+    /// Display language and timezone for reports and notifications
+    pub locale: Option<Locale>,
+    /// When the key was flagged for use from too many clients
+    pub abuse_flagged_at_ms: Option<u64>,
+    /// Lifetime granted at login; sliding expiration extends `expires_at` by this on use
+    pub ttl_secs: u64,
+    /// Expiry carried in the API key; sliding expiration never extends a session past it
+    pub max_expires_at: u64,
+}
+
+impl AgentSession {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+
+    pub fn is_expired(&self, now_secs: u64) -> bool {
+        self.expires_at <= now_secs
+    }
+}
+
+/// How long sessions last
+#[derive(Debug, Clone, Copy)]
+pub struct SessionLifetime {
+    /// Lifetime of a session whose login doesn't ask for one
+    pub default_ttl_secs: u64,
+    /// Longest lifetime a login may ask for, and the most a sliding session can last in total
+    pub max_ttl_secs: u64,
+    /// Extend a session by its lifetime whenever its key authenticates
+    pub sliding: bool,
+}
+
+impl Default for SessionLifetime {
+    fn default() -> Self {
+        Self { default_ttl_secs: 24 * 60 * 60, max_ttl_secs: 7 * 24 * 60 * 60, sliding: false }
+    }
+}
+
+impl SessionLifetime {
+    /// The lifetime to grant a login that asked for `requested` seconds
+    pub fn ttl_secs(&self, requested: Option<u64>) -> Result<u64, String> {
+        match requested {
+            None => Ok(self.default_ttl_secs),
+            Some(ttl) if (MIN_SESSION_TTL_SECS..=self.max_ttl_secs).contains(&ttl) => Ok(ttl),
+            Some(_) => Err(format!("ttl_secs must be between {} and {}", MIN_SESSION_TTL_SECS, self.max_ttl_secs)),
+        }
+    }
+}
+
+/// Agent manager for handling SIWE authentication and sessions.
+///
+/// Both maps are sharded so authenticating a request (every /exchange call) only locks the
+/// shard holding its key, and never waits on a login or session update elsewhere. Methods
+/// return copies rather than references, so no shard lock outlives the call.
+#[derive(Debug, Default)]
+pub struct AgentSessionManager {
+    /// Map API key -> AgentSession
+    sessions: DashMap<String, AgentSession>,
+    /// Map user address -> API key (for duplicate login handling)
+    user_to_api_key: DashMap<String, String>,
+    lifetime: SessionLifetime,
+}
+
+/// Why a SIWE key failed the session check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionRejection {
+    Unknown,
+    Expired,
+}
+
+impl AgentSessionManager {
+    pub fn new() -> Self {
+        Self::with_lifetime(SessionLifetime::default())
+    }
+
+    pub fn with_lifetime(lifetime: SessionLifetime) -> Self {
+        Self {
+            sessions: DashMap::new(),
+            user_to_api_key: DashMap::new(),
+            lifetime,
+        }
+    }
+
+    pub fn lifetime(&self) -> SessionLifetime {
+        self.lifetime
+    }
+
+    /// The user's live session, or a new one lasting `ttl_secs`; true when it already existed.
+    ///
+    /// A live session is replaced, and its key stops working, when the login signed grants that
+    /// differ from the session's. Holds the user's entry throughout, so concurrent logins by one
+    /// user get one session.
+    pub fn login(&self, user_address: Address, grants: LoginGrants, ttl_secs: u64) -> Result<(AgentSession, bool), Box<dyn std::error::Error + Send + Sync>> {
+        let now = now_secs();
+        let user_address = user_address.to_lower_hex();
+        let mut entry = self.user_to_api_key.entry(user_address.clone()).or_default();
+        if let Some(existing) = self.sessions.get(entry.value()).filter(|s| !s.is_expired(now)) {
+            if !grants.signed || grants.matches(&existing) {
+                return Ok((existing.clone(), true));
+            }
+            info!("🔏 Signed grants differ from the live session of {}; replacing it", user_address);
+        }
+
+        let session = self.new_session(user_address, grants.scopes, grants.allowed_assets, ttl_secs, now)?;
+        // Replace the user's expired or superseded session
+        self.sessions.remove(entry.value());
+        self.sessions.insert(session.api_key.clone(), session.clone());
+        *entry = session.api_key.clone();
+
+        info!("👤 Created session for user: {}", session.user_address);
+        info!("🤖 Agent address: {}", session.agent_address);
+        info!("🔑 API key: {}", session.api_key);
+
+        Ok((session, false))
+    }
+
+    fn new_session(&self, user_address: String, scopes: Vec<String>, allowed_assets: Option<Vec<String>>, ttl_secs: u64, now: u64) -> Result<AgentSession, Box<dyn std::error::Error + Send + Sync>> {
+        // Get preset TDX data
+        let preset_data = PresetTDXData::get()
+            .ok_or("Preset TDX data not initialized")?;
+
+        let expires_at = now + ttl_secs;
+        // A sliding session's key must outlive every extension
+        let max_expires_at = if self.lifetime.sliding { now + self.lifetime.max_ttl_secs.max(ttl_secs) } else { expires_at };
+
+        // Attested API key: a MAC over (user, agent, expiry) only this enclave can produce
+        let api_key = api_keys::issue(&user_address, max_expires_at)?;
+
+        Ok(AgentSession {
+            user_address,
+            agent_address: preset_data.agent_address.clone(),
+            api_key,
+            created_at: now,
+            expires_at,
+            referrer_code: None,
+            referrer_opt_out: false,
+            referrer_applied: false,
+            scopes,
+            allowed_assets,
+            cosigner_address: None,
+            onboarding: OnboardingState::LoggedIn,
+            ens_name: None,
+            locale: None,
+            abuse_flagged_at_ms: None,
+            ttl_secs,
+            max_expires_at,
+        })
+    }
+
+    /// Check a key has a live session, sliding its expiry forward when that's enabled.
+    ///
+    /// Extensions happen at most once per SLIDE_GRANULARITY_SECS, so most calls only read.
+    pub fn authenticate(&self, api_key: &str, now_secs: u64) -> Result<(), SessionRejection> {
+        let slide_to = {
+            let session = self.sessions.get(api_key).ok_or(SessionRejection::Unknown)?;
+            if session.is_expired(now_secs) {
+                return Err(SessionRejection::Expired);
+            }
+            let slide_to = (now_secs + session.ttl_secs).min(session.max_expires_at);
+            (self.lifetime.sliding && slide_to >= session.expires_at + SLIDE_GRANULARITY_SECS).then_some(slide_to)
+        };
+        if let Some(slide_to) = slide_to {
+            if let Some(mut session) = self.sessions.get_mut(api_key) {
+                session.expires_at = session.expires_at.max(slide_to);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get session by API key
+    pub fn get_session(&self, api_key: &str) -> Option<AgentSession> {
+        self.sessions.get(api_key).map(|session| session.clone())
+    }
+
+    /// Read one field of a session without copying the rest
+    pub fn with_session<T>(&self, api_key: &str, f: impl FnOnce(&AgentSession) -> T) -> Option<T> {
+        self.sessions.get(api_key).map(|session| f(&session))
+    }
+
+    /// Change a session in place; returns the updated copy
+    fn update(&self, api_key: &str, f: impl FnOnce(&mut AgentSession)) -> Option<AgentSession> {
+        let mut session = self.sessions.get_mut(api_key)?;
+        f(&mut session);
+        Some(session.clone())
+    }
+
+    /// Addresses of every user with a session
+    pub fn user_addresses(&self) -> Vec<String> {
+        self.user_to_api_key.iter().map(|entry| entry.key().clone()).collect()
+    }
+
+    pub fn get_user_session(&self, user_address: &str) -> Option<AgentSession> {
+        let api_key = self.user_to_api_key.get(&address::normalize(user_address))?.value().clone();
+        self.get_session(&api_key)
+    }
+
+    /// Override or opt out of the referrer code applied on the session's first trade
+    pub fn set_referrer_preference(&self, api_key: &str, code: Option<String>, opt_out: bool) -> Option<AgentSession> {
+        self.update(api_key, |session| {
+            session.referrer_code = code;
+            session.referrer_opt_out = opt_out;
+        })
+    }
+
+    /// Referrer code to apply before this session's next trade, if one is still pending.
+    ///
+    /// Marks it applied in the same step, so setReferrer is attempted once, not on every order.
+    pub fn take_pending_referrer(&self, api_key: &str, default_code: Option<&str>) -> Option<String> {
+        let mut session = self.sessions.get_mut(api_key)?;
+        if session.referrer_applied || session.referrer_opt_out {
+            return None;
+        }
+        let code = session.referrer_code.clone().or_else(|| default_code.map(|c| c.to_string()));
+        if code.is_some() {
+            session.referrer_applied = true;
+        }
+        code
+    }
+
+    /// Register or clear the co-signer that must approve this session's actions
+    pub fn set_cosigner(&self, api_key: &str, cosigner_address: Option<String>) -> Option<AgentSession> {
+        self.update(api_key, |session| session.cosigner_address = cosigner_address)
+    }
+
+    /// Attach the resolved ENS name to a session
+    pub fn set_ens_name(&self, api_key: &str, ens_name: Option<String>) {
+        self.update(api_key, |session| session.ens_name = ens_name);
+    }
+
+    /// Set or clear the session's display locale
+    pub fn set_locale(&self, api_key: &str, locale: Option<Locale>) -> Option<AgentSession> {
+        self.update(api_key, |session| session.locale = locale)
+    }
+
+    /// Locale of the user's current session
+    pub fn locale_for_user(&self, user_address: &str) -> Option<Locale> {
+        self.sessions.iter()
+            .find(|session| address::same(&session.user_address, user_address))
+            .and_then(|session| session.locale.clone())
+    }
+
+    /// Flag a session whose key looks shared; returns its user
+    pub fn flag_abuse(&self, api_key: &str, at_ms: u64) -> Option<String> {
+        self.update(api_key, |session| {
+            session.abuse_flagged_at_ms.get_or_insert(at_ms);
+        }).map(|session| session.user_address)
+    }
+
+    /// Move a session's onboarding forward (never backward); returns the resulting state
+    pub fn advance_onboarding(&self, api_key: &str, onboarding: OnboardingState) -> Option<OnboardingState> {
+        self.update(api_key, |session| {
+            if onboarding > session.onboarding {
+                info!("🧭 Onboarding for {}: {:?} -> {:?}", session.user_address, session.onboarding, onboarding);
+                session.onboarding = onboarding;
+            }
+        }).map(|session| session.onboarding)
+    }
+
+    /// Validate API key and return associated agent address
+    pub fn validate_api_key(&self, api_key: &str) -> Option<String> {
+        self.with_session(api_key, |session| session.agent_address.clone())
+    }
+
+    /// End and forget every session of a user; returns their API keys
+    pub fn remove_user(&self, user_address: &str) -> Vec<String> {
+        let api_keys: Vec<String> = self.sessions.iter()
+            .filter(|session| address::same(&session.user_address, user_address))
+            .map(|session| session.api_key.clone())
+            .collect();
+        for api_key in &api_keys {
+            self.sessions.remove(api_key);
+        }
+        self.user_to_api_key.retain(|user, _| !address::same(user, user_address));
+        api_keys
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Agents API handlers
+#[derive(Default)]
+pub struct AgentsAPI {
+    pub session_manager: Arc<AgentSessionManager>,
+}
+
+impl AgentsAPI {
+    pub fn new() -> Self {
+        Self {
+            session_manager: Arc::new(AgentSessionManager::new()),
+        }
+    }
+}
+
+/// Scopes for a new key: those signed as SIWE resources (just `trade` when none were signed),
+/// narrowed to the ones the body asks for
+fn resolve_scopes(signed: Option<Vec<String>>, requested: Option<Vec<String>>) -> Result<Vec<String>, String> {
+    let signed = signed.unwrap_or_else(|| vec![SCOPE_TRADE.to_string()]);
+    let Some(requested) = requested else {
+        return Ok(signed);
+    };
+    if let Some(unknown) = requested.iter().find(|s| !KNOWN_SCOPES.contains(&s.as_str())) {
+        return Err(format!("Unknown scope requested: {}", unknown));
+    }
+    if let Some(unsigned) = requested.iter().find(|s| !signed.contains(s)) {
+        return Err(format!("Scope {} must be signed as a urn:vas:scope: resource in the SIWE message", unsigned));
+    }
+    Ok(requested)
+}
+
+/// POST /agents/login - SIWE authentication
+pub async fn agents_login(
+    State(session_manager): State<Arc<AgentSessionManager>>,
+    Json(payload): Json<SiweLoginRequest>,
+) -> Result<Json<SiweLoginResponse>, (StatusCode, Json<SiweLoginError>)> {
+    info!("🔐 Processing SIWE login request");
+
+    // Validate SIWE signature
+    let siwe_message = match verify_siwe_message(&payload.message, &payload.signature).await {
+        Ok(siwe_message) => siwe_message,
+        Err(e) => {
+            warn!("❌ SIWE authentication failed: {}", e);
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(SiweLoginError {
+                    success: false,
+                    error: format!("SIWE authentication failed: {}", e),
+                    code: 401,
+                })
+            ));
+        }
+    };
+
+    let user_address = Address::from(siwe_message.address);
+    info!("✅ SIWE authentication successful for: {}", user_address);
+
+    let bad_request = |error: String| (
+        StatusCode::BAD_REQUEST,
+        Json(SiweLoginError {
+            success: false,
+            error,
+            code: 400,
+        })
+    );
+
+    // Scopes and assets signed as SIWE resources bound the key; the body may only narrow them
+    let grants = ResourceGrants::from_message(&siwe_message).map_err(bad_request)?;
+    let signed = grants.scopes.is_some() || grants.assets.is_some();
+    let scopes = resolve_scopes(grants.scopes, payload.scopes.clone()).map_err(bad_request)?;
+
+    let ttl_secs = session_manager.lifetime().ttl_secs(payload.ttl_secs).map_err(bad_request)?;
+
+    let preset_data = PresetTDXData::get().unwrap();
+    let grants = LoginGrants { scopes, allowed_assets: grants.assets, signed };
+    match session_manager.login(user_address, grants, ttl_secs) {
+        Ok((session, existing)) => {
+            let message = if existing {
+                info!("👤 User already has active session, returning existing data");
+                "Existing session found. Use this TDX quote and API key."
+            } else {
+                info!("🎉 New agent session created successfully");
+                "Agent wallet generated. Submit tdx_quote_hex to HyperEVM registry, then approve agent with Hyperliquid."
+            };
+
+            Ok(Json(SiweLoginResponse {
+                success: true,
+                user_address: session.user_address,
+                api_key: session.api_key,
+                agent_address: session.agent_address,
+                tdx_quote_hex: hex::encode(&preset_data.tdx_quote),
+                message: message.to_string(),
+                expires_at: session.expires_at.to_string(),
+                max_expires_at: session.max_expires_at.to_string(),
+                scopes: session.scopes,
+                allowed_assets: session.allowed_assets,
+            }))
+        }
+        Err(e) => {
+            error!("❌ Failed to create agent session: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(SiweLoginError {
+                    success: false,
+                    error: format!("Failed to create agent session: {}", e),
+                    code: 500,
+                })
+            ))
+        }
+    }
+}
+
+/// GET /agents/quote - Get TDX quote for verification
+pub async fn agents_quote() -> Result<Json<Value>, StatusCode> {
+    info!("📋 TDX quote requested");
+
+    let preset_data = PresetTDXData::get()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let response = preset_data.create_quote_response();
+    
+    info!("📤 Returning TDX quote: {} bytes", response.quote_size);
+    
+    Ok(Json(serde_json::to_value(response).unwrap()))
+}
+
+/// Referrer preference update for the caller's session
+#[derive(Debug, Deserialize)]
+pub struct ReferrerPreferenceRequest {
+    pub code: Option<String>,
+    #[serde(default)]
+    pub opt_out: bool,
+}
+
+/// PUT /me/referrer - Override or opt out of the server-configured referrer code
+pub async fn set_referrer_preference(
+    State(session_manager): State<Arc<AgentSessionManager>>,
+    headers: HeaderMap,
+    Json(payload): Json<ReferrerPreferenceRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = crate::auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let session = session_manager
+        .set_referrer_preference(api_key, payload.code, payload.opt_out)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    info!("🏷️ Referrer preference updated for {}: code={:?}, opt_out={}",
+        session.user_address, session.referrer_code, session.referrer_opt_out);
+
+    Ok(Json(serde_json::json!({
+        "referrer_code": session.referrer_code,
+        "opt_out": session.referrer_opt_out,
+        "applied": session.referrer_applied
+    })))
+}
+
+/// GET /debug/sessions - Debug endpoint to view active sessions
+pub async fn debug_sessions(
+    State(session_manager): State<Arc<AgentSessionManager>>,
+) -> Json<Value> {
+    let session_count = session_manager.sessions.len();
+    let user_count = session_manager.user_to_api_key.len();
+    
+    info!("📊 Debug: {} active sessions, {} users", session_count, user_count);
+    
+    Json(serde_json::json!({
+        "active_sessions": session_count,
+        "authenticated_users": user_count,
+        "note": "Session details not exposed for security"
+    }))
+}
+
+/// GET /admin/sessions - Active sessions with their identities, for operators
+pub async fn admin_sessions(
+    State(session_manager): State<Arc<AgentSessionManager>>,
+) -> Json<Value> {
+    let sessions: Vec<Value> = session_manager.sessions.iter().map(|session| serde_json::json!({
+        "user_address": session.user_address,
+        "ens_name": session.ens_name,
+        "onboarding": session.onboarding,
+        "abuse_flagged_at_ms": session.abuse_flagged_at_ms,
+        "created_at": session.created_at,
+        "expires_at": session.expires_at,
+        "max_expires_at": session.max_expires_at
+    })).collect();
+    
+    info!("📊 Admin: listed {} sessions", sessions.len());
+
+    Json(serde_json::json!({
+        "active_sessions": sessions.len(),
+        "authenticated_users": session_manager.user_to_api_key.len(),
+        "sessions": sessions,
+        "note": "API keys not exposed for security"
+    }))
+}
+
+// TODO: Add session cleanup for expired sessions
+// TODO: Implement API key rotation
+// TODO: Add rate limiting for SIWE authentication
+// TODO: Add proper nonce tracking for replay protection
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_assets_of_every_asset_bearing_action() {
+        let cases = [
+            (serde_json::json!({"type": "order", "orders": [{"a": 3}, {"a": 1}, {"a": 3}]}), vec![1, 3]),
+            (serde_json::json!({"type": "cancel", "cancels": [{"a": 4, "o": 1}]}), vec![4]),
+            (serde_json::json!({"type": "cancelByCloid", "cancels": [{"asset": 5, "cloid": "0x01"}]}), vec![5]),
+            (serde_json::json!({"type": "modify", "oid": 1, "order": {"a": 6}}), vec![6]),
+            (serde_json::json!({"type": "batchModify", "modifies": [{"oid": 1, "order": {"a": 7}}]}), vec![7]),
+            (serde_json::json!({"type": "twapOrder", "twap": {"a": 8}}), vec![8]),
+            (serde_json::json!({"type": "updateLeverage", "asset": 9, "leverage": 5}), vec![9]),
+            (serde_json::json!({"type": "updateIsolatedMargin", "asset": 10, "ntli": 1}), vec![10]),
+            (serde_json::json!({"type": "usdClassTransfer", "amount": "1", "toPerp": true}), vec![]),
+        ];
+        for (action, expected) in cases {
+            assert_eq!(action_asset_indices(&action), expected, "{}", action);
+        }
+    }
+
+    #[test]
+    fn signed_grants_match_in_any_order() {
+        let session_grants = |scopes: &[&str], assets: Option<&[&str]>| LoginGrants {
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            allowed_assets: assets.map(|a| a.iter().map(|s| s.to_string()).collect()),
+            signed: true,
+        };
+        let session = AgentSession {
+            user_address: String::new(),
+            agent_address: String::new(),
+            api_key: String::new(),
+            created_at: 0,
+            expires_at: 0,
+            referrer_code: None,
+            referrer_opt_out: false,
+            referrer_applied: false,
+            scopes: vec![SCOPE_TRADE.to_string(), SCOPE_TRANSFER.to_string()],
+            allowed_assets: Some(vec!["ETH".to_string(), "BTC".to_string()]),
+            cosigner_address: None,
+            onboarding: OnboardingState::LoggedIn,
+            ens_name: None,
+            locale: None,
+            abuse_flagged_at_ms: None,
+            ttl_secs: 0,
+            max_expires_at: 0,
+        };
+        assert!(session_grants(&[SCOPE_TRANSFER, SCOPE_TRADE], Some(&["BTC", "ETH"])).matches(&session));
+        assert!(!session_grants(&[SCOPE_TRADE, SCOPE_TRANSFER], Some(&["ETH"])).matches(&session));
+        assert!(!session_grants(&[SCOPE_TRADE, SCOPE_TRANSFER], None).matches(&session));
+        assert!(!session_grants(&[SCOPE_TRADE], Some(&["ETH", "BTC"])).matches(&session));
+    }
+
+    #[test]
+    fn unsigned_scopes_grant_trade_only() {
+        let scopes = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        // No urn:vas:scope: resources: the body can't widen past trade
+        assert!(resolve_scopes(None, Some(scopes(&[SCOPE_TRANSFER]))).is_err());
+        assert!(resolve_scopes(None, Some(scopes(&[SCOPE_TRADE, SCOPE_EVM]))).is_err());
+        assert_eq!(resolve_scopes(None, None).unwrap(), scopes(&[SCOPE_TRADE]));
+        assert_eq!(resolve_scopes(None, Some(scopes(&[SCOPE_TRADE]))).unwrap(), scopes(&[SCOPE_TRADE]));
+
+        // Signed scopes can be narrowed, not widened
+        let signed = Some(scopes(&[SCOPE_TRADE, SCOPE_TRANSFER]));
+        assert_eq!(resolve_scopes(signed.clone(), Some(scopes(&[SCOPE_TRANSFER]))).unwrap(), scopes(&[SCOPE_TRANSFER]));
+        assert!(resolve_scopes(signed.clone(), Some(scopes(&[SCOPE_EVM]))).is_err());
+        assert_eq!(resolve_scopes(signed, None).unwrap(), scopes(&[SCOPE_TRADE, SCOPE_TRANSFER]));
+    }
+}
@@ -0,0 +1,192 @@
+use axum::response::Json;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use secp256k1::SecretKey;
+
+use crate::preset_tdx::PresetTDXData;
+
+/// Prefix of session API keys
+pub const API_KEY_PREFIX: &str = "ak_";
+/// Bytes of the HMAC tag kept in the key
+const TAG_LEN: usize = 16;
+
+/// What an attested API key encodes
+#[derive(Debug, Clone)]
+pub struct IssuedKey {
+    pub user_address: String,
+    pub expires_at: u64,
+}
+
+/// The agent key and address keys are issued under
+struct Issuer<'a> {
+    agent_private_key: &'a SecretKey,
+    agent_address: &'a str,
+}
+
+impl<'a> Issuer<'a> {
+    fn current() -> Result<Self, String> {
+        let preset_data = PresetTDXData::get().ok_or("Preset TDX data not initialized")?;
+        Ok(Self { agent_private_key: &preset_data.agent_private_key, agent_address: &preset_data.agent_address })
+    }
+
+    /// MAC key derived from the agent key, so only this enclave generation (the one whose quote
+    /// vouches for the agent address) can issue or validate keys
+    fn mac_key(&self) -> [u8; 32] {
+        Sha256::new()
+            .chain_update(b"vas-api-key-mac")
+            .chain_update(self.agent_private_key.secret_bytes())
+            .finalize()
+            .into()
+    }
+
+    fn tag(&self, user_address: &str, expires_at: u64) -> Hmac<Sha256> {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.mac_key()).expect("HMAC accepts any key length");
+        mac.update(user_address.to_lowercase().as_bytes());
+        mac.update(b"|");
+        mac.update(self.agent_address.to_lowercase().as_bytes());
+        mac.update(b"|");
+        mac.update(expires_at.to_string().as_bytes());
+        mac
+    }
+}
+
+/// Issue `ak_<user address>_<expiry>_<tag>`, where the tag is HMAC-SHA256 over
+/// (user address, agent address, expiry) under the enclave-held MAC key
+pub fn issue(user_address: &str, expires_at: u64) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(issue_with(&Issuer::current()?, user_address, expires_at))
+}
+
+fn issue_with(issuer: &Issuer, user_address: &str, expires_at: u64) -> String {
+    let tag = issuer.tag(user_address, expires_at).finalize().into_bytes();
+    format!(
+        "{}{}_{}_{}",
+        API_KEY_PREFIX,
+        user_address.to_lowercase().trim_start_matches("0x"),
+        expires_at,
+        hex::encode(&tag[..TAG_LEN])
+    )
+}
+
+/// Check a key's tag and expiry without any session state
+pub fn verify(api_key: &str) -> Result<IssuedKey, String> {
+    verify_with(&Issuer::current()?, api_key, now_secs())
+}
+
+fn verify_with(issuer: &Issuer, api_key: &str, now_secs: u64) -> Result<IssuedKey, String> {
+    let mut parts = api_key.strip_prefix(API_KEY_PREFIX).ok_or("Not a session API key")?.split('_');
+    let (Some(user), Some(expires_at), Some(tag_hex), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return Err("Malformed API key".to_string());
+    };
+    let user_address = format!("0x{}", user);
+    let expires_at: u64 = expires_at.parse().map_err(|_| "Malformed API key expiry")?;
+    let provided = hex::decode(tag_hex).map_err(|_| "Malformed API key tag")?;
+
+    if provided.len() != TAG_LEN {
+        return Err("Malformed API key tag".to_string());
+    }
+    issuer.tag(&user_address, expires_at)
+        .verify_truncated_left(&provided)
+        .map_err(|_| "API key was not issued by this enclave generation")?;
+    if expires_at <= now_secs {
+        return Err("API key expired".to_string());
+    }
+
+    Ok(IssuedKey { user_address, expires_at })
+}
+
+/// Body of POST /agents/verify-key
+#[derive(Debug, Deserialize)]
+pub struct VerifyKeyRequest {
+    pub api_key: String,
+}
+
+/// POST /agents/verify-key - Whether an API key was issued by this enclave generation.
+///
+/// Keys are bound to the agent address in the served quote, so a key that verifies here
+/// was minted inside the attested enclave; keys from other generations or deployments fail.
+pub async fn verify_key(Json(payload): Json<VerifyKeyRequest>) -> Json<Value> {
+    let preset_data = PresetTDXData::get();
+    let result = verify(&payload.api_key);
+    info!("🔑 API key verification: {}", if result.is_ok() { "valid" } else { "invalid" });
+
+    Json(match result {
+        Ok(issued) => serde_json::json!({
+            "valid": true,
+            "user_address": issued.user_address,
+            "expires_at": issued.expires_at,
+            "agent_address": preset_data.map(|p| &p.agent_address),
+            "quote_id": preset_data.map(|p| &p.quote_id)
+        }),
+        Err(reason) => serde_json::json!({
+            "valid": false,
+            "reason": reason,
+            "agent_address": preset_data.map(|p| &p.agent_address),
+            "quote_id": preset_data.map(|p| &p.quote_id)
+        }),
+    })
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    const EXPIRES_AT: u64 = 1_700_086_400;
+    const NOW: u64 = 1_700_000_000;
+
+    fn issuer<'a>(key: &'a SecretKey, agent_address: &'a str) -> Issuer<'a> {
+        Issuer { agent_private_key: key, agent_address }
+    }
+
+    #[test]
+    fn issued_key_round_trips() {
+        let key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let issuer = issuer(&key, "0x00000000000000000000000000000000000000aa");
+        let api_key = issue_with(&issuer, USER, EXPIRES_AT);
+        assert!(api_key.starts_with("ak_5aaeb6053f3e94c9b9a09f33669435e7ef1beaed_1700086400_"), "{}", api_key);
+
+        let issued = verify_with(&issuer, &api_key, NOW).unwrap();
+        assert_eq!(issued.user_address, USER.to_lowercase());
+        assert_eq!(issued.expires_at, EXPIRES_AT);
+        assert!(verify_with(&issuer, &api_key, EXPIRES_AT).unwrap_err().contains("expired"));
+    }
+
+    #[test]
+    fn tampered_keys_are_refused() {
+        let key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let issuer = issuer(&key, "0x00000000000000000000000000000000000000aa");
+        let api_key = issue_with(&issuer, USER, EXPIRES_AT);
+
+        let other_user = api_key.replace("5aaeb605", "5aaeb606");
+        let later_expiry = api_key.replace("_1700086400_", "_1800086400_");
+        let mut flipped_tag = api_key.clone();
+        let last = flipped_tag.pop().unwrap();
+        flipped_tag.push(if last == '0' { '1' } else { '0' });
+        for tampered in [other_user, later_expiry, flipped_tag] {
+            assert!(verify_with(&issuer, &tampered, NOW).unwrap_err().contains("not issued"), "{}", tampered);
+        }
+        assert!(verify_with(&issuer, &format!("{}_extra", api_key), NOW).is_err());
+        assert!(verify_with(&issuer, &api_key[..api_key.len() - 2], NOW).is_err());
+    }
+
+    #[test]
+    fn keys_are_bound_to_the_enclave_generation() {
+        let key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let api_key = issue_with(&issuer(&key, "0x00000000000000000000000000000000000000aa"), USER, EXPIRES_AT);
+
+        let rotated = SecretKey::from_slice(&[2u8; 32]).unwrap();
+        assert!(verify_with(&issuer(&rotated, "0x00000000000000000000000000000000000000aa"), &api_key, NOW).is_err());
+        assert!(verify_with(&issuer(&key, "0x00000000000000000000000000000000000000bb"), &api_key, NOW).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+use alloy::primitives::{eip191_hash_message, keccak256};
+use alloy::sol_types::SolCall;
+use axum::{
+    extract::{Query, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, error};
+
+use crate::address::{self, Address};
+use crate::audit::AUDIT_STATEMENT;
+use crate::canonical_json;
+use crate::error_codes::{self, ErrorCode};
+use crate::evm::rpc_call;
+use crate::notify::{Notification, NotificationKind};
+use crate::preset_tdx::PresetTDXData;
+use crate::safe_mode;
+use crate::AppState;
+
+/// TD report body offsets in a TDX v4 quote (after the 48-byte header)
+const TEE_TCB_SVN: (usize, usize) = (48, 16);
+const MRSEAM: (usize, usize) = (64, 48);
+const MRTD: (usize, usize) = (184, 48);
+const RTMR0: usize = 376;
+const MEASUREMENT_LEN: usize = 48;
+/// REPORTDATA of the TD report: 32 free bytes, the protocol id, then the agent address
+const REPORT_DATA: (usize, usize) = (568, 64);
+const PROTOCOL_ID: &[u8; 12] = b"HYPERLIQUID\0";
+const PROTOCOL_ID_OFFSET: usize = 32;
+const AGENT_ADDRESS_OFFSET: usize = 44;
+
+/// Measurement registers of a TDX v4 quote, hex-encoded.
+///
+/// `tee_tcb_svn` and `mrseam` change when the platform's TDX module or microcode is updated;
+/// `mrtd` and the RTMRs identify the build and change only with the image.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Measurements {
+    pub tee_tcb_svn: String,
+    pub mrseam: String,
+    pub mrtd: String,
+    pub rtmr: [String; 4],
+}
+
+impl Measurements {
+    pub fn parse(quote: &[u8]) -> Option<Self> {
+        let field = |(offset, len): (usize, usize)| quote.get(offset..offset + len).map(hex::encode);
+        Some(Self {
+            tee_tcb_svn: field(TEE_TCB_SVN)?,
+            mrseam: field(MRSEAM)?,
+            mrtd: field(MRTD)?,
+            rtmr: [
+                field((RTMR0, MEASUREMENT_LEN))?,
+                field((RTMR0 + MEASUREMENT_LEN, MEASUREMENT_LEN))?,
+                field((RTMR0 + 2 * MEASUREMENT_LEN, MEASUREMENT_LEN))?,
+                field((RTMR0 + 3 * MEASUREMENT_LEN, MEASUREMENT_LEN))?,
+            ],
+        })
+    }
+}
+
+/// Agent address a quote binds in its report data, lowercase, as the registry contract reads it
+pub fn bound_agent_address(quote: &[u8]) -> Result<String, String> {
+    let (offset, len) = REPORT_DATA;
+    let report_data = quote.get(offset..offset + len).ok_or("Quote too short to carry report data")?;
+    if &report_data[PROTOCOL_ID_OFFSET..AGENT_ADDRESS_OFFSET] != PROTOCOL_ID {
+        return Err("Quote report data lacks the HYPERLIQUID protocol id".to_string());
+    }
+    let bytes: [u8; 20] = report_data[AGENT_ADDRESS_OFFSET..].try_into().map_err(|_| "Malformed report data")?;
+    Ok(Address::from(bytes).to_lower_hex())
+}
+
+alloy::sol! {
+    struct AgentRecord {
+        address agentAddress;
+        address registeredBy;
+        bytes32 mrTd;
+        bytes32 mrConfigId;
+        bytes32 mrOwner;
+        uint8 tcbStatus;
+        uint256 timestamp;
+    }
+    function getLatestAgentRecord(address agentAddress) external view returns (AgentRecord memory record);
+}
+
+/// Check `agent_address` is bound by a quote the HyperEVM registry verified with Automata DCAP,
+/// and that the latest registered build has one of `mrtds`.
+///
+/// The key only exists inside the TD that generated it, so a signature recovering to an
+/// address that passes this check comes from an attested build.
+pub async fn check_registered_key(rpc: &str, registry: &str, agent_address: &str, mrtds: &[Vec<u8>]) -> Result<(), String> {
+    let call = getLatestAgentRecordCall { agentAddress: agent_address.parse().map_err(|e| format!("Invalid agent address: {}", e))? };
+    let result = rpc_call(rpc, "eth_call", serde_json::json!([
+        {"to": registry, "data": format!("0x{}", hex::encode(call.abi_encode()))},
+        "latest"
+    ])).await.map_err(|e| format!("No registry record for {}: {}", agent_address, e))?;
+
+    let bytes = hex::decode(result.as_str().ok_or("eth_call returned non-string")?.trim_start_matches("0x"))
+        .map_err(|e| format!("Malformed registry response: {}", e))?;
+    let record = getLatestAgentRecordCall::abi_decode_returns(&bytes)
+        .map_err(|e| format!("Malformed registry record: {}", e))?;
+    if !mrtds.iter().any(|mrtd| keccak256(mrtd) == record.mrTd) {
+        return Err(format!("{} is registered from a build whose MRTD is not allowed", agent_address));
+    }
+    Ok(())
+}
+
+/// Check a quote binds `agent_address`, so the key we sign with is the one users verified
+pub fn check_key_binding(quote: &[u8], agent_address: &str) -> Result<(), String> {
+    let bound = bound_agent_address(quote)?;
+    if !address::same(&bound, agent_address) {
+        return Err(format!("Quote binds agent {} but the loaded key is {}", bound, agent_address));
+    }
+    Ok(())
+}
+
+/// Refuse /agents and /agents/* while the agent key and the served quote disagree: a
+/// login there would hand out a quote that does not vouch for the key actually signing
+pub async fn require_key_binding(request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    if path == "/agents" || path.starts_with("/agents/") {
+        if let Some(error) = PresetTDXData::get().and_then(|data| data.key_binding_error.clone()) {
+            return (StatusCode::SERVICE_UNAVAILABLE, Json(error_codes::err_body(ErrorCode::KeyQuoteMismatch, error))).into_response();
+        }
+    }
+    next.run(request).await
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Query parameters for GET /attestation/inactivity (times in unix ms)
+#[derive(Debug, Deserialize)]
+pub struct InactivityQuery {
+    pub user: String,
+    pub from: u64,
+    pub to: u64,
+}
+
+/// GET /attestation/inactivity?user=&from=&to= - Enclave-signed statement that the agent
+/// produced no signatures for `user` in the range, derived from the transparency log
+pub async fn inactivity_statement(
+    State(state): State<AppState>,
+    Query(query): Query<InactivityQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    if query.user.parse::<alloy::primitives::Address>().is_err() || query.from > query.to {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if query.to > now_ms() {
+        return Ok(Json(error_codes::err_body(ErrorCode::InvalidRange, "Range must end in the past")));
+    }
+
+    let (count, started_at, head) = {
+        let log = state.audit.read().await;
+        let head = log.head().map(|h| serde_json::json!({"seq": h.seq, "entry_hash": h.entry_hash}));
+        (log.signatures_for(&query.user, query.from, query.to).len(), log.started_at_ms(), head)
+    };
+
+    if query.from < started_at {
+        return Ok(Json(error_codes::err_body(ErrorCode::InvalidRange, format!("Transparency log only covers activity since {}", started_at))));
+    }
+    if count > 0 {
+        info!("🧾 Inactivity refused for {}: {} signatures in range", query.user, count);
+        return Ok(Json(error_codes::err_body(ErrorCode::InactivityRefused, format!("Agent produced {} signatures for this user in the range", count))));
+    }
+
+    let preset_data = PresetTDXData::get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let statement = serde_json::json!({
+        "type": "inactivity",
+        "user": query.user.to_lowercase(),
+        "from": query.from,
+        "to": query.to,
+        "signature_count": 0,
+        "log_head": head,
+        "agent_address": preset_data.agent_address,
+        "quote_id": preset_data.quote_id,
+        "issued_at": now_ms()
+    });
+
+    // EIP-191 over the statement JSON so any personal_sign verifier can check it
+    let message = canonical_json::to_string(&statement);
+    let hash = eip191_hash_message(message.as_bytes());
+    let signature = state.signer
+        .sign_digest(hash, None, AUDIT_STATEMENT, statement.clone())
+        .await
+        .map_err(|e| {
+            error!("❌ Failed to sign inactivity statement: {:?}", e);
+            StatusCode::BAD_GATEWAY
+        })?;
+
+    info!("🧾 Issued inactivity statement for {} [{}, {}]", query.user, query.from, query.to);
+
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "response": {
+            "statement": message,
+            "signature": signature.to_json()
+        }
+    })))
+}
+
+/// Query parameters for GET /attestation/history
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    /// Only return the quote active at this unix-ms timestamp
+    pub at: Option<u64>,
+}
+
+/// GET /attestation/history - Every quote served, or the one active at `?at=`
+pub async fn quote_history(
+    State(state): State<AppState>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let archive = state.quote_archive.read().await;
+
+    match query.at {
+        Some(at) => {
+            let record = archive.active_at(at).ok_or(StatusCode::NOT_FOUND)?;
+            Ok(Json(serde_json::json!({ "quote": record })))
+        }
+        None => Ok(Json(serde_json::json!({ "quotes": archive.records() }))),
+    }
+}
+
+/// GET /attestation/measurements - Measurements of the quote being served and of every earlier one
+pub async fn measurements(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    let preset_data = PresetTDXData::get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let archive = state.quote_archive.read().await;
+    let history: Vec<Value> = archive.records().iter().rev()
+        .map(|record| serde_json::json!({
+            "quote_id": record.quote_id,
+            "active_from_ms": record.active_from_ms,
+            "superseded": record.superseded,
+            "measurements": hex::decode(&record.tdx_quote_hex).ok().and_then(|quote| Measurements::parse(&quote))
+        }))
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "quote_id": preset_data.quote_id,
+        "agent_address": preset_data.agent_address,
+        "measurements": Measurements::parse(&preset_data.tdx_quote),
+        "history": history
+    })))
+}
+
+/// Body of POST /admin/tcb-recovery
+#[derive(Debug, Deserialize)]
+pub struct TcbRecoveryRequest {
+    /// Why the quote is being replaced, e.g. the microcode or TDX module update applied
+    pub reason: String,
+}
+
+/// POST /admin/tcb-recovery - Serve a regenerated quote after a platform TCB update.
+///
+/// Runs `REATTEST_COMMAND`, checks the new quote attests the same build, then serves it,
+/// marks earlier quotes superseded in the archive and tells every user with a session that
+/// re-verifying the attestation is recommended. A successful recovery also ends safe mode.
+pub async fn tcb_recovery(
+    State(state): State<AppState>,
+    Json(request): Json<TcbRecoveryRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    if request.reason.trim().is_empty() {
+        return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, "A reason is required")));
+    }
+    let previous = PresetTDXData::get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    if let Err(e) = safe_mode::regenerate_quote(&state.config).await {
+        return Ok(Json(error_codes::err_body(ErrorCode::InternalError, e)));
+    }
+    let quote = match PresetTDXData::read_quote().and_then(|quote| safe_mode::check_measurement(&quote).map(|_| quote)) {
+        Ok(quote) => quote,
+        Err(e) => return Ok(Json(error_codes::err_body(ErrorCode::InternalError, e))),
+    };
+    if let Err(e) = check_key_binding(&quote, &previous.agent_address) {
+        return Ok(Json(error_codes::err_body(ErrorCode::KeyQuoteMismatch, e)));
+    }
+    if quote == previous.tdx_quote {
+        return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, "Regenerated quote is identical to the one being served")));
+    }
+
+    let current = match PresetTDXData::install_quote(quote) {
+        Ok(current) => current,
+        Err(e) => return Ok(Json(error_codes::err_body(ErrorCode::InternalError, e))),
+    };
+    state.quote_archive.write().await.supersede(current, &request.reason);
+    // The remote signer backend is told which quote vouches for this instance
+    let backend = (state.config.signer_backend == "remote")
+        .then(|| crate::create_signer_backend(&state.config)
+            .map_err(|e| error!("❌ Failed to rebuild signer backend after TCB recovery: {}", e))
+            .ok())
+        .flatten();
+    if let Some(backend) = backend {
+        state.signer.install_backend(backend).await;
+    }
+    state.safe_mode.clear();
+
+    let details = serde_json::json!({
+        "reason": request.reason,
+        "quote_id": current.quote_id,
+        "previous_quote_id": previous.quote_id,
+        "measurements": Measurements::parse(&current.tdx_quote),
+        "previous_measurements": Measurements::parse(&previous.tdx_quote),
+        "verify_url": "/attestation/measurements"
+    });
+    let users = state.session_manager.user_addresses();
+    for user in &users {
+        state.notifier.notify(Notification::new(
+            NotificationKind::Alert,
+            Some(user),
+            "Attestation updated: re-verification recommended",
+            details.clone(),
+        ));
+    }
+
+    info!("🛡️ TCB recovery: quote {} superseded by {} ({}); {} users notified",
+        previous.quote_id, current.quote_id, request.reason, users.len());
+    Ok(Json(serde_json::json!({"status": "ok", "response": details, "users_notified": users.len()})))
+}
@@ -0,0 +1,574 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::{info, warn, error};
+
+use crate::address;
+use crate::canonical_json;
+use crate::jsonl;
+
+/// First entry of every log; marks the start of the period the log can vouch for
+pub const AUDIT_LOG_START: &str = "log_start";
+/// L1 exchange action signed with the agent key
+pub const AUDIT_EXCHANGE_ACTION: &str = "exchange_action";
+/// setReferrer signed with the agent key
+pub const AUDIT_SET_REFERRER: &str = "set_referrer";
+/// HyperEVM transaction signed with the agent key
+pub const AUDIT_EVM_TRANSACTION: &str = "evm_transaction";
+/// EIP-712 payload signed with the agent key
+pub const AUDIT_TYPED_DATA: &str = "typed_data";
+/// L1 action re-signed by the replay tool for a previously recorded request
+pub const AUDIT_REPLAY: &str = "replay";
+/// Statement signed by the enclave about its own log
+pub const AUDIT_STATEMENT: &str = "statement";
+/// Webhook callback body signed with the agent key
+pub const AUDIT_WEBHOOK: &str = "webhook";
+/// Pre-sign policy in force from this entry on; recorded at startup whenever it changed
+pub const AUDIT_POLICY: &str = "policy";
+/// Automated strategy created, updated, paused, resumed or cancelled by its owner
+pub const AUDIT_STRATEGY: &str = "strategy";
+/// State-changing /admin request, with the admin key or address that signed it
+pub const AUDIT_ADMIN_ACTION: &str = "admin_action";
+/// A user's data was deleted on their request; names them only by the hash of their address
+pub const AUDIT_USER_ERASURE: &str = "user_erasure";
+
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+/// Entries hashed over serde_json's insertion-ordered output, before canonical JSON
+pub const HASH_FORMAT_LEGACY: u32 = 0;
+/// Entries hashed over canonical JSON (see `canonical_json`), personal fields included
+pub const HASH_FORMAT_CANONICAL: u32 = 1;
+/// Entries whose personal fields are hashed into `personal_commitment`, so the entry hash
+/// still verifies after redaction; written from now on
+pub const HASH_FORMAT_COMMITTED: u32 = 2;
+
+fn is_legacy_format(format: &u32) -> bool {
+    *format == HASH_FORMAT_LEGACY
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// One hash-chained record of a signature the agent produced (or attempted)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp_ms: u64,
+    pub user_address: Option<String>,
+    /// Reverse-ENS name of `user_address` at signing time, when resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_ens: Option<String>,
+    /// Client address of the originating request, resolved through trusted proxies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    pub kind: String,
+    /// Digest identifying what was signed (action hash, tx hash, EIP-712 hash)
+    pub subject_hash: String,
+    pub subject: Value,
+    /// Signature, when the signing path exposes it (SDK-signed actions do not)
+    pub signature: Option<Value>,
+    pub error: Option<String>,
+    pub prev_hash: String,
+    /// How the entry body is serialized for hashing; absent on legacy entries
+    #[serde(default, skip_serializing_if = "is_legacy_format")]
+    pub hash_format: u32,
+    /// sha256 of the canonical user address, ENS name, client IP, subject, signature and error.
+    /// Kept when the entry is redacted; absent before `HASH_FORMAT_COMMITTED`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub personal_commitment: Option<String>,
+    /// sha256(prev_hash || entry body); chains every entry to its predecessor
+    pub entry_hash: String,
+    /// The user's data was deleted: only seq, time, kind, hashes, the personal commitment and
+    /// links remain. Entries redacted before commitments existed are vouched for by links only
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub redacted: bool,
+}
+
+impl AuditEntry {
+    fn compute_personal_commitment(&self) -> String {
+        canonical_json::sha256_hex(&serde_json::json!({
+            "user_address": self.user_address,
+            "user_ens": self.user_ens,
+            "client_ip": self.client_ip,
+            "subject": self.subject,
+            "signature": self.signature,
+            "error": self.error,
+        }))
+    }
+
+    fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.prev_hash.as_bytes());
+        if self.hash_format >= HASH_FORMAT_COMMITTED {
+            hasher.update(canonical_json::to_vec(&serde_json::json!({
+                "seq": self.seq,
+                "timestamp_ms": self.timestamp_ms,
+                "kind": self.kind,
+                "subject_hash": self.subject_hash,
+                "personal_commitment": self.personal_commitment,
+                "hash_format": self.hash_format,
+            })));
+            return hex::encode(hasher.finalize());
+        }
+
+        let mut body = serde_json::json!({
+            "seq": self.seq,
+            "timestamp_ms": self.timestamp_ms,
+            "user_address": self.user_address,
+            "kind": self.kind,
+            "subject_hash": self.subject_hash,
+            "subject": self.subject,
+            "signature": self.signature,
+            "error": self.error,
+        });
+        // Only hashed when present so entries written before these fields existed still verify
+        if let Some(user_ens) = &self.user_ens {
+            body["user_ens"] = serde_json::json!(user_ens);
+        }
+        if let Some(client_ip) = &self.client_ip {
+            body["client_ip"] = serde_json::json!(client_ip);
+        }
+
+        if self.hash_format == HASH_FORMAT_LEGACY {
+            hasher.update(serde_json::to_vec(&body).unwrap_or_default());
+        } else {
+            body["hash_format"] = serde_json::json!(self.hash_format);
+            hasher.update(canonical_json::to_vec(&body));
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Recompute the entry hash, and the personal commitment while the fields behind it remain
+    fn verify_hash(&self) -> Result<(), String> {
+        if self.hash_format >= HASH_FORMAT_COMMITTED {
+            let commitment = self.personal_commitment.as_deref()
+                .ok_or_else(|| format!("entry {} has no personal commitment", self.seq))?;
+            if !self.redacted && self.compute_personal_commitment() != commitment {
+                return Err(format!("entry {} personal fields do not match their commitment", self.seq));
+            }
+        } else if self.redacted {
+            // The hash covered the erased fields, so only the links vouch for this entry
+            return Ok(());
+        }
+        if self.compute_hash() != self.entry_hash {
+            return Err(format!("entry {} hash mismatch", self.seq));
+        }
+        Ok(())
+    }
+}
+
+/// Who a signing request came from
+#[derive(Debug, Clone, Default)]
+pub struct Requester {
+    /// Account the agent acts for
+    pub user_address: Option<String>,
+    pub client_ip: Option<String>,
+}
+
+/// Merkle root standing in for a pruned range of entries; kept indefinitely.
+///
+/// `prev_hash` links the range to the previous checkpoint (or genesis) and the first
+/// retained entry links to `last_entry_hash`, so the chain stays verifiable after pruning.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditCheckpoint {
+    pub from_seq: u64,
+    pub to_seq: u64,
+    pub from_timestamp_ms: u64,
+    pub to_timestamp_ms: u64,
+    pub prev_hash: String,
+    pub last_entry_hash: String,
+    /// Binary sha256 Merkle root over the pruned entries' `entry_hash` values
+    pub merkle_root: String,
+    pub pruned_at_ms: u64,
+}
+
+/// Append-only, hash-chained transparency log of every signing request
+#[derive(Debug)]
+pub struct AuditLog {
+    entries: Vec<AuditEntry>,
+    path: Option<PathBuf>,
+    checkpoints: Vec<AuditCheckpoint>,
+    /// Entries removed by retention since startup
+    pruned_total: u64,
+    /// Lowercased address -> reverse-ENS name, stamped onto new entries
+    identities: HashMap<String, String>,
+}
+
+impl AuditLog {
+    /// Open the log, replaying and checking entries already persisted at `path`
+    pub fn open(path: Option<PathBuf>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let entries: Vec<AuditEntry> = match &path {
+            Some(path) => jsonl::load(path)?,
+            None => Vec::new(),
+        };
+
+        let checkpoints: Vec<AuditCheckpoint> = match &path {
+            Some(path) => jsonl::load(&checkpoints_path(path))?,
+            None => Vec::new(),
+        };
+
+        let mut log = Self { entries, path, checkpoints, pruned_total: 0, identities: HashMap::new() };
+        if let Err(e) = log.verify_chain() {
+            warn!("⚠️ Audit log chain check failed: {}", e);
+        }
+        if log.entries.is_empty() && log.checkpoints.is_empty() {
+            log.append(&Requester::default(), AUDIT_LOG_START, GENESIS_HASH.to_string(), Value::Null, None, None);
+        }
+
+        info!("🧾 Audit log opened with {} entries", log.entries.len());
+        Ok(log)
+    }
+
+    /// Append an entry and return a copy of it
+    pub fn append(
+        &mut self,
+        requester: &Requester,
+        kind: &str,
+        subject_hash: String,
+        subject: Value,
+        signature: Option<Value>,
+        error: Option<String>,
+    ) -> AuditEntry {
+        let (seq, prev_hash) = match (self.entries.last(), self.checkpoints.last()) {
+            (Some(last), _) => (last.seq + 1, last.entry_hash.clone()),
+            (None, Some(checkpoint)) => (checkpoint.to_seq + 1, checkpoint.last_entry_hash.clone()),
+            (None, None) => (1, GENESIS_HASH.to_string()),
+        };
+
+        let user_address = requester.user_address.as_deref().map(address::normalize);
+        let user_ens = user_address.as_ref().and_then(|u| self.identities.get(u).cloned());
+        let mut entry = AuditEntry {
+            seq,
+            timestamp_ms: now_ms(),
+            user_address,
+            user_ens,
+            client_ip: requester.client_ip.clone(),
+            kind: kind.to_string(),
+            subject_hash,
+            subject,
+            signature,
+            error,
+            prev_hash,
+            hash_format: HASH_FORMAT_COMMITTED,
+            personal_commitment: None,
+            entry_hash: String::new(),
+            redacted: false,
+        };
+        entry.personal_commitment = Some(entry.compute_personal_commitment());
+        entry.entry_hash = entry.compute_hash();
+
+        if let Some(path) = &self.path {
+            if let Err(e) = jsonl::append(path, &entry) {
+                error!("❌ Failed to persist audit entry {}: {}", entry.seq, e);
+            }
+        }
+
+        self.entries.push(entry.clone());
+        entry
+    }
+
+    /// Remember the ENS name to record alongside this address's future entries
+    pub fn set_identity(&mut self, user_address: &str, ens_name: String) {
+        self.identities.insert(address::normalize(user_address), ens_name);
+    }
+
+    /// Check every entry's hash and link to its predecessor, starting from the last checkpoint
+    pub fn verify_chain(&self) -> Result<(), String> {
+        verify_checkpoints(&self.checkpoints)?;
+        verify_entries_from(&self.entries, &chain_anchor(&self.checkpoints))
+    }
+
+    /// Replace entries older than `cutoff_ms` with a Merkle-root checkpoint.
+    /// The newest entry is always kept so the head stays available.
+    pub fn prune_before(&mut self, cutoff_ms: u64) -> Result<Option<AuditCheckpoint>, Box<dyn std::error::Error + Send + Sync>> {
+        let count = self.entries.iter()
+            .take(self.entries.len().saturating_sub(1))
+            .take_while(|e| e.timestamp_ms < cutoff_ms)
+            .count();
+        if count == 0 {
+            return Ok(None);
+        }
+
+        let pruned = &self.entries[..count];
+        let checkpoint = AuditCheckpoint {
+            from_seq: pruned[0].seq,
+            to_seq: pruned[count - 1].seq,
+            from_timestamp_ms: pruned[0].timestamp_ms,
+            to_timestamp_ms: pruned[count - 1].timestamp_ms,
+            prev_hash: pruned[0].prev_hash.clone(),
+            last_entry_hash: pruned[count - 1].entry_hash.clone(),
+            merkle_root: merkle_root(pruned.iter().map(|e| e.entry_hash.as_str())),
+            pruned_at_ms: now_ms(),
+        };
+
+        // Persist the checkpoint before dropping the entries it stands for
+        if let Some(path) = &self.path {
+            jsonl::append(&checkpoints_path(path), &checkpoint)?;
+            jsonl::rewrite(path, &self.entries[count..])?;
+        }
+
+        self.entries.drain(..count);
+        self.checkpoints.push(checkpoint.clone());
+        self.pruned_total += count as u64;
+        info!("🧾 Pruned audit entries {}..={} into checkpoint {}", checkpoint.from_seq, checkpoint.to_seq, checkpoint.merkle_root);
+
+        Ok(Some(checkpoint))
+    }
+
+    /// Retained entries, checkpoints, entries pruned since startup and on-disk bytes
+    pub fn storage_stats(&self) -> (usize, usize, u64, u64) {
+        let bytes = jsonl::file_size(self.path.as_deref())
+            + self.path.as_ref().map(|p| jsonl::file_size(Some(&checkpoints_path(p)))).unwrap_or(0);
+        (self.entries.len(), self.checkpoints.len(), self.pruned_total, bytes)
+    }
+
+    /// Latest entry (the log always has at least the start marker)
+    pub fn head(&self) -> Option<&AuditEntry> {
+        self.entries.last()
+    }
+
+    /// Time from which the log has a complete record
+    pub fn started_at_ms(&self) -> u64 {
+        self.entries.first().map(|e| e.timestamp_ms).unwrap_or_else(now_ms)
+    }
+
+    /// Most recent L1 signing entry recorded for an action hash
+    pub fn find_action(&self, subject_hash: &str) -> Option<&AuditEntry> {
+        self.entries.iter()
+            .rev()
+            .filter(|e| e.kind == AUDIT_EXCHANGE_ACTION || e.kind == AUDIT_SET_REFERRER)
+            .find(|e| e.subject_hash == subject_hash)
+    }
+
+    /// Record the policy in force unless it matches the latest recorded one.
+    /// The subject hash is the sha256 of the canonical policy JSON.
+    pub fn record_policy(&mut self, policy: Value) -> Option<AuditEntry> {
+        let latest = self.entries.iter().rev().find(|e| e.kind == AUDIT_POLICY);
+        if latest.is_some_and(|e| e.subject == policy) {
+            return None;
+        }
+        let subject_hash = format!("0x{}", canonical_json::sha256_hex(&policy));
+        Some(self.append(&Requester::default(), AUDIT_POLICY, subject_hash, policy, None, None))
+    }
+
+    /// Record a change to a user's automated strategy. The subject hash is the sha256 of the canonical subject JSON.
+    pub fn record_strategy(&mut self, user_address: &str, subject: Value) -> AuditEntry {
+        let subject_hash = format!("0x{}", canonical_json::sha256_hex(&subject));
+        let requester = Requester { user_address: Some(user_address.to_string()), client_ip: None };
+        self.append(&requester, AUDIT_STRATEGY, subject_hash, subject, None, None)
+    }
+
+    /// Record a signed /admin request. The subject hash is the sha256 of the canonical subject JSON.
+    pub fn record_admin_action(&mut self, requester: &Requester, subject: Value) -> AuditEntry {
+        let subject_hash = format!("0x{}", canonical_json::sha256_hex(&subject));
+        self.append(requester, AUDIT_ADMIN_ACTION, subject_hash, subject, None, None)
+    }
+
+    /// Record that a user's data was deleted, naming them by the sha256 of their lowercased address
+    pub fn record_user_erasure(&mut self, user_address: &str, erased: Value) -> AuditEntry {
+        let user_sha256 = hex::encode(Sha256::digest(address::normalize(user_address).as_bytes()));
+        let subject = serde_json::json!({"user_sha256": user_sha256, "erased": erased});
+        let subject_hash = format!("0x{}", canonical_json::sha256_hex(&subject));
+        self.append(&Requester::default(), AUDIT_USER_ERASURE, subject_hash, subject, None, None)
+    }
+
+    /// Strip a user's entries down to what keeps the chain verifiable: seq, time, kind,
+    /// subject hash, personal commitment and links. Returns how many entries were redacted.
+    pub fn redact_user(&mut self, user_address: &str) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let user_address = address::normalize(user_address);
+        let mut redacted = 0;
+        for entry in self.entries.iter_mut().filter(|e| e.user_address.as_deref() == Some(user_address.as_str())) {
+            entry.user_address = None;
+            entry.user_ens = None;
+            entry.client_ip = None;
+            entry.subject = Value::Null;
+            entry.signature = None;
+            entry.error = None;
+            entry.redacted = true;
+            redacted += 1;
+        }
+        self.identities.remove(&user_address);
+
+        if redacted > 0 {
+            if let Some(path) = &self.path {
+                jsonl::rewrite(path, &self.entries)?;
+            }
+        }
+        Ok(redacted)
+    }
+
+    /// Policy entries covering `[from_ms, to_ms]`: the one in force at `from_ms` and any recorded after
+    pub fn policies_between(&self, from_ms: u64, to_ms: u64) -> Vec<&AuditEntry> {
+        let policies: Vec<&AuditEntry> = self.entries.iter().filter(|e| e.kind == AUDIT_POLICY).collect();
+        let first = policies.iter().rposition(|e| e.timestamp_ms <= from_ms).unwrap_or(0);
+        policies[first..].iter()
+            .filter(|e| e.timestamp_ms <= to_ms)
+            .copied()
+            .collect()
+    }
+
+    /// Signing entries for a user within `[from_ms, to_ms]`
+    pub fn signatures_for(&self, user_address: &str, from_ms: u64, to_ms: u64) -> Vec<&AuditEntry> {
+        let user_address = address::normalize(user_address);
+        self.entries.iter()
+            .filter(|e| e.kind != AUDIT_LOG_START)
+            .filter(|e| e.user_address.as_deref() == Some(user_address.as_str()))
+            .filter(|e| e.timestamp_ms >= from_ms && e.timestamp_ms <= to_ms)
+            .collect()
+    }
+}
+
+/// Check a sequence of entries (e.g. loaded from an exported log) forms an unbroken chain
+/// from `anchor` (genesis, or the last checkpoint's final hash)
+pub fn verify_entries_from(entries: &[AuditEntry], anchor: &str) -> Result<(), String> {
+    let mut prev_hash = anchor.to_string();
+    for entry in entries {
+        if entry.prev_hash != prev_hash {
+            return Err(format!("entry {} does not link to its predecessor", entry.seq));
+        }
+        entry.verify_hash()?;
+        prev_hash = entry.entry_hash.clone();
+    }
+    Ok(())
+}
+
+/// Check checkpoints link to each other back to genesis
+pub fn verify_checkpoints(checkpoints: &[AuditCheckpoint]) -> Result<(), String> {
+    let mut prev_hash = GENESIS_HASH;
+    for checkpoint in checkpoints {
+        if checkpoint.prev_hash != prev_hash {
+            return Err(format!("checkpoint {}..={} does not link to its predecessor", checkpoint.from_seq, checkpoint.to_seq));
+        }
+        prev_hash = &checkpoint.last_entry_hash;
+    }
+    Ok(())
+}
+
+/// Hash the first retained entry must link to
+pub fn chain_anchor(checkpoints: &[AuditCheckpoint]) -> String {
+    checkpoints.last().map(|c| c.last_entry_hash.clone()).unwrap_or_else(|| GENESIS_HASH.to_string())
+}
+
+/// Where checkpoints for the log at `path` are kept (`audit.jsonl` -> `audit.roots.jsonl`)
+pub fn checkpoints_path(path: &std::path::Path) -> PathBuf {
+    path.with_extension("roots.jsonl")
+}
+
+/// Binary sha256 Merkle root over hex leaf hashes; an odd node is carried up unchanged
+pub fn merkle_root<'a>(leaves: impl Iterator<Item = &'a str>) -> String {
+    let mut level: Vec<Vec<u8>> = leaves.map(|leaf| hex::decode(leaf).unwrap_or_default()).collect();
+    if level.is_empty() {
+        return GENESIS_HASH.to_string();
+    }
+
+    while level.len() > 1 {
+        level = level.chunks(2)
+            .map(|pair| match pair {
+                [left, right] => Sha256::new().chain_update(left).chain_update(right).finalize().to_vec(),
+                [single] => single.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    hex::encode(&level[0])
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const USER: &str = "0x1111111111111111111111111111111111111111";
+
+    fn log_with_user_entries() -> AuditLog {
+        let mut log = AuditLog::open(None).unwrap();
+        let user = Requester { user_address: Some(USER.to_string()), client_ip: Some("203.0.113.7".to_string()) };
+        log.append(&user, AUDIT_EXCHANGE_ACTION, "0xaa".to_string(), json!({"nonce": 1}), Some(json!({"r": "0x1"})), None);
+        log.append(&Requester::default(), AUDIT_POLICY, "0xbb".to_string(), json!({"max_notional": 10}), None, None);
+        log.append(&user, AUDIT_EXCHANGE_ACTION, "0xcc".to_string(), json!({"nonce": 2}), None, Some("rejected".to_string()));
+        log
+    }
+
+    #[test]
+    fn test_redacted_entries_still_verify_by_hash() {
+        let mut log = log_with_user_entries();
+        assert_eq!(log.redact_user(USER).unwrap(), 2);
+        log.verify_chain().unwrap();
+
+        let redacted = &log.entries[1];
+        assert!(redacted.redacted && redacted.user_address.is_none() && redacted.subject.is_null());
+        assert!(redacted.personal_commitment.is_some());
+
+        // Rewriting a redacted entry's header no longer goes unnoticed
+        let mut tampered = log.entries.clone();
+        tampered[1].subject_hash = "0xdd".to_string();
+        assert!(verify_entries_from(&tampered, GENESIS_HASH).unwrap_err().contains("hash mismatch"));
+
+        let mut tampered = log.entries.clone();
+        tampered[1].personal_commitment = Some("00".repeat(32));
+        assert!(verify_entries_from(&tampered, GENESIS_HASH).unwrap_err().contains("hash mismatch"));
+
+        let mut tampered = log.entries.clone();
+        tampered[1].personal_commitment = None;
+        assert!(verify_entries_from(&tampered, GENESIS_HASH).unwrap_err().contains("no personal commitment"));
+    }
+
+    #[test]
+    fn test_personal_fields_are_bound_by_commitment() {
+        let log = log_with_user_entries();
+        log.verify_chain().unwrap();
+
+        for edit in [
+            (|e: &mut AuditEntry| e.client_ip = Some("198.51.100.1".to_string())) as fn(&mut AuditEntry),
+            |e| e.subject = json!({"nonce": 99}),
+            |e| e.user_ens = Some("someone.eth".to_string()),
+            |e| e.error = Some("edited".to_string()),
+        ] {
+            let mut tampered = log.entries.clone();
+            edit(&mut tampered[1]);
+            let err = verify_entries_from(&tampered, GENESIS_HASH).unwrap_err();
+            assert!(err.contains("do not match their commitment"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_earlier_formats_still_verify() {
+        let mut entry = AuditEntry {
+            seq: 1,
+            timestamp_ms: 1_700_000_000_000,
+            user_address: Some(USER.to_string()),
+            user_ens: None,
+            client_ip: None,
+            kind: AUDIT_EXCHANGE_ACTION.to_string(),
+            subject_hash: "0xaa".to_string(),
+            subject: json!({"nonce": 1}),
+            signature: None,
+            error: None,
+            prev_hash: GENESIS_HASH.to_string(),
+            hash_format: HASH_FORMAT_CANONICAL,
+            personal_commitment: None,
+            entry_hash: String::new(),
+            redacted: false,
+        };
+        entry.entry_hash = entry.compute_hash();
+        verify_entries_from(std::slice::from_ref(&entry), GENESIS_HASH).unwrap();
+
+        entry.subject = json!({"nonce": 2});
+        assert!(verify_entries_from(std::slice::from_ref(&entry), GENESIS_HASH).is_err());
+
+        // Redacted before commitments existed: checked by links only
+        entry.subject = Value::Null;
+        entry.user_address = None;
+        entry.redacted = true;
+        verify_entries_from(std::slice::from_ref(&entry), GENESIS_HASH).unwrap();
+    }
+}
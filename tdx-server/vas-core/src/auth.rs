@@ -0,0 +1,136 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{info, warn};
+
+use crate::{AppState, config::Config};
+use crate::agents::SessionRejection;
+use crate::api_keys::{self, API_KEY_PREFIX};
+use crate::client_ip;
+use crate::share::{share_token_allows, SHARE_TOKEN_PREFIX};
+
+pub async fn api_key_auth(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    // Extract API key from X-API-Key header
+    let api_key = headers
+        .get("X-API-Key")
+        .and_then(|value| value.to_str().ok());
+
+    match api_key {
+        Some(key) => {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            // Check both fixed API key and SIWE-generated API keys
+            let is_valid = if key == state.config.fixed_api_key {
+                info!("Valid fixed API key provided: {}", key);
+                true
+            } else if key.starts_with(API_KEY_PREFIX) {
+                // SIWE-issued keys must carry this enclave's MAC and be unexpired before the session lookup
+                match api_keys::verify(key) {
+                    Ok(_) => match state.session_manager.authenticate(key, now) {
+                        Ok(()) => {
+                            info!("Valid SIWE API key provided: {}", key);
+                            true
+                        }
+                        Err(SessionRejection::Expired) => {
+                            warn!("Rejected API key: session expired");
+                            false
+                        }
+                        Err(SessionRejection::Unknown) => false,
+                    },
+                    Err(reason) => {
+                        warn!("Rejected API key: {}", reason);
+                        false
+                    }
+                }
+            } else if key.starts_with(SHARE_TOKEN_PREFIX) {
+                // Read-only share tokens only reach the owner's /me/* views
+                state.shares.read().await.resolve(key).is_some()
+                    && share_token_allows(request.method(), request.uri().path())
+            } else {
+                false
+            };
+
+            if is_valid {
+                Ok(next.run(request).await)
+            } else {
+                warn!("Invalid API key provided: {} (from {})", key, client_ip::describe(&request));
+                Err(StatusCode::UNAUTHORIZED)
+            }
+        }
+        None => {
+            warn!("No API key provided in X-API-Key header (from {})", client_ip::describe(&request));
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+}
+
+/// Gate /ha peer endpoints on the shared HA_PEER_TOKEN
+pub async fn ha_peer_auth(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let Some(expected) = state.config.ha_peer_token.as_deref() else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let provided = headers.get(crate::ha::HA_PEER_TOKEN_HEADER).and_then(|value| value.to_str().ok());
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Ok(next.run(request).await),
+        _ => {
+            warn!("Invalid or missing {} header (from {})", crate::ha::HA_PEER_TOKEN_HEADER, client_ip::describe(&request));
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Extract the API key from the X-API-Key header
+pub fn api_key_from_headers(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("X-API-Key")
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Resolve the master wallet address bound to an API key.
+/// SIWE sessions and share tokens carry a user address; the fixed development key has none.
+pub async fn user_address_for_api_key(state: &AppState, api_key: &str) -> Option<String> {
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return state.shares.read().await.resolve(api_key).map(|user| user.to_string());
+    }
+
+    state.session_manager.with_session(api_key, |session| session.user_address.clone())
+}
+
+/// Whether an API key may use `scope` (the fixed development key has every scope)
+pub async fn api_key_has_scope(state: &AppState, api_key: &str, scope: &str) -> bool {
+    if api_key == state.config.fixed_api_key {
+        return true;
+    }
+    state.session_manager
+        .with_session(api_key, |session| session.has_scope(scope))
+        .unwrap_or(false)
+}
+
+pub fn get_agent_address_for_api_key(api_key: &str, config: &Config) -> Option<String> {
+    // For now, return a fixed test agent address for the test key
+    if api_key == config.fixed_api_key {
+        Some(config.test_agent_address.clone())
+    } else {
+        None
+    }
+}
\ No newline at end of file
@@ -0,0 +1,894 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::agents::SCOPE_TRADE;
+use crate::auth;
+use crate::dca::{format_px, size_for_notional};
+use crate::drawdown;
+use crate::error_codes::{self, ErrorCode};
+use crate::jsonl;
+use crate::margin::MarginSummary;
+use crate::oco;
+use crate::risk::position_size;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::strategy_limits::STRATEGY_TAG_HEADER;
+use crate::AppState;
+
+const MAX_STRATEGIES_PER_USER: usize = 10;
+const MAX_GRID_LEVELS: usize = 50;
+const MAX_REBALANCE_TARGETS: usize = 10;
+const MIN_REBALANCE_PERIOD_SECS: u64 = 3600;
+/// How often a grid's resting orders are reconciled against the mark
+const GRID_INTERVAL_SECS: u64 = 15;
+/// Runs kept per strategy
+const HISTORY_LEN: usize = 100;
+const DEFAULT_TOLERANCE_PCT: f64 = 1.0;
+const DEFAULT_SLIPPAGE_BPS: u64 = 50;
+
+/// What a strategy does, validated on upload and on every parameter update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StrategyParams {
+    Grid(GridParams),
+    /// Every `period_secs`, trade each target's position back to `weight` x account value
+    Rebalance {
+        targets: Vec<RebalanceTarget>,
+        period_secs: u64,
+        /// Targets within this many percent of account value are left alone
+        #[serde(default = "default_tolerance_pct")]
+        tolerance_pct: f64,
+        #[serde(default = "default_slippage_bps")]
+        slippage_bps: u64,
+    },
+}
+
+fn default_tolerance_pct() -> f64 {
+    DEFAULT_TOLERANCE_PCT
+}
+
+fn default_slippage_bps() -> u64 {
+    DEFAULT_SLIPPAGE_BPS
+}
+
+/// `levels` evenly spaced prices from `lower_px` to `upper_px`: a resting buy at each level
+/// below the mark and a sell at each level above, leaving the level nearest the mark empty.
+/// A filled level is re-placed on the other side once the mark has moved away from it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridParams {
+    pub asset: u64,
+    pub lower_px: f64,
+    pub upper_px: f64,
+    pub levels: usize,
+    /// Order size at every level, in the asset's units
+    #[serde(default)]
+    pub size_per_level: Option<f64>,
+    /// Alternatively, the order value at every level; sizes then shrink as prices rise
+    #[serde(default)]
+    pub order_notional_usd: Option<f64>,
+}
+
+impl GridParams {
+    fn prices(&self) -> Vec<f64> {
+        let step = (self.upper_px - self.lower_px) / (self.levels - 1) as f64;
+        (0..self.levels).map(|i| self.lower_px + step * i as f64).collect()
+    }
+
+    /// Order size at `px`, rounded down to the asset's size decimals
+    fn size_at(&self, px: f64, sz_decimals: u32) -> f64 {
+        match (self.size_per_level, self.order_notional_usd) {
+            (Some(size), _) => size_for_notional(size * px, px, sz_decimals),
+            (None, Some(notional)) => size_for_notional(notional, px, sz_decimals),
+            (None, None) => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalanceTarget {
+    pub asset: u64,
+    /// Share of account value held in the position; negative for a short
+    pub weight: f64,
+}
+
+impl StrategyParams {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Grid(grid) => {
+                if !(grid.lower_px.is_finite() && grid.lower_px > 0.0 && grid.upper_px > grid.lower_px && grid.upper_px.is_finite()) {
+                    return Err("Grid needs 0 < lower_px < upper_px".to_string());
+                }
+                if !(2..=MAX_GRID_LEVELS).contains(&grid.levels) {
+                    return Err(format!("Grid levels must be between 2 and {}", MAX_GRID_LEVELS));
+                }
+                match (grid.size_per_level, grid.order_notional_usd) {
+                    (Some(amount), None) | (None, Some(amount)) if amount.is_finite() && amount > 0.0 => {}
+                    _ => return Err("Give a positive size_per_level or order_notional_usd, not both".to_string()),
+                }
+            }
+            Self::Rebalance { targets, period_secs, tolerance_pct, slippage_bps } => {
+                validate_targets(targets, *tolerance_pct, *slippage_bps)?;
+                if *period_secs < MIN_REBALANCE_PERIOD_SECS {
+                    return Err(format!("period_secs must be at least {}", MIN_REBALANCE_PERIOD_SECS));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn interval_secs(&self) -> u64 {
+        match self {
+            Self::Grid(_) => GRID_INTERVAL_SECS,
+            Self::Rebalance { period_secs, .. } => *period_secs,
+        }
+    }
+}
+
+fn validate_targets(targets: &[RebalanceTarget], tolerance_pct: f64, slippage_bps: u64) -> Result<(), String> {
+    if targets.is_empty() || targets.len() > MAX_REBALANCE_TARGETS {
+        return Err(format!("Rebalance needs 1 to {} targets", MAX_REBALANCE_TARGETS));
+    }
+    if targets.iter().enumerate().any(|(i, t)| targets[..i].iter().any(|o| o.asset == t.asset)) {
+        return Err("Each asset may be targeted once".to_string());
+    }
+    let gross: f64 = targets.iter().map(|t| t.weight.abs()).sum();
+    if !gross.is_finite() || gross > 1.0 {
+        return Err("Target weights may add up to at most 1 (unlevered)".to_string());
+    }
+    if !(0.0..100.0).contains(&tolerance_pct) || slippage_bps > 1000 {
+        return Err("tolerance_pct must be below 100 and slippage_bps at most 1000".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyStatus {
+    Active,
+    Paused,
+    Cancelled,
+}
+
+/// One run: orders placed and cancelled, or why nothing was done
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyRun {
+    pub at: u64,
+    pub placed: usize,
+    pub cancelled: usize,
+    pub skipped: Option<String>,
+    pub results: Vec<Value>,
+}
+
+/// An order a grid placed at one of its levels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridOrder {
+    pub cloid: String,
+    pub is_buy: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct LevelFills {
+    pub buys: u64,
+    pub sells: u64,
+}
+
+/// What a grid knows about its own orders, carried across runs and restarts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GridState {
+    /// The order resting at each level as of the last run
+    pub resting: BTreeMap<usize, GridOrder>,
+    pub fills: BTreeMap<usize, LevelFills>,
+    pub last_mark_px: Option<f64>,
+}
+
+/// A user's strategy, executed by the agent through the /exchange pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomatedStrategy {
+    pub id: String,
+    /// Session that uploaded the strategy; not persisted, so after a restart the user's
+    /// current session is used instead
+    #[serde(skip)]
+    pub api_key: String,
+    pub user_address: String,
+    pub params: StrategyParams,
+    pub status: StrategyStatus,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub next_run_at: u64,
+    /// Grid orders placed so far; numbers each cloid so none is reused
+    pub placements: u64,
+    #[serde(default)]
+    pub grid: Option<GridState>,
+    pub history: Vec<StrategyRun>,
+}
+
+impl AutomatedStrategy {
+    /// First 8 bytes of every cloid this strategy places
+    fn cloid_prefix(&self) -> String {
+        format!("0x{}", hex::encode(&Sha256::digest(self.id.as_bytes())[..8]))
+    }
+
+    /// Grid level of one of this strategy's orders, from its cloid
+    fn level_of(&self, cloid: &str) -> Option<usize> {
+        let rest = cloid.strip_prefix(&self.cloid_prefix())?;
+        usize::from_str_radix(rest.get(..8)?, 16).ok()
+    }
+
+    fn push_history(&mut self, run: StrategyRun) {
+        if self.history.len() >= HISTORY_LEN {
+            self.history.remove(0);
+        }
+        self.history.push(run);
+    }
+}
+
+/// Automated strategies by id, persisted to a JSON-lines file so they survive restarts
+#[derive(Debug)]
+pub struct StrategyBook {
+    strategies: HashMap<String, AutomatedStrategy>,
+    path: Option<PathBuf>,
+}
+
+impl StrategyBook {
+    /// Open the book, keeping strategies that were not cancelled when last written
+    pub fn open(path: Option<PathBuf>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let strategies: Vec<AutomatedStrategy> = match &path {
+            Some(path) => jsonl::load(path)?,
+            None => Vec::new(),
+        };
+        let strategies: HashMap<String, AutomatedStrategy> = strategies.into_iter()
+            .filter(|s| s.status != StrategyStatus::Cancelled)
+            .map(|s| (s.id.clone(), s))
+            .collect();
+
+        info!("🤖 Strategy book opened with {} strategies", strategies.len());
+        Ok(Self { strategies, path })
+    }
+
+    /// Rewrite the backing file with the strategies still in use
+    fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        let kept: Vec<&AutomatedStrategy> = self.strategies.values().filter(|s| s.status != StrategyStatus::Cancelled).collect();
+        if let Err(e) = jsonl::rewrite(path, &kept) {
+            error!("❌ Failed to persist strategies: {}", e);
+        }
+    }
+
+    fn insert(&mut self, strategy: AutomatedStrategy) {
+        self.strategies.insert(strategy.id.clone(), strategy);
+        self.persist();
+    }
+
+    pub fn list(&self, user_address: &str) -> Vec<AutomatedStrategy> {
+        let mut strategies: Vec<_> = self.strategies.values()
+            .filter(|s| s.user_address == user_address && s.status != StrategyStatus::Cancelled)
+            .cloned()
+            .collect();
+        strategies.sort_by_key(|s| s.created_at);
+        strategies
+    }
+
+    fn owned_mut(&mut self, id: &str, user_address: &str) -> Option<&mut AutomatedStrategy> {
+        self.strategies.get_mut(id).filter(|s| s.user_address == user_address && s.status != StrategyStatus::Cancelled)
+    }
+
+    /// Active strategies whose next run is due, advanced to their following slot
+    fn claim_due(&mut self, now: u64) -> Vec<AutomatedStrategy> {
+        let mut due = Vec::new();
+        for strategy in self.strategies.values_mut().filter(|s| s.status == StrategyStatus::Active && s.next_run_at <= now) {
+            strategy.next_run_at = now + strategy.params.interval_secs();
+            due.push(strategy.clone());
+        }
+        due
+    }
+
+    /// Keep what a run learned; grid state is dropped if the parameters changed meanwhile
+    fn record(&mut self, ran: &AutomatedStrategy, run: Option<StrategyRun>) {
+        let Some(strategy) = self.strategies.get_mut(&ran.id) else { return };
+        strategy.placements = strategy.placements.max(ran.placements);
+        if strategy.updated_at == ran.updated_at {
+            strategy.grid = ran.grid.clone();
+        }
+        if let Some(run) = run {
+            strategy.push_history(run);
+        }
+        self.persist();
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> usize {
+        let before = self.strategies.len();
+        self.strategies.retain(|_, strategy| !strategy.user_address.eq_ignore_ascii_case(user_address));
+        let removed = before - self.strategies.len();
+        if removed > 0 {
+            self.persist();
+        }
+        removed
+    }
+}
+
+/// Run due strategies under the session that owns them
+pub fn spawn_runner(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(5));
+        loop {
+            ticker.tick().await;
+            if state.ha.check().is_err() {
+                continue;
+            }
+
+            let due = state.automation.write().await.claim_due(now_secs());
+            for mut strategy in due {
+                let run = match execute(&state, &mut strategy).await {
+                    Ok(run) => run,
+                    Err(reason) => {
+                        warn!("⚠️ Strategy {} run skipped: {}", strategy.id, reason);
+                        Some(StrategyRun { at: now_secs(), placed: 0, cancelled: 0, skipped: Some(reason), results: Vec::new() })
+                    }
+                };
+                state.automation.write().await.record(&strategy, run);
+            }
+        }
+    });
+}
+
+/// The orders a run would send now: an `order` action and, for grids, the oids to cancel
+struct Plan {
+    orders: Vec<Value>,
+    cancels: Vec<Value>,
+}
+
+async fn plan(state: &AppState, strategy: &mut AutomatedStrategy) -> Result<Plan, String> {
+    match strategy.params.clone() {
+        StrategyParams::Grid(grid) => {
+            let info = state.market.asset(grid.asset).await
+                .map_err(|e| format!("Market data unavailable: {}", e))?
+                .ok_or_else(|| format!("Unknown asset index {}", grid.asset))?;
+            let mark_px = info.mark_px.ok_or("No mark price")?;
+
+            // The grid's orders on the book, recognised by cloid
+            let mut on_book: HashMap<String, (usize, bool, u64)> = HashMap::new();
+            for order in grid_orders(state, strategy).await? {
+                let (Some(cloid), Some(oid)) = (order.get("cloid").and_then(|c| c.as_str()), order.get("oid").and_then(|o| o.as_u64())) else { continue };
+                if let Some(level) = strategy.level_of(cloid) {
+                    on_book.insert(cloid.to_string(), (level, order.get("side").and_then(|s| s.as_str()) == Some("B"), oid));
+                }
+            }
+
+            // Orders gone since the last run either filled or were cancelled outside the grid
+            let mut state_now = strategy.grid.clone().unwrap_or_default();
+            for (level, order) in &state_now.resting {
+                if on_book.contains_key(&order.cloid) {
+                    continue;
+                }
+                match oco::order_status(state, &strategy.user_address, serde_json::json!(order.cloid)).await {
+                    Ok(status) if status == "filled" => {
+                        let fills = state_now.fills.entry(*level).or_default();
+                        if order.is_buy { fills.buys += 1 } else { fills.sells += 1 }
+                        info!("🤖 Grid {} level {} {} filled", strategy.id, level, if order.is_buy { "buy" } else { "sell" });
+                    }
+                    Ok(_) => {}
+                    // Unknown yet: look again next run
+                    Err(_) => { on_book.insert(order.cloid.clone(), (*level, order.is_buy, 0)); }
+                }
+            }
+            state_now.resting = on_book.iter()
+                .map(|(cloid, (level, is_buy, _))| (*level, GridOrder { cloid: cloid.clone(), is_buy: *is_buy }))
+                .collect();
+            state_now.last_mark_px = Some(mark_px);
+
+            let prices = grid.prices();
+            let nearest = prices.iter().enumerate()
+                .min_by(|a, b| (a.1 - mark_px).abs().total_cmp(&(b.1 - mark_px).abs()))
+                .map(|(i, _)| i);
+
+            let mut plan = Plan { orders: Vec::new(), cancels: Vec::new() };
+            for (level, px) in prices.into_iter().enumerate() {
+                if Some(level) == nearest {
+                    continue;
+                }
+                let is_buy = px < mark_px;
+                let resting = on_book.values().find(|(l, _, _)| *l == level);
+                match resting {
+                    Some((_, side, _)) if *side == is_buy => continue,
+                    Some((_, _, oid)) if *oid > 0 => plan.cancels.push(serde_json::json!({"a": grid.asset, "o": oid})),
+                    Some(_) => continue,
+                    None => {}
+                }
+                let size = grid.size_at(px, info.sz_decimals);
+                if size <= 0.0 {
+                    return Err("Order size per level rounds to zero".to_string());
+                }
+                strategy.placements += 1;
+                let cloid = format!("{}{:08x}{:08x}", strategy.cloid_prefix(), level, strategy.placements as u32);
+                state_now.resting.insert(level, GridOrder { cloid: cloid.clone(), is_buy });
+                plan.orders.push(serde_json::json!({
+                    "a": grid.asset,
+                    "b": is_buy,
+                    "p": format_px(px, info.sz_decimals),
+                    "s": format!("{:.*}", info.sz_decimals as usize, size),
+                    "r": false,
+                    "t": {"limit": {"tif": "Gtc"}},
+                    "c": cloid
+                }));
+            }
+            strategy.grid = Some(state_now);
+            Ok(plan)
+        }
+        StrategyParams::Rebalance { targets, tolerance_pct, slippage_bps, .. } => {
+            let trades = rebalance_trades(state, &strategy.user_address, &targets, tolerance_pct, slippage_bps).await?;
+            Ok(Plan { orders: trades.iter().map(RebalanceTrade::order).collect(), cancels: Vec::new() })
+        }
+    }
+}
+
+/// One order a rebalance sends to bring a position to its target
+#[derive(Debug, Clone, Serialize)]
+pub struct RebalanceTrade {
+    pub asset: u64,
+    pub coin: String,
+    pub is_buy: bool,
+    pub size: String,
+    /// IOC limit, `slippage_bps` through the mark
+    pub limit_px: String,
+    pub mark_px: f64,
+    pub current_usd: f64,
+    pub target_usd: f64,
+    /// Whether the trade only shrinks the position, freeing margin for the others
+    pub reduces: bool,
+}
+
+impl RebalanceTrade {
+    fn order(&self) -> Value {
+        serde_json::json!({
+            "a": self.asset,
+            "b": self.is_buy,
+            "p": self.limit_px,
+            "s": self.size,
+            "r": false,
+            "t": {"limit": {"tif": "Ioc"}}
+        })
+    }
+}
+
+/// The fewest orders that bring each target within `tolerance_pct` of `weight` x account value:
+/// one per asset off target, none for the rest. Reducing trades come first so the margin they
+/// free is available to the trades that grow positions.
+async fn rebalance_trades(
+    state: &AppState,
+    user_address: &str,
+    targets: &[RebalanceTarget],
+    tolerance_pct: f64,
+    slippage_bps: u64,
+) -> Result<Vec<RebalanceTrade>, String> {
+    let clearinghouse = state.market.clearinghouse_state(user_address).await
+        .map_err(|e| format!("Failed to fetch clearinghouseState: {}", e))?;
+    let account_value = MarginSummary::from_clearinghouse_state(user_address, &clearinghouse).account_value;
+    if account_value <= 0.0 {
+        return Err("Account has no value to rebalance".to_string());
+    }
+
+    let slippage = slippage_bps as f64 / 10_000.0;
+    let mut trades = Vec::new();
+    for target in targets {
+        let info = state.market.asset(target.asset).await
+            .map_err(|e| format!("Market data unavailable: {}", e))?
+            .ok_or_else(|| format!("Unknown asset index {}", target.asset))?;
+        let mark_px = info.mark_px.ok_or_else(|| format!("No mark price for {}", info.name))?;
+        let current = position_size(&clearinghouse, &info.name) * mark_px;
+        let target_usd = target.weight * account_value;
+        let delta = target_usd - current;
+        if delta.abs() / account_value * 100.0 < tolerance_pct {
+            continue;
+        }
+        let size = size_for_notional(delta.abs(), mark_px, info.sz_decimals);
+        if size <= 0.0 {
+            continue;
+        }
+        let is_buy = delta > 0.0;
+        let px = if is_buy { mark_px * (1.0 + slippage) } else { mark_px * (1.0 - slippage) };
+        trades.push(RebalanceTrade {
+            asset: target.asset,
+            coin: info.name.clone(),
+            is_buy,
+            size: format!("{:.*}", info.sz_decimals as usize, size),
+            limit_px: format_px(px, info.sz_decimals),
+            mark_px,
+            current_usd: current,
+            target_usd,
+            reduces: target_usd * current >= 0.0 && target_usd.abs() < current.abs(),
+        });
+    }
+    trades.sort_by_key(|t| !t.reduces);
+    Ok(trades)
+}
+
+fn order_action(orders: Vec<Value>) -> Value {
+    serde_json::json!({"type": "order", "orders": orders, "grouping": "na"})
+}
+
+/// Plan a run and send it through /exchange, which applies scope, policy, drawdown and risk checks
+/// and audits every signature. Runs that send nothing (a grid already in place) are not kept.
+async fn execute(state: &AppState, strategy: &mut AutomatedStrategy) -> Result<Option<StrategyRun>, String> {
+    let plan = plan(state, strategy).await?;
+    let mut run = StrategyRun { at: now_secs(), placed: plan.orders.len(), cancelled: plan.cancels.len(), skipped: None, results: Vec::new() };
+    if !plan.cancels.is_empty() {
+        run.results.push(submit(state, strategy, serde_json::json!({"type": "cancel", "cancels": plan.cancels})).await);
+    }
+    if !plan.orders.is_empty() {
+        run.results.push(submit(state, strategy, order_action(plan.orders)).await);
+    }
+    Ok((!run.results.is_empty()).then_some(run))
+}
+
+async fn submit(state: &AppState, strategy: &AutomatedStrategy, action: Value) -> Value {
+    // Prefer the user's current session; the uploading session may have expired
+    let api_key = state.session_manager
+        .get_user_session(&strategy.user_address)
+        .map(|session| session.api_key)
+        .unwrap_or_else(|| strategy.api_key.clone());
+    submit_as(state, &api_key, Some(&strategy.id), action).await
+}
+
+/// Send an action through the /exchange pipeline under `api_key`, tagged for the strategy's sub-limits
+async fn submit_as(state: &AppState, api_key: &str, tag: Option<&str>, action: Value) -> Value {
+    let Ok(api_key) = HeaderValue::from_str(api_key) else {
+        return error_codes::err_body(ErrorCode::SessionExpired, "No session to act under");
+    };
+    let mut headers = HeaderMap::new();
+    headers.insert("X-API-Key", api_key);
+    if let Some(tag) = tag.and_then(|t| HeaderValue::from_str(t).ok()) {
+        headers.insert(STRATEGY_TAG_HEADER, tag);
+    }
+
+    match crate::proxy_exchange(State(state.clone()), headers, Json(serde_json::json!({"action": action}))).await {
+        Ok(Json(response)) => response,
+        Err(status) => {
+            error!("❌ Automated submission failed: {}", status);
+            error_codes::err_body(ErrorCode::for_status(status), status.to_string())
+        }
+    }
+}
+
+/// The user's resting orders placed by this strategy
+async fn grid_orders(state: &AppState, strategy: &AutomatedStrategy) -> Result<Vec<Value>, String> {
+    let open_orders = state.proxy
+        .proxy_info_request(&serde_json::json!({"type": "frontendOpenOrders", "user": strategy.user_address}))
+        .await
+        .map_err(|e| format!("Failed to fetch open orders: {}", e))?;
+    let prefix = strategy.cloid_prefix();
+    Ok(open_orders.as_array().into_iter().flatten()
+        .filter(|o| o.get("cloid").and_then(|c| c.as_str()).is_some_and(|c| c.starts_with(&prefix)))
+        .cloned()
+        .collect())
+}
+
+/// Pull a grid's resting orders, when it is paused, cancelled or re-parameterized
+async fn cancel_grid(state: &AppState, strategy: &AutomatedStrategy) {
+    let StrategyParams::Grid(GridParams { asset, .. }) = strategy.params else { return };
+    let cancels: Vec<Value> = match grid_orders(state, strategy).await {
+        Ok(orders) => orders.iter()
+            .filter_map(|o| o.get("oid").and_then(|o| o.as_u64()))
+            .map(|oid| serde_json::json!({"a": asset, "o": oid}))
+            .collect(),
+        Err(e) => {
+            warn!("⚠️ Strategy {}: {}", strategy.id, e);
+            return;
+        }
+    };
+    if !cancels.is_empty() {
+        let response = submit(state, strategy, serde_json::json!({"type": "cancel", "cancels": cancels})).await;
+        info!("🤖 Strategy {} pulled {} grid orders: {}", strategy.id, cancels.len(), response);
+    }
+}
+
+/// Check the orders the strategy would send right now against the gates /exchange applies
+async fn validate(state: &AppState, api_key: &str, strategy: &mut AutomatedStrategy) -> Result<(), Value> {
+    strategy.params.validate().map_err(|e| error_codes::err_body(ErrorCode::BadRequest, e))?;
+    if !auth::api_key_has_scope(state, api_key, SCOPE_TRADE).await {
+        return Err(error_codes::err_body(ErrorCode::ScopeNotAllowed, format!("API key is not authorized for the '{}' scope", SCOPE_TRADE)));
+    }
+    let plan = plan(state, strategy).await.map_err(|e| error_codes::err_body(ErrorCode::BadRequest, e))?;
+    if plan.orders.is_empty() {
+        return Ok(());
+    }
+    check_action(state, &strategy.user_address, Some(&strategy.id), &order_action(plan.orders)).await
+}
+
+/// Policy, strategy sub-limits, drawdown guard and risk checks for an order action, as /exchange would apply them
+async fn check_action(state: &AppState, user_address: &str, tag: Option<&str>, action: &Value) -> Result<(), Value> {
+    state.policy.read().await.evaluate(action).map_err(|violation| violation.to_response())?;
+    state.strategy_limits.read().await.check(user_address, tag, action, now_secs() * 1000)
+        .map_err(|violation| violation.to_response())?;
+    drawdown::check_reduce_only(state, user_address, action).await
+        .map_err(|reason| error_codes::err_body(ErrorCode::DrawdownReduceOnly, reason))?;
+    let mut warnings = Vec::new();
+    crate::run_risk_checks(state, user_address, action, &mut warnings).await
+        .map_err(|reason| error_codes::err_body(ErrorCode::RiskCheckFailed, reason))?;
+    Ok(())
+}
+
+async fn audit(state: &AppState, strategy: &AutomatedStrategy, event: &str) {
+    let subject = serde_json::json!({"strategy_id": strategy.id, "event": event, "params": strategy.params});
+    state.audit.write().await.record_strategy(&strategy.user_address, subject);
+}
+
+/// Body of POST /me/strategies and PUT /me/strategies/:id
+#[derive(Debug, Deserialize)]
+pub struct StrategyRequest {
+    pub params: StrategyParams,
+}
+
+/// POST /me/strategies - Upload a strategy; it starts running right away
+pub async fn create_strategy(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<StrategyRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let (api_key, user_address) = session_user(&state, &headers).await?;
+    if state.automation.read().await.list(&user_address).len() >= MAX_STRATEGIES_PER_USER {
+        return Ok(Json(error_codes::err_body(ErrorCode::LimitExceeded, format!("At most {} strategies per user", MAX_STRATEGIES_PER_USER))));
+    }
+
+    let now = now_secs();
+    let mut strategy = AutomatedStrategy {
+        id: uuid::Uuid::new_v4().to_string(),
+        api_key: api_key.clone(),
+        user_address,
+        params: payload.params,
+        status: StrategyStatus::Active,
+        created_at: now,
+        updated_at: now,
+        next_run_at: now,
+        placements: 0,
+        grid: None,
+        history: Vec::new(),
+    };
+    if let Err(body) = validate(&state, &api_key, &mut strategy).await {
+        return Ok(Json(body));
+    }
+    strategy.placements = 0;
+    strategy.grid = None;
+    state.automation.write().await.insert(strategy.clone());
+    audit(&state, &strategy, "created").await;
+
+    info!("🤖 Strategy {} created for {}", strategy.id, strategy.user_address);
+    Ok(Json(serde_json::json!({"status": "ok", "response": strategy})))
+}
+
+/// GET /me/strategies - The caller's strategies with their recent runs
+pub async fn list_strategies(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(serde_json::json!({
+        "strategies": state.automation.read().await.list(&user_address.to_lowercase())
+    })))
+}
+
+/// PUT /me/strategies/:id - Replace a strategy's parameters; a grid's resting orders are pulled and re-placed
+pub async fn update_strategy(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<StrategyRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let (api_key, user_address) = session_user(&state, &headers).await?;
+    let previous = state.automation.write().await.owned_mut(&id, &user_address).ok_or(StatusCode::NOT_FOUND)?.clone();
+
+    let mut updated = AutomatedStrategy { params: payload.params, grid: None, ..previous.clone() };
+    if let Err(body) = validate(&state, &api_key, &mut updated).await {
+        return Ok(Json(body));
+    }
+    cancel_grid(&state, &previous).await;
+
+    let strategy = {
+        let mut book = state.automation.write().await;
+        let strategy = book.owned_mut(&id, &user_address).ok_or(StatusCode::NOT_FOUND)?;
+        strategy.params = updated.params;
+        strategy.grid = None;
+        strategy.updated_at = now_secs();
+        strategy.next_run_at = now_secs();
+        let strategy = strategy.clone();
+        book.persist();
+        strategy
+    };
+    audit(&state, &strategy, "updated").await;
+
+    info!("🤖 Strategy {} parameters updated", id);
+    Ok(Json(serde_json::json!({"status": "ok", "response": strategy})))
+}
+
+/// POST /me/strategies/:id/pause - Stop running; a grid's resting orders are pulled
+pub async fn pause_strategy(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    set_status(&state, &headers, &id, StrategyStatus::Paused).await
+}
+
+/// POST /me/strategies/:id/resume - Resume a paused strategy; the next run is due immediately
+pub async fn resume_strategy(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    set_status(&state, &headers, &id, StrategyStatus::Active).await
+}
+
+/// DELETE /me/strategies/:id - Stop the strategy for good; a grid's resting orders are pulled
+pub async fn cancel_strategy(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    set_status(&state, &headers, &id, StrategyStatus::Cancelled).await
+}
+
+async fn set_status(state: &AppState, headers: &HeaderMap, id: &str, status: StrategyStatus) -> Result<Json<Value>, StatusCode> {
+    let (_, user_address) = session_user(state, headers).await?;
+    let strategy = {
+        let mut book = state.automation.write().await;
+        let strategy = book.owned_mut(id, &user_address).ok_or(StatusCode::NOT_FOUND)?;
+        if status == StrategyStatus::Active && strategy.status == StrategyStatus::Paused {
+            strategy.next_run_at = now_secs();
+        }
+        strategy.status = status;
+        strategy.updated_at = now_secs();
+        let strategy = strategy.clone();
+        book.persist();
+        strategy
+    };
+    if status != StrategyStatus::Active {
+        cancel_grid(state, &strategy).await;
+    }
+    let event = match status {
+        StrategyStatus::Active => "resumed",
+        StrategyStatus::Paused => "paused",
+        StrategyStatus::Cancelled => "cancelled",
+    };
+    audit(state, &strategy, event).await;
+
+    info!("🤖 Strategy {} is now {:?}", id, status);
+    Ok(Json(serde_json::json!({"status": "ok", "response": strategy})))
+}
+
+/// POST /strategies/grid - Start a managed grid; the same as uploading a `grid` strategy
+pub async fn create_grid(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(grid): Json<GridParams>,
+) -> Result<Json<Value>, StatusCode> {
+    create_strategy(State(state), headers, Json(StrategyRequest { params: StrategyParams::Grid(grid) })).await
+}
+
+/// GET /strategies/grid/:id - Each level's price, resting order and fills
+pub async fn grid_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?.to_lowercase();
+    let strategy = state.automation.read().await.strategies.get(&id)
+        .filter(|s| s.user_address == user_address)
+        .cloned()
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let StrategyParams::Grid(grid) = &strategy.params else {
+        return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, "Strategy is not a grid")));
+    };
+
+    let grid_state = strategy.grid.clone().unwrap_or_default();
+    let levels: Vec<Value> = grid.prices().into_iter().enumerate().map(|(level, px)| {
+        let order = grid_state.resting.get(&level);
+        let fills = grid_state.fills.get(&level).copied().unwrap_or_default();
+        serde_json::json!({
+            "level": level,
+            "px": px,
+            "resting": order.map(|o| if o.is_buy { "buy" } else { "sell" }),
+            "cloid": order.map(|o| &o.cloid),
+            "buys_filled": fills.buys,
+            "sells_filled": fills.sells
+        })
+    }).collect();
+    let buys: u64 = grid_state.fills.values().map(|f| f.buys).sum();
+    let sells: u64 = grid_state.fills.values().map(|f| f.sells).sum();
+
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "response": {
+            "id": strategy.id,
+            "status": strategy.status,
+            "params": grid,
+            "mark_px": grid_state.last_mark_px,
+            "levels": levels,
+            "buys_filled": buys,
+            "sells_filled": sells,
+            // A buy and a sell one level apart make one round trip
+            "round_trips": buys.min(sells),
+            "last_run": strategy.history.last()
+        }
+    })))
+}
+
+/// Body of POST /strategies/rebalance
+#[derive(Debug, Deserialize)]
+pub struct RebalanceRequest {
+    pub targets: Vec<RebalanceTarget>,
+    #[serde(default = "default_tolerance_pct")]
+    pub tolerance_pct: f64,
+    #[serde(default = "default_slippage_bps")]
+    pub slippage_bps: u64,
+    /// Plan and check the trades without sending them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// POST /strategies/rebalance - Trade the account to `targets` once, or show the plan with `dry_run`
+pub async fn rebalance_now(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<RebalanceRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let (api_key, user_address) = session_user(&state, &headers).await?;
+    if let Err(e) = validate_targets(&payload.targets, payload.tolerance_pct, payload.slippage_bps) {
+        return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, e)));
+    }
+    if !auth::api_key_has_scope(&state, &api_key, SCOPE_TRADE).await {
+        return Ok(Json(error_codes::err_body(ErrorCode::ScopeNotAllowed, format!("API key is not authorized for the '{}' scope", SCOPE_TRADE))));
+    }
+
+    let trades = match rebalance_trades(&state, &user_address, &payload.targets, payload.tolerance_pct, payload.slippage_bps).await {
+        Ok(trades) => trades,
+        Err(e) => return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, e))),
+    };
+    if trades.is_empty() {
+        return Ok(Json(serde_json::json!({"status": "ok", "response": {"dry_run": payload.dry_run, "trades": trades, "result": null}})));
+    }
+
+    let action = order_action(trades.iter().map(RebalanceTrade::order).collect());
+    let rejection = check_action(&state, &user_address, None, &action).await.err();
+    if payload.dry_run {
+        return Ok(Json(serde_json::json!({
+            "status": "ok",
+            "response": {"dry_run": true, "trades": trades, "would_pass": rejection.is_none(), "rejection": rejection}
+        })));
+    }
+    if let Some(rejection) = rejection {
+        return Ok(Json(rejection));
+    }
+
+    info!("🤖 Rebalancing {} with {} orders", user_address, trades.len());
+    let result = submit_as(&state, &api_key, None, action).await;
+    Ok(Json(serde_json::json!({"status": "ok", "response": {"dry_run": false, "trades": trades, "result": result}})))
+}
+
+/// Trading session behind the request; share tokens can't manage strategies
+async fn session_user(state: &AppState, headers: &HeaderMap) -> Result<(String, String), StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok((api_key.to_string(), user_address.to_lowercase()))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
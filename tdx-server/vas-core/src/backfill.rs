@@ -0,0 +1,146 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use tracing::{info, error};
+
+use crate::auth;
+use crate::events::{EventStore, EVENT_BACKFILL_FILLS, EVENT_BACKFILL_ORDERS, EVENT_WS_USER_FILLS};
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+/// userFillsByTime returns at most this many fills per call
+const FILLS_PAGE_SIZE: usize = 2000;
+/// Pages fetched per backfill; Hyperliquid only serves the most recent 10k fills by time anyway
+const MAX_FILL_PAGES: usize = 5;
+
+/// Body of POST /me/backfill
+#[derive(Debug, Default, Deserialize)]
+pub struct BackfillRequest {
+    /// Earliest fill time to import (unix ms); defaults to everything Hyperliquid still serves
+    #[serde(default)]
+    pub start_time_ms: u64,
+}
+
+/// Fill identity: trade id, which Hyperliquid assigns per fill
+fn fill_id(fill: &Value) -> Option<u64> {
+    fill.get("tid").and_then(|t| t.as_u64())
+}
+
+/// Order status record identity: an order appears once per status change
+fn order_id(order: &Value) -> Option<(u64, String)> {
+    let oid = order.pointer("/order/oid").and_then(|o| o.as_u64())?;
+    let status = order.get("status").and_then(|s| s.as_str()).unwrap_or_default();
+    Some((oid, status.to_string()))
+}
+
+/// Fills already in the store for the user, live or imported
+fn recorded_fill_ids(store: &EventStore, user_address: &str) -> HashSet<u64> {
+    [EVENT_WS_USER_FILLS, EVENT_BACKFILL_FILLS].into_iter()
+        .flat_map(|kind| store.of_kind_since(user_address, kind, 0))
+        .flat_map(|event| event.payload.get("fills").and_then(|f| f.as_array()).into_iter().flatten())
+        .filter_map(fill_id)
+        .collect()
+}
+
+fn recorded_order_ids(store: &EventStore, user_address: &str) -> HashSet<(u64, String)> {
+    store.of_kind_since(user_address, EVENT_BACKFILL_ORDERS, 0)
+        .flat_map(|event| event.payload.get("orders").and_then(|o| o.as_array()).into_iter().flatten())
+        .filter_map(order_id)
+        .collect()
+}
+
+/// Page through userFillsByTime from `start_time_ms` to now, oldest first
+async fn fetch_fills(state: &AppState, user_address: &str, start_time_ms: u64) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut fills = Vec::new();
+    let mut start = start_time_ms;
+    for _ in 0..MAX_FILL_PAGES {
+        let page = state.proxy
+            .proxy_info_request(&serde_json::json!({
+                "type": "userFillsByTime",
+                "user": user_address,
+                "startTime": start,
+                "aggregateByTime": false
+            }))
+            .await?;
+        let page = page.as_array().cloned().unwrap_or_default();
+        let last_time = page.iter().filter_map(|f| f.get("time").and_then(|t| t.as_u64())).max();
+        let full = page.len() >= FILLS_PAGE_SIZE;
+        fills.extend(page);
+
+        match last_time {
+            Some(last_time) if full => start = last_time + 1,
+            _ => break,
+        }
+    }
+    Ok(fills)
+}
+
+/// POST /me/backfill - Import the caller's fill and order history from Hyperliquid.
+///
+/// Imported records are appended to the event store under their own kinds, shaped like live
+/// pushes, so /events, the leaderboard and other fill-based reports include activity from
+/// before onboarding. Already-recorded fills and order statuses are skipped, so reruns are safe.
+pub async fn backfill(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Option<Json<BackfillRequest>>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(&state, api_key)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?
+        .to_lowercase();
+    let Json(request) = payload.unwrap_or_default();
+
+    let fills = fetch_fills(&state, &user_address, request.start_time_ms).await.map_err(|e| {
+        error!("❌ Backfill could not load fills for {}: {}", user_address, e);
+        StatusCode::BAD_GATEWAY
+    })?;
+    let orders = state.proxy
+        .proxy_info_request(&serde_json::json!({"type": "historicalOrders", "user": user_address}))
+        .await
+        .map_err(|e| {
+            error!("❌ Backfill could not load orders for {}: {}", user_address, e);
+            StatusCode::BAD_GATEWAY
+        })?;
+    let orders = orders.as_array().cloned().unwrap_or_default();
+
+    // Dedup under the write lock so concurrent backfills can't import a record twice
+    let mut store = state.event_store.write().await;
+    let mut seen_fills = recorded_fill_ids(&store, &user_address);
+    let new_fills: Vec<Value> = fills.into_iter()
+        .filter(|fill| fill_id(fill).is_some_and(|tid| seen_fills.insert(tid)))
+        .collect();
+    let mut seen_orders = recorded_order_ids(&store, &user_address);
+    let new_orders: Vec<Value> = orders.into_iter()
+        .filter(|order| order_id(order).is_some_and(|id| seen_orders.insert(id)))
+        .collect();
+
+    for chunk in new_fills.chunks(FILLS_PAGE_SIZE) {
+        store.append(&user_address, EVENT_BACKFILL_FILLS, serde_json::json!({"user": user_address, "fills": chunk}));
+    }
+    if !new_orders.is_empty() {
+        store.append(&user_address, EVENT_BACKFILL_ORDERS, serde_json::json!({"user": user_address, "orders": new_orders}));
+    }
+    drop(store);
+
+    let earliest_fill_ms = new_fills.iter().filter_map(|f| f.get("time").and_then(|t| t.as_u64())).min();
+    info!("📥 Backfilled {} fills and {} order statuses for {}", new_fills.len(), new_orders.len(), user_address);
+
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "response": {
+            "fills_imported": new_fills.len(),
+            "orders_imported": new_orders.len(),
+            "earliest_fill_ms": earliest_fill_ms
+        }
+    })))
+}
@@ -0,0 +1,534 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use crate::auth;
+use crate::dca::{format_px, size_for_notional};
+use crate::error_codes::{self, ErrorCode};
+use crate::fees::{self, FeeRates};
+use crate::market_history::{row_time, HistoryKind};
+use crate::AppState;
+
+/// Most candles replayed in one run
+const MAX_CANDLES: usize = 100_000;
+/// Longest indicator lookback
+const MAX_PERIOD: usize = 1000;
+/// Most conditions across all entry and exit rules
+const MAX_CONDITIONS: usize = 32;
+const DEFAULT_SLIPPAGE_BPS: u64 = 10;
+/// Hyperliquid settles funding every hour
+const FUNDING_INTERVAL_MS: u64 = 3_600_000;
+
+/// A value a rule compares, taken from the candle that just closed
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operand {
+    Open,
+    High,
+    Low,
+    Close,
+    Volume,
+    /// Simple moving average of closes
+    Sma(usize),
+    /// Exponential moving average of closes, seeded with the SMA
+    Ema(usize),
+    /// Wilder's RSI of closes
+    Rsi(usize),
+    Value(f64),
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Gt,
+    Lt,
+    /// `left` was at or below `right` on the previous candle and is above it now
+    CrossesAbove,
+    CrossesBelow,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Condition {
+    pub left: Operand,
+    pub op: Comparison,
+    pub right: Operand,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Long,
+    Short,
+}
+
+/// Opens a position when every condition holds
+#[derive(Debug, Clone, Deserialize)]
+pub struct EntryRule {
+    pub side: Side,
+    pub all: Vec<Condition>,
+}
+
+/// Closes the open position when every condition holds
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExitRule {
+    pub all: Vec<Condition>,
+}
+
+/// Declarative strategy: rules over candle data, no code. One position is held at a time.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StrategySpec {
+    pub entries: Vec<EntryRule>,
+    #[serde(default)]
+    pub exits: Vec<ExitRule>,
+    pub order_notional_usd: f64,
+    /// Closes at this loss from the entry price, checked against each candle's range
+    pub stop_loss_pct: Option<f64>,
+    pub take_profit_pct: Option<f64>,
+}
+
+impl StrategySpec {
+    fn validate(&self) -> Result<(), String> {
+        if self.entries.is_empty() {
+            return Err("strategy needs at least one entry rule".to_string());
+        }
+        let rules = self.entries.iter().map(|e| &e.all).chain(self.exits.iter().map(|e| &e.all));
+        let mut conditions = 0;
+        for rule in rules {
+            if rule.is_empty() {
+                return Err("every rule needs at least one condition".to_string());
+            }
+            conditions += rule.len();
+            for operand in rule.iter().flat_map(|c| [c.left, c.right]) {
+                if let Operand::Sma(n) | Operand::Ema(n) | Operand::Rsi(n) = operand {
+                    if n == 0 || n > MAX_PERIOD {
+                        return Err(format!("indicator periods must be between 1 and {}", MAX_PERIOD));
+                    }
+                }
+            }
+        }
+        if conditions > MAX_CONDITIONS {
+            return Err(format!("at most {} conditions per strategy", MAX_CONDITIONS));
+        }
+        if self.order_notional_usd <= 0.0 {
+            return Err("order_notional_usd must be positive".to_string());
+        }
+        if self.stop_loss_pct.is_some_and(|pct| pct <= 0.0 || pct >= 100.0) {
+            return Err("stop_loss_pct must be between 0 and 100".to_string());
+        }
+        if self.take_profit_pct.is_some_and(|pct| pct <= 0.0) {
+            return Err("take_profit_pct must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BacktestRequest {
+    pub coin: String,
+    /// Unix ms, inclusive; defaults to the first recorded candle
+    pub start: Option<u64>,
+    /// Unix ms, inclusive; defaults to now
+    pub end: Option<u64>,
+    pub strategy: StrategySpec,
+    /// Orders are priced this far through the reference price, as live IOC orders are
+    pub slippage_bps: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Candle {
+    time: u64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+}
+
+impl Candle {
+    fn from_row(row: &Value) -> Option<Self> {
+        Some(Self {
+            time: row_time(row),
+            open: row.get("open")?.as_f64()?,
+            high: row.get("high")?.as_f64()?,
+            low: row.get("low")?.as_f64()?,
+            close: row.get("close")?.as_f64()?,
+            volume: row.get("volume")?.as_f64()?,
+        })
+    }
+}
+
+/// One simulated fill
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedFill {
+    pub time: u64,
+    pub side: &'static str,
+    pub px: f64,
+    pub sz: f64,
+    pub fee: f64,
+    /// "entry", "exit", "stop_loss" or "take_profit"
+    pub reason: &'static str,
+    /// Price PnL of the position this fill closed
+    pub realized_pnl: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenPosition {
+    pub side: &'static str,
+    pub sz: f64,
+    pub entry_px: f64,
+    pub unrealized_pnl: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BacktestSummary {
+    pub candles: usize,
+    pub round_trips: usize,
+    pub winning_round_trips: usize,
+    pub realized_pnl: f64,
+    pub fees: f64,
+    /// Positive when the position received funding
+    pub funding: f64,
+    pub unrealized_pnl: f64,
+    pub net_pnl: f64,
+    /// Largest fall in net PnL from a previous high, marked at candle closes
+    pub max_drawdown: f64,
+    /// Entry signals that rounded to a zero size
+    pub skipped_entries: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestResult {
+    pub fills: Vec<SimulatedFill>,
+    pub open_position: Option<OpenPosition>,
+    pub summary: BacktestSummary,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    side: Side,
+    sz: f64,
+    entry_px: f64,
+}
+
+impl Position {
+    fn pnl_at(&self, px: f64) -> f64 {
+        match self.side {
+            Side::Long => (px - self.entry_px) * self.sz,
+            Side::Short => (self.entry_px - px) * self.sz,
+        }
+    }
+}
+
+/// Indicator series computed once per distinct operand
+struct Indicators {
+    series: Vec<(Operand, Vec<Option<f64>>)>,
+}
+
+impl Indicators {
+    fn new(spec: &StrategySpec, candles: &[Candle]) -> Self {
+        let mut series: Vec<(Operand, Vec<Option<f64>>)> = Vec::new();
+        let operands = spec.entries.iter().flat_map(|e| &e.all)
+            .chain(spec.exits.iter().flat_map(|e| &e.all))
+            .flat_map(|c| [c.left, c.right]);
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+        for operand in operands {
+            if series.iter().any(|(o, _)| *o == operand) {
+                continue;
+            }
+            let values = match operand {
+                Operand::Sma(n) => sma(&closes, n),
+                Operand::Ema(n) => ema(&closes, n),
+                Operand::Rsi(n) => rsi(&closes, n),
+                _ => continue,
+            };
+            series.push((operand, values));
+        }
+        Self { series }
+    }
+
+    fn value(&self, operand: Operand, candles: &[Candle], i: usize) -> Option<f64> {
+        let candle = candles.get(i)?;
+        match operand {
+            Operand::Open => Some(candle.open),
+            Operand::High => Some(candle.high),
+            Operand::Low => Some(candle.low),
+            Operand::Close => Some(candle.close),
+            Operand::Volume => Some(candle.volume),
+            Operand::Value(v) => Some(v),
+            _ => self.series.iter().find(|(o, _)| *o == operand).and_then(|(_, values)| values[i]),
+        }
+    }
+
+    /// False while either side is still warming up
+    fn holds(&self, condition: &Condition, candles: &[Candle], i: usize) -> bool {
+        let at = |i: usize| Some((self.value(condition.left, candles, i)?, self.value(condition.right, candles, i)?));
+        let Some((left, right)) = at(i) else { return false };
+        match condition.op {
+            Comparison::Gt => left > right,
+            Comparison::Lt => left < right,
+            Comparison::CrossesAbove => i > 0 && at(i - 1).is_some_and(|(l, r)| l <= r) && left > right,
+            Comparison::CrossesBelow => i > 0 && at(i - 1).is_some_and(|(l, r)| l >= r) && left < right,
+        }
+    }
+}
+
+fn sma(closes: &[f64], n: usize) -> Vec<Option<f64>> {
+    let mut values = vec![None; closes.len()];
+    let mut sum = 0.0;
+    for i in 0..closes.len() {
+        sum += closes[i];
+        if i >= n {
+            sum -= closes[i - n];
+        }
+        if i + 1 >= n {
+            values[i] = Some(sum / n as f64);
+        }
+    }
+    values
+}
+
+fn ema(closes: &[f64], n: usize) -> Vec<Option<f64>> {
+    let alpha = 2.0 / (n as f64 + 1.0);
+    let mut values = sma(closes, n);
+    for i in n..closes.len() {
+        values[i] = values[i - 1].map(|previous| previous + alpha * (closes[i] - previous));
+    }
+    values
+}
+
+fn rsi(closes: &[f64], n: usize) -> Vec<Option<f64>> {
+    let mut values = vec![None; closes.len()];
+    let (mut gain, mut loss) = (0.0, 0.0);
+    for i in 1..closes.len() {
+        let change = closes[i] - closes[i - 1];
+        let (up, down) = (change.max(0.0), (-change).max(0.0));
+        if i <= n {
+            gain += up / n as f64;
+            loss += down / n as f64;
+        } else {
+            gain = (gain * (n - 1) as f64 + up) / n as f64;
+            loss = (loss * (n - 1) as f64 + down) / n as f64;
+        }
+        if i >= n {
+            values[i] = Some(if loss == 0.0 { 100.0 } else { 100.0 - 100.0 / (1.0 + gain / loss) });
+        }
+    }
+    values
+}
+
+/// How simulated orders are priced, sized and charged
+struct Execution {
+    sz_decimals: u32,
+    slippage: f64,
+    rates: FeeRates,
+}
+
+impl Execution {
+    /// IOC limit price `slippage` through the reference, rounded as live orders are
+    fn fill_px(&self, reference: f64, is_buy: bool) -> f64 {
+        let px = if is_buy { reference * (1.0 + self.slippage) } else { reference * (1.0 - self.slippage) };
+        format_px(px, self.sz_decimals).parse().unwrap_or(px)
+    }
+
+    fn close(&self, position: &mut Option<Position>, result: &mut BacktestResult, time: u64, reference: f64, reason: &'static str) {
+        let Some(open) = position.take() else { return };
+        let px = self.fill_px(reference, open.side == Side::Short);
+        let fee = px * open.sz * self.rates.taker;
+        let pnl = open.pnl_at(px);
+        result.summary.realized_pnl += pnl;
+        result.summary.fees += fee;
+        result.summary.round_trips += 1;
+        if pnl > 0.0 {
+            result.summary.winning_round_trips += 1;
+        }
+        result.fills.push(SimulatedFill {
+            time,
+            side: if open.side == Side::Long { "sell" } else { "buy" },
+            px,
+            sz: open.sz,
+            fee,
+            reason,
+            realized_pnl: Some(pnl),
+        });
+    }
+}
+
+/// Run `spec` over `candles` (oldest first).
+///
+/// Rules are evaluated when a candle closes and act at the next candle's open, so a signal
+/// never trades on the candle that produced it. Stops and targets fill intrabar, the stop
+/// first when one candle reaches both. Every fill is an IOC taker order priced and sized with
+/// the same rounding live orders use.
+fn run(spec: &StrategySpec, candles: &[Candle], funding: &[(u64, f64)], execution: &Execution) -> BacktestResult {
+    let indicators = Indicators::new(spec, candles);
+    let mut result = BacktestResult {
+        fills: Vec::new(),
+        open_position: None,
+        summary: BacktestSummary { candles: candles.len(), ..BacktestSummary::default() },
+    };
+    let mut position: Option<Position> = None;
+    // Entry side to open, or None to close, at the next open
+    let mut signal: Option<Option<Side>> = None;
+    let mut peak = 0.0f64;
+
+    for (i, candle) in candles.iter().enumerate() {
+        // Funding settles on the hour at the rate last sampled before it
+        if let (Some(open), Some(previous)) = (position, i.checked_sub(1).map(|p| candles[p].time)) {
+            let mut settlement = (previous / FUNDING_INTERVAL_MS + 1) * FUNDING_INTERVAL_MS;
+            while settlement <= candle.time {
+                if let Some((_, rate)) = funding.iter().rev().find(|(time, _)| *time <= settlement) {
+                    let payment = open.sz * candle.open * rate;
+                    result.summary.funding += if open.side == Side::Long { -payment } else { payment };
+                }
+                settlement += FUNDING_INTERVAL_MS;
+            }
+        }
+
+        match signal.take() {
+            Some(Some(side)) if position.is_none() => {
+                let px = execution.fill_px(candle.open, side == Side::Long);
+                let sz = size_for_notional(spec.order_notional_usd, px, execution.sz_decimals);
+                if sz <= 0.0 {
+                    result.summary.skipped_entries += 1;
+                } else {
+                    let fee = px * sz * execution.rates.taker;
+                    result.summary.fees += fee;
+                    position = Some(Position { side, sz, entry_px: px });
+                    result.fills.push(SimulatedFill {
+                        time: candle.time,
+                        side: if side == Side::Long { "buy" } else { "sell" },
+                        px,
+                        sz,
+                        fee,
+                        reason: "entry",
+                        realized_pnl: None,
+                    });
+                }
+            }
+            Some(None) => execution.close(&mut position, &mut result, candle.time, candle.open, "exit"),
+            _ => {}
+        }
+
+        if let Some(open) = position {
+            let long = open.side == Side::Long;
+            let stop = spec.stop_loss_pct.map(|pct| open.entry_px * if long { 1.0 - pct / 100.0 } else { 1.0 + pct / 100.0 });
+            let target = spec.take_profit_pct.map(|pct| open.entry_px * if long { 1.0 + pct / 100.0 } else { 1.0 - pct / 100.0 });
+            // A candle that gaps through a level fills at its open
+            if let Some(stop) = stop.filter(|stop| if long { candle.low <= *stop } else { candle.high >= *stop }) {
+                let reference = if long { stop.min(candle.open) } else { stop.max(candle.open) };
+                execution.close(&mut position, &mut result, candle.time, reference, "stop_loss");
+            } else if let Some(target) = target.filter(|target| if long { candle.high >= *target } else { candle.low <= *target }) {
+                let reference = if long { target.max(candle.open) } else { target.min(candle.open) };
+                execution.close(&mut position, &mut result, candle.time, reference, "take_profit");
+            }
+        }
+
+        let all = |conditions: &[Condition]| conditions.iter().all(|c| indicators.holds(c, candles, i));
+        signal = match position {
+            None => spec.entries.iter().find(|entry| all(&entry.all)).map(|entry| Some(entry.side)),
+            Some(_) => spec.exits.iter().any(|exit| all(&exit.all)).then_some(None),
+        };
+
+        let summary = &mut result.summary;
+        let net = summary.realized_pnl - summary.fees + summary.funding + position.map_or(0.0, |p| p.pnl_at(candle.close));
+        peak = peak.max(net);
+        summary.max_drawdown = summary.max_drawdown.max(peak - net);
+    }
+
+    let last_close = candles.last().map_or(0.0, |c| c.close);
+    result.open_position = position.map(|open| OpenPosition {
+        side: if open.side == Side::Long { "long" } else { "short" },
+        sz: open.sz,
+        entry_px: open.entry_px,
+        unrealized_pnl: open.pnl_at(last_close),
+    });
+    let summary = &mut result.summary;
+    summary.unrealized_pnl = result.open_position.as_ref().map_or(0.0, |p| p.unrealized_pnl);
+    summary.net_pnl = summary.realized_pnl - summary.fees + summary.funding + summary.unrealized_pnl;
+    result
+}
+
+/// POST /backtest - Replay recorded candles through a declarative strategy.
+///
+/// Uses the candles and funding the market history recorder stored for `coin`, the asset's
+/// live size decimals and the caller's fee rates. Nothing is signed or submitted.
+pub async fn backtest(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<BacktestRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if let Err(e) = request.strategy.validate() {
+        return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, e)));
+    }
+    let history = state.market_history.clone();
+    if !history.is_recorded(&request.coin) {
+        return Ok(Json(error_codes::err_body(ErrorCode::UnknownAsset, format!("{} has no recorded market history (see MARKET_HISTORY_ASSETS)", request.coin))));
+    }
+    let asset = match state.market.asset_index(&request.coin).await {
+        Ok(Some(index)) => state.market.asset(index).await,
+        Ok(None) => Ok(None),
+        Err(e) => Err(e),
+    };
+    let sz_decimals = match asset {
+        Ok(Some(asset)) => asset.sz_decimals,
+        Ok(None) => return Ok(Json(error_codes::err_body(ErrorCode::UnknownAsset, format!("Unknown perp: {}", request.coin)))),
+        Err(e) => return Ok(Json(error_codes::err_body(ErrorCode::UpstreamUnavailable, e.to_string()))),
+    };
+
+    let start = request.start.unwrap_or(0);
+    let end = request.end.unwrap_or(u64::MAX);
+    if start > end {
+        return Ok(Json(error_codes::err_body(ErrorCode::InvalidRange, "start must not be after end")));
+    }
+    let coin = request.coin.clone();
+    let loaded = tokio::task::spawn_blocking(move || {
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>((
+            history.query(HistoryKind::Candles, &coin, start, end)?,
+            history.query(HistoryKind::Funding, &coin, start.saturating_sub(FUNDING_INTERVAL_MS), end)?,
+        ))
+    }).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (candle_rows, funding_rows) = match loaded {
+        Ok(rows) => rows,
+        Err(e) => return Ok(Json(error_codes::err_body(ErrorCode::InternalError, format!("Failed to read market history: {}", e)))),
+    };
+    let candles: Vec<Candle> = candle_rows.iter().filter_map(Candle::from_row).collect();
+    if candles.is_empty() {
+        return Ok(Json(error_codes::err_body(ErrorCode::InvalidRange, format!("No recorded candles for {} in range", request.coin))));
+    }
+    if candles.len() > MAX_CANDLES {
+        return Ok(Json(error_codes::err_body(ErrorCode::LimitExceeded, format!("Range covers {} candles; at most {} per run", candles.len(), MAX_CANDLES))));
+    }
+    let funding: Vec<(u64, f64)> = funding_rows.iter()
+        .filter_map(|row| Some((row_time(row), row.get("funding")?.as_f64()?)))
+        .collect();
+
+    let rates = fees::rates_for(&state, &user_address).await;
+    let slippage_bps = request.slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS);
+    let execution = Execution { sz_decimals, slippage: slippage_bps as f64 / 10_000.0, rates };
+    let result = run(&request.strategy, &candles, &funding, &execution);
+    info!("🧪 Backtest for {} on {}: {} fills over {} candles, net PnL {:.2}",
+        user_address, request.coin, result.fills.len(), candles.len(), result.summary.net_pnl);
+
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "response": {
+            "coin": request.coin,
+            "start": candles.first().map(|c| c.time),
+            "end": candles.last().map(|c| c.time),
+            "interval": candle_rows.first().and_then(|row| row.get("interval")).cloned(),
+            "slippage_bps": slippage_bps,
+            "fee_rates": rates,
+            "result": result
+        }
+    })))
+}
@@ -0,0 +1,277 @@
+use axum::{
+    extract::{Path, State},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::error_codes::{self, ErrorCode};
+use crate::ws_feed::WsFeed;
+use crate::AppState;
+
+/// How long GET /market/book waits for the first push of a newly tracked book
+const WARMUP_WAIT: Duration = Duration::from_secs(2);
+
+/// One price level as Hyperliquid reports it; `sz` "0" in a diff means the level is gone
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Level {
+    pub px: String,
+    pub sz: String,
+    /// Number of orders at the level
+    pub n: u64,
+}
+
+/// Latest L2 snapshot of one coin; `seq` counts upstream pushes since tracking began
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Book {
+    pub coin: String,
+    pub seq: u64,
+    pub time: u64,
+    pub bids: Vec<Level>,
+    pub asks: Vec<Level>,
+}
+
+/// Levels that changed between two snapshots. A diff with `prev_seq` 0 lists the whole book.
+///
+/// Hyperliquid pushes the top levels only, so a level that drops out of range shows as removed.
+#[derive(Debug, Clone, Serialize)]
+pub struct BookDiff {
+    pub coin: String,
+    pub seq: u64,
+    pub prev_seq: u64,
+    pub time: u64,
+    pub bids: Vec<Level>,
+    pub asks: Vec<Level>,
+}
+
+/// L2 books kept from the shared WS feed, so colocated bots need no upstream connection of their own
+#[derive(Debug)]
+pub struct BookService {
+    books: RwLock<HashMap<String, Book>>,
+    diffs: broadcast::Sender<BookDiff>,
+    ws_feed: Arc<WsFeed>,
+    max_books: usize,
+}
+
+impl BookService {
+    /// Start applying l2Book pushes and track `coins` right away
+    pub fn spawn(ws_feed: Arc<WsFeed>, coins: &[String], max_books: usize) -> Arc<Self> {
+        let (diffs, _) = broadcast::channel(1024);
+        let service = Arc::new(Self { books: RwLock::new(HashMap::new()), diffs, ws_feed, max_books });
+
+        let mut messages = service.ws_feed.listen();
+        let applier = service.clone();
+        tokio::spawn(async move {
+            loop {
+                let message = match messages.recv().await {
+                    Ok(message) => message,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        // The next push is a full snapshot, so books recover on their own
+                        warn!("⚠️ Book service lagged, {} WS messages dropped", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                if message.get("channel").and_then(|c| c.as_str()) == Some("l2Book") {
+                    if let Some(data) = message.get("data") {
+                        applier.apply(data);
+                    }
+                }
+            }
+        });
+
+        let tracker = service.clone();
+        let coins = coins.to_vec();
+        tokio::spawn(async move {
+            for coin in coins {
+                if let Err(e) = tracker.track(&coin).await {
+                    warn!("⚠️ Not tracking book for {}: {}", coin, e);
+                }
+            }
+        });
+        service
+    }
+
+    /// Start tracking a coin's book (idempotent)
+    pub async fn track(&self, coin: &str) -> Result<(), String> {
+        {
+            let mut books = self.books.write().unwrap();
+            if books.contains_key(coin) {
+                return Ok(());
+            }
+            if books.len() >= self.max_books {
+                return Err(format!("At most {} books are tracked", self.max_books));
+            }
+            books.insert(coin.to_string(), Book { coin: coin.to_string(), ..Book::default() });
+        }
+        self.ws_feed.subscribe(serde_json::json!({"type": "l2Book", "coin": coin})).await;
+        info!("📚 Tracking L2 book for {}", coin);
+        Ok(())
+    }
+
+    pub fn snapshot(&self, coin: &str) -> Option<Book> {
+        self.books.read().unwrap().get(coin).cloned()
+    }
+
+    pub fn listen(&self) -> broadcast::Receiver<BookDiff> {
+        self.diffs.subscribe()
+    }
+
+    /// Replace the coin's book with an upstream push and publish what changed
+    fn apply(&self, data: &Value) {
+        let Some(coin) = data.get("coin").and_then(|c| c.as_str()) else {
+            return;
+        };
+        let side = |i: usize| -> Vec<Level> {
+            data.pointer(&format!("/levels/{}", i))
+                .and_then(|l| l.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|level| Some(Level {
+                    px: level.get("px")?.as_str()?.to_string(),
+                    sz: level.get("sz")?.as_str()?.to_string(),
+                    n: level.get("n").and_then(|n| n.as_u64()).unwrap_or(0),
+                }))
+                .collect()
+        };
+        let (bids, asks) = (side(0), side(1));
+        let time = data.get("time").and_then(|t| t.as_u64()).unwrap_or(0);
+
+        // Publish under the lock so diffs go out in sequence order
+        let mut books = self.books.write().unwrap();
+        let Some(book) = books.get_mut(coin) else {
+            return;
+        };
+        let diff = BookDiff {
+            coin: coin.to_string(),
+            seq: book.seq + 1,
+            prev_seq: book.seq,
+            time,
+            bids: diff_side(&book.bids, &bids),
+            asks: diff_side(&book.asks, &asks),
+        };
+        *book = Book { coin: coin.to_string(), seq: diff.seq, time, bids, asks };
+        let _ = self.diffs.send(diff);
+    }
+}
+
+/// Levels added or changed in `new`, plus levels of `old` that are gone (with size "0")
+fn diff_side(old: &[Level], new: &[Level]) -> Vec<Level> {
+    let previous: HashMap<&str, &Level> = old.iter().map(|l| (l.px.as_str(), l)).collect();
+    let current: HashMap<&str, &Level> = new.iter().map(|l| (l.px.as_str(), l)).collect();
+
+    let mut changes: Vec<Level> = new.iter()
+        .filter(|level| previous.get(level.px.as_str()) != Some(level))
+        .cloned()
+        .collect();
+    changes.extend(old.iter()
+        .filter(|level| !current.contains_key(level.px.as_str()))
+        .map(|level| Level { px: level.px.clone(), sz: "0".to_string(), n: 0 }));
+    changes
+}
+
+/// Resolve a coin name or perp asset index to a tracked coin, starting to track it if needed
+async fn tracked_coin(state: &AppState, asset: &str) -> Result<String, Value> {
+    let unknown = || error_codes::err_body(ErrorCode::UnknownAsset, format!("Unknown asset: {}", asset));
+    let coin = match asset.parse::<u64>() {
+        Ok(index) => match state.market.asset(index).await {
+            Ok(Some(info)) => info.name,
+            Ok(None) => return Err(unknown()),
+            Err(e) => return Err(error_codes::err_body(ErrorCode::UpstreamUnavailable, e.to_string())),
+        },
+        Err(_) => match state.market.asset_index(asset).await {
+            Ok(Some(_)) => asset.to_string(),
+            Ok(None) => return Err(unknown()),
+            Err(e) => return Err(error_codes::err_body(ErrorCode::UpstreamUnavailable, e.to_string())),
+        },
+    };
+    state.books.track(&coin).await.map_err(|e| error_codes::err_body(ErrorCode::LimitExceeded, e))?;
+    Ok(coin)
+}
+
+/// GET /market/book/:asset - Latest L2 snapshot for a coin name or perp asset index
+pub async fn get_book(State(state): State<AppState>, Path(asset): Path<String>) -> Json<Value> {
+    let coin = match tracked_coin(&state, &asset).await {
+        Ok(coin) => coin,
+        Err(body) => return Json(body),
+    };
+
+    let mut updates = state.books.listen();
+    let mut book = state.books.snapshot(&coin).unwrap_or_default();
+    if book.seq == 0 {
+        // Just subscribed: wait briefly for the first push
+        let _ = tokio::time::timeout(WARMUP_WAIT, async {
+            while let Ok(diff) = updates.recv().await {
+                if diff.coin == coin {
+                    return;
+                }
+            }
+        }).await;
+        book = state.books.snapshot(&coin).unwrap_or_default();
+    }
+    if book.seq == 0 {
+        return Json(error_codes::err_body(ErrorCode::ServiceUnavailable, format!("Book for {} is not available yet; retry shortly", coin)));
+    }
+
+    Json(serde_json::json!({"status": "ok", "response": book}))
+}
+
+/// GET /market/book/:asset/stream - Server-sent events: a `snapshot`, then a `diff` per upstream push.
+///
+/// Event ids are sequence numbers. A client that falls behind is sent a fresh `snapshot`,
+/// so applying events in order always reproduces the book.
+pub async fn stream_book(State(state): State<AppState>, Path(asset): Path<String>) -> Response {
+    let coin = match tracked_coin(&state, &asset).await {
+        Ok(coin) => coin,
+        Err(body) => return Json(body).into_response(),
+    };
+
+    // Listen before taking the snapshot so no diff falls in between
+    let updates = state.books.listen();
+    let initial = state.books.snapshot(&coin).filter(|book| book.seq > 0);
+    let books = state.books.clone();
+
+    let events = futures_util::stream::unfold(
+        (books, updates, coin, initial, 0u64),
+        |(books, mut updates, coin, mut pending, mut last_seq)| async move {
+            if let Some(book) = pending.take() {
+                last_seq = book.seq;
+                return Some((Ok::<_, Infallible>(event("snapshot", book.seq, &book)), (books, updates, coin, None, last_seq)));
+            }
+            loop {
+                match updates.recv().await {
+                    Ok(diff) if diff.coin == coin && diff.seq > last_seq => {
+                        last_seq = diff.seq;
+                        return Some((Ok(event("diff", diff.seq, &diff)), (books, updates, coin, None, last_seq)));
+                    }
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        if let Some(book) = books.snapshot(&coin).filter(|book| book.seq > 0) {
+                            last_seq = book.seq;
+                            return Some((Ok(event("snapshot", book.seq, &book)), (books, updates, coin, None, last_seq)));
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        },
+    );
+
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+fn event(kind: &str, seq: u64, body: &impl Serialize) -> Event {
+    Event::default()
+        .event(kind)
+        .id(seq.to_string())
+        .data(serde_json::to_string(body).unwrap_or_default())
+}
@@ -0,0 +1,341 @@
+use axum::{
+    extract::{Query, Request, State},
+    http::{HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::auth;
+use crate::config::Config;
+use crate::error_codes::{self, ErrorCode};
+use crate::notify::{Notification, NotificationKind};
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+/// Keys unseen this long are dropped from tracking
+const FORGET_AFTER_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+/// Bounds on a user's liveness expectation
+const MIN_SILENCE_SECS: u64 = 60;
+const MAX_SILENCE_SECS: u64 = 7 * 24 * 60 * 60;
+/// POST routes that submit signed actions; /exchange/simulate signs nothing
+const TRADING_PATHS: &[&str] = &["/exchange", "/exchange/raw", "/exchange/cancel-asset"];
+/// How often liveness expectations are checked
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Where a key stands, by how recently it traded and was seen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotStatus {
+    /// Sent an /exchange request within the active window
+    Active,
+    /// Seen recently, but not trading
+    Idle,
+    /// Not seen within the stale window
+    Stale,
+}
+
+/// Requests one API key has made since it was first seen
+#[derive(Debug, Clone)]
+struct KeyActivity {
+    user_address: String,
+    first_seen_ms: u64,
+    last_seen_ms: u64,
+    last_exchange_ms: Option<u64>,
+    requests: u64,
+    exchange_requests: u64,
+}
+
+/// A user's promise that their bots trade at least this often
+#[derive(Debug, Clone, Serialize)]
+pub struct LivenessExpectation {
+    pub max_silence_secs: u64,
+    pub set_at_ms: u64,
+    /// When the current silence was alerted; cleared once a bot trades again
+    pub alerted_at_ms: Option<u64>,
+}
+
+/// Last-seen activity per API key, and the liveness expectations users set on their bots.
+///
+/// Uses std locks: the middleware records every authenticated request and never holds them
+/// across an await.
+#[derive(Debug)]
+pub struct BotMonitor {
+    active_ms: u64,
+    stale_ms: u64,
+    keys: Mutex<HashMap<String, KeyActivity>>,
+    expectations: Mutex<HashMap<String, LivenessExpectation>>,
+}
+
+impl BotMonitor {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            active_ms: config.bot_active_secs * 1000,
+            stale_ms: config.bot_stale_secs * 1000,
+            keys: Mutex::new(HashMap::new()),
+            expectations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn observe(&self, api_key: &str, user_address: &str, exchange: bool, now: u64) {
+        let mut keys = self.keys.lock().unwrap();
+        if !keys.contains_key(api_key) {
+            keys.retain(|_, activity| activity.last_seen_ms + FORGET_AFTER_MS > now);
+        }
+        let activity = keys.entry(api_key.to_string()).or_insert_with(|| KeyActivity {
+            user_address: user_address.to_string(),
+            first_seen_ms: now,
+            last_seen_ms: now,
+            last_exchange_ms: None,
+            requests: 0,
+            exchange_requests: 0,
+        });
+        activity.last_seen_ms = now;
+        activity.requests += 1;
+        if exchange {
+            activity.last_exchange_ms = Some(now);
+            activity.exchange_requests += 1;
+        }
+    }
+
+    fn status(&self, activity: &KeyActivity, now: u64) -> BotStatus {
+        if activity.last_exchange_ms.is_some_and(|at| at + self.active_ms > now) {
+            BotStatus::Active
+        } else if activity.last_seen_ms + self.stale_ms > now {
+            BotStatus::Idle
+        } else {
+            BotStatus::Stale
+        }
+    }
+
+    /// Most recent /exchange request across the user's keys
+    fn last_trade_ms(&self, user_address: &str) -> Option<u64> {
+        self.keys.lock().unwrap().values()
+            .filter(|activity| activity.user_address == user_address)
+            .filter_map(|activity| activity.last_exchange_ms)
+            .max()
+    }
+
+    /// How long the user's bots have been silent, counted from when the expectation was set at the earliest
+    fn silent_for_ms(&self, user_address: &str, expectation: &LivenessExpectation, now: u64) -> u64 {
+        let since = self.last_trade_ms(user_address).unwrap_or(0).max(expectation.set_at_ms);
+        now.saturating_sub(since)
+    }
+
+    pub fn expectation(&self, user_address: &str) -> Option<LivenessExpectation> {
+        self.expectations.lock().unwrap().get(user_address).cloned()
+    }
+
+    /// Users whose bots went silent past their expectation, and users whose bots came back
+    fn check_expectations(&self, now: u64) -> (Vec<(String, u64, u64)>, Vec<String>) {
+        let mut silent = Vec::new();
+        let mut resumed = Vec::new();
+        let mut expectations = self.expectations.lock().unwrap();
+        for (user_address, expectation) in expectations.iter_mut() {
+            let silent_for_ms = self.silent_for_ms(user_address, expectation, now);
+            let overdue = silent_for_ms > expectation.max_silence_secs * 1000;
+            match (overdue, expectation.alerted_at_ms) {
+                (true, None) => {
+                    expectation.alerted_at_ms = Some(now);
+                    silent.push((user_address.clone(), silent_for_ms, expectation.max_silence_secs));
+                }
+                (false, Some(_)) => {
+                    expectation.alerted_at_ms = None;
+                    resumed.push(user_address.clone());
+                }
+                _ => {}
+            }
+        }
+        (silent, resumed)
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&self, user_address: &str) -> usize {
+        let mut keys = self.keys.lock().unwrap();
+        let before = keys.len();
+        keys.retain(|_, activity| !activity.user_address.eq_ignore_ascii_case(user_address));
+        let removed = before - keys.len();
+        removed + usize::from(self.expectations.lock().unwrap().remove(user_address).is_some())
+    }
+}
+
+/// Middleware: record each authenticated API key's activity. Runs after auth; share tokens
+/// are read-only viewers, not bots, and aren't tracked.
+pub async fn track(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(api_key) = auth::api_key_from_headers(request.headers()).filter(|k| !k.starts_with(SHARE_TOKEN_PREFIX)) else {
+        return next.run(request).await;
+    };
+    let api_key = api_key.to_string();
+    let path = request.uri().path();
+    let exchange = request.method() == Method::POST && (TRADING_PATHS.contains(&path) || path.starts_with("/exchange/cosign/"));
+
+    // Keys that resolve to no account (public routes skip auth) aren't tracked
+    if let Some(user_address) = auth::user_address_for_api_key(&state, &api_key).await {
+        state.bots.observe(&api_key, &user_address.to_lowercase(), exchange, now_ms());
+    }
+    next.run(request).await
+}
+
+/// Alert users whose bots stopped trading for longer than they said they would
+pub fn spawn_watchdog(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(WATCHDOG_INTERVAL);
+        loop {
+            ticker.tick().await;
+
+            let (silent, resumed) = state.bots.check_expectations(now_ms());
+            for (user_address, silent_for_ms, max_silence_secs) in silent {
+                warn!("🤖 Bots of {} silent for {}s (expected at most {}s)", user_address, silent_for_ms / 1000, max_silence_secs);
+                state.notifier.notify(Notification::new(
+                    NotificationKind::Alert,
+                    Some(&user_address),
+                    "Bot went silent",
+                    serde_json::json!({"silent_for_ms": silent_for_ms, "max_silence_secs": max_silence_secs}),
+                ));
+            }
+            for user_address in resumed {
+                info!("🤖 Bots of {} trading again", user_address);
+                state.notifier.notify(Notification::new(
+                    NotificationKind::Alert,
+                    Some(&user_address),
+                    "Bot trading again",
+                    serde_json::json!({}),
+                ));
+            }
+        }
+    });
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BotsQuery {
+    pub status: Option<BotStatus>,
+}
+
+/// GET /admin/bots?status= - Every tracked API key with its last-seen activity and status, most recent first
+pub async fn admin_bots(
+    State(state): State<AppState>,
+    Query(query): Query<BotsQuery>,
+) -> Json<Value> {
+    let monitor = &state.bots;
+    let now = now_ms();
+    let keys: Vec<(String, KeyActivity)> = monitor.keys.lock().unwrap()
+        .iter()
+        .map(|(key, activity)| (key.clone(), activity.clone()))
+        .collect();
+
+    let mut summary: HashMap<BotStatus, usize> = HashMap::new();
+    let mut bots: Vec<(u64, Value)> = Vec::new();
+    for (api_key, activity) in keys {
+        let status = monitor.status(&activity, now);
+        *summary.entry(status).or_default() += 1;
+        if query.status.is_some_and(|wanted| wanted != status) {
+            continue;
+        }
+        let liveness = monitor.expectation(&activity.user_address).map(|expectation| serde_json::json!({
+            "max_silence_secs": expectation.max_silence_secs,
+            "silent_for_ms": monitor.silent_for_ms(&activity.user_address, &expectation, now),
+            "alerting": expectation.alerted_at_ms.is_some()
+        }));
+        bots.push((activity.last_seen_ms, serde_json::json!({
+            "key_fingerprint": hex::encode(&Sha256::digest(api_key.as_bytes())[..8]),
+            "user_address": activity.user_address,
+            "status": status,
+            "first_seen_ms": activity.first_seen_ms,
+            "last_seen_ms": activity.last_seen_ms,
+            "last_exchange_ms": activity.last_exchange_ms,
+            "requests": activity.requests,
+            "exchange_requests": activity.exchange_requests,
+            "liveness": liveness
+        })));
+    }
+    bots.sort_by_key(|(last_seen_ms, _)| std::cmp::Reverse(*last_seen_ms));
+
+    Json(serde_json::json!({
+        "thresholds": {"active_secs": monitor.active_ms / 1000, "stale_secs": monitor.stale_ms / 1000},
+        "summary": {
+            "active": summary.get(&BotStatus::Active).copied().unwrap_or(0),
+            "idle": summary.get(&BotStatus::Idle).copied().unwrap_or(0),
+            "stale": summary.get(&BotStatus::Stale).copied().unwrap_or(0)
+        },
+        "bots": bots.into_iter().map(|(_, bot)| bot).collect::<Vec<_>>()
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LivenessRequest {
+    /// Alert when none of the caller's keys sends an /exchange request for this long; absent clears it
+    pub max_silence_secs: Option<u64>,
+}
+
+/// Trading session behind the request; share tokens can't read or change expectations
+async fn session_user(state: &AppState, headers: &HeaderMap) -> Result<String, StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok(user_address.to_lowercase())
+}
+
+/// GET /me/bot-liveness - The caller's liveness expectation and how long their bots have been silent
+pub async fn get_liveness(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_address = session_user(&state, &headers).await?;
+    let expectation = state.bots.expectation(&user_address);
+    let silent_for_ms = expectation.as_ref().map(|e| state.bots.silent_for_ms(&user_address, e, now_ms()));
+
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "response": {
+            "expectation": expectation,
+            "last_exchange_ms": state.bots.last_trade_ms(&user_address),
+            "silent_for_ms": silent_for_ms
+        }
+    })))
+}
+
+/// PUT /me/bot-liveness - Set (or clear) how long the caller's bots may go without trading
+pub async fn set_liveness(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<LivenessRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_address = session_user(&state, &headers).await?;
+    let mut expectations = state.bots.expectations.lock().unwrap();
+
+    let Some(max_silence_secs) = payload.max_silence_secs else {
+        expectations.remove(&user_address);
+        info!("🤖 Liveness expectation removed for {}", user_address);
+        return Ok(Json(serde_json::json!({"status": "ok", "response": "removed"})));
+    };
+    if !(MIN_SILENCE_SECS..=MAX_SILENCE_SECS).contains(&max_silence_secs) {
+        return Ok(Json(error_codes::err_body(
+            ErrorCode::BadRequest,
+            format!("max_silence_secs must be between {} and {}", MIN_SILENCE_SECS, MAX_SILENCE_SECS),
+        )));
+    }
+
+    let expectation = LivenessExpectation { max_silence_secs, set_at_ms: now_ms(), alerted_at_ms: None };
+    expectations.insert(user_address.clone(), expectation.clone());
+    info!("🤖 Liveness expectation for {} set to {}s", user_address, max_silence_secs);
+    Ok(Json(serde_json::json!({"status": "ok", "response": expectation})))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,90 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{info, error};
+
+use crate::auth;
+use crate::delegation;
+use crate::error_codes::{self, ErrorCode};
+use crate::AppState;
+
+/// Body of POST /exchange/cancel-asset
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelAssetRequest {
+    /// Perp asset index, as used in order actions
+    pub asset: u64,
+    pub vault_address: Option<String>,
+}
+
+/// POST /exchange/cancel-asset - Cancel every resting order the account has on one asset.
+///
+/// Open orders are read from upstream `openOrders`, then cancelled in a single bulk `cancel`
+/// that goes through the same scope, delegation, policy and audit path as /exchange.
+pub async fn cancel_asset(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CancelAssetRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+
+    // Orders live on the vault or grantor when acting for one; /exchange re-checks the delegation
+    let account = match (&payload.vault_address, headers.get(delegation::DELEGATED_FROM_HEADER).and_then(|v| v.to_str().ok())) {
+        (Some(vault), _) => vault.to_lowercase(),
+        (None, Some(grantor)) => grantor.to_lowercase(),
+        (None, None) => auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?,
+    };
+
+    let coin = match state.market.asset(payload.asset).await {
+        Ok(Some(asset)) => asset.name,
+        Ok(None) => {
+            return Ok(Json(error_codes::err_body(ErrorCode::UnknownAsset, format!("Unknown asset index {}", payload.asset))));
+        }
+        Err(e) => {
+            error!("❌ Failed to load asset metadata: {}", e);
+            return Err(StatusCode::BAD_GATEWAY);
+        }
+    };
+
+    let open_orders = state.proxy
+        .proxy_info_request(&serde_json::json!({"type": "openOrders", "user": account}))
+        .await
+        .map_err(|e| {
+            error!("❌ Failed to fetch open orders: {}", e);
+            StatusCode::BAD_GATEWAY
+        })?;
+
+    let cancels: Vec<Value> = open_orders.as_array()
+        .map(|orders| orders.iter()
+            .filter(|order| order.get("coin").and_then(|c| c.as_str()) == Some(coin.as_str()))
+            .filter_map(|order| order.get("oid").and_then(|o| o.as_u64()))
+            .map(|oid| serde_json::json!({"a": payload.asset, "o": oid}))
+            .collect())
+        .unwrap_or_default();
+
+    if cancels.is_empty() {
+        return Ok(Json(serde_json::json!({
+            "status": "ok",
+            "response": {"type": "cancel", "data": {"statuses": []}},
+            "cancelled": 0
+        })));
+    }
+
+    info!("🧹 Cancelling {} resting {} orders for {}", cancels.len(), coin, account);
+    let count = cancels.len();
+
+    let mut exchange_payload = serde_json::json!({
+        "action": {"type": "cancel", "cancels": cancels}
+    });
+    if let Some(vault) = &payload.vault_address {
+        exchange_payload["vaultAddress"] = serde_json::json!(vault);
+    }
+
+    let Json(mut response) = crate::proxy_exchange(State(state), headers, Json(exchange_payload)).await?;
+    response["cancelled"] = serde_json::json!(count);
+    Ok(Json(response))
+}
@@ -0,0 +1,158 @@
+use serde_json::Value;
+use tracing::{info, warn, error};
+
+use crate::error_codes::{self, ErrorCode};
+use crate::signer::ActionRequest;
+use crate::AppState;
+
+/// Body field asking that a multi-order request be undone if any of its orders is rejected;
+/// it is removed before the action is signed
+pub const ALL_OR_NOTHING_FIELD: &str = "allOrNothing";
+
+/// Remove the all-or-nothing flag from an /exchange body; it only applies to orders placed together
+pub fn take_all_or_nothing(payload: &mut Value) -> Result<bool, String> {
+    let Some(flag) = payload.as_object_mut().and_then(|obj| obj.remove(ALL_OR_NOTHING_FIELD)) else {
+        return Ok(false);
+    };
+    let Some(flag) = flag.as_bool() else {
+        return Err(format!("{} must be a boolean", ALL_OR_NOTHING_FIELD));
+    };
+    if flag && order_count(payload.get("action").unwrap_or(&Value::Null)) < 2 {
+        return Err(format!("{} applies only to order actions with more than one order", ALL_OR_NOTHING_FIELD));
+    }
+    Ok(flag)
+}
+
+fn order_count(action: &Value) -> usize {
+    if action.get("type").and_then(|t| t.as_str()) != Some("order") {
+        return 0;
+    }
+    action.get("orders").and_then(|o| o.as_array()).map_or(0, Vec::len)
+}
+
+/// Outcome of one order of a multi-order request, in request order
+struct Leg {
+    index: usize,
+    asset: Option<u64>,
+    cloid: Option<String>,
+    status: Value,
+}
+
+impl Leg {
+    fn error(&self) -> Option<&str> {
+        self.status.get("error").and_then(|e| e.as_str())
+    }
+
+    /// Resting legs can be cancelled; filled ones have already traded
+    fn resting_oid(&self) -> Option<u64> {
+        self.status.pointer("/resting/oid").and_then(|o| o.as_u64())
+    }
+
+    fn to_json(&self) -> Value {
+        let mut result = serde_json::json!({
+            "index": self.index,
+            "cloid": self.cloid,
+            "ok": self.error().is_none(),
+            "status": self.status,
+        });
+        if let Some(error) = self.error() {
+            result["error"] = Value::String(error.to_string());
+        }
+        result
+    }
+}
+
+/// Give a multi-order response explicit per-order results, keyed to the client's cloids.
+///
+/// Hyperliquid answers `status: ok` as long as the batch was accepted, even when some orders
+/// in it were rejected. The response's `status` becomes `partial` when orders both succeeded
+/// and failed, and `err` when all failed. With `all_or_nothing`, a partial batch has its
+/// resting orders cancelled; filled orders cannot be undone and are reported as such.
+pub async fn settle(state: &AppState, request: &ActionRequest, response: &mut Value, all_or_nothing: bool) {
+    if order_count(&request.action) < 2 {
+        return;
+    }
+    let Some(statuses) = response.pointer("/response/data/statuses").and_then(|s| s.as_array()) else {
+        return;
+    };
+    let orders = request.action.get("orders").and_then(|o| o.as_array()).cloned().unwrap_or_default();
+    let legs: Vec<Leg> = statuses.iter().enumerate()
+        .map(|(index, status)| Leg {
+            index,
+            asset: orders.get(index).and_then(|o| o.get("a")).and_then(|a| a.as_u64()),
+            cloid: orders.get(index).and_then(|o| o.get("c")).and_then(|c| c.as_str()).map(str::to_string),
+            status: status.clone(),
+        })
+        .collect();
+
+    let failed = legs.iter().filter(|leg| leg.error().is_some()).count();
+    let succeeded = legs.len() - failed;
+    response["results"] = Value::Array(legs.iter().map(Leg::to_json).collect());
+    response["summary"] = serde_json::json!({"succeeded": succeeded, "failed": failed});
+
+    if failed == 0 {
+        return;
+    }
+    if succeeded == 0 {
+        let first_error = legs.iter().find_map(Leg::error).unwrap_or_default();
+        response["status"] = serde_json::json!("err");
+        response["code"] = serde_json::json!(ErrorCode::for_upstream(first_error));
+        return;
+    }
+
+    response["status"] = serde_json::json!("partial");
+    response["code"] = serde_json::json!(ErrorCode::PartialFailure);
+    warn!("⚠️ {} of {} orders rejected in one request", failed, legs.len());
+    if all_or_nothing {
+        response["rollback"] = roll_back(state, request, &legs).await;
+    }
+}
+
+/// Cancel the resting legs of a partially failed all-or-nothing request
+async fn roll_back(state: &AppState, request: &ActionRequest, legs: &[Leg]) -> Value {
+    let mut cancels = Vec::new();
+    let mut cancel_sent = Vec::new();
+    let mut not_cancelled = Vec::new();
+    for leg in legs.iter().filter(|leg| leg.error().is_none()) {
+        match (leg.resting_oid(), leg.asset) {
+            (Some(oid), Some(asset)) => {
+                cancels.push(serde_json::json!({"a": asset, "o": oid}));
+                cancel_sent.push(serde_json::json!({"index": leg.index, "cloid": leg.cloid, "oid": oid}));
+            }
+            _ => not_cancelled.push(serde_json::json!({
+                "index": leg.index,
+                "cloid": leg.cloid,
+                "reason": "filled orders cannot be cancelled"
+            })),
+        }
+    }
+    if cancels.is_empty() {
+        return serde_json::json!({"cancel_sent": cancel_sent, "not_cancelled": not_cancelled});
+    }
+
+    let cancel = ActionRequest {
+        action: serde_json::json!({"type": "cancel", "cancels": cancels}),
+        nonce: now_ms().max(request.nonce + 1),
+        vault_address: request.vault_address.clone(),
+        is_mainnet: request.is_mainnet,
+        user_address: request.user_address.clone(),
+    };
+    let response = match state.signer.sign_action(cancel).await {
+        Ok(response) => {
+            info!("↩️ Rolled back {} resting orders of a partially failed request", cancel_sent.len());
+            response
+        }
+        Err(e) => {
+            error!("❌ Rollback of a partially failed request failed: {}", e);
+            error_codes::err_body(ErrorCode::for_upstream(&e.to_string()), e.to_string())
+        }
+    };
+    serde_json::json!({"cancel_sent": cancel_sent, "not_cancelled": not_cancelled, "response": response})
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
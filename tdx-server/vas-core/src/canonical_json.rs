@@ -0,0 +1,261 @@
+//! Canonical JSON (RFC 8785, JCS) for everything the enclave hashes or signs as JSON.
+//!
+//! serde_json is built with `preserve_order` so msgpack action hashes follow the client's field
+//! order; that makes its output depend on how a `Value` was built. Hashes that external verifiers
+//! recompute go through here instead: object keys sorted by UTF-16 code units, no whitespace,
+//! minimal string escapes and ECMAScript number formatting, so `JSON.stringify` over sorted keys
+//! (or any JCS library) reproduces the bytes. One deviation: integers beyond 2^53 keep every
+//! digit rather than being rounded to the nearest double.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+
+/// Canonical text of `value`
+pub fn to_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out
+}
+
+/// Canonical bytes of `value`
+pub fn to_vec(value: &Value) -> Vec<u8> {
+    to_string(value).into_bytes()
+}
+
+/// Hex sha256 of the canonical bytes of `value`
+pub fn sha256_hex(value: &Value) -> String {
+    hex::encode(Sha256::digest(to_string(value).as_bytes()))
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(out, n),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(fields) => {
+            let mut fields: Vec<(&String, &Value)> = fields.iter().collect();
+            fields.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, value)) in fields.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, value);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_number(out: &mut String, n: &serde_json::Number) {
+    if let Some(u) = n.as_u64() {
+        let _ = write!(out, "{}", u);
+    } else if let Some(i) = n.as_i64() {
+        let _ = write!(out, "{}", i);
+    } else if let Some(f) = n.as_f64() {
+        out.push_str(&format_f64(f));
+    }
+}
+
+/// ECMAScript Number::toString for a finite double: shortest round-trip digits, plain
+/// notation for exponents in [-7, 21), exponent notation (`1e+21`, `1e-7`) outside it
+fn format_f64(f: f64) -> String {
+    if f == 0.0 {
+        return "0".to_string();
+    }
+    let sign = if f < 0.0 { "-" } else { "" };
+    // `{:e}` gives the shortest digits that round-trip, as `d.ddde<exp>`
+    let scientific = format!("{:e}", f.abs());
+    let (mantissa, exponent) = scientific.split_once('e').expect("LowerExp always has an exponent");
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent.parse().expect("LowerExp exponent is an integer");
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat((-n) as usize), digits)
+    } else {
+        let exp_sign = if n - 1 < 0 { "-" } else { "+" };
+        let fraction = if k > 1 { format!(".{}", &digits[1..]) } else { String::new() };
+        format!("{}{}e{}{}", &digits[..1], fraction, exp_sign, (n - 1).abs())
+    };
+    format!("{}{}", sign, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn canon(text: &str) -> String {
+        to_string(&serde_json::from_str(text).unwrap())
+    }
+
+    #[test]
+    fn test_literals() {
+        assert_eq!(to_string(&json!(null)), "null");
+        assert_eq!(to_string(&json!(true)), "true");
+        assert_eq!(to_string(&json!(false)), "false");
+        assert_eq!(to_string(&json!([])), "[]");
+        assert_eq!(to_string(&json!({})), "{}");
+    }
+
+    #[test]
+    fn test_keys_sorted_regardless_of_insertion_order() {
+        let mut a = serde_json::Map::new();
+        a.insert("b".to_string(), json!(1));
+        a.insert("a".to_string(), json!(2));
+        let mut b = serde_json::Map::new();
+        b.insert("a".to_string(), json!(2));
+        b.insert("b".to_string(), json!(1));
+        assert_eq!(to_string(&Value::Object(a)), to_string(&Value::Object(b)));
+        assert_eq!(canon(r#"{"b":1,"a":2}"#), r#"{"a":2,"b":1}"#);
+    }
+
+    #[test]
+    fn test_nested_objects_are_sorted() {
+        assert_eq!(
+            canon(r#"{"z":{"y":[{"b":1,"a":0}],"x":null},"a":[3,{"d":1,"c":2}]}"#),
+            r#"{"a":[3,{"c":2,"d":1}],"z":{"x":null,"y":[{"a":0,"b":1}]}}"#
+        );
+    }
+
+    #[test]
+    fn test_array_order_is_kept() {
+        assert_eq!(canon("[3, 1, 2]"), "[3,1,2]");
+    }
+
+    #[test]
+    fn test_whitespace_removed() {
+        assert_eq!(canon("{ \"a\" : [ 1 , 2 ] ,\n \"b\" : { } }"), r#"{"a":[1,2],"b":{}}"#);
+    }
+
+    #[test]
+    fn test_keys_sort_by_utf16_code_units() {
+        // RFC 8785 section 3.2.3: U+1F600 (surrogates D83D DE00) sorts before U+FB33 in UTF-16,
+        // though after it by code point
+        assert_eq!(
+            canon(r#"{"דּ":3,"😀":2,"é":1,"A":0,"a":4,"":5}"#),
+            "{\"\":5,\"A\":0,\"a\":4,\"\u{e9}\":1,\"\u{1f600}\":2,\"\u{fb33}\":3}"
+        );
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        assert_eq!(to_string(&json!("quote\" backslash\\")), r#""quote\" backslash\\""#);
+        assert_eq!(to_string(&json!("\u{08}\t\n\u{0c}\r")), r#""\b\t\n\f\r""#);
+        assert_eq!(to_string(&json!("\u{00}\u{01}\u{1f}")), r#""\u0000\u0001\u001f""#);
+        // Solidus, DEL, non-ASCII and line separators are not escaped
+        assert_eq!(to_string(&json!("/\u{7f}é\u{2028}€😀")), "\"/\u{7f}é\u{2028}€😀\"");
+    }
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(to_string(&json!(0)), "0");
+        assert_eq!(to_string(&json!(-1)), "-1");
+        assert_eq!(to_string(&json!(1_700_000_000_000u64)), "1700000000000");
+        assert_eq!(to_string(&json!(u64::MAX)), "18446744073709551615");
+        assert_eq!(to_string(&json!(i64::MIN)), "-9223372036854775808");
+    }
+
+    #[test]
+    fn test_floats_follow_ecmascript() {
+        let cases: [(f64, &str); 22] = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-1.5, "-1.5"),
+            (4.50, "4.5"),
+            (0.1, "0.1"),
+            (2e-3, "0.002"),
+            (0.000001, "0.000001"),
+            (1e-7, "1e-7"),
+            (1.5e-7, "1.5e-7"),
+            (123.456, "123.456"),
+            (1e20, "100000000000000000000"),
+            (1e21, "1e+21"),
+            (1.2345e21, "1.2345e+21"),
+            (1e30, "1e+30"),
+            (333333333.3333333, "333333333.3333333"),
+            (9007199254740993.0, "9007199254740992"),
+            (5e-324, "5e-324"),
+            (-5e-324, "-5e-324"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+            (295147905179352830000.0, "295147905179352830000"),
+            (0.30000000000000004, "0.30000000000000004"),
+        ];
+        for (f, expected) in cases {
+            assert_eq!(to_string(&json!(f)), expected, "{:?}", f);
+        }
+    }
+
+    #[test]
+    fn test_parsed_floats_are_normalized() {
+        assert_eq!(canon("[1.0, 1e2, 1E-2, -0.0, 10.50]"), "[1,100,0.01,0,10.5]");
+    }
+
+    #[test]
+    fn test_rfc8785_example() {
+        let input = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "€$\u000F\u000aA'B\"\\\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        assert_eq!(
+            canon(input),
+            "{\"literals\":[null,true,false],\"numbers\":[333333333.3333333,1e+30,4.5,0.002,1e-27],\"string\":\"€$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\"}"
+        );
+    }
+
+    #[test]
+    fn test_canonical_output_is_a_fixed_point() {
+        let once = canon(r#"{"b":[1.50,{"d":"\u0007","c":-0}],"a":1e25}"#);
+        assert_eq!(canon(&once), once);
+    }
+
+    #[test]
+    fn test_sha256_hex_ignores_key_order() {
+        let a = sha256_hex(&serde_json::from_str(r#"{"x":1,"y":[true]}"#).unwrap());
+        let b = sha256_hex(&serde_json::from_str(r#"{"y":[true],"x":1}"#).unwrap());
+        assert_eq!(a, b);
+        assert_eq!(a, hex::encode(Sha256::digest(br#"{"x":1,"y":[true]}"#)));
+    }
+}
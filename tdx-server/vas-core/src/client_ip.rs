@@ -0,0 +1,157 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use std::net::{IpAddr, SocketAddr};
+
+use crate::AppState;
+
+tokio::task_local! {
+    /// Resolved client address for the request being handled; read when recording audit entries
+    pub static CLIENT_IP: Option<IpAddr>;
+}
+
+/// Client address of a request after trusted-proxy resolution, available as a request extension
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+/// Address ranges whose forwarding headers are believed
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    cidrs: Vec<(IpAddr, u8)>,
+    /// Trust connections arriving over unix sockets (a local reverse proxy)
+    unix: bool,
+}
+
+impl TrustedProxies {
+    /// Parse comma-separated CIDRs or bare addresses; `unix` trusts unix-socket peers
+    pub fn parse(entries: &[String]) -> Result<Self, String> {
+        let mut proxies = Self::default();
+        for entry in entries {
+            if entry == "unix" {
+                proxies.unix = true;
+                continue;
+            }
+            let (addr, prefix) = match entry.split_once('/') {
+                Some((addr, prefix)) => (addr, Some(prefix)),
+                None => (entry.as_str(), None),
+            };
+            let addr: IpAddr = addr.parse().map_err(|e| format!("Invalid trusted proxy '{}': {}", entry, e))?;
+            let max = if addr.is_ipv4() { 32 } else { 128 };
+            let prefix = match prefix {
+                Some(prefix) => prefix.parse().ok().filter(|p| *p <= max)
+                    .ok_or_else(|| format!("Invalid prefix length in '{}'", entry))?,
+                None => max,
+            };
+            proxies.cidrs.push((addr, prefix));
+        }
+        Ok(proxies)
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = canonical(ip);
+        self.cidrs.iter().any(|(net, prefix)| match (net, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => prefix_matches(&net.octets(), &ip.octets(), *prefix),
+            (IpAddr::V6(net), IpAddr::V6(ip)) => prefix_matches(&net.octets(), &ip.octets(), *prefix),
+            _ => false,
+        })
+    }
+
+    /// Client address for a request from `peer` (None = unix socket).
+    /// Forwarding headers are only read when the peer is trusted; the chain is walked from
+    /// the right, skipping trusted hops, so a client can't spoof its address by prepending.
+    pub fn resolve(&self, peer: Option<SocketAddr>, headers: &HeaderMap) -> Option<IpAddr> {
+        let peer_ip = peer.map(|p| canonical(p.ip()));
+        let peer_trusted = match peer_ip {
+            Some(ip) => self.contains(ip),
+            None => self.unix,
+        };
+        if !peer_trusted {
+            return peer_ip;
+        }
+
+        let chain = forwarded_for(headers);
+        chain.iter().rev()
+            .find(|ip| !self.contains(**ip))
+            .or_else(|| chain.first())
+            .copied()
+            .or(peer_ip)
+    }
+}
+
+/// Addresses from `Forwarded: for=` (preferred) or `X-Forwarded-For`, nearest hop last
+fn forwarded_for(headers: &HeaderMap) -> Vec<IpAddr> {
+    let forwarded: Vec<IpAddr> = headers.get_all("forwarded").iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .flat_map(|element| element.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .filter(|(key, _)| key.eq_ignore_ascii_case("for"))
+        .filter_map(|(_, value)| parse_node(value.trim_matches('"')))
+        .collect();
+    if !forwarded.is_empty() {
+        return forwarded;
+    }
+
+    headers.get_all("x-forwarded-for").iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|node| parse_node(node.trim()))
+        .collect()
+}
+
+/// Parse `1.2.3.4`, `1.2.3.4:567`, `[::1]` or `[::1]:567`
+fn parse_node(node: &str) -> Option<IpAddr> {
+    if let Ok(ip) = node.parse::<IpAddr>() {
+        return Some(canonical(ip));
+    }
+    if let Ok(addr) = node.parse::<SocketAddr>() {
+        return Some(canonical(addr.ip()));
+    }
+    node.strip_prefix('[')?.split(']').next()?.parse().ok().map(canonical)
+}
+
+/// IPv4-mapped IPv6 addresses (from dual-stack sockets) compare as IPv4
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        ip => ip,
+    }
+}
+
+fn prefix_matches(net: &[u8], ip: &[u8], prefix: u8) -> bool {
+    let full = (prefix / 8) as usize;
+    let rest = prefix % 8;
+    if net[..full] != ip[..full] {
+        return false;
+    }
+    rest == 0 || (net[full] ^ ip[full]) & (0xffu8 << (8 - rest)) == 0
+}
+
+/// Middleware: resolve the client address once per request for handlers and the audit log
+pub async fn resolve_client_ip(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0);
+    let ip = state.trusted_proxies.resolve(peer, request.headers());
+    request.extensions_mut().insert(ClientIp(ip));
+
+    CLIENT_IP.scope(ip, next.run(request)).await
+}
+
+/// Client address recorded on `request` by `resolve_client_ip`, for logging
+pub fn describe(request: &Request) -> String {
+    match request.extensions().get::<ClientIp>() {
+        Some(ClientIp(Some(ip))) => ip.to_string(),
+        _ => "unknown client".to_string(),
+    }
+}
+
+/// Client address of the request currently being handled, if any
+pub fn current() -> Option<String> {
+    CLIENT_IP.try_with(|ip| ip.map(|ip| ip.to_string())).ok().flatten()
+}
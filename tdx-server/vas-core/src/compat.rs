@@ -0,0 +1,175 @@
+use axum::{extract::State, response::Json};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::AppState;
+
+/// String statuses in an exchange response's `data.statuses`
+const EXCHANGE_STATUS_STRINGS: &[&str] = &["success", "waitingForFill", "waitingForTrigger"];
+/// Object statuses in an exchange response's `data.statuses`, by their single key
+const EXCHANGE_STATUS_KEYS: &[&str] = &["resting", "filled", "error"];
+const EXCHANGE_RESULT_STATUSES: &[&str] = &["ok", "err"];
+
+/// Fields of a `meta.universe` entry; the first three are read by pre-sign checks
+const META_ASSET_FIELDS: &[&str] = &[
+    "name", "szDecimals", "maxLeverage", "onlyIsolated", "isDelisted", "marginTableId",
+    "marginMode", "growthMode", "lastGrowthModeChangeTime",
+];
+const META_ASSET_REQUIRED: &[&str] = &["name", "szDecimals", "maxLeverage"];
+/// Fields of an asset context; markPx and funding are read by pre-sign checks
+const ASSET_CTX_FIELDS: &[&str] = &[
+    "funding", "openInterest", "prevDayPx", "dayNtlVlm", "premium", "oraclePx", "markPx",
+    "midPx", "impactPxs", "dayBaseVlm",
+];
+const ASSET_CTX_REQUIRED: &[&str] = &["markPx", "funding"];
+
+/// One unexpected shape seen in an upstream response
+#[derive(Debug, Clone, Serialize)]
+pub struct Observation {
+    pub count: u64,
+    pub first_seen_ms: u64,
+    pub last_seen_ms: u64,
+    /// The value that carried it, truncated, to help update the known shapes
+    pub sample: String,
+}
+
+/// Unknown fields, statuses and missing required fields seen in Hyperliquid responses.
+///
+/// Responses are always passed through untouched; this only makes upstream schema changes
+/// visible (log on first sight, metric on every sight) before they turn into silent gaps.
+#[derive(Debug, Default)]
+pub struct SchemaWatch {
+    /// (context, item) -> observation
+    seen: Mutex<BTreeMap<(String, String), Observation>>,
+}
+
+impl SchemaWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&self, context: &str, item: &str, sample: &Value) {
+        let now = now_ms();
+        let mut seen = self.seen.lock().unwrap();
+        let observation = seen.entry((context.to_string(), item.to_string())).or_insert_with(|| {
+            warn!("🧬 Upstream schema change: unexpected {} '{}' in {}", context, item, sample);
+            Observation { count: 0, first_seen_ms: now, last_seen_ms: now, sample: sample.to_string().chars().take(256).collect() }
+        });
+        observation.count += 1;
+        observation.last_seen_ms = now;
+    }
+
+    /// Check a signed action's response for result and order statuses we don't model
+    pub fn inspect_exchange_response(&self, response: &Value) {
+        if let Some(status) = response.get("status").and_then(|s| s.as_str()) {
+            if !EXCHANGE_RESULT_STATUSES.contains(&status) {
+                self.record("exchange_result", status, response);
+            }
+        }
+        let statuses = response.pointer("/response/data/statuses").and_then(|s| s.as_array());
+        for status in statuses.into_iter().flatten() {
+            match status {
+                Value::String(name) if !EXCHANGE_STATUS_STRINGS.contains(&name.as_str()) => {
+                    self.record("exchange_status", name, status);
+                }
+                Value::Object(fields) => {
+                    for key in fields.keys().filter(|k| !EXCHANGE_STATUS_KEYS.contains(&k.as_str())) {
+                        self.record("exchange_status", key, status);
+                    }
+                }
+                Value::String(_) => {}
+                other => self.record("exchange_status", "non-string/object", other),
+            }
+        }
+    }
+
+    /// Check `metaAndAssetCtxs` for new fields and for fields pre-sign checks rely on going missing
+    pub fn inspect_meta_and_ctxs(&self, meta_and_ctxs: &Value) {
+        let universe = meta_and_ctxs.pointer("/0/universe").and_then(|u| u.as_array());
+        let ctxs = meta_and_ctxs.get(1).and_then(|c| c.as_array());
+        if universe.is_none() || ctxs.is_none() {
+            self.record("meta_and_ctxs", "layout", &Value::String("expected [meta, ctxs]".to_string()));
+        }
+        for asset in universe.into_iter().flatten() {
+            self.inspect_object("meta_asset", asset, META_ASSET_FIELDS, META_ASSET_REQUIRED);
+        }
+        for ctx in ctxs.into_iter().flatten() {
+            self.inspect_object("asset_ctx", ctx, ASSET_CTX_FIELDS, ASSET_CTX_REQUIRED);
+        }
+    }
+
+    fn inspect_object(&self, context: &str, value: &Value, known: &[&str], required: &[&str]) {
+        let Some(fields) = value.as_object() else {
+            self.record(context, "non-object", value);
+            return;
+        };
+        for key in fields.keys().filter(|k| !known.contains(&k.as_str())) {
+            self.record(&format!("{}_field", context), key, value);
+        }
+        for field in required.iter().filter(|f| !fields.contains_key(**f)) {
+            self.record(&format!("{}_missing", context), field, value);
+        }
+    }
+
+    fn snapshot(&self) -> BTreeMap<(String, String), Observation> {
+        self.seen.lock().unwrap().clone()
+    }
+}
+
+/// Probe upstream response shapes on an interval, so drift shows up even without traffic
+pub fn spawn_probe(state: AppState) {
+    let Some(interval_secs) = state.config.schema_probe_interval_secs else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            ticker.tick().await;
+            match state.market.meta_and_asset_ctxs().await {
+                Ok(meta_and_ctxs) => state.schema.inspect_meta_and_ctxs(&meta_and_ctxs),
+                Err(e) => warn!("⚠️ Schema probe could not load metaAndAssetCtxs: {}", e),
+            }
+        }
+    });
+    info!("🧬 Upstream schema probe every {}s", interval_secs);
+}
+
+/// GET /admin/upstream-schema - Unexpected upstream shapes seen since startup
+pub async fn admin_upstream_schema(State(state): State<AppState>) -> Json<Value> {
+    let observations: Vec<Value> = state.schema.snapshot().into_iter()
+        .map(|((context, item), observation)| serde_json::json!({
+            "context": context,
+            "item": item,
+            "observation": observation
+        }))
+        .collect();
+    Json(serde_json::json!({"observations": observations}))
+}
+
+/// Prometheus lines for /admin/metrics
+pub fn prometheus(state: &AppState) -> String {
+    let mut out = String::new();
+    out.push_str("# HELP vas_upstream_schema_unknown_total Unexpected fields or statuses seen in Hyperliquid responses\n");
+    out.push_str("# TYPE vas_upstream_schema_unknown_total counter\n");
+    for ((context, item), observation) in state.schema.snapshot() {
+        out.push_str(&format!(
+            "vas_upstream_schema_unknown_total{{context=\"{}\",item=\"{}\"}} {}\n",
+            context,
+            item.replace(['"', '\\', '\n'], "_"),
+            observation.count
+        ));
+    }
+    out
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
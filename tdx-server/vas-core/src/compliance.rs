@@ -0,0 +1,158 @@
+use alloy::primitives::eip191_hash_message;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::{info, error};
+
+use crate::audit::AUDIT_STATEMENT;
+use crate::canonical_json;
+use crate::error_codes::{self, ErrorCode};
+use crate::events::EVENT_EXCHANGE_RESPONSE;
+use crate::locale;
+use crate::preset_tdx::PresetTDXData;
+use crate::AppState;
+
+/// Query parameters for GET /admin/reports/compliance (times in unix ms)
+#[derive(Debug, Deserialize)]
+pub struct ComplianceQuery {
+    pub user: String,
+    pub from: u64,
+    pub to: u64,
+}
+
+/// GET /admin/reports/compliance?user=&from=&to= - Enclave-signed evidence of the controls over
+/// a user's automated trading in the range.
+///
+/// The report combines the user's audit entries, the attestation quotes and pre-sign policies in
+/// force over the range, and the /exchange receipts. It is returned as the exact JSON string that
+/// was hashed; the signed statement carries its sha256, so the report can be archived or rendered
+/// (e.g. to PDF) elsewhere and still be checked against the signature.
+pub async fn compliance_report(
+    State(state): State<AppState>,
+    Query(query): Query<ComplianceQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    if query.user.parse::<alloy::primitives::Address>().is_err() || query.from > query.to {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let user = query.user.to_lowercase();
+
+    let (entries, policies, started_at, head, chain_verified) = {
+        let log = state.audit.read().await;
+        let entries: Vec<Value> = log.signatures_for(&user, query.from, query.to).into_iter()
+            .map(|e| serde_json::json!(e))
+            .collect();
+        let policies: Vec<Value> = log.policies_between(query.from, query.to).into_iter()
+            .map(|e| serde_json::json!({
+                "in_force_from_ms": e.timestamp_ms,
+                "audit_seq": e.seq,
+                "entry_hash": e.entry_hash,
+                "policy": e.subject
+            }))
+            .collect();
+        let head = log.head().map(|h| serde_json::json!({"seq": h.seq, "entry_hash": h.entry_hash}));
+        (entries, policies, log.started_at_ms(), head, log.verify_chain().is_ok())
+    };
+    if query.from < started_at {
+        return Ok(Json(error_codes::err_body(ErrorCode::InvalidRange, format!("Transparency log only covers activity since {}", started_at))));
+    }
+
+    let quotes: Vec<Value> = {
+        let archive = state.quote_archive.read().await;
+        let in_force_at_start = archive.active_at(query.from).map(|q| q.active_from_ms).unwrap_or(0);
+        archive.records().iter()
+            .filter(|q| q.active_from_ms >= in_force_at_start && q.active_from_ms <= query.to)
+            .map(|q| serde_json::json!({
+                "quote_id": q.quote_id,
+                "agent_address": q.agent_address,
+                "active_from_ms": q.active_from_ms,
+                "tdx_quote_hex": q.tdx_quote_hex
+            }))
+            .collect()
+    };
+
+    let receipts: Vec<Value> = state.event_store.read().await
+        .of_kind_since(&user, EVENT_EXCHANGE_RESPONSE, query.from)
+        .filter(|event| event.timestamp_ms <= query.to)
+        .map(|event| serde_json::json!({
+            "timestamp_ms": event.timestamp_ms,
+            "action_hash": event.payload.get("action_hash"),
+            "status": event.payload.get("status"),
+            "response": event.payload.get("response")
+        }))
+        .collect();
+
+    // Display times follow the user's session locale; every machine field stays UTC unix ms
+    let locale = locale::for_user(&state, &user).await;
+    let local_times = locale.as_ref().map(|locale| serde_json::json!({
+        "from": locale.format_ms(query.from),
+        "to": locale.format_ms(query.to)
+    }));
+
+    let report = serde_json::json!({
+        "type": "compliance_report",
+        "user": user,
+        "from": query.from,
+        "to": query.to,
+        "locale": locale,
+        "local_times": local_times,
+        "audit": {
+            "log_head": head,
+            "chain_verified": chain_verified,
+            "entries": entries
+        },
+        "attestation_quotes": quotes,
+        "policies": policies,
+        "receipts": receipts
+    });
+    let report = canonical_json::to_string(&report);
+
+    let preset_data = PresetTDXData::get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let statement = serde_json::json!({
+        "type": "compliance_report",
+        "user": user,
+        "from": query.from,
+        "to": query.to,
+        "report_sha256": hex::encode(Sha256::digest(report.as_bytes())),
+        "audit_entries": entries.len(),
+        "receipts": receipts.len(),
+        "log_head": head,
+        "agent_address": preset_data.agent_address,
+        "quote_id": preset_data.quote_id,
+        "issued_at": now_ms()
+    });
+
+    // EIP-191 over the statement JSON, as for inactivity statements
+    let message = canonical_json::to_string(&statement);
+    let hash = eip191_hash_message(message.as_bytes());
+    let signature = state.signer
+        .sign_digest(hash, None, AUDIT_STATEMENT, statement)
+        .await
+        .map_err(|e| {
+            error!("❌ Failed to sign compliance report: {:?}", e);
+            StatusCode::BAD_GATEWAY
+        })?;
+
+    info!("🧾 Issued compliance report for {} [{}, {}]: {} entries, {} receipts",
+        user, query.from, query.to, entries.len(), receipts.len());
+
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "response": {
+            "report": report,
+            "statement": message,
+            "signature": signature.to_json()
+        }
+    })))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,346 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::Json,
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{info, warn, error};
+
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::market::parse_number;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+/// Pending conditional orders one user may hold at a time
+const MAX_PENDING_PER_USER: usize = 50;
+const DEFAULT_TTL_SECS: u64 = 7 * 24 * 3600;
+const MAX_TTL_SECS: u64 = 30 * 24 * 3600;
+
+/// External price source from EXTERNAL_PRICE_FEEDS: `name=<url>#<json pointer>`,
+/// e.g. `binance_btc=https://api.binance.com/api/v3/ticker/price?symbol=BTCUSDT#/price`
+#[derive(Debug, Clone)]
+pub struct PriceFeed {
+    pub name: String,
+    pub url: String,
+    pub pointer: String,
+}
+
+impl PriceFeed {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (name, source) = spec.split_once('=').ok_or_else(|| format!("Invalid price feed '{}'", spec))?;
+        let (url, pointer) = source.split_once('#').ok_or_else(|| format!("Price feed '{}' needs a #/json/pointer", name))?;
+        Ok(Self { name: name.trim().to_string(), url: url.trim().to_string(), pointer: pointer.trim().to_string() })
+    }
+
+    async fn fetch(&self, client: &Client) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        let body: Value = client.get(&self.url).send().await?.error_for_status()?.json().await?;
+        parse_number(body.pointer(&self.pointer))
+            .filter(|px| px.is_finite() && *px > 0.0)
+            .ok_or_else(|| format!("No price at {} in {} response", self.pointer, self.name).into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerDirection {
+    /// Fire once the feed trades at or above the trigger price
+    Above,
+    /// Fire once the feed trades at or below the trigger price
+    Below,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionalStatus {
+    Pending,
+    Triggered,
+    Failed,
+    Cancelled,
+    Expired,
+}
+
+/// An order held by the enclave until an external price condition is met
+#[derive(Debug, Clone, Serialize)]
+pub struct ConditionalOrder {
+    pub id: String,
+    #[serde(skip)]
+    pub api_key: String,
+    pub user_address: String,
+    pub feed: String,
+    pub direction: TriggerDirection,
+    pub trigger_px: f64,
+    /// Wire `order` action submitted through /exchange when triggered
+    pub action: Value,
+    pub status: ConditionalStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub triggered_at: Option<u64>,
+    pub observed_px: Option<f64>,
+    pub result: Option<Value>,
+}
+
+impl ConditionalOrder {
+    fn is_met(&self, px: f64) -> bool {
+        match self.direction {
+            TriggerDirection::Above => px >= self.trigger_px,
+            TriggerDirection::Below => px <= self.trigger_px,
+        }
+    }
+}
+
+/// Conditional orders by id
+#[derive(Debug, Default)]
+pub struct ConditionalOrderBook {
+    orders: HashMap<String, ConditionalOrder>,
+}
+
+impl ConditionalOrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn pending_for(&self, user_address: &str) -> usize {
+        self.orders.values()
+            .filter(|o| o.user_address == user_address && o.status == ConditionalStatus::Pending)
+            .count()
+    }
+
+    pub fn list(&self, user_address: &str) -> Vec<ConditionalOrder> {
+        let mut orders: Vec<_> = self.orders.values().filter(|o| o.user_address == user_address).cloned().collect();
+        orders.sort_by_key(|o| o.created_at);
+        orders
+    }
+
+    /// Cancel a pending order owned by `user_address`
+    pub fn cancel(&mut self, id: &str, user_address: &str) -> bool {
+        match self.orders.get_mut(id) {
+            Some(order) if order.user_address == user_address && order.status == ConditionalStatus::Pending => {
+                order.status = ConditionalStatus::Cancelled;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Expire stale orders and claim those whose condition is met at the given prices.
+    /// Claimed orders leave `Pending` before submission so they can only fire once.
+    fn claim_triggered(&mut self, prices: &HashMap<String, f64>, now: u64) -> Vec<ConditionalOrder> {
+        let mut triggered = Vec::new();
+        for order in self.orders.values_mut().filter(|o| o.status == ConditionalStatus::Pending) {
+            if order.expires_at <= now {
+                order.status = ConditionalStatus::Expired;
+                continue;
+            }
+            let Some(px) = prices.get(&order.feed).copied() else { continue };
+            if order.is_met(px) {
+                order.status = ConditionalStatus::Triggered;
+                order.triggered_at = Some(now);
+                order.observed_px = Some(px);
+                triggered.push(order.clone());
+            }
+        }
+        triggered
+    }
+
+    fn pending_feeds(&self) -> Vec<String> {
+        let mut feeds: Vec<String> = self.orders.values()
+            .filter(|o| o.status == ConditionalStatus::Pending)
+            .map(|o| o.feed.clone())
+            .collect();
+        feeds.sort();
+        feeds.dedup();
+        feeds
+    }
+
+    fn record_result(&mut self, id: &str, result: Value, ok: bool) {
+        if let Some(order) = self.orders.get_mut(id) {
+            if !ok {
+                order.status = ConditionalStatus::Failed;
+            }
+            order.result = Some(result);
+        }
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> usize {
+        let before = self.orders.len();
+        self.orders.retain(|_, order| !order.user_address.eq_ignore_ascii_case(user_address));
+        before - self.orders.len()
+    }
+}
+
+/// Poll the feeds referenced by pending orders and submit those whose condition is met.
+/// Triggered orders go through the full /exchange pipeline (scope, policy, risk, audit)
+/// under the API key that created them, so they are held to the same limits.
+pub fn spawn_trigger_engine(state: AppState, feeds: Vec<PriceFeed>) {
+    if feeds.is_empty() {
+        info!("🎯 No external price feeds configured; conditional orders disabled");
+        return;
+    }
+    let feeds: HashMap<String, PriceFeed> = feeds.into_iter().map(|f| (f.name.clone(), f)).collect();
+    let client = Client::builder().timeout(Duration::from_secs(5)).build().unwrap_or_default();
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_millis(state.config.conditional_poll_ms.max(250)));
+        loop {
+            ticker.tick().await;
+
+            // A fenced standby leaves triggering to the instance that can sign
+            if state.ha.check().is_err() {
+                continue;
+            }
+
+            let wanted = state.conditional_orders.read().await.pending_feeds();
+            let mut prices = HashMap::new();
+            for name in wanted {
+                let Some(feed) = feeds.get(&name) else { continue };
+                // A failed fetch never triggers anything; orders wait for the next good price
+                match feed.fetch(&client).await {
+                    Ok(px) => { prices.insert(name, px); }
+                    Err(e) => warn!("⚠️ Price feed {} unavailable: {}", name, e),
+                }
+            }
+
+            let triggered = state.conditional_orders.write().await.claim_triggered(&prices, now_secs());
+            for order in triggered {
+                info!("🎯 Conditional order {} triggered: {} {:?} {} at {:?}",
+                    order.id, order.feed, order.direction, order.trigger_px, order.observed_px);
+                let (result, ok) = submit(&state, &order).await;
+                state.conditional_orders.write().await.record_result(&order.id, result, ok);
+            }
+        }
+    });
+}
+
+async fn submit(state: &AppState, order: &ConditionalOrder) -> (Value, bool) {
+    let mut headers = HeaderMap::new();
+    let Ok(api_key) = HeaderValue::from_str(&order.api_key) else {
+        return (error_codes::err_body(ErrorCode::SessionExpired, "Invalid stored API key"), false);
+    };
+    headers.insert("X-API-Key", api_key);
+
+    if auth::user_address_for_api_key(state, &order.api_key).await.is_none() {
+        return (error_codes::err_body(ErrorCode::SessionExpired, "Session expired before the order triggered"), false);
+    }
+
+    let payload = serde_json::json!({"action": order.action});
+    match crate::proxy_exchange(State(state.clone()), headers, Json(payload)).await {
+        Ok(Json(response)) => {
+            let ok = response.get("status").and_then(|s| s.as_str()) != Some("err");
+            (response, ok)
+        }
+        Err(status) => {
+            error!("❌ Conditional order {} failed: {}", order.id, status);
+            (error_codes::err_body(ErrorCode::for_status(status), status.to_string()), false)
+        }
+    }
+}
+
+/// Conditional order parameters
+#[derive(Debug, Deserialize)]
+pub struct CreateConditionalRequest {
+    pub feed: String,
+    pub direction: TriggerDirection,
+    pub trigger_px: f64,
+    pub action: Value,
+    pub ttl_secs: Option<u64>,
+}
+
+/// POST /me/conditional-orders - Hold an order until an external price feed crosses a level
+pub async fn create_conditional(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateConditionalRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let (api_key, user_address) = session_user(&state, &headers).await?;
+
+    let feed_known = state.config.external_price_feeds.iter()
+        .filter_map(|spec| PriceFeed::parse(spec).ok())
+        .any(|feed| feed.name == payload.feed);
+    if !feed_known {
+        return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, format!("Unknown price feed '{}'", payload.feed))));
+    }
+    if payload.action.get("type").and_then(|t| t.as_str()) != Some("order") || !payload.trigger_px.is_finite() || payload.trigger_px <= 0.0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    // Static policy is checked now for early feedback, and again by /exchange when it fires
+    if let Err(violation) = state.policy.read().await.evaluate(&payload.action) {
+        return Ok(Json(violation.to_response()));
+    }
+
+    let mut book = state.conditional_orders.write().await;
+    if book.pending_for(&user_address) >= MAX_PENDING_PER_USER {
+        return Ok(Json(error_codes::err_body(ErrorCode::LimitExceeded, format!("At most {} pending conditional orders per user", MAX_PENDING_PER_USER))));
+    }
+
+    let created_at = now_secs();
+    let order = ConditionalOrder {
+        id: uuid::Uuid::new_v4().to_string(),
+        api_key,
+        user_address,
+        feed: payload.feed,
+        direction: payload.direction,
+        trigger_px: payload.trigger_px,
+        action: payload.action,
+        status: ConditionalStatus::Pending,
+        created_at,
+        expires_at: created_at + payload.ttl_secs.unwrap_or(DEFAULT_TTL_SECS).min(MAX_TTL_SECS),
+        triggered_at: None,
+        observed_px: None,
+        result: None,
+    };
+    book.orders.insert(order.id.clone(), order.clone());
+
+    info!("🎯 Conditional order {} created on {} for {}", order.id, order.feed, order.user_address);
+    Ok(Json(serde_json::json!({"status": "ok", "response": order})))
+}
+
+/// GET /me/conditional-orders - The caller's conditional orders and their outcomes
+pub async fn list_conditional(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(serde_json::json!({
+        "conditional_orders": state.conditional_orders.read().await.list(&user_address.to_lowercase())
+    })))
+}
+
+/// DELETE /me/conditional-orders/:id - Cancel a pending conditional order
+pub async fn cancel_conditional(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let (_, user_address) = session_user(&state, &headers).await?;
+    if !state.conditional_orders.write().await.cancel(&id, &user_address) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("🎯 Conditional order {} cancelled", id);
+    Ok(Json(serde_json::json!({"status": "ok", "response": "cancelled"})))
+}
+
+/// Trading session behind the request; share tokens can't manage orders
+async fn session_user(state: &AppState, headers: &HeaderMap) -> Result<(String, String), StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok((api_key.to_string(), user_address.to_lowercase()))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
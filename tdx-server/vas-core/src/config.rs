@@ -0,0 +1,845 @@
+use serde::Serialize;
+use std::env;
+
+use crate::address;
+use crate::notify::NotificationKind;
+use crate::risk::LiquidationGuardMode;
+use crate::universal_signing::SignatureChain;
+
+/// Delivery channel settings for one notifier
+#[derive(Debug, Clone)]
+pub enum NotifierTransport {
+    Webhook { url: String },
+    Slack { webhook_url: String },
+    Telegram { bot_token: String, chat_id: String },
+    Email { api_url: String, to: String },
+}
+
+/// A notifier and the event kinds routed to it
+#[derive(Debug, Clone)]
+pub struct NotifierConfig {
+    pub transport: NotifierTransport,
+    pub events: Vec<NotificationKind>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Config {
+    /// Listener specs (see `listener::ListenerConfig::parse`), bound side by side
+    pub listeners: Vec<String>,
+    /// CIDRs (or `unix`) of reverse proxies whose X-Forwarded-For/Forwarded headers are believed
+    pub trusted_proxies: Vec<String>,
+    /// `<path prefix>=<ms>` response-time limits (see `route_timeout::RouteTimeouts`)
+    pub route_timeouts: Vec<String>,
+    /// /info request types whose upstream bodies are streamed rather than buffered
+    pub streamed_info_types: Vec<String>,
+    /// Largest /info request body accepted
+    pub info_max_request_bytes: usize,
+    /// Largest upstream /info response relayed; longer bodies are refused rather than buffered
+    pub info_max_response_bytes: usize,
+    pub hyperliquid_url: String,
+    /// SPKI pins for the Hyperliquid API (`sha256/<base64>[@<retire unix secs>]`); empty disables pinning
+    pub upstream_tls_pins: Vec<String>,
+    /// Network signed for, `mainnet` or `testnet`; inferred from `hyperliquid_url` when unset
+    pub hyperliquid_network: String,
+    /// `signatureChainId` (EIP-712 domain chain id) of user-signed actions on mainnet
+    pub mainnet_signature_chain_id: u64,
+    /// `signatureChainId` of user-signed actions on testnet
+    pub testnet_signature_chain_id: u64,
+    /// Phantom agent `source` of L1 actions on mainnet
+    pub mainnet_agent_source: String,
+    /// Phantom agent `source` of L1 actions on testnet
+    pub testnet_agent_source: String,
+    pub log_level: String,
+    pub fixed_api_key: String,
+    /// Agent address of the fixed development key, stored lowercase like session addresses
+    pub test_agent_address: String,
+    /// Reject orders whose projected margin usage (0.0-1.0) would exceed this
+    pub max_margin_usage: Option<f64>,
+    /// Guard band (fraction of mark) for estimated post-trade liquidation price
+    pub liquidation_guard_band: Option<f64>,
+    /// What to do when an order lands inside the liquidation guard band
+    pub liquidation_guard_mode: LiquidationGuardMode,
+    /// Maximum builder fee accepted on orders, in tenths of a basis point
+    pub max_builder_fee: Option<u64>,
+    /// Builder addresses orders may pay fees to (unset = any)
+    pub builder_allowlist: Option<Vec<String>>,
+    /// Referrer code applied via setReferrer on each user's first trade
+    pub referrer_code: Option<String>,
+    /// JSON-lines file for the event store (empty disables persistence)
+    pub event_store_path: Option<String>,
+    /// Hash-chained audit (transparency) log file; None keeps it in memory only
+    pub audit_log_path: Option<String>,
+    /// Days of raw events to keep (None keeps them indefinitely)
+    pub event_retention_days: Option<u64>,
+    /// Days of audit entries to keep before folding them into Merkle-root checkpoints (None keeps them)
+    pub audit_retention_days: Option<u64>,
+    /// How often the retention compactor runs
+    pub retention_interval_secs: u64,
+    /// Archive of every attestation quote served; None keeps it in memory only
+    pub quote_archive_path: Option<String>,
+    /// JSON-lines file holding active OCO links; None keeps them in memory only
+    pub oco_store_path: Option<String>,
+    /// JSON-lines file holding automated strategies and grid state; None keeps them in memory only
+    pub strategy_store_path: Option<String>,
+    /// JSON-lines file holding users' encrypted agent notes; None keeps them in memory only
+    pub agent_notes_path: Option<String>,
+    /// JSON-lines file holding equity snapshots; None keeps them in memory only
+    pub equity_store_path: Option<String>,
+    /// Seconds between equity snapshots of users with a session; None (0) disables them
+    pub equity_snapshot_secs: Option<u64>,
+    /// Days of equity snapshots to keep
+    pub equity_retention_days: u64,
+    /// JSON-lines file holding pending user deletions; None keeps them in memory only
+    pub deletion_store_path: Option<String>,
+    /// Seconds between POST /me/delete and the erasure, during which it can be cancelled
+    pub user_deletion_grace_secs: u64,
+    /// Hyperliquid testnet REST endpoint used by POST /agents/test-drive; None disables it
+    pub testnet_url: Option<String>,
+    /// Hyperliquid WebSocket endpoint (derived from the REST URL by default)
+    pub hyperliquid_ws_url: String,
+    /// Enabled notification transports
+    #[serde(skip)]
+    pub notifiers: Vec<NotifierConfig>,
+    /// Signing backend: "local" (key in enclave memory) or "remote" (external signer service)
+    pub signer_backend: String,
+    /// Remote signer endpoint receiving `{hash, address}` and returning `{r, s, v}`
+    pub remote_signer_url: Option<String>,
+    /// Bearer token for the remote signer
+    pub remote_signer_auth_token: Option<String>,
+    /// Address every remote signature must recover to
+    pub remote_signer_address: Option<String>,
+    /// MRTDs the remote signer's registered quote must carry (hex, lowercase)
+    pub remote_signer_mrtds: Vec<String>,
+    /// How long a co-sign request waits for the user's signature
+    pub cosign_timeout_secs: u64,
+    /// Orders whose notional exceeds this (USD) need user confirmation (None disables)
+    pub confirm_notional_threshold: Option<f64>,
+    /// How long a held order waits for confirmation
+    pub confirm_timeout_secs: u64,
+    /// How long /exchange remembers an Idempotency-Key and its response
+    pub idempotency_ttl_secs: u64,
+    /// Require a personal_sign from the session wallet rather than just a second API call
+    pub confirm_require_signature: bool,
+    /// `0xcontract:0xselector` (or `0xcontract:*`) pairs the agent may call on HyperEVM
+    pub evm_allowlist: Vec<String>,
+    /// HyperEVM JSON-RPC endpoint for nonce/fee lookup and broadcasting
+    pub hyperevm_rpc_url: Option<String>,
+    /// Ethereum JSON-RPC endpoint for reverse-ENS lookups at login (unset disables)
+    pub ens_rpc_url: Option<String>,
+    /// HyperEVM registry contract checked during onboarding (skipped when unset)
+    pub registry_address: Option<String>,
+    /// Override for the HyperEVM chain id (defaults to 999 mainnet / 998 testnet)
+    pub hyperevm_chain_id: Option<u64>,
+    /// `name:chainId:verifyingContract:PrimaryType` entries (`*` = any) the agent may sign as EIP-712
+    pub typed_data_allowlist: Vec<String>,
+    /// Attach estimated fees (at the user's fee tier) to signed order responses
+    pub fee_estimates_in_responses: bool,
+    /// HMAC secret /admin requests can be signed with, as key id `admin`
+    pub admin_token: Option<String>,
+    /// `<key id>=<secret>` further HMAC keys for /admin requests, one per operator
+    pub admin_key_secrets: Vec<String>,
+    /// Wallets whose EIP-191 signatures authorize /admin requests
+    pub admin_addresses: Vec<String>,
+    /// How far a signed /admin request's timestamp may be from server time
+    pub admin_request_max_skew_secs: u64,
+    /// Requests per minute allowed for each API key
+    pub rate_limit_per_minute: u64,
+    /// Lifetime of a SIWE session when the login doesn't ask for one
+    pub session_ttl_secs: u64,
+    /// Longest session lifetime a login may ask for; also caps sliding sessions
+    pub session_max_ttl_secs: u64,
+    /// Extend sessions by their lifetime each time their key authenticates
+    pub session_sliding_expiration: bool,
+    /// Window over which distinct client IPs / user agents of one API key are counted
+    pub key_abuse_window_ms: u64,
+    /// More distinct IPs than this within the window flags the key as shared
+    pub key_abuse_max_ips: usize,
+    /// More distinct user agents than this within the window flags the key as shared
+    pub key_abuse_max_user_agents: usize,
+    /// Revoke flagged sessions so the owner must sign in with SIWE again
+    pub key_abuse_require_reauth: bool,
+    /// Keys that sent an /exchange request within this many seconds count as actively trading
+    pub bot_active_secs: u64,
+    /// Keys not seen for this many seconds count as stale in /admin/bots
+    pub bot_stale_secs: u64,
+    /// How far behind / ahead of server time a client nonce may be
+    pub nonce_window_past_ms: u64,
+    pub nonce_window_future_ms: u64,
+    /// Smoothed drift beyond which a key is flagged in /admin/clock-drift
+    pub nonce_drift_warn_ms: u64,
+    /// Seconds between end-to-end signing probes (unset disables them)
+    pub signing_probe_interval_secs: Option<u64>,
+    /// Seconds between re-attestations; a failure puts the server in safe mode. None (0) disables them
+    pub reattest_interval_secs: Option<u64>,
+    /// Shell command that regenerates agent_quote.bin before each re-attestation
+    pub reattest_command: Option<String>,
+    /// Hold cancels while Hyperliquid is down or in maintenance and submit them once it is back
+    pub upstream_queue_cancels: bool,
+    /// Seconds between checks of whether Hyperliquid is back, while it is down
+    pub upstream_recheck_secs: u64,
+    /// MRTD values of the published builds, offered to the /verify page (hex, lowercase)
+    pub expected_mrtds: Vec<String>,
+    /// Seconds between upstream response-shape probes; None (0) disables them
+    pub schema_probe_interval_secs: Option<u64>,
+    /// `name=<url>#<json pointer>` price sources for conditional orders
+    pub external_price_feeds: Vec<String>,
+    /// Coins whose L2 book is tracked from startup (others are added on first request)
+    pub book_assets: Vec<String>,
+    /// Most L2 books tracked at once, so public book requests can't grow subscriptions unbounded
+    pub book_max_assets: usize,
+    /// Coins whose trades, candles and funding are recorded for GET /market/history (empty disables it)
+    pub market_history_assets: Vec<String>,
+    /// Directory of the recorder's parquet files
+    pub market_history_dir: String,
+    /// Candle interval recorded, as Hyperliquid names it ("1m", "1h", ...)
+    pub market_history_candle_interval: String,
+    /// Seconds between writes of buffered rows to disk
+    pub market_history_flush_secs: u64,
+    pub conditional_poll_ms: u64,
+    /// Seconds between equity samples for users with a drawdown guard
+    pub drawdown_poll_secs: u64,
+    /// Hex secp256k1 public key support bundles are encrypted to (unset disables them)
+    pub support_bundle_public_key: Option<String>,
+    /// Traces kept per API key that opted in to debug recording
+    pub debug_recorder_capacity: usize,
+    /// End-to-end /exchange latency a request must stay under to count as good
+    pub slo_latency_target_ms: u64,
+    /// Fraction of requests that must meet the latency target (e.g. 0.99)
+    pub slo_objective: f64,
+    /// "primary", "standby" or "standalone" (default) in a warm-standby pair
+    pub ha_role: String,
+    /// Base URL of the other instance in the pair
+    pub ha_peer_url: Option<String>,
+    /// Shared token both peers present on /ha endpoints
+    pub ha_peer_token: Option<String>,
+    /// How long one standby heartbeat keeps the primary allowed to sign
+    pub ha_lease_ms: u64,
+    /// How long the standby waits without reaching the primary before taking over (must exceed the lease)
+    pub ha_failover_ms: u64,
+    /// Shell command run on takeover, e.g. to claim the virtual IP
+    pub ha_takeover_command: Option<String>,
+    /// Hex key sealing the escrowed agent key at rest (unset keeps it in memory only)
+    pub ha_seal_key: Option<String>,
+    pub ha_sealed_key_path: String,
+    /// TTL for cached info responses (meta, clearinghouse state)
+    pub market_cache_ttl_ms: u64,
+    /// Oldest market snapshot (mark/oracle/mid prices) served or used by risk checks
+    pub price_max_staleness_ms: u64,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, String> {
+        // Load from environment or use defaults
+        let listeners = env::var("LISTENERS")
+            .map(|v| v.split(',').map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+            .unwrap_or_else(|_| vec!["0.0.0.0:8080".to_string()]);
+
+        let trusted_proxies = env::var("TRUSTED_PROXIES")
+            .map(|v| v.split(',').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect())
+            .unwrap_or_default();
+
+        let route_timeouts = env::var("ROUTE_TIMEOUTS")
+            .map(|v| v.split(',').map(|r| r.trim().to_string()).filter(|r| !r.is_empty()).collect())
+            .unwrap_or_else(|_| vec!["/info=10000".to_string(), "/exchange=30000".to_string()]);
+
+        let streamed_info_types = env::var("STREAMED_INFO_TYPES")
+            .map(|v| v.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect())
+            .unwrap_or_else(|_| vec!["l2Book".to_string(), "candleSnapshot".to_string()]);
+
+        let info_max_request_bytes = env::var("INFO_MAX_REQUEST_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(64 * 1024);
+
+        let info_max_response_bytes = env::var("INFO_MAX_RESPONSE_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(64 * 1024 * 1024);
+
+        let hyperliquid_url = env::var("HYPERLIQUID_API_URL")
+            .unwrap_or_else(|_| "https://api.hyperliquid.xyz".to_string());
+
+        let upstream_tls_pins = env::var("UPSTREAM_TLS_PINS")
+            .map(|v| v.split(',').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect())
+            .unwrap_or_default();
+
+        let hyperliquid_network = env::var("HYPERLIQUID_NETWORK")
+            .map(|v| v.trim().to_lowercase())
+            .unwrap_or_else(|_| if hyperliquid_url.contains("api.hyperliquid.xyz") { "mainnet" } else { "testnet" }.to_string());
+
+        // Both networks default to 0x66eee, as the official SDKs sign
+        let mainnet_signature_chain_id = env::var("MAINNET_SIGNATURE_CHAIN_ID")
+            .ok()
+            .and_then(|v| parse_chain_id(&v))
+            .unwrap_or(0x66eee);
+
+        let testnet_signature_chain_id = env::var("TESTNET_SIGNATURE_CHAIN_ID")
+            .ok()
+            .and_then(|v| parse_chain_id(&v))
+            .unwrap_or(0x66eee);
+
+        let mainnet_agent_source = env::var("MAINNET_AGENT_SOURCE")
+            .unwrap_or_else(|_| "a".to_string());
+
+        let testnet_agent_source = env::var("TESTNET_AGENT_SOURCE")
+            .unwrap_or_else(|_| "b".to_string());
+            
+        let log_level = env::var("LOG_LEVEL")
+            .unwrap_or_else(|_| "info".to_string());
+            
+        let fixed_api_key = env::var("FIXED_API_KEY")
+            .unwrap_or_else(|_| "test-key".to_string());
+            
+        let test_agent_address = address::normalize(&env::var("TEST_AGENT_ADDRESS")
+            .unwrap_or_else(|_| "0x742d35Cc6635C0532925a3b8D23cfcdCF83C4Ba1".to_string()));
+
+        let max_margin_usage = env::var("MAX_MARGIN_USAGE")
+            .ok()
+            .and_then(|v| v.parse().ok());
+
+        let liquidation_guard_band = env::var("LIQUIDATION_GUARD_BAND")
+            .ok()
+            .and_then(|v| v.parse().ok());
+
+        let max_builder_fee = env::var("MAX_BUILDER_FEE")
+            .ok()
+            .and_then(|v| v.parse().ok());
+
+        let builder_allowlist = env::var("BUILDER_ALLOWLIST")
+            .ok()
+            .map(|v| v.split(',').map(|b| b.trim().to_string()).filter(|b| !b.is_empty()).collect());
+
+        let liquidation_guard_mode = match env::var("LIQUIDATION_GUARD_MODE") {
+            Ok(mode) => LiquidationGuardMode::parse(&mode)
+                .ok_or_else(|| format!("LIQUIDATION_GUARD_MODE must be reject or warn, not '{}'", mode))?,
+            Err(_) => LiquidationGuardMode::Reject,
+        };
+
+        let referrer_code = env::var("REFERRER_CODE")
+            .ok()
+            .filter(|code| !code.is_empty());
+
+        let event_store_path = match env::var("EVENT_STORE_PATH") {
+            Ok(path) if path.is_empty() => None,
+            Ok(path) => Some(path),
+            Err(_) => Some("data/events.jsonl".to_string()),
+        };
+
+        let audit_log_path = match env::var("AUDIT_LOG_PATH") {
+            Ok(path) if path.is_empty() => None,
+            Ok(path) => Some(path),
+            Err(_) => Some("data/audit.jsonl".to_string()),
+        };
+
+        let oco_store_path = match env::var("OCO_STORE_PATH") {
+            Ok(path) if path.is_empty() => None,
+            Ok(path) => Some(path),
+            Err(_) => Some("data/oco.jsonl".to_string()),
+        };
+
+        let strategy_store_path = match env::var("STRATEGY_STORE_PATH") {
+            Ok(path) if path.is_empty() => None,
+            Ok(path) => Some(path),
+            Err(_) => Some("data/strategies.jsonl".to_string()),
+        };
+
+        let agent_notes_path = match env::var("AGENT_NOTES_PATH") {
+            Ok(path) if path.is_empty() => None,
+            Ok(path) => Some(path),
+            Err(_) => Some("data/agent_notes.jsonl".to_string()),
+        };
+
+        let equity_store_path = match env::var("EQUITY_STORE_PATH") {
+            Ok(path) if path.is_empty() => None,
+            Ok(path) => Some(path),
+            Err(_) => Some("data/equity.jsonl".to_string()),
+        };
+        let equity_snapshot_secs = Some(env::var("EQUITY_SNAPSHOT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300))
+            .filter(|secs| *secs > 0);
+        let equity_retention_days = env::var("EQUITY_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(90);
+
+        let deletion_store_path = match env::var("DELETION_STORE_PATH") {
+            Ok(path) if path.is_empty() => None,
+            Ok(path) => Some(path),
+            Err(_) => Some("data/deletions.jsonl".to_string()),
+        };
+        let user_deletion_grace_secs = env::var("USER_DELETION_GRACE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(86400);
+
+        let testnet_url = match env::var("TESTNET_API_URL") {
+            Ok(url) if url.is_empty() => None,
+            Ok(url) => Some(url),
+            Err(_) => Some("https://api.hyperliquid-testnet.xyz".to_string()),
+        };
+
+        let event_retention_days = match env::var("EVENT_RETENTION_DAYS") {
+            Ok(days) if days.is_empty() || days == "0" => None,
+            Ok(days) => days.parse().ok(),
+            Err(_) => Some(90),
+        };
+
+        let audit_retention_days = env::var("AUDIT_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|days| *days > 0);
+
+        let retention_interval_secs = env::var("RETENTION_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3600);
+
+        let quote_archive_path = match env::var("QUOTE_ARCHIVE_PATH") {
+            Ok(path) if path.is_empty() => None,
+            Ok(path) => Some(path),
+            Err(_) => Some("data/quotes.jsonl".to_string()),
+        };
+
+        let hyperliquid_ws_url = env::var("HYPERLIQUID_WS_URL")
+            .unwrap_or_else(|_| crate::ws_feed::ws_url_for(&hyperliquid_url));
+
+        let notifiers = notifiers_from_env();
+
+        let signer_backend = env::var("SIGNER_BACKEND")
+            .unwrap_or_else(|_| "local".to_string());
+        let remote_signer_url = env::var("REMOTE_SIGNER_URL").ok();
+        let remote_signer_auth_token = env::var("REMOTE_SIGNER_AUTH_TOKEN").ok();
+        let remote_signer_address = env::var("REMOTE_SIGNER_ADDRESS").ok();
+        let remote_signer_mrtds = env::var("REMOTE_SIGNER_MRTDS")
+            .map(|v| v.split(',').map(|m| m.trim().trim_start_matches("0x").to_lowercase()).filter(|m| !m.is_empty()).collect())
+            .unwrap_or_default();
+
+        let cosign_timeout_secs = env::var("COSIGN_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(120);
+
+        let confirm_notional_threshold = env::var("CONFIRM_NOTIONAL_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse().ok());
+
+        let confirm_timeout_secs = env::var("CONFIRM_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300);
+
+        let idempotency_ttl_secs = env::var("IDEMPOTENCY_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(24 * 60 * 60);
+
+        let confirm_require_signature = env::var("CONFIRM_REQUIRE_SIGNATURE")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+
+        let evm_allowlist = env::var("EVM_ALLOWLIST")
+            .map(|v| v.split(',').map(|e| e.trim().to_string()).filter(|e| !e.is_empty()).collect())
+            .unwrap_or_default();
+
+        let hyperevm_rpc_url = env::var("HYPEREVM_RPC_URL").ok();
+
+        let registry_address = env::var("REGISTRY_ADDRESS").ok();
+
+        let ens_rpc_url = env::var("ENS_RPC_URL")
+            .ok()
+            .filter(|url| !url.is_empty());
+
+        let hyperevm_chain_id = env::var("HYPEREVM_CHAIN_ID")
+            .ok()
+            .and_then(|v| v.parse().ok());
+
+        let typed_data_allowlist = env::var("TYPED_DATA_ALLOWLIST")
+            .map(|v| v.split(',').map(|e| e.trim().to_string()).filter(|e| !e.is_empty()).collect())
+            .unwrap_or_default();
+
+        let fee_estimates_in_responses = env::var("FEE_ESTIMATES_IN_RESPONSES")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+
+        let admin_token = env::var("ADMIN_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty());
+
+        let admin_key_secrets = env::var("ADMIN_KEYS")
+            .map(|v| v.split(',').map(|k| k.trim().to_string()).filter(|k| !k.is_empty()).collect())
+            .unwrap_or_default();
+
+        // Entries that aren't addresses could never sign, so they are dropped
+        let admin_addresses = env::var("ADMIN_ADDRESSES")
+            .map(|v| v.split(',')
+                .map(|a| a.trim().to_lowercase())
+                .filter(|a| a.parse::<alloy::primitives::Address>().is_ok())
+                .collect())
+            .unwrap_or_default();
+
+        let admin_request_max_skew_secs = env::var("ADMIN_REQUEST_MAX_SKEW_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(300);
+
+        let rate_limit_per_minute = env::var("RATE_LIMIT_PER_MINUTE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(600);
+
+        let session_ttl_secs = env::var("SESSION_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(24 * 60 * 60);
+        // The default lifetime is always allowed
+        let session_max_ttl_secs = env::var("SESSION_MAX_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(7 * 24 * 60 * 60)
+            .max(session_ttl_secs);
+        let session_sliding_expiration = env::var("SESSION_SLIDING_EXPIRATION")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+
+        let key_abuse_window_ms = env::var("KEY_ABUSE_WINDOW_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10 * 60 * 1000);
+        let key_abuse_max_ips = env::var("KEY_ABUSE_MAX_IPS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+        let key_abuse_max_user_agents = env::var("KEY_ABUSE_MAX_USER_AGENTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+        let key_abuse_require_reauth = env::var("KEY_ABUSE_REQUIRE_REAUTH")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+
+        let bot_active_secs = env::var("BOT_ACTIVE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300);
+        let bot_stale_secs = env::var("BOT_STALE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3600);
+
+        let nonce_window_past_ms = env::var("NONCE_WINDOW_PAST_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2 * 24 * 60 * 60 * 1000);
+
+        let nonce_window_future_ms = env::var("NONCE_WINDOW_FUTURE_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(24 * 60 * 60 * 1000);
+
+        let nonce_drift_warn_ms = env::var("NONCE_DRIFT_WARN_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60_000);
+
+        let signing_probe_interval_secs = env::var("SIGNING_PROBE_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|secs| *secs > 0);
+
+        let reattest_interval_secs = Some(env::var("REATTEST_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3600))
+            .filter(|secs| *secs > 0);
+        let reattest_command = env::var("REATTEST_COMMAND").ok().filter(|c| !c.is_empty());
+
+        let upstream_queue_cancels = env::var("UPSTREAM_QUEUE_CANCELS")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        let upstream_recheck_secs = env::var("UPSTREAM_RECHECK_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+        let expected_mrtds = env::var("EXPECTED_MRTDS")
+            .map(|v| v.split(',').map(|m| m.trim().trim_start_matches("0x").to_lowercase()).filter(|m| !m.is_empty()).collect())
+            .unwrap_or_default();
+
+        let schema_probe_interval_secs = Some(env::var("SCHEMA_PROBE_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300))
+            .filter(|secs| *secs > 0);
+
+        let external_price_feeds = env::var("EXTERNAL_PRICE_FEEDS")
+            .map(|v| v.split(',').map(|f| f.trim().to_string()).filter(|f| !f.is_empty()).collect())
+            .unwrap_or_default();
+
+        let book_assets = env::var("BOOK_ASSETS")
+            .map(|v| v.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect())
+            .unwrap_or_default();
+
+        let book_max_assets = env::var("BOOK_MAX_ASSETS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(32);
+
+        let market_history_assets = env::var("MARKET_HISTORY_ASSETS")
+            .map(|v| v.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect())
+            .unwrap_or_default();
+
+        let market_history_dir = env::var("MARKET_HISTORY_DIR")
+            .unwrap_or_else(|_| "data/market_history".to_string());
+
+        let market_history_candle_interval = env::var("MARKET_HISTORY_CANDLE_INTERVAL")
+            .unwrap_or_else(|_| "1m".to_string());
+
+        let market_history_flush_secs = env::var("MARKET_HISTORY_FLUSH_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(60);
+
+        let conditional_poll_ms = env::var("CONDITIONAL_POLL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2000);
+
+        let drawdown_poll_secs = env::var("DRAWDOWN_POLL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
+
+        let support_bundle_public_key = env::var("SUPPORT_BUNDLE_PUBLIC_KEY")
+            .ok()
+            .filter(|key| !key.is_empty());
+
+        let debug_recorder_capacity = env::var("DEBUG_RECORDER_CAPACITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(100);
+
+        let slo_latency_target_ms = env::var("SLO_LATENCY_TARGET_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1000);
+
+        let slo_objective = env::var("SLO_OBJECTIVE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.99);
+
+        let ha_role = env::var("HA_ROLE")
+            .unwrap_or_else(|_| "standalone".to_string());
+        let ha_peer_url = env::var("HA_PEER_URL").ok()
+            .map(|url| url.trim_end_matches('/').to_string());
+        let ha_peer_token = env::var("HA_PEER_TOKEN").ok();
+
+        let ha_lease_ms = env::var("HA_LEASE_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5000);
+
+        let ha_failover_ms = env::var("HA_FAILOVER_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(15000);
+
+        let ha_takeover_command = env::var("HA_TAKEOVER_COMMAND").ok();
+        let ha_seal_key = env::var("HA_SEAL_KEY").ok();
+        let ha_sealed_key_path = env::var("HA_SEALED_KEY_PATH")
+            .unwrap_or_else(|_| "data/escrow.sealed".to_string());
+
+        let market_cache_ttl_ms = env::var("MARKET_CACHE_TTL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2000);
+
+        let price_max_staleness_ms = env::var("PRICE_MAX_STALENESS_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10_000);
+
+        Ok(Self {
+            listeners,
+            trusted_proxies,
+            route_timeouts,
+            streamed_info_types,
+            info_max_request_bytes,
+            info_max_response_bytes,
+            hyperliquid_url,
+            upstream_tls_pins,
+            hyperliquid_network,
+            mainnet_signature_chain_id,
+            testnet_signature_chain_id,
+            mainnet_agent_source,
+            testnet_agent_source,
+            log_level,
+            fixed_api_key,
+            test_agent_address,
+            max_margin_usage,
+            liquidation_guard_band,
+            liquidation_guard_mode,
+            max_builder_fee,
+            builder_allowlist,
+            referrer_code,
+            event_store_path,
+            audit_log_path,
+            event_retention_days,
+            audit_retention_days,
+            retention_interval_secs,
+            quote_archive_path,
+            oco_store_path,
+            strategy_store_path,
+            agent_notes_path,
+            equity_store_path,
+            equity_snapshot_secs,
+            equity_retention_days,
+            deletion_store_path,
+            user_deletion_grace_secs,
+            testnet_url,
+            hyperliquid_ws_url,
+            notifiers,
+            signer_backend,
+            remote_signer_url,
+            remote_signer_auth_token,
+            remote_signer_address,
+            remote_signer_mrtds,
+            cosign_timeout_secs,
+            confirm_notional_threshold,
+            confirm_timeout_secs,
+            idempotency_ttl_secs,
+            confirm_require_signature,
+            evm_allowlist,
+            hyperevm_rpc_url,
+            ens_rpc_url,
+            registry_address,
+            hyperevm_chain_id,
+            typed_data_allowlist,
+            fee_estimates_in_responses,
+            admin_token,
+            admin_key_secrets,
+            admin_addresses,
+            admin_request_max_skew_secs,
+            rate_limit_per_minute,
+            session_ttl_secs,
+            session_max_ttl_secs,
+            session_sliding_expiration,
+            key_abuse_window_ms,
+            key_abuse_max_ips,
+            key_abuse_max_user_agents,
+            key_abuse_require_reauth,
+            bot_active_secs,
+            bot_stale_secs,
+            nonce_window_past_ms,
+            nonce_window_future_ms,
+            nonce_drift_warn_ms,
+            signing_probe_interval_secs,
+            reattest_interval_secs,
+            reattest_command,
+            upstream_queue_cancels,
+            upstream_recheck_secs,
+            expected_mrtds,
+            schema_probe_interval_secs,
+            external_price_feeds,
+            book_assets,
+            book_max_assets,
+            market_history_assets,
+            market_history_dir,
+            market_history_candle_interval,
+            market_history_flush_secs,
+            conditional_poll_ms,
+            drawdown_poll_secs,
+            support_bundle_public_key,
+            debug_recorder_capacity,
+            slo_latency_target_ms,
+            slo_objective,
+            ha_role,
+            ha_peer_url,
+            ha_peer_token,
+            ha_lease_ms,
+            ha_failover_ms,
+            ha_takeover_command,
+            ha_seal_key,
+            ha_sealed_key_path,
+            market_cache_ttl_ms,
+            price_max_staleness_ms,
+        })
+    }
+
+    /// Whether this deployment signs for Hyperliquid mainnet
+    pub fn is_mainnet(&self) -> bool {
+        self.hyperliquid_network == "mainnet"
+    }
+
+    /// Mainnet and testnet signing parameters, for `SignatureChain::configure`
+    pub fn signature_chains(&self) -> (SignatureChain, SignatureChain) {
+        (
+            SignatureChain {
+                signature_chain_id: self.mainnet_signature_chain_id,
+                agent_source: self.mainnet_agent_source.clone(),
+                ..SignatureChain::sdk_default(true)
+            },
+            SignatureChain {
+                signature_chain_id: self.testnet_signature_chain_id,
+                agent_source: self.testnet_agent_source.clone(),
+                ..SignatureChain::sdk_default(false)
+            },
+        )
+    }
+}
+
+/// Chain id as hex (`0x66eee`) or decimal
+fn parse_chain_id(value: &str) -> Option<u64> {
+    let value = value.trim();
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// Build notifier configs from NOTIFY_* variables.
+/// Each transport routes the kinds listed in NOTIFY_<TRANSPORT>_EVENTS (default: all).
+fn notifiers_from_env() -> Vec<NotifierConfig> {
+    let events_for = |prefix: &str| -> Vec<NotificationKind> {
+        match env::var(format!("NOTIFY_{}_EVENTS", prefix)) {
+            Ok(list) => list.split(',').filter_map(NotificationKind::parse).collect(),
+            Err(_) => NotificationKind::ALL.to_vec(),
+        }
+    };
+
+    let mut notifiers = Vec::new();
+
+    if let Ok(url) = env::var("NOTIFY_WEBHOOK_URL") {
+        notifiers.push(NotifierConfig {
+            transport: NotifierTransport::Webhook { url },
+            events: events_for("WEBHOOK"),
+        });
+    }
+
+    if let Ok(webhook_url) = env::var("NOTIFY_SLACK_WEBHOOK_URL") {
+        notifiers.push(NotifierConfig {
+            transport: NotifierTransport::Slack { webhook_url },
+            events: events_for("SLACK"),
+        });
+    }
+
+    if let (Ok(bot_token), Ok(chat_id)) = (env::var("NOTIFY_TELEGRAM_BOT_TOKEN"), env::var("NOTIFY_TELEGRAM_CHAT_ID")) {
+        notifiers.push(NotifierConfig {
+            transport: NotifierTransport::Telegram { bot_token, chat_id },
+            events: events_for("TELEGRAM"),
+        });
+    }
+
+    if let (Ok(api_url), Ok(to)) = (env::var("NOTIFY_EMAIL_API_URL"), env::var("NOTIFY_EMAIL_TO")) {
+        notifiers.push(NotifierConfig {
+            transport: NotifierTransport::Email { api_url, to },
+            events: events_for("EMAIL"),
+        });
+    }
+
+    notifiers
+}
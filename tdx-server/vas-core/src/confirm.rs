@@ -0,0 +1,206 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::market::parse_number;
+use crate::signer::ActionRequest;
+use crate::universal_signing::recover_personal_sign;
+use crate::AppState;
+
+/// An order above the confirmation threshold, held until the user confirms it
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingOrder {
+    pub id: String,
+    #[serde(skip)]
+    pub api_key: String,
+    #[serde(flatten)]
+    pub request: ActionRequest,
+    /// Total limit-price notional across the batch (USD)
+    pub notional: f64,
+    pub created_at: u64,
+    pub expires_at: u64,
+    /// Exact text to personal_sign when signature confirmation is required
+    pub confirmation_message: String,
+}
+
+/// Queue of large orders awaiting user confirmation, keyed by id
+#[derive(Debug)]
+pub struct ConfirmationQueue {
+    pending: HashMap<String, PendingOrder>,
+    ttl_secs: u64,
+}
+
+impl ConfirmationQueue {
+    pub fn new(ttl_secs: u64) -> Self {
+        Self {
+            pending: HashMap::new(),
+            ttl_secs,
+        }
+    }
+
+    /// Hold an order and return its queue entry
+    pub fn enqueue(
+        &mut self,
+        api_key: &str,
+        request: ActionRequest,
+    ) -> PendingOrder {
+        self.purge_expired();
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let notional = order_notional(&request.action);
+        let created_at = now_secs();
+        let expires_at = created_at + self.ttl_secs;
+
+        let pending = PendingOrder {
+            confirmation_message: confirmation_message(&id, notional, expires_at),
+            id,
+            api_key: api_key.to_string(),
+            request,
+            notional,
+            created_at,
+            expires_at,
+        };
+        self.pending.insert(pending.id.clone(), pending.clone());
+
+        pending
+    }
+
+    /// Pending orders belonging to an API key
+    pub fn list(&mut self, api_key: &str) -> Vec<PendingOrder> {
+        self.purge_expired();
+
+        let mut orders: Vec<PendingOrder> = self.pending.values()
+            .filter(|p| p.api_key == api_key)
+            .cloned()
+            .collect();
+        orders.sort_by_key(|p| p.created_at);
+        orders
+    }
+
+    /// Look up a pending order owned by `api_key` without removing it
+    pub fn get(&mut self, id: &str, api_key: &str) -> Option<&PendingOrder> {
+        self.purge_expired();
+        self.pending.get(id).filter(|p| p.api_key == api_key)
+    }
+
+    /// Remove a pending order owned by `api_key`
+    pub fn take(&mut self, id: &str, api_key: &str) -> Option<PendingOrder> {
+        self.get(id, api_key)?;
+        self.pending.remove(id)
+    }
+
+    fn purge_expired(&mut self) {
+        let now = now_secs();
+        self.pending.retain(|id, p| {
+            if p.expires_at <= now {
+                info!("⌛ Pending order {} expired unconfirmed", id);
+            }
+            p.expires_at > now
+        });
+    }
+}
+
+/// Sum of `price * size` over an order batch
+pub fn order_notional(action: &Value) -> f64 {
+    action.get("orders")
+        .and_then(|o| o.as_array())
+        .map(|orders| orders.iter()
+            .map(|o| parse_number(o.get("p")).unwrap_or(0.0) * parse_number(o.get("s")).unwrap_or(0.0))
+            .sum())
+        .unwrap_or(0.0)
+}
+
+fn confirmation_message(id: &str, notional: f64, expires_at: u64) -> String {
+    format!(
+        "Confirm Hyperliquid order {}\nNotional: {:.2} USD\nExpires: {}",
+        id, notional, expires_at
+    )
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// GET /exchange/pending - Orders waiting for confirmation on this API key
+pub async fn list_pending(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let pending = state.confirmations.write().await.list(api_key);
+
+    Ok(Json(serde_json::json!({ "pending": pending })))
+}
+
+/// Confirmation (or rejection) of a pending order
+#[derive(Debug, Deserialize)]
+pub struct ResolvePendingRequest {
+    pub id: String,
+    /// false discards the order
+    #[serde(default = "default_confirm")]
+    pub confirm: bool,
+    /// personal_sign over `confirmation_message`, required when CONFIRM_REQUIRE_SIGNATURE is set
+    pub signature: Option<String>,
+}
+
+fn default_confirm() -> bool {
+    true
+}
+
+/// POST /exchange/pending - Confirm (sign and submit) or reject a pending order
+pub async fn resolve_pending(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<ResolvePendingRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if !payload.confirm {
+        return match state.confirmations.write().await.take(&payload.id, api_key) {
+            Some(_) => {
+                info!("🗑️ Pending order {} rejected by user", payload.id);
+                Ok(Json(serde_json::json!({"status": "ok", "response": "rejected"})))
+            }
+            None => Err(StatusCode::NOT_FOUND),
+        };
+    }
+
+    let message = state.confirmations.write().await
+        .get(&payload.id, api_key)
+        .map(|p| p.confirmation_message.clone())
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if state.config.confirm_require_signature || payload.signature.is_some() {
+        let user_address = auth::user_address_for_api_key(&state, api_key).await;
+        let verified = match (&payload.signature, &user_address) {
+            (Some(signature), Some(user_address)) => recover_personal_sign(&message, signature)
+                .map(|signer| signer == user_address.to_lowercase())
+                .unwrap_or(false),
+            _ => false,
+        };
+        if !verified {
+            warn!("❌ Confirmation signature invalid for pending order {}", payload.id);
+            return Ok(Json(error_codes::err_body(ErrorCode::InvalidSignature, "Confirmation must be signed by the session's wallet")));
+        }
+    }
+
+    let pending = state.confirmations.write().await
+        .take(&payload.id, api_key)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    info!("✅ Pending order {} confirmed ({:.2} USD)", pending.id, pending.notional);
+
+    // Latency is measured from confirmation, not from when the order was first held
+    crate::submit_action(&state, api_key, pending.request, Vec::new(), std::time::Instant::now()).await
+}
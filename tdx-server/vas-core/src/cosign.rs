@@ -0,0 +1,321 @@
+use alloy::primitives::B256;
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::address;
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::signer::ActionRequest;
+use crate::siwe_auth::verify_siwe_message;
+use crate::universal_signing::{is_user_signed, prepare_action, recover_personal_sign, signing_digest, ExchangeSignature};
+use crate::AppState;
+
+/// An action awaiting the user's co-signature before the enclave signs it
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingCosign {
+    pub id: String,
+    #[serde(skip)]
+    pub api_key: String,
+    #[serde(flatten)]
+    pub request: ActionRequest,
+    /// L1 digest the co-signer must sign (phantom agent over the action hash)
+    pub digest: String,
+    pub cosigner_address: String,
+    pub expires_at: u64,
+}
+
+/// Co-signer approval gate: sessions with a registered co-signer get every
+/// `/exchange` action parked here until the co-signer key signs the same digest
+/// the enclave will.
+///
+/// This is not a key split. The enclave still holds the whole agent key, and only
+/// the paths that go through `submit_action` wait for the co-signer.
+#[derive(Debug)]
+pub struct CosignManager {
+    pending: HashMap<String, PendingCosign>,
+    ttl_secs: u64,
+}
+
+impl CosignManager {
+    pub fn new(ttl_secs: u64) -> Self {
+        Self {
+            pending: HashMap::new(),
+            ttl_secs,
+        }
+    }
+
+    /// Park an action and return the co-sign challenge
+    pub fn create(
+        &mut self,
+        api_key: &str,
+        cosigner_address: &str,
+        mut request: ActionRequest,
+    ) -> Result<PendingCosign, Box<dyn std::error::Error + Send + Sync>> {
+        self.purge_expired();
+
+        // The co-signer approves the same digest the agent will sign for the prepared action
+        request.action = prepare_action(&request.action)?;
+        let vault_address = request.vault_address.as_deref().filter(|_| !is_user_signed(&request.action));
+        let digest = signing_digest(&request.action, request.nonce, vault_address, request.is_mainnet)?;
+
+        let pending = PendingCosign {
+            id: uuid::Uuid::new_v4().to_string(),
+            api_key: api_key.to_string(),
+            request,
+            digest: format!("{:?}", digest),
+            cosigner_address: cosigner_address.to_lowercase(),
+            expires_at: now_secs() + self.ttl_secs,
+        };
+        self.pending.insert(pending.id.clone(), pending.clone());
+
+        Ok(pending)
+    }
+
+    /// Remove and return a pending action after checking ownership and the co-signature
+    pub fn complete(
+        &mut self,
+        id: &str,
+        api_key: &str,
+        signature: &ExchangeSignature,
+    ) -> Result<PendingCosign, String> {
+        self.purge_expired();
+
+        let pending = self.pending.get(id).ok_or("Co-sign request not found or expired")?;
+        if pending.api_key != api_key {
+            return Err("Co-sign request belongs to a different API key".to_string());
+        }
+
+        let digest: B256 = pending.digest.parse().map_err(|_| "Corrupt co-sign digest")?;
+        let recovered = signature.recover_address(&digest)
+            .map_err(|e| format!("Invalid co-signature: {}", e))?;
+        if recovered != pending.cosigner_address {
+            return Err(format!("Co-signature is from {} (expected {})", recovered, pending.cosigner_address));
+        }
+
+        Ok(self.pending.remove(id).expect("checked above"))
+    }
+
+    fn purge_expired(&mut self) {
+        let now = now_secs();
+        self.pending.retain(|_, p| p.expires_at > now);
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// How long a co-signer change approval stays valid
+const CHANGE_WINDOW_SECS: u64 = 300;
+/// SIWE resource naming the co-signer a fresh owner login approves, e.g. `urn:vas:cosigner:none`
+pub const COSIGNER_RESOURCE_PREFIX: &str = "urn:vas:cosigner:";
+
+/// Co-signer registration for the caller's session
+#[derive(Debug, Deserialize)]
+pub struct CosignerRequest {
+    /// Address of the user-held co-signer key; null disables co-signing
+    pub address: Option<String>,
+    /// Unix seconds the current co-signer's approval was signed at
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+    /// personal_sign by the current co-signer over [`change_message`]
+    #[serde(default)]
+    pub cosigner_signature: Option<String>,
+    /// SIWE message and signature from the session's wallet approving the change instead
+    #[serde(default)]
+    pub siwe_message: Option<String>,
+    #[serde(default)]
+    pub siwe_signature: Option<String>,
+}
+
+/// What the current co-signer signs to approve replacing itself with `new` (or clearing it)
+pub fn change_message(user_address: &str, current: &str, new: Option<&str>, timestamp: u64) -> String {
+    format!("vas-cosigner\n{}\n{}\n{}\n{}", user_address, current, new.unwrap_or("none"), timestamp)
+}
+
+/// Check that the change away from `current` was approved by the current co-signer, or by a
+/// SIWE message from the owner issued within the window and naming the new co-signer
+async fn check_change_approval(
+    payload: &CosignerRequest,
+    user_address: &str,
+    current: &str,
+    new: Option<&str>,
+) -> Result<(), String> {
+    let now = now_secs();
+    if let (Some(signature), Some(timestamp)) = (&payload.cosigner_signature, payload.timestamp) {
+        if now.abs_diff(timestamp) > CHANGE_WINDOW_SECS {
+            return Err(format!("Co-signer approval timestamp is outside the {}s window", CHANGE_WINDOW_SECS));
+        }
+        let signer = recover_personal_sign(&change_message(user_address, current, new, timestamp), signature)
+            .map_err(|e| format!("Invalid co-signer signature: {}", e))?;
+        if signer != current {
+            return Err(format!("Co-signer change is signed by {} (expected the current co-signer {})", signer, current));
+        }
+        return Ok(());
+    }
+
+    if let (Some(message), Some(signature)) = (&payload.siwe_message, &payload.siwe_signature) {
+        let siwe_message = verify_siwe_message(message, signature).await.map_err(|e| e.to_string())?;
+        let signer = address::Address::from(siwe_message.address).to_lower_hex();
+        if signer != user_address {
+            return Err("Co-signer change must be signed in by the session's wallet".to_string());
+        }
+        let issued_at = siwe_message.issued_at.as_ref().unix_timestamp();
+        if issued_at < 0 || now.abs_diff(issued_at as u64) > CHANGE_WINDOW_SECS || !siwe_message.valid_now() {
+            return Err(format!("SIWE approval must be issued within the last {}s", CHANGE_WINDOW_SECS));
+        }
+        let expected = format!("{}{}", COSIGNER_RESOURCE_PREFIX, new.unwrap_or("none"));
+        if !siwe_message.resources.iter().any(|r| r.as_str().eq_ignore_ascii_case(&expected)) {
+            return Err(format!("SIWE approval must list the resource {}", expected));
+        }
+        return Ok(());
+    }
+
+    Err("Changing or clearing the co-signer needs a signature from the current co-signer or a fresh SIWE from the owner".to_string())
+}
+
+/// PUT /me/cosigner - Enable (or disable) the co-signer approval gate for this session.
+/// Once a co-signer is set, the API key alone can't replace or clear it.
+pub async fn set_cosigner(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CosignerRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if let Some(address) = &payload.address {
+        if address.parse::<alloy::primitives::Address>().is_err() {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    let new = payload.address.as_deref().map(|a| a.to_lowercase());
+
+    let (user_address, current) = state.session_manager
+        .with_session(api_key, |session| (session.user_address.to_lowercase(), session.cosigner_address.clone()))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if let Some(current) = current.filter(|current| Some(current) != new.as_ref()) {
+        if let Err(reason) = check_change_approval(&payload, &user_address, &current, new.as_deref()).await {
+            warn!("❌ Co-signer change for {} refused: {}", user_address, reason);
+            return Ok(Json(error_codes::err_body(ErrorCode::CosignRejected, reason)));
+        }
+    }
+
+    let session = state.session_manager
+        .set_cosigner(api_key, new)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    info!("🤝 Co-signer for {} set to {:?}", session.user_address, session.cosigner_address);
+
+    Ok(Json(serde_json::json!({
+        "cosigner_address": session.cosigner_address
+    })))
+}
+
+/// Co-signature over a pending action's digest
+#[derive(Debug, Deserialize)]
+pub struct CosignRequest {
+    pub signature: CosignSignature,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CosignSignature {
+    pub r: String,
+    pub s: String,
+    pub v: u64,
+}
+
+/// POST /exchange/cosign/:id - Supply the co-signature; the enclave then signs and submits
+pub async fn complete_cosign(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<CosignRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let signature = ExchangeSignature {
+        r: payload.signature.r,
+        s: payload.signature.s,
+        v: payload.signature.v,
+    };
+
+    let pending = match state.cosign.write().await.complete(&id, api_key, &signature) {
+        Ok(pending) => pending,
+        Err(reason) => {
+            warn!("❌ Co-sign {} rejected: {}", id, reason);
+            return Ok(Json(error_codes::err_body(ErrorCode::CosignRejected, reason)));
+        }
+    };
+
+    info!("🤝 Co-signature verified for {}, releasing enclave signature", id);
+
+    match state.signer.sign_action(pending.request).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            warn!("❌ Signing co-signed action failed: {:?}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::universal_signing::sign_hash_with_key;
+    use secp256k1::SecretKey;
+
+    const USER: &str = "0x1111111111111111111111111111111111111111";
+    const NEW: &str = "0x2222222222222222222222222222222222222222";
+
+    fn personal_sign(key: &SecretKey, message: &str) -> String {
+        let signature = sign_hash_with_key(key, &alloy::primitives::eip191_hash_message(message));
+        format!("{}{}{:02x}", signature.r, &signature.s[2..], signature.v)
+    }
+
+    fn request(signature: Option<String>, timestamp: u64) -> CosignerRequest {
+        CosignerRequest {
+            address: Some(NEW.to_string()),
+            timestamp: Some(timestamp),
+            cosigner_signature: signature,
+            siwe_message: None,
+            siwe_signature: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_change_needs_current_cosigner_signature() {
+        let cosigner = SecretKey::from_slice(&[3u8; 32]).unwrap();
+        let current = address::secret_key_to_address(&cosigner);
+        let now = now_secs();
+
+        // The API key alone is not enough
+        let err = check_change_approval(&request(None, now), USER, &current, Some(NEW)).await.unwrap_err();
+        assert!(err.contains("current co-signer"), "{}", err);
+
+        let signature = personal_sign(&cosigner, &change_message(USER, &current, Some(NEW), now));
+        check_change_approval(&request(Some(signature.clone()), now), USER, &current, Some(NEW)).await.unwrap();
+
+        // The approval names the new co-signer, so it can't be reused to clear it
+        assert!(check_change_approval(&request(Some(signature), now), USER, &current, None).await.is_err());
+
+        let other = SecretKey::from_slice(&[4u8; 32]).unwrap();
+        let forged = personal_sign(&other, &change_message(USER, &current, Some(NEW), now));
+        let err = check_change_approval(&request(Some(forged), now), USER, &current, Some(NEW)).await.unwrap_err();
+        assert!(err.contains("expected the current co-signer"), "{}", err);
+
+        let stale = now - CHANGE_WINDOW_SECS - 1;
+        let signature = personal_sign(&cosigner, &change_message(USER, &current, Some(NEW), stale));
+        let err = check_change_approval(&request(Some(signature), stale), USER, &current, Some(NEW)).await.unwrap_err();
+        assert!(err.contains("window"), "{}", err);
+    }
+}
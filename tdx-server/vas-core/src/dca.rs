@@ -0,0 +1,395 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::funding;
+use crate::market::AssetInfo;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+const MAX_PLANS_PER_USER: usize = 20;
+const MIN_CADENCE_SECS: u64 = 60;
+/// Executions kept per plan
+const HISTORY_LEN: usize = 100;
+/// Default IOC price tolerance around the mark
+const DEFAULT_SLIPPAGE_BPS: u64 = 50;
+/// Wait after a funding settlement before retrying a deferred run
+const FUNDING_SETTLE_GRACE_SECS: u64 = 60;
+
+/// What to do with a run when funding for the plan's side is above its threshold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FundingAction {
+    #[default]
+    Skip,
+    /// Retry just after the next funding settlement, if that comes before the next slot
+    Defer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStatus {
+    Active,
+    Paused,
+    Cancelled,
+}
+
+/// One scheduled run of a plan: an order submitted, or the reason it was skipped
+#[derive(Debug, Clone, Serialize)]
+pub struct Execution {
+    pub at: u64,
+    pub mark_px: Option<f64>,
+    pub size: Option<String>,
+    pub skipped: Option<String>,
+    /// Set when the run was pushed back past a funding settlement
+    pub deferred_to: Option<u64>,
+    pub result: Option<Value>,
+}
+
+/// A recurring order: buy or sell `notional_usd` of an asset every `cadence_secs`
+#[derive(Debug, Clone, Serialize)]
+pub struct RecurringPlan {
+    pub id: String,
+    #[serde(skip)]
+    pub api_key: String,
+    pub user_address: String,
+    pub asset: u64,
+    pub is_buy: bool,
+    pub notional_usd: f64,
+    pub cadence_secs: u64,
+    /// Skip runs while the mark is above this (buys) or below it (sells)
+    pub limit_px: Option<f64>,
+    pub slippage_bps: u64,
+    /// Hourly funding cost (bps) for the plan's side above which runs are skipped or deferred
+    pub max_funding_bps: Option<f64>,
+    pub funding_action: FundingAction,
+    pub status: PlanStatus,
+    pub created_at: u64,
+    pub next_run_at: u64,
+    pub history: Vec<Execution>,
+}
+
+impl RecurringPlan {
+    fn push_history(&mut self, execution: Execution) {
+        if self.history.len() >= HISTORY_LEN {
+            self.history.remove(0);
+        }
+        self.history.push(execution);
+    }
+}
+
+/// Recurring plans by id
+#[derive(Debug, Default)]
+pub struct DcaScheduler {
+    plans: HashMap<String, RecurringPlan>,
+}
+
+impl DcaScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn list(&self, user_address: &str) -> Vec<RecurringPlan> {
+        let mut plans: Vec<_> = self.plans.values()
+            .filter(|p| p.user_address == user_address && p.status != PlanStatus::Cancelled)
+            .cloned()
+            .collect();
+        plans.sort_by_key(|p| p.created_at);
+        plans
+    }
+
+    fn owned_mut(&mut self, id: &str, user_address: &str) -> Option<&mut RecurringPlan> {
+        self.plans.get_mut(id).filter(|p| p.user_address == user_address && p.status != PlanStatus::Cancelled)
+    }
+
+    /// Active plans whose next run is due, advanced to their following slot
+    fn claim_due(&mut self, now: u64) -> Vec<RecurringPlan> {
+        let mut due = Vec::new();
+        for plan in self.plans.values_mut().filter(|p| p.status == PlanStatus::Active && p.next_run_at <= now) {
+            // Missed slots (downtime, standby) are not made up; the plan resumes from now
+            plan.next_run_at = now + plan.cadence_secs;
+            due.push(plan.clone());
+        }
+        due
+    }
+
+    fn record(&mut self, id: &str, execution: Execution) {
+        if let Some(plan) = self.plans.get_mut(id) {
+            if let Some(deferred_to) = execution.deferred_to {
+                plan.next_run_at = plan.next_run_at.min(deferred_to);
+            }
+            plan.push_history(execution);
+        }
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> usize {
+        let before = self.plans.len();
+        self.plans.retain(|_, plan| !plan.user_address.eq_ignore_ascii_case(user_address));
+        before - self.plans.len()
+    }
+}
+
+/// Run due plans: price each run off the current mark and submit an IOC limit order
+/// through the /exchange pipeline under the session that created the plan
+pub fn spawn_scheduler(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(5));
+        loop {
+            ticker.tick().await;
+            if state.ha.check().is_err() {
+                continue;
+            }
+
+            let due = state.dca.write().await.claim_due(now_secs());
+            for plan in due {
+                let execution = execute(&state, &plan).await;
+                state.dca.write().await.record(&plan.id, execution);
+            }
+        }
+    });
+}
+
+async fn execute(state: &AppState, plan: &RecurringPlan) -> Execution {
+    let mut execution = Execution { at: now_secs(), mark_px: None, size: None, skipped: None, deferred_to: None, result: None };
+
+    let asset = match state.market.asset(plan.asset).await {
+        Ok(Some(asset)) => asset,
+        Ok(None) => {
+            execution.skipped = Some(format!("Unknown asset index {}", plan.asset));
+            return execution;
+        }
+        Err(e) => {
+            execution.skipped = Some(format!("Market data unavailable: {}", e));
+            return execution;
+        }
+    };
+    let Some(mark_px) = asset.mark_px else {
+        execution.skipped = Some("No mark price".to_string());
+        return execution;
+    };
+    execution.mark_px = Some(mark_px);
+
+    if let Some(limit_px) = plan.limit_px {
+        let outside = if plan.is_buy { mark_px > limit_px } else { mark_px < limit_px };
+        if outside {
+            execution.skipped = Some(format!("Mark {} is outside the plan's limit {}", mark_px, limit_px));
+            return execution;
+        }
+    }
+
+    if let Some(max_funding_bps) = plan.max_funding_bps {
+        if let Some((cost_bps, next_funding)) = funding::funding_cost_bps(state, plan.asset, plan.is_buy).await {
+            if cost_bps > max_funding_bps {
+                execution.skipped = Some(format!("Funding cost {:.3}bps/h exceeds the plan's {}bps/h", cost_bps, max_funding_bps));
+                // Claiming the run already moved next_run_at to the following slot
+                let retry_at = next_funding + FUNDING_SETTLE_GRACE_SECS;
+                if plan.funding_action == FundingAction::Defer && retry_at < plan.next_run_at {
+                    execution.deferred_to = Some(retry_at);
+                }
+                return execution;
+            }
+        }
+    }
+
+    let Some(order) = build_order(plan, &asset, mark_px) else {
+        execution.skipped = Some("Notional rounds to a zero size".to_string());
+        return execution;
+    };
+    execution.size = order.get("s").and_then(|s| s.as_str()).map(|s| s.to_string());
+
+    let mut headers = HeaderMap::new();
+    let Ok(api_key) = HeaderValue::from_str(&plan.api_key) else {
+        execution.skipped = Some("Invalid stored API key".to_string());
+        return execution;
+    };
+    headers.insert("X-API-Key", api_key);
+
+    let payload = serde_json::json!({
+        "action": {"type": "order", "orders": [order], "grouping": "na"}
+    });
+    execution.result = Some(match crate::proxy_exchange(State(state.clone()), headers, Json(payload)).await {
+        Ok(Json(response)) => response,
+        Err(status) => {
+            warn!("⚠️ Recurring plan {} run failed: {}", plan.id, status);
+            error_codes::err_body(ErrorCode::for_status(status), status.to_string())
+        }
+    });
+    info!("🔁 Recurring plan {} ran at mark {}", plan.id, mark_px);
+    execution
+}
+
+/// IOC limit order for the plan's notional, priced `slippage_bps` through the mark
+fn build_order(plan: &RecurringPlan, asset: &AssetInfo, mark_px: f64) -> Option<Value> {
+    let slippage = plan.slippage_bps as f64 / 10_000.0;
+    let px = if plan.is_buy { mark_px * (1.0 + slippage) } else { mark_px * (1.0 - slippage) };
+
+    let size = size_for_notional(plan.notional_usd, mark_px, asset.sz_decimals);
+    if size <= 0.0 {
+        return None;
+    }
+
+    Some(serde_json::json!({
+        "a": plan.asset,
+        "b": plan.is_buy,
+        "p": format_px(px, asset.sz_decimals),
+        "s": format!("{:.*}", asset.sz_decimals as usize, size),
+        "r": false,
+        "t": {"limit": {"tif": "Ioc"}}
+    }))
+}
+
+/// Order size worth `notional_usd` at `px`, rounded down to the asset's size decimals
+pub fn size_for_notional(notional_usd: f64, px: f64, sz_decimals: u32) -> f64 {
+    let size_scale = 10f64.powi(sz_decimals as i32);
+    (notional_usd / px * size_scale).floor() / size_scale
+}
+
+/// Perp prices allow 5 significant figures and at most `6 - sz_decimals` decimals
+pub fn format_px(px: f64, sz_decimals: u32) -> String {
+    let max_decimals = 6u32.saturating_sub(sz_decimals) as i32;
+    let magnitude = px.abs().log10().floor() as i32;
+    let decimals = (4 - magnitude).clamp(0, max_decimals);
+    let scale = 10f64.powi(decimals);
+    format!("{:.*}", decimals as usize, (px * scale).round() / scale)
+}
+
+/// Recurring plan parameters
+#[derive(Debug, Deserialize)]
+pub struct CreatePlanRequest {
+    pub asset: u64,
+    pub is_buy: bool,
+    pub notional_usd: f64,
+    pub cadence_secs: u64,
+    pub limit_px: Option<f64>,
+    pub slippage_bps: Option<u64>,
+    pub max_funding_bps: Option<f64>,
+    #[serde(default)]
+    pub funding_action: FundingAction,
+    /// First run; defaults to now
+    pub start_at: Option<u64>,
+}
+
+/// POST /me/recurring-orders - Schedule a recurring order
+pub async fn create_plan(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreatePlanRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let (api_key, user_address) = session_user(&state, &headers).await?;
+
+    if payload.cadence_secs < MIN_CADENCE_SECS || !(payload.notional_usd.is_finite() && payload.notional_usd > 0.0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if !matches!(state.market.asset(payload.asset).await, Ok(Some(_))) {
+        return Ok(Json(error_codes::err_body(ErrorCode::UnknownAsset, format!("Unknown asset index {}", payload.asset))));
+    }
+
+    let mut scheduler = state.dca.write().await;
+    if scheduler.list(&user_address).len() >= MAX_PLANS_PER_USER {
+        return Ok(Json(error_codes::err_body(ErrorCode::LimitExceeded, format!("At most {} recurring orders per user", MAX_PLANS_PER_USER))));
+    }
+
+    let created_at = now_secs();
+    let plan = RecurringPlan {
+        id: uuid::Uuid::new_v4().to_string(),
+        api_key,
+        user_address,
+        asset: payload.asset,
+        is_buy: payload.is_buy,
+        notional_usd: payload.notional_usd,
+        cadence_secs: payload.cadence_secs,
+        limit_px: payload.limit_px,
+        slippage_bps: payload.slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS),
+        max_funding_bps: payload.max_funding_bps,
+        funding_action: payload.funding_action,
+        status: PlanStatus::Active,
+        created_at,
+        next_run_at: payload.start_at.unwrap_or(created_at).max(created_at),
+        history: Vec::new(),
+    };
+    scheduler.plans.insert(plan.id.clone(), plan.clone());
+
+    info!("🔁 Recurring plan {} created for {} every {}s", plan.id, plan.user_address, plan.cadence_secs);
+    Ok(Json(serde_json::json!({"status": "ok", "response": plan})))
+}
+
+/// GET /me/recurring-orders - The caller's plans with execution history
+pub async fn list_plans(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(serde_json::json!({
+        "recurring_orders": state.dca.read().await.list(&user_address.to_lowercase())
+    })))
+}
+
+/// POST /me/recurring-orders/:id/pause
+pub async fn pause_plan(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    set_status(&state, &headers, &id, PlanStatus::Paused).await
+}
+
+/// POST /me/recurring-orders/:id/resume - Resume a paused plan; the next run is due immediately
+pub async fn resume_plan(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    set_status(&state, &headers, &id, PlanStatus::Active).await
+}
+
+/// DELETE /me/recurring-orders/:id
+pub async fn cancel_plan(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    set_status(&state, &headers, &id, PlanStatus::Cancelled).await
+}
+
+async fn set_status(state: &AppState, headers: &HeaderMap, id: &str, status: PlanStatus) -> Result<Json<Value>, StatusCode> {
+    let (_, user_address) = session_user(state, headers).await?;
+    let mut scheduler = state.dca.write().await;
+    let plan = scheduler.owned_mut(id, &user_address).ok_or(StatusCode::NOT_FOUND)?;
+
+    if status == PlanStatus::Active && plan.status == PlanStatus::Paused {
+        plan.next_run_at = now_secs();
+    }
+    plan.status = status;
+
+    info!("🔁 Recurring plan {} is now {:?}", id, status);
+    Ok(Json(serde_json::json!({"status": "ok", "response": plan.clone()})))
+}
+
+/// Trading session behind the request; share tokens can't manage plans
+async fn session_user(state: &AppState, headers: &HeaderMap) -> Result<(String, String), StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok((api_key.to_string(), user_address.to_lowercase()))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -0,0 +1,388 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::siwe_auth::validate_siwe_signature;
+use crate::AppState;
+
+/// Header a grantee sets to trade on behalf of a grantor's account
+pub const DELEGATED_FROM_HEADER: &str = "X-Delegated-From";
+
+/// Longest grantor -> ... -> grantee chain the auth layer will follow
+const MAX_DELEGATION_DEPTH: usize = 3;
+
+/// SIWE resource prefixes carrying the grant terms
+const RESOURCE_GRANTEE: &str = "urn:vas:grantee:";
+const RESOURCE_ASSETS: &str = "urn:vas:assets:";
+const RESOURCE_MAX_NOTIONAL: &str = "urn:vas:max-notional:";
+
+/// Temporary right for `grantee` to trade through `grantor`'s agent
+#[derive(Debug, Clone, Serialize)]
+pub struct Grant {
+    /// sha256 of the signed grant message
+    pub id: String,
+    pub grantor: String,
+    pub grantee: String,
+    /// Perp asset indices the grantee may trade (None = all)
+    pub assets: Option<Vec<u64>>,
+    /// Per-action notional cap in USD (None = uncapped)
+    pub max_notional: Option<f64>,
+    pub expires_at: i64,
+}
+
+/// Effective rights after walking a delegation chain
+#[derive(Debug, Clone)]
+pub struct ResolvedDelegation {
+    pub grantor: String,
+    pub assets: Option<Vec<u64>>,
+    pub max_notional: Option<f64>,
+}
+
+impl ResolvedDelegation {
+    /// Check an action against the delegated limits
+    pub fn authorize(&self, action: &Value) -> Result<(), String> {
+        if !matches!(action.get("type").and_then(|t| t.as_str()), Some("order") | Some("cancel")) {
+            return Err("Delegated keys may only place and cancel orders".to_string());
+        }
+
+        let entries = action.get("orders").or_else(|| action.get("cancels"))
+            .and_then(|o| o.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        if let Some(assets) = &self.assets {
+            for entry in &entries {
+                let asset = entry.get("a").and_then(|a| a.as_u64()).unwrap_or(u64::MAX);
+                if !assets.contains(&asset) {
+                    return Err(format!("Asset {} is not covered by the delegation grant", asset));
+                }
+            }
+        }
+
+        if let Some(max_notional) = self.max_notional {
+            let notional = crate::confirm::order_notional(action);
+            if notional > max_notional {
+                return Err(format!(
+                    "Order notional {:.2} USD exceeds delegated cap {:.2} USD",
+                    notional, max_notional
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Active delegation grants, keyed by grant id
+#[derive(Debug, Default)]
+pub struct DelegationManager {
+    grants: HashMap<String, Grant>,
+}
+
+impl DelegationManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, grant: Grant) {
+        self.grants.insert(grant.id.clone(), grant);
+    }
+
+    /// Revoke a grant; only its grantor may do so
+    pub fn revoke(&mut self, id: &str, grantor: &str) -> bool {
+        match self.grants.get(id) {
+            Some(grant) if grant.grantor == grantor.to_lowercase() => {
+                self.grants.remove(id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Grants issued by or to an address
+    pub fn grants_for(&mut self, address: &str) -> Vec<Grant> {
+        self.purge_expired();
+        let address = address.to_lowercase();
+        self.grants.values()
+            .filter(|g| g.grantor == address || g.grantee == address)
+            .cloned()
+            .collect()
+    }
+
+    /// Find a chain of live grants from `grantor` down to `grantee` and intersect their limits
+    pub fn resolve(&mut self, grantee: &str, grantor: &str) -> Option<ResolvedDelegation> {
+        self.purge_expired();
+        self.resolve_from(&grantee.to_lowercase(), &grantor.to_lowercase(), MAX_DELEGATION_DEPTH)
+    }
+
+    fn resolve_from(&self, grantee: &str, grantor: &str, depth: usize) -> Option<ResolvedDelegation> {
+        if depth == 0 {
+            return None;
+        }
+
+        for grant in self.grants.values().filter(|g| g.grantee == grantee) {
+            let upstream = if grant.grantor == grantor {
+                Some(ResolvedDelegation {
+                    grantor: grantor.to_string(),
+                    assets: None,
+                    max_notional: None,
+                })
+            } else {
+                self.resolve_from(&grant.grantor, grantor, depth - 1)
+            };
+
+            if let Some(upstream) = upstream {
+                return Some(ResolvedDelegation {
+                    grantor: upstream.grantor,
+                    assets: intersect_assets(upstream.assets, grant.assets.clone()),
+                    max_notional: match (upstream.max_notional, grant.max_notional) {
+                        (Some(a), Some(b)) => Some(a.min(b)),
+                        (a, b) => a.or(b),
+                    },
+                });
+            }
+        }
+
+        None
+    }
+
+    fn purge_expired(&mut self) {
+        let now = chrono::Utc::now().timestamp();
+        self.grants.retain(|_, g| g.expires_at > now);
+    }
+
+    /// Grants the user gave or received
+    pub fn involving(&self, user_address: &str) -> Vec<Grant> {
+        self.grants.values()
+            .filter(|g| g.grantor.eq_ignore_ascii_case(user_address) || g.grantee.eq_ignore_ascii_case(user_address))
+            .cloned()
+            .collect()
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> usize {
+        let before = self.grants.len();
+        self.grants.retain(|_, g| !g.grantor.eq_ignore_ascii_case(user_address) && !g.grantee.eq_ignore_ascii_case(user_address));
+        before - self.grants.len()
+    }
+}
+
+fn intersect_assets(a: Option<Vec<u64>>, b: Option<Vec<u64>>) -> Option<Vec<u64>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.into_iter().filter(|x| b.contains(x)).collect()),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Build a grant from a SIWE message's `Resources` and `Expiration Time` lines
+fn parse_grant(message: &str, grantor: String) -> Result<Grant, String> {
+    let mut grantee = None;
+    let mut assets = None;
+    let mut max_notional = None;
+    let mut expires_at = None;
+
+    for line in message.lines() {
+        let line = line.trim();
+        if let Some(value) = line.strip_prefix("Expiration Time: ") {
+            let time = chrono::DateTime::parse_from_rfc3339(value)
+                .map_err(|e| format!("Invalid Expiration Time: {}", e))?;
+            expires_at = Some(time.timestamp());
+        }
+
+        let resource = match line.strip_prefix("- ") {
+            Some(resource) => resource,
+            None => continue,
+        };
+        if let Some(value) = resource.strip_prefix(RESOURCE_GRANTEE) {
+            value.parse::<alloy::primitives::Address>()
+                .map_err(|_| format!("Invalid grantee address: {}", value))?;
+            grantee = Some(value.to_lowercase());
+        } else if let Some(value) = resource.strip_prefix(RESOURCE_ASSETS) {
+            let parsed = value.split(',')
+                .map(|a| a.trim().parse::<u64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("Invalid asset list: {}", value))?;
+            assets = Some(parsed);
+        } else if let Some(value) = resource.strip_prefix(RESOURCE_MAX_NOTIONAL) {
+            max_notional = Some(value.parse::<f64>().map_err(|_| format!("Invalid notional cap: {}", value))?);
+        }
+    }
+
+    Ok(Grant {
+        id: hex::encode(Sha256::digest(message.as_bytes())),
+        grantor,
+        grantee: grantee.ok_or("Grant message has no grantee resource")?,
+        assets,
+        max_notional,
+        expires_at: expires_at.ok_or("Grant message must carry an Expiration Time")?,
+    })
+}
+
+/// SIWE-signed delegation grant
+#[derive(Debug, Deserialize)]
+pub struct GrantRequest {
+    pub message: String,
+    pub signature: String,
+}
+
+/// POST /me/grants - Register a delegation grant signed by the caller's wallet
+pub async fn create_grant(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<GrantRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?
+        .to_lowercase();
+
+    let grantor = match validate_siwe_signature(&payload.message, &payload.signature).await {
+        Ok(address) => address.to_lower_hex(),
+        Err(e) => {
+            warn!("❌ Grant signature rejected: {}", e);
+            return Ok(Json(error_codes::err_body(ErrorCode::InvalidSignature, e.to_string())));
+        }
+    };
+    if grantor != user_address {
+        return Ok(Json(error_codes::err_body(ErrorCode::InvalidSignature, "Grant must be signed by the session's wallet")));
+    }
+
+    let grant = match parse_grant(&payload.message, grantor) {
+        Ok(grant) => grant,
+        Err(reason) => return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, reason))),
+    };
+
+    info!("🎫 Delegation grant {} -> {} (assets {:?}, cap {:?}, expires {})",
+        grant.grantor, grant.grantee, grant.assets, grant.max_notional, grant.expires_at);
+    state.delegations.write().await.insert(grant.clone());
+
+    Ok(Json(serde_json::json!({"status": "ok", "response": grant})))
+}
+
+/// GET /me/grants - Grants issued by or to the caller
+pub async fn list_grants(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let grants = state.delegations.write().await.grants_for(&user_address);
+    Ok(Json(serde_json::json!({ "grants": grants })))
+}
+
+/// DELETE /me/grants/:id - Revoke a grant issued by the caller
+pub async fn revoke_grant(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if !state.delegations.write().await.revoke(&id, &user_address) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("🎫 Delegation grant {} revoked by {}", id, user_address);
+    Ok(Json(serde_json::json!({"status": "ok", "response": "revoked"})))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: &str = "0x00000000000000000000000000000000000000aa";
+    const B: &str = "0x00000000000000000000000000000000000000bb";
+    const C: &str = "0x00000000000000000000000000000000000000cc";
+    const D: &str = "0x00000000000000000000000000000000000000dd";
+    const E: &str = "0x00000000000000000000000000000000000000ee";
+
+    fn grant(grantor: &str, grantee: &str, assets: Option<Vec<u64>>, max_notional: Option<f64>) -> Grant {
+        Grant {
+            id: format!("{}->{}", grantor, grantee),
+            grantor: grantor.to_string(),
+            grantee: grantee.to_string(),
+            assets,
+            max_notional,
+            expires_at: chrono::Utc::now().timestamp() + 3600,
+        }
+    }
+
+    fn order(asset: u64, px: &str, sz: &str) -> Value {
+        serde_json::json!({"type": "order", "orders": [{"a": asset, "b": true, "p": px, "s": sz, "r": false, "t": {"limit": {"tif": "Gtc"}}}], "grouping": "na"})
+    }
+
+    #[test]
+    fn follows_chains_up_to_max_depth() {
+        let mut manager = DelegationManager::new();
+        for (grantor, grantee) in [(A, B), (B, C), (C, D), (D, E)] {
+            manager.insert(grant(grantor, grantee, None, None));
+        }
+        assert_eq!(manager.resolve(B, A).unwrap().grantor, A);
+        assert_eq!(manager.resolve(&D.to_uppercase().replace("0X", "0x"), A).unwrap().grantor, A);
+        assert!(manager.resolve(E, A).is_none(), "four links exceed MAX_DELEGATION_DEPTH");
+        assert!(manager.resolve(A, B).is_none(), "grants do not run backwards");
+    }
+
+    #[test]
+    fn each_link_can_only_narrow_limits() {
+        let mut manager = DelegationManager::new();
+        manager.insert(grant(A, B, Some(vec![0, 1, 2]), Some(10_000.0)));
+        manager.insert(grant(B, C, Some(vec![1, 2, 3]), Some(50_000.0)));
+        manager.insert(grant(C, D, None, Some(2_000.0)));
+
+        let resolved = manager.resolve(D, A).unwrap();
+        assert_eq!(resolved.assets, Some(vec![1, 2]));
+        assert_eq!(resolved.max_notional, Some(2_000.0));
+
+        assert!(resolved.authorize(&order(1, "100", "10")).is_ok());
+        assert!(resolved.authorize(&order(3, "100", "1")).unwrap_err().contains("Asset 3"));
+        assert!(resolved.authorize(&order(2, "100", "21")).unwrap_err().contains("exceeds"));
+        assert!(resolved.authorize(&serde_json::json!({"type": "vaultTransfer"})).is_err());
+    }
+
+    #[test]
+    fn expired_and_revoked_grants_do_not_resolve() {
+        let mut manager = DelegationManager::new();
+        let mut expired = grant(A, B, None, None);
+        expired.expires_at = chrono::Utc::now().timestamp() - 1;
+        manager.insert(expired);
+        assert!(manager.resolve(B, A).is_none());
+
+        manager.insert(grant(A, C, None, None));
+        assert!(!manager.revoke(&format!("{}->{}", A, C), B), "only the grantor may revoke");
+        assert!(manager.revoke(&format!("{}->{}", A, C), A));
+        assert!(manager.resolve(C, A).is_none());
+    }
+
+    #[test]
+    fn parses_grant_terms_from_resources() {
+        let message = format!(
+            "example.com wants you to sign in with your Ethereum account:\n{}\n\nURI: https://example.com\nVersion: 1\nChain ID: 1\nNonce: abc\nIssued At: 2026-01-01T00:00:00Z\nExpiration Time: 2026-01-02T00:00:00Z\nResources:\n- {}{}\n- {}0, 4\n- {}2500",
+            A, RESOURCE_GRANTEE, B.to_uppercase().replace("0X", "0x"), RESOURCE_ASSETS, RESOURCE_MAX_NOTIONAL
+        );
+        let grant = parse_grant(&message, A.to_string()).unwrap();
+        assert_eq!(grant.grantee, B);
+        assert_eq!(grant.assets, Some(vec![0, 4]));
+        assert_eq!(grant.max_notional, Some(2500.0));
+        assert_eq!(grant.expires_at, 1767312000);
+
+        assert!(parse_grant(&message.replace("Expiration Time: 2026-01-02T00:00:00Z\n", ""), A.to_string()).is_err());
+        assert!(parse_grant(&message.replace(RESOURCE_GRANTEE, "urn:vas:other:"), A.to_string()).is_err());
+    }
+}
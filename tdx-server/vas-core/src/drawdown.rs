@@ -0,0 +1,260 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tracing::{info, warn, error};
+
+use crate::auth;
+use crate::market::parse_number;
+use crate::notify::{Notification, NotificationKind};
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+/// A user's max-drawdown rule and the equity curve it watches
+#[derive(Debug, Clone, Serialize)]
+pub struct DrawdownGuard {
+    /// Trip when equity falls this fraction (0.0-1.0) below its peak within the window
+    pub max_drawdown: f64,
+    pub window_secs: u64,
+    /// Session used to cancel open orders when the guard trips
+    #[serde(skip)]
+    pub api_key: String,
+    /// (unix secs, account value) samples from clearinghouse snapshots
+    #[serde(skip)]
+    pub curve: VecDeque<(u64, f64)>,
+    pub tripped_at: Option<u64>,
+    pub tripped_drawdown: Option<f64>,
+}
+
+impl DrawdownGuard {
+    fn record(&mut self, now: u64, equity: f64) {
+        self.curve.push_back((now, equity));
+        while self.curve.front().is_some_and(|(at, _)| *at + self.window_secs < now) {
+            self.curve.pop_front();
+        }
+    }
+
+    /// Drawdown of the latest sample from the window's peak
+    pub fn current_drawdown(&self) -> Option<f64> {
+        let (_, latest) = self.curve.back()?;
+        let peak = self.curve.iter().map(|(_, equity)| *equity).fold(f64::MIN, f64::max);
+        (peak > 0.0).then(|| ((peak - latest) / peak).max(0.0))
+    }
+}
+
+/// Drawdown guards by lowercased user address
+#[derive(Debug, Default)]
+pub struct DrawdownGuards {
+    guards: HashMap<String, DrawdownGuard>,
+}
+
+impl DrawdownGuards {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the user's account is held to reduce-only orders
+    pub fn is_tripped(&self, user_address: &str) -> bool {
+        self.guards.get(&user_address.to_lowercase()).is_some_and(|g| g.tripped_at.is_some())
+    }
+}
+
+/// Reject orders that could add exposure while the user's guard is tripped
+pub async fn check_reduce_only(state: &AppState, user_address: &str, action: &Value) -> Result<(), String> {
+    if !state.drawdown.read().await.is_tripped(user_address) {
+        return Ok(());
+    }
+
+    let all_reduce_only = action.get("orders")
+        .and_then(|orders| orders.as_array())
+        .is_some_and(|orders| orders.iter().all(|order| order.get("r").and_then(|r| r.as_bool()) == Some(true)));
+    if all_reduce_only {
+        Ok(())
+    } else {
+        Err("Drawdown circuit breaker tripped: account is reduce-only until reset".to_string())
+    }
+}
+
+/// Sample guarded users' equity and trip guards whose drawdown exceeds their limit
+pub fn spawn_monitor(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(state.config.drawdown_poll_secs.max(10)));
+        loop {
+            ticker.tick().await;
+
+            let users: Vec<String> = state.drawdown.read().await.guards.iter()
+                .filter(|(_, guard)| guard.tripped_at.is_none())
+                .map(|(user, _)| user.clone())
+                .collect();
+
+            for user in users {
+                let equity = match state.market.clearinghouse_state(&user).await {
+                    Ok(summary) => parse_number(summary.pointer("/marginSummary/accountValue")),
+                    Err(e) => {
+                        warn!("⚠️ Drawdown monitor could not load {}: {}", user, e);
+                        continue;
+                    }
+                };
+                let Some(equity) = equity else { continue };
+
+                let now = now_secs();
+                let tripped = {
+                    let mut guards = state.drawdown.write().await;
+                    let Some(guard) = guards.guards.get_mut(&user) else { continue };
+                    guard.record(now, equity);
+                    match guard.current_drawdown() {
+                        Some(drawdown) if drawdown > guard.max_drawdown => {
+                            guard.tripped_at = Some(now);
+                            guard.tripped_drawdown = Some(drawdown);
+                            Some((drawdown, guard.api_key.clone()))
+                        }
+                        _ => None,
+                    }
+                };
+
+                if let Some((drawdown, api_key)) = tripped {
+                    warn!("🧯 Drawdown guard tripped for {}: {:.2}%", user, drawdown * 100.0);
+                    state.notifier.notify(Notification::new(
+                        NotificationKind::Alert,
+                        Some(&user),
+                        "Drawdown circuit breaker tripped",
+                        serde_json::json!({"drawdown": drawdown, "equity": equity}),
+                    ));
+                    cancel_open_orders(&state, &user, &api_key).await;
+                }
+            }
+        }
+    });
+}
+
+/// Cancel every resting order through the normal /exchange pipeline
+async fn cancel_open_orders(state: &AppState, user: &str, api_key: &str) {
+    let open_orders = match state.proxy.proxy_info_request(&serde_json::json!({"type": "openOrders", "user": user})).await {
+        Ok(orders) => orders,
+        Err(e) => {
+            error!("❌ Drawdown guard could not list open orders for {}: {}", user, e);
+            return;
+        }
+    };
+
+    let mut cancels = Vec::new();
+    for order in open_orders.as_array().into_iter().flatten() {
+        let (Some(coin), Some(oid)) = (order.get("coin").and_then(|c| c.as_str()), order.get("oid").and_then(|o| o.as_u64())) else {
+            continue;
+        };
+        if let Ok(Some(asset)) = state.market.asset_index(coin).await {
+            cancels.push(serde_json::json!({"a": asset, "o": oid}));
+        }
+    }
+    if cancels.is_empty() {
+        return;
+    }
+
+    let mut headers = HeaderMap::new();
+    let Ok(api_key) = HeaderValue::from_str(api_key) else { return };
+    headers.insert("X-API-Key", api_key);
+
+    let payload = serde_json::json!({"action": {"type": "cancel", "cancels": cancels}});
+    match crate::proxy_exchange(State(state.clone()), headers, Json(payload)).await {
+        Ok(Json(response)) => info!("🧯 Drawdown guard cancelled open orders for {}: {}", user, response),
+        Err(status) => error!("❌ Drawdown guard cancel failed for {}: {}", user, status),
+    }
+}
+
+/// Drawdown guard settings; `max_drawdown_pct: null` removes the guard
+#[derive(Debug, Deserialize)]
+pub struct DrawdownGuardRequest {
+    pub max_drawdown_pct: Option<f64>,
+    pub window_secs: Option<u64>,
+}
+
+/// PUT /me/drawdown-guard - Configure (or remove) the max-drawdown circuit breaker
+pub async fn set_guard(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<DrawdownGuardRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let (api_key, user_address) = session_user(&state, &headers).await?;
+    let mut guards = state.drawdown.write().await;
+
+    let Some(pct) = payload.max_drawdown_pct else {
+        guards.guards.remove(&user_address);
+        info!("🧯 Drawdown guard removed for {}", user_address);
+        return Ok(Json(serde_json::json!({"status": "ok", "response": "removed"})));
+    };
+    if !(pct > 0.0 && pct < 100.0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Keep the curve and trip state when only the thresholds change
+    let guard = guards.guards.entry(user_address.clone()).or_insert_with(|| DrawdownGuard {
+        max_drawdown: 0.0,
+        window_secs: 0,
+        api_key: String::new(),
+        curve: VecDeque::new(),
+        tripped_at: None,
+        tripped_drawdown: None,
+    });
+    guard.max_drawdown = pct / 100.0;
+    guard.window_secs = payload.window_secs.unwrap_or(24 * 3600);
+    guard.api_key = api_key;
+
+    info!("🧯 Drawdown guard for {} set to {}% over {}s", user_address, pct, guard.window_secs);
+    Ok(Json(serde_json::json!({"status": "ok", "response": guard.clone()})))
+}
+
+/// GET /me/drawdown-guard - Guard settings, trip state and current drawdown
+pub async fn get_guard(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    let guards = state.drawdown.read().await;
+    let guard = guards.guards.get(&user_address.to_lowercase()).ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(serde_json::json!({
+        "guard": guard,
+        "current_drawdown": guard.current_drawdown(),
+        "samples": guard.curve.len()
+    })))
+}
+
+/// POST /me/drawdown-guard/reset - Lift reduce-only and restart the equity curve
+pub async fn reset_guard(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let (_, user_address) = session_user(&state, &headers).await?;
+    let mut guards = state.drawdown.write().await;
+    let guard = guards.guards.get_mut(&user_address).ok_or(StatusCode::NOT_FOUND)?;
+
+    guard.tripped_at = None;
+    guard.tripped_drawdown = None;
+    guard.curve.clear();
+
+    info!("🧯 Drawdown guard reset for {}", user_address);
+    Ok(Json(serde_json::json!({"status": "ok", "response": "reset"})))
+}
+
+/// Trading session behind the request; share tokens can't change the guard
+async fn session_user(state: &AppState, headers: &HeaderMap) -> Result<(String, String), StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok((api_key.to_string(), user_address.to_lowercase()))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
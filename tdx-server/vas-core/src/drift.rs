@@ -0,0 +1,124 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Json,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::warn;
+
+use crate::auth;
+use crate::AppState;
+
+/// Weight of each new sample in the smoothed drift
+const EWMA_ALPHA: f64 = 0.2;
+/// Samples needed before a key can be flagged as a chronic offender
+const MIN_SAMPLES: u64 = 5;
+
+/// Observed clock drift for one API key; drift is client nonce minus server time
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DriftStats {
+    pub samples: u64,
+    pub rejected: u64,
+    pub last_drift_ms: i64,
+    pub smoothed_drift_ms: f64,
+    pub max_abs_drift_ms: u64,
+    pub last_seen_ms: u64,
+}
+
+/// Per-key drift telemetry for client-supplied nonces
+#[derive(Debug, Default)]
+pub struct DriftTracker {
+    stats: HashMap<String, DriftStats>,
+}
+
+impl DriftTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn observe(&mut self, api_key: &str, drift_ms: i64, rejected: bool, now: u64) {
+        let stats = self.stats.entry(api_key.to_string()).or_default();
+        stats.smoothed_drift_ms = if stats.samples == 0 {
+            drift_ms as f64
+        } else {
+            EWMA_ALPHA * drift_ms as f64 + (1.0 - EWMA_ALPHA) * stats.smoothed_drift_ms
+        };
+        stats.samples += 1;
+        stats.rejected += rejected as u64;
+        stats.last_drift_ms = drift_ms;
+        stats.max_abs_drift_ms = stats.max_abs_drift_ms.max(drift_ms.unsigned_abs());
+        stats.last_seen_ms = now;
+    }
+
+    /// Drop the telemetry of a deleted user's key
+    pub fn remove_key(&mut self, api_key: &str) -> usize {
+        usize::from(self.stats.remove(api_key).is_some())
+    }
+}
+
+/// Check a client nonce against the accepted window and record the key's drift.
+/// Hyperliquid rejects nonces outside (now - 2d, now + 1d); the defaults match, and
+/// tighter windows surface bad clocks here before orders start failing upstream.
+pub async fn check_nonce(state: &AppState, api_key: &str, nonce: u64) -> Result<(), String> {
+    let now = now_ms();
+    let drift_ms = nonce as i64 - now as i64;
+    let config = &state.config;
+
+    let rejected = if drift_ms < 0 {
+        drift_ms.unsigned_abs() > config.nonce_window_past_ms
+    } else {
+        drift_ms as u64 > config.nonce_window_future_ms
+    };
+    state.drift.write().await.observe(api_key, drift_ms, rejected, now);
+
+    if rejected {
+        warn!("🕰️ Rejected nonce {}ms from server time", drift_ms);
+        return Err(format!(
+            "Nonce is {}ms from server time; accepted window is -{}ms to +{}ms",
+            drift_ms, config.nonce_window_past_ms, config.nonce_window_future_ms
+        ));
+    }
+    Ok(())
+}
+
+/// GET /admin/clock-drift - Per-key nonce drift, chronic offenders first
+pub async fn admin_clock_drift(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    let warn_ms = state.config.nonce_drift_warn_ms as f64;
+    let stats: Vec<(String, DriftStats)> = state.drift.read().await.stats.iter()
+        .map(|(key, stats)| (key.clone(), stats.clone()))
+        .collect();
+
+    let mut keys = Vec::with_capacity(stats.len());
+    for (api_key, stats) in stats {
+        let chronic = stats.samples >= MIN_SAMPLES && stats.smoothed_drift_ms.abs() > warn_ms;
+        keys.push(serde_json::json!({
+            "api_key_prefix": api_key.chars().take(10).collect::<String>(),
+            "user_address": auth::user_address_for_api_key(&state, &api_key).await,
+            "chronic": chronic,
+            "stats": stats
+        }));
+    }
+    keys.sort_by(|a, b| {
+        let drift = |v: &Value| v["stats"]["smoothed_drift_ms"].as_f64().unwrap_or(0.0).abs();
+        b["chronic"].as_bool().cmp(&a["chronic"].as_bool())
+            .then(drift(b).total_cmp(&drift(a)))
+    });
+
+    Ok(Json(serde_json::json!({
+        "window": {
+            "past_ms": state.config.nonce_window_past_ms,
+            "future_ms": state.config.nonce_window_future_ms,
+            "warn_ms": state.config.nonce_drift_warn_ms
+        },
+        "keys": keys
+    })))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,105 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Json,
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use crate::config::NotifierTransport;
+use crate::sealed_config::{self, ENC_PREFIX};
+use crate::universal_signing::SignatureChain;
+use crate::AppState;
+
+/// Config fields whose variable is not simply the upper-cased field name
+const ENV_NAMES: &[(&str, &str)] = &[
+    ("hyperliquid_url", "HYPERLIQUID_API_URL"),
+    ("testnet_url", "TESTNET_API_URL"),
+    ("admin_key_secrets", "ADMIN_KEYS"),
+];
+
+/// Fields shown only as a fingerprint; RPC URLs commonly carry an API key in the path
+fn is_secret(field: &str) -> bool {
+    ["token", "secret", "password", "seal_key", "api_key", "rpc_url"].iter().any(|s| field.contains(s))
+}
+
+/// First 8 bytes of the value's sha256, so operators can compare a secret against what they set
+fn fingerprint(value: &Value) -> String {
+    let raw = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    hex::encode(&Sha256::digest(raw.as_bytes())[..8])
+}
+
+fn env_name(field: &str) -> String {
+    ENV_NAMES.iter()
+        .find(|(name, _)| *name == field)
+        .map(|(_, env)| env.to_string())
+        .unwrap_or_else(|| field.to_uppercase())
+}
+
+/// Where a variable's value came from: the process environment, the .env file, or the built-in default
+fn source(name: &str, file: &HashMap<String, String>) -> &'static str {
+    let Ok(value) = std::env::var(name) else {
+        return "default";
+    };
+    match file.get(name) {
+        // Sealed values were replaced by their plaintext at startup
+        Some(file_value) if *file_value == value || (file_value.starts_with(ENC_PREFIX) && sealed_config::was_sealed(name)) => "file",
+        _ => "env",
+    }
+}
+
+/// GET /admin/effective-config - The resolved runtime configuration and active policy, redacted.
+///
+/// Each setting reports its variable, the value the server is running with and its source
+/// (`env`, `file` or `default`), so operators can check a deployment without shelling in.
+/// Secrets are replaced by a fingerprint.
+pub async fn effective_config(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    // dotenvy never overrides variables already set, so a value matching the file came from it
+    let file: HashMap<String, String> = dotenvy::dotenv_iter()
+        .map(|iter| iter.filter_map(Result::ok).collect())
+        .unwrap_or_default();
+
+    let Value::Object(fields) = serde_json::to_value(&*state.config).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? else {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    };
+    let settings: serde_json::Map<String, Value> = fields.into_iter().map(|(field, value)| {
+        let name = env_name(&field);
+        let mut setting = serde_json::json!({
+            "env": name,
+            "source": source(&name, &file),
+            "sealed": sealed_config::was_sealed(&name)
+        });
+        if is_secret(&field) && !value.is_null() {
+            setting["fingerprint"] = Value::String(fingerprint(&value));
+            setting["redacted"] = Value::Bool(true);
+        } else {
+            setting["value"] = value;
+        }
+        (field, setting)
+    }).collect();
+
+    let notifiers: Vec<Value> = state.config.notifiers.iter().map(|notifier| {
+        let (transport, prefix) = match &notifier.transport {
+            NotifierTransport::Webhook { .. } => ("webhook", "NOTIFY_WEBHOOK_URL"),
+            NotifierTransport::Slack { .. } => ("slack", "NOTIFY_SLACK_WEBHOOK_URL"),
+            NotifierTransport::Telegram { .. } => ("telegram", "NOTIFY_TELEGRAM_BOT_TOKEN"),
+            NotifierTransport::Email { .. } => ("email", "NOTIFY_EMAIL_API_URL"),
+        };
+        serde_json::json!({"transport": transport, "events": notifier.events, "source": source(prefix, &file)})
+    }).collect();
+
+    Ok(Json(serde_json::json!({
+        "settings": settings,
+        "notifiers": notifiers,
+        "policy": *state.policy.read().await,
+        "network": state.config.hyperliquid_network,
+        "signature_chains": {
+            "mainnet": SignatureChain::resolve(true),
+            "testnet": SignatureChain::resolve(false)
+        }
+    })))
+}
@@ -0,0 +1,183 @@
+use aes_gcm::{aead::{Aead, Payload}, Aes128Gcm, KeyInit};
+use alloy::primitives::eip191_hash_message;
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use hkdf::Hkdf;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use tokio::sync::OnceCell;
+use tracing::{debug, error};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::audit::AUDIT_STATEMENT;
+use crate::canonical_json;
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::preset_tdx::PresetTDXData;
+use crate::AppState;
+
+/// Body field carrying an encrypted exchange request: `{"hpke": {"enc": hex, "ciphertext": hex}}`
+pub const ENCRYPTED_FIELD: &str = "hpke";
+
+/// RFC 9180 suite: DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, AES-128-GCM
+const KEM_ID: u16 = 0x0020;
+const KDF_ID: u16 = 0x0001;
+const AEAD_ID: u16 = 0x0001;
+/// HPKE `info`; the AAD is the caller's X-API-Key, so a ciphertext only opens for its sender
+const INFO: &[u8] = b"vas-exchange-v1";
+
+static KEY_PAIR: OnceLock<(StaticSecret, PublicKey)> = OnceLock::new();
+static KEY_STATEMENT: OnceCell<Value> = OnceCell::const_new();
+
+/// The enclave's encryption key, derived from the attested agent key so it needs no storage
+/// and is the same across restarts of the same enclave generation
+fn key_pair() -> Option<&'static (StaticSecret, PublicKey)> {
+    if let Some(pair) = KEY_PAIR.get() {
+        return Some(pair);
+    }
+    let preset_data = PresetTDXData::get()?;
+    let seed: [u8; 32] = Sha256::new()
+        .chain_update(b"vas-hpke-x25519")
+        .chain_update(preset_data.agent_private_key.secret_bytes())
+        .finalize()
+        .into();
+    let secret = StaticSecret::from(seed);
+    let public = PublicKey::from(&secret);
+    Some(KEY_PAIR.get_or_init(|| (secret, public)))
+}
+
+fn suite_id() -> Vec<u8> {
+    [b"HPKE".as_slice(), &KEM_ID.to_be_bytes(), &KDF_ID.to_be_bytes(), &AEAD_ID.to_be_bytes()].concat()
+}
+
+fn labeled_extract(suite_id: &[u8], salt: &[u8], label: &[u8], ikm: &[u8]) -> Vec<u8> {
+    let labeled_ikm = [b"HPKE-v1".as_slice(), suite_id, label, ikm].concat();
+    Hkdf::<Sha256>::extract(Some(salt), &labeled_ikm).0.to_vec()
+}
+
+fn labeled_expand(suite_id: &[u8], prk: &[u8], label: &[u8], info: &[u8], len: u16) -> Result<Vec<u8>, String> {
+    let labeled_info = [len.to_be_bytes().as_slice(), b"HPKE-v1", suite_id, label, info].concat();
+    let mut okm = vec![0u8; len as usize];
+    let hkdf = Hkdf::<Sha256>::from_prk(prk).map_err(|_| "Invalid HKDF PRK".to_string())?;
+    hkdf.expand(&labeled_info, &mut okm).map_err(|_| "HKDF output too long".to_string())?;
+    Ok(okm)
+}
+
+/// Single-shot HPKE base-mode open (RFC 9180 §6.1)
+fn open(secret: &StaticSecret, public: &PublicKey, info: &[u8], enc: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    let enc: [u8; 32] = enc.try_into().map_err(|_| "enc must be a 32-byte X25519 public key")?;
+
+    // Decap: DHKEM(X25519, HKDF-SHA256)
+    let kem_suite = [b"KEM".as_slice(), &KEM_ID.to_be_bytes()].concat();
+    let dh = secret.diffie_hellman(&PublicKey::from(enc));
+    if !dh.was_contributory() {
+        return Err("enc is a low-order point".to_string());
+    }
+    let kem_context = [enc.as_slice(), public.as_bytes()].concat();
+    let eae_prk = labeled_extract(&kem_suite, b"", b"eae_prk", dh.as_bytes());
+    let shared_secret = labeled_expand(&kem_suite, &eae_prk, b"shared_secret", &kem_context, 32)?;
+
+    // Key schedule, mode_base with no PSK
+    let suite = suite_id();
+    let psk_id_hash = labeled_extract(&suite, b"", b"psk_id_hash", b"");
+    let info_hash = labeled_extract(&suite, b"", b"info_hash", info);
+    let context = [[0u8].as_slice(), &psk_id_hash, &info_hash].concat();
+    let secret = labeled_extract(&suite, &shared_secret, b"secret", b"");
+    let key = labeled_expand(&suite, &secret, b"key", &context, 16)?;
+    let nonce = labeled_expand(&suite, &secret, b"base_nonce", &context, 12)?;
+
+    Aes128Gcm::new_from_slice(&key)
+        .map_err(|e| e.to_string())?
+        .decrypt(nonce.as_slice().into(), Payload { msg: ciphertext, aad })
+        .map_err(|_| "Ciphertext did not decrypt; check the key, info and that AAD is your X-API-Key".to_string())
+}
+
+/// Replace an encrypted exchange body with its plaintext; other bodies pass through unchanged.
+///
+/// Returns the error body to send when the request is encrypted but does not open.
+pub fn decrypt_request(headers: &HeaderMap, payload: Value) -> Result<Value, Value> {
+    let Some(sealed) = payload.get(ENCRYPTED_FIELD) else {
+        return Ok(payload);
+    };
+    let bad_request = |reason: String| error_codes::err_body(ErrorCode::BadRequest, reason);
+    let field = |name: &str| sealed.get(name)
+        .and_then(|v| v.as_str())
+        .and_then(|v| hex::decode(v.trim_start_matches("0x")).ok())
+        .ok_or_else(|| bad_request(format!("{}.{} must be hex", ENCRYPTED_FIELD, name)));
+    let (enc, ciphertext) = (field("enc")?, field("ciphertext")?);
+    let aad = auth::api_key_from_headers(headers).unwrap_or_default();
+
+    let (secret, public) = key_pair().ok_or_else(|| bad_request("Encryption key is not available".to_string()))?;
+    let plaintext = open(secret, public, INFO, &enc, &ciphertext, aad.as_bytes()).map_err(bad_request)?;
+    let request: Value = serde_json::from_slice(&plaintext)
+        .map_err(|e| bad_request(format!("Decrypted request is not JSON: {}", e)))?;
+    if !request.is_object() || request.get(ENCRYPTED_FIELD).is_some() {
+        return Err(bad_request("Decrypted request must be a plain exchange body".to_string()));
+    }
+    debug!("🔏 Opened end-to-end encrypted exchange request");
+    Ok(request)
+}
+
+/// GET /encryption-key - The enclave's HPKE public key, signed by the attested agent key.
+///
+/// Clients verify the signature against the agent address in the attestation before
+/// encrypting to the key, so an operator or middlebox cannot substitute its own.
+pub async fn encryption_key(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    let signed = KEY_STATEMENT.get_or_try_init(|| async {
+        let preset_data = PresetTDXData::get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+        let (_, public) = key_pair().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+        let statement = serde_json::json!({
+            "type": "encryption_key",
+            "public_key": hex::encode(public.as_bytes()),
+            "kem_id": KEM_ID,
+            "kdf_id": KDF_ID,
+            "aead_id": AEAD_ID,
+            "agent_address": preset_data.agent_address,
+            "quote_id": preset_data.quote_id
+        });
+        let message = canonical_json::to_string(&statement);
+        let signature = state.signer
+            .sign_digest(eip191_hash_message(message.as_bytes()), None, AUDIT_STATEMENT, statement.clone())
+            .await
+            .map_err(|e| {
+                error!("❌ Failed to sign encryption key statement: {:?}", e);
+                StatusCode::BAD_GATEWAY
+            })?;
+        Ok::<_, StatusCode>(serde_json::json!({
+            "public_key": statement["public_key"],
+            "suite": {"kem_id": KEM_ID, "kdf_id": KDF_ID, "aead_id": AEAD_ID, "mode": "base"},
+            "info": String::from_utf8_lossy(INFO),
+            "aad": "X-API-Key header value",
+            "statement": message,
+            "signature": signature.to_json()
+        }))
+    }).await?;
+
+    Ok(Json(signed.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 9180 Appendix A.1.1: DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, AES-128-GCM, base mode
+    #[test]
+    fn test_open_matches_rfc9180_vector() {
+        let sk_r: [u8; 32] = hex::decode("4612c550263fc8ad58375df3f557aac531d26850903e55a9f23f21d8534e8ac8").unwrap().try_into().unwrap();
+        let secret = StaticSecret::from(sk_r);
+        let public = PublicKey::from(&secret);
+        let info = hex::decode("4f6465206f6e2061204772656369616e2055726e").unwrap();
+        let enc = hex::decode("37fda3567bdbd628e88668c3c8d7e97d1d1253b6d4ea6d44c150f741f1bf4431").unwrap();
+        let ciphertext = hex::decode("f938558b5d72f1a23810b4be2ab4f84331acc02fc97babc53a52ae8218a355a96d8770ac83d07bea87e13c512a").unwrap();
+        let aad = hex::decode("436f756e742d30").unwrap();
+
+        let plaintext = open(&secret, &public, &info, &enc, &ciphertext, &aad).unwrap();
+        assert_eq!(plaintext, b"Beauty is truth, truth beauty");
+
+        assert!(open(&secret, &public, &info, &enc, &ciphertext, b"other aad").is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::jsonl;
+use crate::market::parse_number;
+use crate::AppState;
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+/// How often expired snapshots are dropped from memory and the file
+const PRUNE_INTERVAL_MS: u64 = 60 * 60 * 1000;
+/// Most points returned by one GET /me/equity (the latest are kept)
+const MAX_POINTS: usize = 5000;
+/// Bucket sizes accepted by `?interval=`
+const INTERVALS: [(&str, u64); 6] = [
+    ("5m", 5 * 60_000),
+    ("15m", 15 * 60_000),
+    ("1h", 60 * 60_000),
+    ("4h", 4 * 60 * 60_000),
+    ("1d", DAY_MS),
+    ("1w", 7 * DAY_MS),
+];
+
+/// Account value and margin totals from one clearinghouse snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp_ms: u64,
+    pub account_value: f64,
+    pub total_notional: Option<f64>,
+    pub margin_used: Option<f64>,
+    pub withdrawable: Option<f64>,
+}
+
+impl EquityPoint {
+    fn from_clearinghouse(summary: &Value, timestamp_ms: u64) -> Option<Self> {
+        Some(Self {
+            timestamp_ms,
+            account_value: parse_number(summary.pointer("/marginSummary/accountValue"))?,
+            total_notional: parse_number(summary.pointer("/marginSummary/totalNtlPos")),
+            margin_used: parse_number(summary.pointer("/marginSummary/totalMarginUsed")),
+            withdrawable: parse_number(summary.get("withdrawable")),
+        })
+    }
+}
+
+/// One persisted line: a point and the user it belongs to
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredPoint {
+    user_address: String,
+    #[serde(flatten)]
+    point: EquityPoint,
+}
+
+/// Equity curves of users with a session, sampled by the snapshot scheduler
+#[derive(Debug)]
+pub struct EquityHistory {
+    /// Lowercased user -> points, oldest first
+    series: HashMap<String, Vec<EquityPoint>>,
+    path: Option<PathBuf>,
+    last_pruned_ms: u64,
+}
+
+impl EquityHistory {
+    /// Open the store, replaying snapshots already persisted at `path`
+    pub fn open(path: Option<PathBuf>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let stored: Vec<StoredPoint> = match &path {
+            Some(path) => jsonl::load(path)?,
+            None => Vec::new(),
+        };
+        let mut series: HashMap<String, Vec<EquityPoint>> = HashMap::new();
+        for stored in stored {
+            series.entry(stored.user_address).or_default().push(stored.point);
+        }
+        for points in series.values_mut() {
+            points.sort_by_key(|p| p.timestamp_ms);
+        }
+
+        info!("📈 Equity history opened with {} users", series.len());
+        Ok(Self { series, path, last_pruned_ms: 0 })
+    }
+
+    fn record(&mut self, user_address: &str, point: EquityPoint) {
+        let user_address = user_address.to_lowercase();
+        if let Some(path) = &self.path {
+            let stored = StoredPoint { user_address: user_address.clone(), point: point.clone() };
+            if let Err(e) = jsonl::append(path, &stored) {
+                error!("❌ Failed to persist equity snapshot: {}", e);
+            }
+        }
+        self.series.entry(user_address).or_default().push(point);
+    }
+
+    /// Drop points older than `cutoff_ms`, rewriting the file when any were dropped
+    fn prune_before(&mut self, cutoff_ms: u64) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let mut pruned = 0;
+        for points in self.series.values_mut() {
+            let before = points.len();
+            points.retain(|p| p.timestamp_ms >= cutoff_ms);
+            pruned += before - points.len();
+        }
+        self.series.retain(|_, points| !points.is_empty());
+
+        if pruned > 0 {
+            if let Some(path) = &self.path {
+                let stored: Vec<StoredPoint> = self.series.iter()
+                    .flat_map(|(user, points)| points.iter().map(|point| StoredPoint { user_address: user.clone(), point: point.clone() }))
+                    .collect();
+                jsonl::rewrite(path, &stored)?;
+            }
+        }
+        Ok(pruned)
+    }
+
+    /// Points in [from, to], one per `bucket_ms` bucket (the last in it) when bucketed
+    fn curve(&self, user_address: &str, from_ms: u64, to_ms: u64, bucket_ms: Option<u64>) -> Vec<EquityPoint> {
+        let points = self.series.get(&user_address.to_lowercase()).map(Vec::as_slice).unwrap_or_default();
+        let in_range = points.iter().filter(|p| p.timestamp_ms >= from_ms && p.timestamp_ms <= to_ms);
+
+        let mut curve: Vec<EquityPoint> = match bucket_ms {
+            None => in_range.cloned().collect(),
+            Some(bucket_ms) => {
+                let mut buckets: Vec<EquityPoint> = Vec::new();
+                for point in in_range {
+                    let start = point.timestamp_ms - point.timestamp_ms % bucket_ms;
+                    let bucketed = EquityPoint { timestamp_ms: start, ..point.clone() };
+                    match buckets.last_mut() {
+                        Some(last) if last.timestamp_ms == start => *last = bucketed,
+                        _ => buckets.push(bucketed),
+                    }
+                }
+                buckets
+            }
+        };
+        if curve.len() > MAX_POINTS {
+            curve.drain(..curve.len() - MAX_POINTS);
+        }
+        curve
+    }
+
+    /// Every point held for a user, oldest first
+    pub fn export_user(&self, user_address: &str) -> Vec<EquityPoint> {
+        self.series.get(&user_address.to_lowercase()).cloned().unwrap_or_default()
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(points) = self.series.remove(&user_address.to_lowercase()) else { return Ok(0) };
+        if let Some(path) = &self.path {
+            let stored: Vec<StoredPoint> = self.series.iter()
+                .flat_map(|(user, points)| points.iter().map(|point| StoredPoint { user_address: user.clone(), point: point.clone() }))
+                .collect();
+            jsonl::rewrite(path, &stored)?;
+        }
+        Ok(points.len())
+    }
+}
+
+/// Snapshot the clearinghouse state of every user with a live session on a fixed schedule
+pub fn spawn_scheduler(state: AppState) {
+    let Some(interval_secs) = state.config.equity_snapshot_secs else {
+        info!("📈 Equity snapshots disabled");
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs.max(60)));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            // An outage would record gaps as failures; wait for the venue instead
+            if !state.upstream.is_available() {
+                continue;
+            }
+
+            let now_secs = now_ms() / 1000;
+            let users: Vec<String> = state.session_manager.user_addresses().into_iter()
+                .filter(|user| state.session_manager.get_user_session(user).is_some_and(|s| !s.is_expired(now_secs)))
+                .collect();
+
+            let mut recorded = 0;
+            for user in &users {
+                let summary = match state.market.clearinghouse_state(user).await {
+                    Ok(summary) => summary,
+                    Err(e) => {
+                        warn!("⚠️ Equity snapshot could not load {}: {}", user, e);
+                        continue;
+                    }
+                };
+                let Some(point) = EquityPoint::from_clearinghouse(&summary, now_ms()) else { continue };
+                state.equity.write().await.record(user, point);
+                recorded += 1;
+            }
+            debug!("📈 Recorded {} equity snapshots", recorded);
+
+            let now = now_ms();
+            let mut history = state.equity.write().await;
+            if now.saturating_sub(history.last_pruned_ms) >= PRUNE_INTERVAL_MS {
+                history.last_pruned_ms = now;
+                match history.prune_before(now.saturating_sub(state.config.equity_retention_days * DAY_MS)) {
+                    Ok(0) => {}
+                    Ok(pruned) => info!("📈 Pruned {} expired equity snapshots", pruned),
+                    Err(e) => error!("❌ Equity retention failed: {}", e),
+                }
+            }
+        }
+    });
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EquityQuery {
+    /// Bucket size (5m, 15m, 1h, 4h, 1d, 1w); every snapshot when absent
+    pub interval: Option<String>,
+    /// Range in unix ms, inclusive; the whole retained history by default
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+/// GET /me/equity?interval=&from=&to= - The caller's account value over time, oldest first
+pub async fn me_equity(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<EquityQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    let bucket_ms = match query.interval.as_deref() {
+        None => None,
+        Some(interval) => match INTERVALS.iter().find(|(name, _)| *name == interval) {
+            Some((_, ms)) => Some(*ms),
+            None => {
+                let names: Vec<&str> = INTERVALS.iter().map(|(name, _)| *name).collect();
+                return Ok(Json(error_codes::err_body(
+                    ErrorCode::BadRequest,
+                    format!("interval must be one of {}", names.join(", ")),
+                )));
+            }
+        },
+    };
+    let from_ms = query.from.unwrap_or(0);
+    let to_ms = query.to.unwrap_or(u64::MAX);
+    if from_ms > to_ms {
+        return Ok(Json(error_codes::err_body(ErrorCode::InvalidRange, "from is after to")));
+    }
+
+    let points = state.equity.read().await.curve(&user_address, from_ms, to_ms, bucket_ms);
+    Ok(Json(serde_json::json!({
+        "user": user_address.to_lowercase(),
+        "interval": query.interval,
+        "snapshot_secs": state.config.equity_snapshot_secs,
+        "points": points
+    })))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,199 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{Json, Response},
+};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Largest non-JSON error body kept as the message
+const MAX_TEXT_BODY: usize = 4096;
+
+/// Stable, machine-readable error codes returned as `code` in every error body.
+///
+/// Codes are part of the API: clients branch on them instead of on `response`, which is a
+/// human-readable message that may change. Never rename or reuse a code; add new ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    // Transport-level errors, filled in for responses that carry only a status
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    RateLimited,
+    Timeout,
+    InternalError,
+    ServiceUnavailable,
+    UpstreamUnavailable,
+
+    // Request validation
+    UnknownAsset,
+    InvalidTick,
+    LimitExceeded,
+    InvalidRange,
+    InvalidSignature,
+    NonceOutOfWindow,
+    NonceMismatch,
+    ApproveAgentUnsigned,
+    IdempotencyConflict,
+
+    // Authorization
+    ScopeNotAllowed,
+    AssetNotAllowed,
+    AgentNotApproved,
+    DelegationRejected,
+    SessionExpired,
+    ReauthRequired,
+
+    // Pre-sign checks
+    PolicyBuilderFeeExceeded,
+    PolicyBuilderNotAllowed,
+    PolicyStrategyLimitExceeded,
+    TypedDataNotAllowed,
+    EvmCallNotAllowed,
+    RiskCheckFailed,
+    DrawdownReduceOnly,
+    CosignRejected,
+    InactivityRefused,
+    SafeModeRestricted,
+    KeyQuoteMismatch,
+    SimulationFailed,
+    OrderStateConflict,
+    PartialFailure,
+
+    // Upstream (Hyperliquid) outcomes
+    UpstreamRateLimited,
+    UpstreamRejected,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 40] = [
+        Self::BadRequest, Self::Unauthorized, Self::Forbidden, Self::NotFound, Self::RateLimited,
+        Self::Timeout, Self::InternalError, Self::ServiceUnavailable, Self::UpstreamUnavailable,
+        Self::UnknownAsset, Self::InvalidTick, Self::LimitExceeded, Self::InvalidRange, Self::InvalidSignature,
+        Self::NonceOutOfWindow, Self::NonceMismatch, Self::ApproveAgentUnsigned, Self::IdempotencyConflict,
+        Self::ScopeNotAllowed, Self::AssetNotAllowed, Self::AgentNotApproved, Self::DelegationRejected, Self::SessionExpired, Self::ReauthRequired,
+        Self::PolicyBuilderFeeExceeded, Self::PolicyBuilderNotAllowed, Self::PolicyStrategyLimitExceeded,
+        Self::TypedDataNotAllowed, Self::EvmCallNotAllowed,
+        Self::RiskCheckFailed, Self::DrawdownReduceOnly, Self::CosignRejected, Self::InactivityRefused, Self::SafeModeRestricted,
+        Self::KeyQuoteMismatch, Self::SimulationFailed, Self::OrderStateConflict, Self::PartialFailure, Self::UpstreamRateLimited, Self::UpstreamRejected,
+    ];
+
+    /// What the code means, for the published catalogue
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::BadRequest => "The request was malformed or failed validation",
+            Self::Unauthorized => "Missing or invalid API key or admin token",
+            Self::Forbidden => "The credential is valid but may not perform this request",
+            Self::NotFound => "The resource does not exist or belongs to another user",
+            Self::RateLimited => "This API key exceeded its per-minute request budget",
+            Self::Timeout => "The request exceeded its route's time budget",
+            Self::InternalError => "Unexpected server error",
+            Self::ServiceUnavailable => "The server cannot serve this request right now (e.g. HA standby)",
+            Self::UpstreamUnavailable => "Hyperliquid could not be reached, is in maintenance, or returned an error",
+            Self::UnknownAsset => "The asset index is not in the current perp universe",
+            Self::InvalidTick => "A price or size has more decimals or significant figures than the asset allows",
+            Self::LimitExceeded => "A per-user limit (pending orders, plans, pairs, ...) is reached",
+            Self::InvalidRange => "The requested time range is invalid or outside retained history",
+            Self::InvalidSignature => "A user signature did not verify or came from the wrong wallet",
+            Self::NonceOutOfWindow => "The nonce is too far from server time",
+            Self::NonceMismatch => "The request nonce differs from the nonce inside the action",
+            Self::ApproveAgentUnsigned => "approveAgent must be signed by the master wallet",
+            Self::IdempotencyConflict => "The Idempotency-Key was already used for a different request body",
+            Self::ScopeNotAllowed => "The API key lacks the scope this action requires",
+            Self::AssetNotAllowed => "The action touches an asset outside the API key's signed asset allowlist",
+            Self::AgentNotApproved => "The session's agent is not approved on Hyperliquid yet",
+            Self::DelegationRejected => "The delegation grant does not cover this action",
+            Self::SessionExpired => "The session that scheduled this action has ended",
+            Self::ReauthRequired => "The API key was used from too many clients; sign in with SIWE again",
+            Self::PolicyBuilderFeeExceeded => "The builder fee is above the operator's cap",
+            Self::PolicyBuilderNotAllowed => "The builder address is not on the operator's allowlist",
+            Self::PolicyStrategyLimitExceeded => "The order would exceed a strategy's or the account's notional or order-rate sub-limit",
+            Self::TypedDataNotAllowed => "The typed-data domain or type is not on the signing allowlist",
+            Self::EvmCallNotAllowed => "The HyperEVM call target is not on the operator's allowlist",
+            Self::RiskCheckFailed => "A pre-sign risk check (margin usage, liquidation distance) failed",
+            Self::DrawdownReduceOnly => "The drawdown circuit breaker holds the account to reduce-only orders",
+            Self::CosignRejected => "The co-signature or co-signer change approval was invalid, or the request expired",
+            Self::InactivityRefused => "The agent signed for this user in the range, so inactivity can't be attested",
+            Self::SafeModeRestricted => "Re-attestation failed; only cancels and reduce-only orders are signed until it succeeds",
+            Self::KeyQuoteMismatch => "The agent key does not match the address bound in the attestation quote, so /agents is not served",
+            Self::SimulationFailed => "The action could not be simulated",
+            Self::OrderStateConflict => "The order's status or remaining size no longer matches the request's expectation",
+            Self::PartialFailure => "Some orders of a multi-order request were rejected; see results for each order",
+            Self::UpstreamRateLimited => "Hyperliquid rate-limited the request",
+            Self::UpstreamRejected => "Hyperliquid rejected the signed action",
+        }
+    }
+
+    /// Code for a response that carries nothing but its status
+    pub fn for_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Self::BadRequest,
+            StatusCode::UNAUTHORIZED => Self::Unauthorized,
+            StatusCode::FORBIDDEN => Self::Forbidden,
+            StatusCode::NOT_FOUND => Self::NotFound,
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited,
+            StatusCode::GATEWAY_TIMEOUT => Self::Timeout,
+            StatusCode::SERVICE_UNAVAILABLE => Self::ServiceUnavailable,
+            StatusCode::BAD_GATEWAY => Self::UpstreamUnavailable,
+            _ => Self::InternalError,
+        }
+    }
+
+    /// Best-effort code for an error message returned by Hyperliquid
+    pub fn for_upstream(message: &str) -> Self {
+        let message = message.to_lowercase();
+        if message.contains("429") || message.contains("rate limit") || message.contains("too many requests") {
+            Self::UpstreamRateLimited
+        } else if message.contains("does not exist") && (message.contains("wallet") || message.contains("user")) {
+            Self::AgentNotApproved
+        } else {
+            Self::UpstreamRejected
+        }
+    }
+}
+
+/// Exchange-style error body: `{"status": "err", "code": ..., "response": message}`
+pub fn err_body(code: ErrorCode, message: impl Into<Value>) -> Value {
+    serde_json::json!({
+        "status": "err",
+        "code": code,
+        "response": message.into()
+    })
+}
+
+/// Give error responses without a JSON body (bare statuses, extractor rejections) one with a code
+pub async fn fill_error_body(req: Request, next: Next) -> Response {
+    let response = next.run(req).await;
+    let status = response.status();
+    let is_json = response.headers().get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !(status.is_client_error() || status.is_server_error()) || is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    // Rejection bodies are short plain-text explanations; keep them as the message
+    let text = axum::body::to_bytes(body, MAX_TEXT_BODY).await
+        .ok()
+        .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+        .filter(|text| !text.is_empty());
+    let message = text.unwrap_or_else(|| status.canonical_reason().unwrap_or("Error").to_string());
+
+    let body = err_body(ErrorCode::for_status(status), message);
+    parts.headers.insert(header::CONTENT_TYPE, header::HeaderValue::from_static("application/json"));
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body.to_string()))
+}
+
+/// GET /errors - The error code catalogue, for generating client SDKs in any language
+pub async fn catalogue() -> Json<Value> {
+    let codes: Vec<Value> = ErrorCode::ALL.iter()
+        .map(|code| serde_json::json!({"code": code, "description": code.description()}))
+        .collect();
+    Json(serde_json::json!({"codes": codes}))
+}
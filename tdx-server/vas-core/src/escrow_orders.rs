@@ -0,0 +1,498 @@
+use alloy::primitives::B256;
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{info, warn, error};
+
+use crate::agents;
+use crate::auth;
+use crate::confirm;
+use crate::cosign::CosignRequest;
+use crate::drawdown;
+use crate::error_codes::{self, ErrorCode};
+use crate::notify::{Notification, NotificationKind};
+use crate::onboarding;
+use crate::rejections::{self, RejectionReason};
+use crate::safe_mode::SafeMode;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::signer::ActionRequest;
+use crate::universal_signing::{is_user_signed, signing_digest, ExchangeSignature};
+use crate::AppState;
+
+/// Held escrows one user may have at a time
+const MAX_HELD_PER_USER: usize = 50;
+/// Longest delay before a timed release
+const MAX_DELAY_MS: u64 = 30 * 24 * 3600 * 1000;
+/// Upstream accepts nonces up to a day ahead and two days behind its clock
+const NONCE_MAX_AHEAD_MS: u64 = 24 * 3600 * 1000;
+const NONCE_MAX_BEHIND_MS: u64 = 2 * 24 * 3600 * 1000;
+/// Margin kept inside the upstream nonce window for clock skew and submission latency
+const NONCE_MARGIN_MS: u64 = 5 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscrowStatus {
+    Held,
+    Released,
+    Failed,
+    Cancelled,
+    Expired,
+}
+
+/// An action signed at creation and held by the enclave until it is released
+#[derive(Debug, Clone, Serialize)]
+pub struct EscrowOrder {
+    pub id: String,
+    pub user_address: String,
+    /// The prepared action exactly as signed
+    pub action: Value,
+    pub nonce: u64,
+    /// Digest the agent signed; a release countersignature signs the same digest
+    pub digest: String,
+    /// Submitted automatically at this time
+    pub release_at_ms: Option<u64>,
+    /// Key whose signature over `digest` releases the escrow
+    pub release_signer: Option<String>,
+    pub status: EscrowStatus,
+    pub created_at_ms: u64,
+    /// Held escrows expire here, once the signed nonce would no longer be accepted upstream
+    pub expires_at_ms: u64,
+    pub released_at_ms: Option<u64>,
+    pub result: Option<Value>,
+    /// Signed /exchange body; never leaves the enclave before release
+    #[serde(skip)]
+    signed_payload: Value,
+}
+
+impl EscrowOrder {
+    /// Whether upstream would accept the signed nonce if submitted at `now_ms`
+    fn nonce_acceptable(&self, now_ms: u64) -> bool {
+        self.nonce <= now_ms + NONCE_MAX_AHEAD_MS - NONCE_MARGIN_MS
+    }
+}
+
+/// Escrowed actions by id
+#[derive(Debug, Default)]
+pub struct EscrowBook {
+    orders: HashMap<String, EscrowOrder>,
+}
+
+impl EscrowBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn held_for(&self, user_address: &str) -> usize {
+        self.orders.values()
+            .filter(|o| o.user_address == user_address && o.status == EscrowStatus::Held)
+            .count()
+    }
+
+    pub fn list(&self, user_address: &str) -> Vec<EscrowOrder> {
+        let mut orders: Vec<_> = self.orders.values().filter(|o| o.user_address == user_address).cloned().collect();
+        orders.sort_by_key(|o| o.created_at_ms);
+        orders
+    }
+
+    /// Cancel a held escrow owned by `user_address`; its signature is discarded unsubmitted
+    pub fn cancel(&mut self, id: &str, user_address: &str) -> bool {
+        match self.orders.get_mut(id) {
+            Some(order) if order.user_address == user_address && order.status == EscrowStatus::Held => {
+                order.status = EscrowStatus::Cancelled;
+                order.signed_payload = Value::Null;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Expire stale escrows and claim those whose release time has come.
+    /// Claimed escrows leave `Held` before submission so they are only submitted once.
+    /// In safe mode, escrows that could add risk stay held (until they expire).
+    fn claim_due(&mut self, now_ms: u64, safe_mode: &SafeMode) -> Vec<EscrowOrder> {
+        let mut due = Vec::new();
+        for order in self.orders.values_mut().filter(|o| o.status == EscrowStatus::Held) {
+            if order.expires_at_ms <= now_ms {
+                order.status = EscrowStatus::Expired;
+                order.signed_payload = Value::Null;
+            } else if order.release_at_ms.is_some_and(|at| at <= now_ms) && safe_mode.check_action(&order.action).is_ok() {
+                order.status = EscrowStatus::Released;
+                order.released_at_ms = Some(now_ms);
+                due.push(order.clone());
+            }
+        }
+        due
+    }
+
+    /// Claim a held escrow for an early release countersigned by its release signer
+    fn claim_countersigned(&mut self, id: &str, user_address: &str, signature: &ExchangeSignature, now_ms: u64, safe_mode: &SafeMode) -> Result<EscrowOrder, (ErrorCode, String)> {
+        let rejected = |reason: String| (ErrorCode::InvalidSignature, reason);
+        let order = self.orders.get_mut(id)
+            .filter(|o| o.user_address == user_address)
+            .ok_or_else(|| rejected("Escrow not found".to_string()))?;
+        if order.status != EscrowStatus::Held {
+            return Err(rejected(format!("Escrow is {:?}, not held", order.status)));
+        }
+        let release_signer = order.release_signer.as_deref().ok_or_else(|| rejected("This escrow has no release signer".to_string()))?;
+        if !order.nonce_acceptable(now_ms) {
+            return Err(rejected("The release time is more than a day away; upstream would refuse the signed nonce until then".to_string()));
+        }
+
+        let digest: B256 = order.digest.parse().map_err(|_| rejected("Corrupt escrow digest".to_string()))?;
+        let recovered = signature.recover_address(&digest)
+            .map_err(|e| rejected(format!("Invalid release signature: {}", e)))?;
+        if recovered != release_signer {
+            return Err(rejected(format!("Release signature is from {} (expected {})", recovered, release_signer)));
+        }
+        // The signature was made before safe mode, so the signer's own check never sees it
+        safe_mode.check_action(&order.action).map_err(|reason| (ErrorCode::SafeModeRestricted, reason))?;
+
+        order.status = EscrowStatus::Released;
+        order.released_at_ms = Some(now_ms);
+        Ok(order.clone())
+    }
+
+    fn record_result(&mut self, id: &str, result: Value, ok: bool) {
+        if let Some(order) = self.orders.get_mut(id) {
+            if !ok {
+                order.status = EscrowStatus::Failed;
+            }
+            order.result = Some(result);
+            order.signed_payload = Value::Null;
+        }
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> usize {
+        let before = self.orders.len();
+        self.orders.retain(|_, order| !order.user_address.eq_ignore_ascii_case(user_address));
+        before - self.orders.len()
+    }
+}
+
+/// Submit escrows as their release times arrive
+pub fn spawn_releaser(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            ticker.tick().await;
+            // A fenced standby leaves releases to the instance that owns the nonces
+            if state.ha.check().is_err() {
+                continue;
+            }
+
+            let due = state.escrow_orders.write().await.claim_due(now_ms(), &state.safe_mode);
+            for order in due {
+                release(&state, order).await;
+            }
+        }
+    });
+}
+
+/// Forward the held signature upstream and record the outcome
+async fn release(state: &AppState, order: EscrowOrder) {
+    info!("🔓 Releasing escrow {} for {}", order.id, order.user_address);
+    let (result, ok) = match state.proxy.proxy_exchange_request(&order.signed_payload).await {
+        Ok(response) => {
+            let ok = response.get("status").and_then(|s| s.as_str()) != Some("err");
+            (response, ok)
+        }
+        Err(e) => {
+            error!("❌ Escrow {} submission failed: {}", order.id, e);
+            (error_codes::err_body(ErrorCode::UpstreamUnavailable, e.to_string()), false)
+        }
+    };
+
+    state.notifier.notify(Notification::new(
+        NotificationKind::Alert,
+        Some(&order.user_address),
+        if ok { "Escrowed action released" } else { "Escrowed action failed on release" },
+        serde_json::json!({"escrow_id": order.id, "result": result}),
+    ));
+    state.escrow_orders.write().await.record_result(&order.id, result, ok);
+}
+
+/// Escrow parameters: a release time, a release signer, or both
+#[derive(Debug, Deserialize)]
+pub struct CreateEscrowRequest {
+    pub action: Value,
+    #[serde(rename = "vaultAddress")]
+    pub vault_address: Option<String>,
+    pub release_at_ms: Option<u64>,
+    pub release_signer: Option<String>,
+}
+
+/// POST /orders/escrow - Validate and sign an action now, and hold it until its release
+pub async fn create_escrow(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateEscrowRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let (api_key, user_address) = session_user(&state, &headers).await?;
+    let bad_request = |reason: &str| Ok(Json(error_codes::err_body(ErrorCode::BadRequest, reason)));
+
+    let action_type = payload.action.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+    if action_type.is_empty() || action_type == "approveAgent" || is_user_signed(&payload.action) {
+        return bad_request("Only L1 actions signed by the agent can be escrowed");
+    }
+    let release_signer = match &payload.release_signer {
+        Some(address) if address.parse::<alloy::primitives::Address>().is_err() => return bad_request("release_signer must be an address"),
+        other => other.as_ref().map(|a| a.to_lowercase()),
+    };
+    let created_at_ms = now_ms();
+    if let Some(at) = payload.release_at_ms {
+        if at <= created_at_ms || at > created_at_ms + MAX_DELAY_MS {
+            return bad_request("release_at_ms must be in the future and within 30 days");
+        }
+    } else if release_signer.is_none() {
+        return bad_request("Give release_at_ms, release_signer or both");
+    }
+
+    // /exchange's gates, checked now because the signature is made now. Sessions in co-sign
+    // mode and orders above the confirmation threshold need a hold escrow can't give them.
+    let session = state.session_manager.with_session(&api_key, |session| (session.onboarding, session.cosigner_address.is_some()));
+    if let Some((current, cosigned)) = session {
+        if cosigned {
+            return unsupported("Sessions in co-sign mode");
+        }
+        if !current.can_trade() {
+            let refreshed = onboarding::refresh(&state, &api_key).await.unwrap_or(current);
+            if !refreshed.can_trade() {
+                return Ok(Json(onboarding::not_ready_response(refreshed)));
+            }
+        }
+    }
+    let required_scope = agents::required_scope(action_type);
+    if !auth::api_key_has_scope(&state, &api_key, required_scope).await {
+        return Ok(Json(error_codes::err_body(ErrorCode::ScopeNotAllowed, format!("API key is not authorized for the '{}' scope", required_scope))));
+    }
+    if let Err(body) = crate::check_asset_allowlist(&state, &api_key, &payload.action).await {
+        return Ok(Json(body));
+    }
+    if let Err(body) = rejections::check(&state, &payload.action, Some(&user_address)).await {
+        return Ok(Json(body));
+    }
+    if let Err(reason) = state.safe_mode.check_action(&payload.action) {
+        return Ok(Json(error_codes::err_body(ErrorCode::SafeModeRestricted, reason)));
+    }
+    if let Err(violation) = state.policy.read().await.evaluate(&payload.action) {
+        state.rejections.record(RejectionReason::PolicyDenial, &payload.action, Some(&user_address), &violation.message);
+        return Ok(Json(violation.to_response()));
+    }
+    if action_type == "order" {
+        if let Err(reason) = drawdown::check_reduce_only(&state, &user_address, &payload.action).await {
+            return Ok(Json(error_codes::err_body(ErrorCode::DrawdownReduceOnly, reason)));
+        }
+        let mut warnings = Vec::new();
+        if let Err(reason) = crate::run_risk_checks(&state, &user_address, &payload.action, &mut warnings).await {
+            return Ok(Json(error_codes::err_body(ErrorCode::RiskCheckFailed, reason)));
+        }
+        if let Some(threshold) = state.config.confirm_notional_threshold {
+            if confirm::order_notional(&payload.action) > threshold {
+                return unsupported("Orders above the confirmation threshold");
+            }
+        }
+    }
+    if state.escrow_orders.read().await.held_for(&user_address) >= MAX_HELD_PER_USER {
+        return Ok(Json(error_codes::err_body(ErrorCode::LimitExceeded, format!("At most {} held escrows per user", MAX_HELD_PER_USER))));
+    }
+    // Escrowed orders count against the strategy's budget when they are signed
+    if action_type == "order" {
+        if let Err(violation) = state.strategy_limits.write().await.charge_request(&headers, &user_address, &payload.action, created_at_ms) {
+            return Ok(Json(violation.to_response()));
+        }
+    }
+
+    // Timed escrows sign with the release time as nonce, so the signature is valid exactly when due
+    let nonce = payload.release_at_ms.unwrap_or(created_at_ms);
+    let expires_at_ms = nonce + NONCE_MAX_BEHIND_MS - NONCE_MARGIN_MS;
+    let is_mainnet = state.config.is_mainnet();
+    let request = ActionRequest {
+        action: payload.action,
+        nonce,
+        vault_address: payload.vault_address,
+        is_mainnet,
+        user_address: Some(user_address.clone()),
+    };
+    let signed_payload = match state.signer.sign_and_hold(request).await {
+        Ok(signed_payload) => signed_payload,
+        Err(e) => {
+            warn!("❌ Escrow signing failed: {}", e);
+            return bad_request(&e.to_string());
+        }
+    };
+    let action = signed_payload["action"].clone();
+    let vault_address = signed_payload.get("vaultAddress").and_then(|v| v.as_str());
+    let digest = signing_digest(&action, nonce, vault_address, is_mainnet).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let order = EscrowOrder {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_address,
+        action,
+        nonce,
+        digest: format!("{:?}", digest),
+        release_at_ms: payload.release_at_ms,
+        release_signer,
+        status: EscrowStatus::Held,
+        created_at_ms,
+        expires_at_ms,
+        released_at_ms: None,
+        result: None,
+        signed_payload,
+    };
+    state.escrow_orders.write().await.orders.insert(order.id.clone(), order.clone());
+
+    info!("🔒 Escrow {} held for {} (release at {:?}, signer {:?})", order.id, order.user_address, order.release_at_ms, order.release_signer);
+    Ok(Json(serde_json::json!({"status": "ok", "response": order})))
+}
+
+/// GET /orders/escrow - The caller's escrows and their outcomes
+pub async fn list_escrow(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(serde_json::json!({
+        "escrows": state.escrow_orders.read().await.list(&user_address.to_lowercase())
+    })))
+}
+
+/// DELETE /orders/escrow/:id - Cancel a held escrow before it is released
+pub async fn cancel_escrow(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let (_, user_address) = session_user(&state, &headers).await?;
+    if !state.escrow_orders.write().await.cancel(&id, &user_address) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("🔒 Escrow {} cancelled", id);
+    Ok(Json(serde_json::json!({"status": "ok", "response": "cancelled"})))
+}
+
+/// POST /orders/escrow/:id/release - Release early with the release signer's signature over `digest`
+pub async fn release_escrow(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<CosignRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let (_, user_address) = session_user(&state, &headers).await?;
+    state.ha.check().map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let signature = ExchangeSignature {
+        r: payload.signature.r,
+        s: payload.signature.s,
+        v: payload.signature.v,
+    };
+    let claimed = state.escrow_orders.write().await.claim_countersigned(&id, &user_address, &signature, now_ms(), &state.safe_mode);
+    let order = match claimed {
+        Ok(order) => order,
+        Err((code, reason)) => {
+            warn!("❌ Escrow {} release rejected: {}", id, reason);
+            return Ok(Json(error_codes::err_body(code, reason)));
+        }
+    };
+
+    let id = order.id.clone();
+    release(&state, order).await;
+    let order = state.escrow_orders.read().await.orders.get(&id).cloned();
+    Ok(Json(serde_json::json!({"status": "ok", "response": order})))
+}
+
+fn unsupported(what: &str) -> Result<Json<Value>, StatusCode> {
+    Ok(Json(error_codes::err_body(ErrorCode::BadRequest, format!("{} can't be escrowed; send them to /exchange", what))))
+}
+
+/// Trading session behind the request; share tokens can't manage escrows
+async fn session_user(state: &AppState, headers: &HeaderMap) -> Result<(String, String), StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok((api_key.to_string(), user_address.to_lowercase()))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::universal_signing::sign_hash_with_key;
+    use secp256k1::SecretKey;
+    use serde_json::json;
+
+    const USER: &str = "0x1111111111111111111111111111111111111111";
+    const NOW: u64 = 1_700_000_000_000;
+
+    fn held(id: &str, reduce_only: bool, release_signer: Option<String>) -> EscrowOrder {
+        EscrowOrder {
+            id: id.to_string(),
+            user_address: USER.to_string(),
+            action: json!({"type": "order", "orders": [{"a": 0, "b": true, "p": "100", "s": "1", "r": reduce_only, "t": {"limit": {"tif": "Gtc"}}}], "grouping": "na"}),
+            nonce: NOW - 1000,
+            digest: format!("{:?}", B256::repeat_byte(7)),
+            release_at_ms: Some(NOW - 1000),
+            release_signer,
+            status: EscrowStatus::Held,
+            created_at_ms: NOW - 10_000,
+            expires_at_ms: NOW + NONCE_MAX_BEHIND_MS,
+            released_at_ms: None,
+            result: None,
+            signed_payload: json!({"signature": {}}),
+        }
+    }
+
+    fn book(orders: Vec<EscrowOrder>) -> EscrowBook {
+        EscrowBook { orders: orders.into_iter().map(|o| (o.id.clone(), o)).collect() }
+    }
+
+    #[test]
+    fn test_safe_mode_holds_timed_escrows_that_add_risk() {
+        let safe_mode = SafeMode::new();
+        safe_mode.enter("re-attestation failed".to_string());
+        let mut book = book(vec![held("open", false, None), held("reduce", true, None)]);
+
+        let due = book.claim_due(NOW, &safe_mode);
+        assert_eq!(due.iter().map(|o| o.id.as_str()).collect::<Vec<_>>(), ["reduce"]);
+        assert_eq!(book.orders["open"].status, EscrowStatus::Held);
+
+        // Released once safe mode clears
+        safe_mode.clear();
+        let due = book.claim_due(NOW, &safe_mode);
+        assert_eq!(due.iter().map(|o| o.id.as_str()).collect::<Vec<_>>(), ["open"]);
+    }
+
+    #[test]
+    fn test_safe_mode_refuses_countersigned_release_that_adds_risk() {
+        let key = SecretKey::from_slice(&[8u8; 32]).unwrap();
+        let signer = crate::address::secret_key_to_address(&key);
+        let signature = sign_hash_with_key(&key, &B256::repeat_byte(7));
+        let safe_mode = SafeMode::new();
+        safe_mode.enter("re-attestation failed".to_string());
+        let mut book = book(vec![held("open", false, Some(signer))]);
+
+        let (code, _) = book.claim_countersigned("open", USER, &signature, NOW, &safe_mode).unwrap_err();
+        assert_eq!(code, ErrorCode::SafeModeRestricted);
+        assert_eq!(book.orders["open"].status, EscrowStatus::Held);
+
+        safe_mode.clear();
+        assert_eq!(book.claim_countersigned("open", USER, &signature, NOW, &safe_mode).unwrap().status, EscrowStatus::Released);
+    }
+}
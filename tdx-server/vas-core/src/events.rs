@@ -0,0 +1,224 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, error};
+
+use crate::auth;
+use crate::jsonl;
+use crate::ws_feed::WsFeed;
+use crate::AppState;
+
+/// Kind tag for signed /exchange responses
+pub const EVENT_EXCHANGE_RESPONSE: &str = "exchange_response";
+/// Kind tag for fills pushed by the upstream userFills WS subscription
+pub const EVENT_WS_USER_FILLS: &str = "ws_user_fills";
+/// Kind tag for historical fills imported by POST /me/backfill, shaped like userFills pushes
+pub const EVENT_BACKFILL_FILLS: &str = "backfill_fills";
+/// Kind tag for historical order statuses imported by POST /me/backfill
+pub const EVENT_BACKFILL_ORDERS: &str = "backfill_orders";
+/// Kind tag for SIWE logins
+pub const EVENT_SESSION_LOGIN: &str = "session_login";
+
+/// Maximum events returned per page
+const MAX_PAGE_SIZE: usize = 500;
+
+/// One persisted event; `seq` is the cursor clients resume from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEvent {
+    pub seq: u64,
+    pub user_address: String,
+    pub kind: String,
+    pub timestamp_ms: u64,
+    pub payload: Value,
+}
+
+/// Append-only per-user event log backed by a JSON-lines file
+#[derive(Debug)]
+pub struct EventStore {
+    events: Vec<StoredEvent>,
+    next_seq: u64,
+    path: Option<PathBuf>,
+    /// Events removed by retention since startup
+    pruned_total: u64,
+}
+
+impl EventStore {
+    /// Open the store, replaying any events already persisted at `path`
+    pub fn open(path: Option<PathBuf>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let events: Vec<StoredEvent> = match &path {
+            Some(path) => jsonl::load(path)?,
+            None => Vec::new(),
+        };
+        let next_seq = events.last().map(|e| e.seq + 1).unwrap_or(1);
+
+        info!("📚 Event store opened with {} events", events.len());
+
+        Ok(Self { events, next_seq, path, pruned_total: 0 })
+    }
+
+    /// Record an event for a user and return its cursor
+    pub fn append(&mut self, user_address: &str, kind: &str, payload: Value) -> u64 {
+        let event = StoredEvent {
+            seq: self.next_seq,
+            user_address: user_address.to_lowercase(),
+            kind: kind.to_string(),
+            timestamp_ms: now_ms(),
+            payload,
+        };
+        self.next_seq += 1;
+
+        if let Some(path) = &self.path {
+            if let Err(e) = jsonl::append(path, &event) {
+                error!("❌ Failed to persist event {}: {}", event.seq, e);
+            }
+        }
+
+        let seq = event.seq;
+        self.events.push(event);
+        seq
+    }
+
+    /// Drop events older than `cutoff_ms`, keeping the newest so cursors never restart
+    pub fn prune_before(&mut self, cutoff_ms: u64) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let count = self.events.iter()
+            .take(self.events.len().saturating_sub(1))
+            .take_while(|e| e.timestamp_ms < cutoff_ms)
+            .count();
+        if count == 0 {
+            return Ok(0);
+        }
+
+        if let Some(path) = &self.path {
+            jsonl::rewrite(path, &self.events[count..])?;
+        }
+        self.events.drain(..count);
+        self.pruned_total += count as u64;
+        info!("📚 Pruned {} events older than {}", count, cutoff_ms);
+
+        Ok(count)
+    }
+
+    /// Retained events, events pruned since startup and on-disk bytes
+    pub fn storage_stats(&self) -> (usize, u64, u64) {
+        (self.events.len(), self.pruned_total, jsonl::file_size(self.path.as_deref()))
+    }
+
+    /// A user's events of one kind recorded at or after `from_ms`
+    pub fn of_kind_since<'a>(&'a self, user_address: &str, kind: &'a str, from_ms: u64) -> impl Iterator<Item = &'a StoredEvent> {
+        let user_address = user_address.to_lowercase();
+        let start = self.events.partition_point(|e| e.timestamp_ms < from_ms);
+        self.events[start..].iter().filter(move |e| e.user_address == user_address && e.kind == kind)
+    }
+
+    /// Events for `user_address` strictly after cursor `since`, oldest first
+    pub fn page(&self, user_address: &str, since: u64, limit: usize) -> Vec<StoredEvent> {
+        let user_address = user_address.to_lowercase();
+        let start = self.events.partition_point(|e| e.seq <= since);
+
+        self.events[start..]
+            .iter()
+            .filter(|e| e.user_address == user_address)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Every event held for a user, oldest first
+    pub fn for_user(&self, user_address: &str) -> Vec<&StoredEvent> {
+        let user_address = user_address.to_lowercase();
+        self.events.iter().filter(|e| e.user_address == user_address).collect()
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let user_address = user_address.to_lowercase();
+        let before = self.events.len();
+        self.events.retain(|e| e.user_address != user_address);
+        let removed = before - self.events.len();
+        if removed > 0 {
+            if let Some(path) = &self.path {
+                jsonl::rewrite(path, &self.events)?;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Query parameters for GET /events
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    #[serde(default)]
+    pub since: u64,
+    pub limit: Option<usize>,
+}
+
+/// GET /events?since=<cursor> - Replay the caller's exchange responses and user events
+pub async fn get_events(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<EventsQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let limit = query.limit.unwrap_or(100).clamp(1, MAX_PAGE_SIZE);
+    let events = state.event_store.read().await.page(&user_address, query.since, limit);
+    let next_cursor = events.last().map(|e| e.seq).unwrap_or(query.since);
+
+    info!("📚 Replaying {} events for {} since {}", events.len(), user_address, query.since);
+
+    Ok(Json(serde_json::json!({
+        "events": events,
+        "next_cursor": next_cursor,
+        "has_more": events.len() == limit
+    })))
+}
+
+/// Record live fills from the WS feed into the event store
+pub fn spawn_ws_recorder(feed: &WsFeed, store: Arc<RwLock<EventStore>>) {
+    let mut messages = feed.listen();
+
+    tokio::spawn(async move {
+        loop {
+            let message = match messages.recv().await {
+                Ok(message) => message,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    error!("❌ Event recorder lagged, {} WS messages dropped", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+
+            if message.get("channel").and_then(|c| c.as_str()) != Some("userFills") {
+                continue;
+            }
+            let data = match message.get("data") {
+                Some(data) => data,
+                None => continue,
+            };
+            // Snapshots repeat history on every (re)subscribe; only live pushes are new
+            if data.get("isSnapshot").and_then(|s| s.as_bool()).unwrap_or(false) {
+                continue;
+            }
+            if let Some(user) = data.get("user").and_then(|u| u.as_str()) {
+                store.write().await.append(user, EVENT_WS_USER_FILLS, data.clone());
+            }
+        }
+    });
+}
@@ -0,0 +1,249 @@
+use alloy::consensus::{SignableTransaction, TxEip1559, TxEnvelope};
+use alloy::eips::eip2718::Encodable2718;
+use alloy::primitives::{Address, Bytes, Signature, TxKind, U256};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{info, warn, error};
+
+use crate::agents::SCOPE_EVM;
+use crate::audit::AUDIT_EVM_TRANSACTION;
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::preset_tdx::PresetTDXData;
+use crate::universal_signing::ExchangeSignature;
+use crate::AppState;
+
+/// HyperEVM chain ids
+const HYPEREVM_MAINNET_CHAIN_ID: u64 = 999;
+const HYPEREVM_TESTNET_CHAIN_ID: u64 = 998;
+
+/// Contract/method pairs the agent may call on HyperEVM.
+///
+/// Entries are `0xcontract:0xselector`, or `0xcontract:*` for any method
+/// (including plain value transfers).
+#[derive(Debug, Clone, Default)]
+pub struct EvmPolicy {
+    rules: Vec<(Address, Option<[u8; 4]>)>,
+}
+
+impl EvmPolicy {
+    pub fn parse(entries: &[String]) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for entry in entries {
+            let (contract, method) = entry.split_once(':')
+                .ok_or_else(|| format!("Invalid EVM allowlist entry (want contract:selector): {}", entry))?;
+            let contract: Address = contract.parse()
+                .map_err(|_| format!("Invalid contract address in EVM allowlist: {}", contract))?;
+            let selector = match method {
+                "*" => None,
+                selector => {
+                    let bytes = hex::decode(selector.trim_start_matches("0x"))
+                        .map_err(|_| format!("Invalid selector in EVM allowlist: {}", selector))?;
+                    Some(<[u8; 4]>::try_from(bytes.as_slice())
+                        .map_err(|_| format!("Selector must be 4 bytes: {}", selector))?)
+                }
+            };
+            rules.push((contract, selector));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Whether a call to `to` with `input` is allowed
+    pub fn allows(&self, to: &Address, input: &[u8]) -> bool {
+        self.rules.iter().any(|(contract, selector)| {
+            contract == to && match selector {
+                None => true,
+                Some(selector) => input.len() >= 4 && &input[..4] == selector,
+            }
+        })
+    }
+}
+
+/// HyperEVM transaction to sign; omitted nonce and fees are filled from the RPC
+#[derive(Debug, Deserialize)]
+pub struct EvmTransactionRequest {
+    pub to: String,
+    #[serde(default)]
+    pub data: Option<String>,
+    /// Wei, decimal string
+    #[serde(default)]
+    pub value: Option<String>,
+    pub gas_limit: u64,
+    pub nonce: Option<u64>,
+    pub max_fee_per_gas: Option<u128>,
+    pub max_priority_fee_per_gas: Option<u128>,
+    /// Submit via eth_sendRawTransaction after signing
+    #[serde(default)]
+    pub broadcast: bool,
+}
+
+/// POST /evm/sign-transaction - Sign (and optionally broadcast) an allowlisted HyperEVM transaction
+pub async fn sign_transaction(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<EvmTransactionRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if !auth::api_key_has_scope(&state, api_key, SCOPE_EVM).await {
+        return Ok(err_response(ErrorCode::ScopeNotAllowed, format!("API key is not authorized for the '{}' scope", SCOPE_EVM)));
+    }
+
+    let policy = match EvmPolicy::parse(&state.config.evm_allowlist) {
+        Ok(policy) => policy,
+        Err(e) => {
+            error!("❌ {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let to: Address = payload.to.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let input = match &payload.data {
+        Some(data) => Bytes::from(hex::decode(data.trim_start_matches("0x")).map_err(|_| StatusCode::BAD_REQUEST)?),
+        None => Bytes::new(),
+    };
+    let value = match &payload.value {
+        Some(value) => value.parse::<U256>().map_err(|_| StatusCode::BAD_REQUEST)?,
+        None => U256::ZERO,
+    };
+
+    if !policy.allows(&to, &input) {
+        warn!("❌ EVM transaction to {} (selector {}) not in allowlist", to, hex::encode(input.get(..4).unwrap_or_default()));
+        return Ok(err_response(ErrorCode::EvmCallNotAllowed, format!("Call to {} is not permitted by the EVM allowlist", to)));
+    }
+
+    if let Err(reason) = state.safe_mode.check_digest(AUDIT_EVM_TRANSACTION) {
+        return Ok(err_response(ErrorCode::SafeModeRestricted, reason));
+    }
+
+    let is_mainnet = state.config.is_mainnet();
+    let chain_id = state.config.hyperevm_chain_id.unwrap_or(if is_mainnet {
+        HYPEREVM_MAINNET_CHAIN_ID
+    } else {
+        HYPEREVM_TESTNET_CHAIN_ID
+    });
+
+    let rpc = state.config.hyperevm_rpc_url.as_deref();
+    let agent_address = PresetTDXData::get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?.agent_address.clone();
+
+    let tx = match build_transaction(rpc, &agent_address, chain_id, to, input, value, &payload).await {
+        Ok(tx) => tx,
+        Err(e) => return Ok(err_response(ErrorCode::UpstreamUnavailable, e.to_string())),
+    };
+
+    let user_address = auth::user_address_for_api_key(&state, api_key).await;
+    let subject = serde_json::json!({
+        "chain_id": chain_id,
+        "to": format!("{:?}", to),
+        "data": format!("0x{}", hex::encode(&tx.input)),
+        "value": tx.value.to_string(),
+        "nonce": tx.nonce
+    });
+    let signature = state.signer.sign_digest(tx.signature_hash(), user_address, AUDIT_EVM_TRANSACTION, subject).await.map_err(|e| {
+        error!("❌ EVM signing failed: {:?}", e);
+        StatusCode::BAD_GATEWAY
+    })?;
+    let signed = tx.into_signed(to_alloy_signature(&signature).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?);
+    let tx_hash = format!("{:?}", signed.hash());
+    let raw = format!("0x{}", hex::encode(TxEnvelope::from(signed).encoded_2718()));
+
+    info!("⛓️ Signed HyperEVM transaction {} to {}", tx_hash, to);
+
+    let mut response = serde_json::json!({
+        "status": "ok",
+        "response": {
+            "tx_hash": tx_hash,
+            "raw_transaction": raw,
+            "from": agent_address,
+            "chain_id": chain_id
+        }
+    });
+
+    if payload.broadcast {
+        let rpc = match rpc {
+            Some(rpc) => rpc,
+            None => return Ok(err_response(ErrorCode::BadRequest, "Broadcast requested but HYPEREVM_RPC_URL is not configured".to_string())),
+        };
+        match rpc_call(rpc, "eth_sendRawTransaction", serde_json::json!([raw])).await {
+            Ok(result) => {
+                info!("📡 Broadcast HyperEVM transaction {}", tx_hash);
+                response["response"]["broadcast"] = result;
+            }
+            Err(e) => {
+                error!("❌ HyperEVM broadcast failed: {}", e);
+                response["response"]["broadcast_error"] = Value::String(e.to_string());
+            }
+        }
+    }
+
+    Ok(Json(response))
+}
+
+async fn build_transaction(
+    rpc: Option<&str>,
+    from: &str,
+    chain_id: u64,
+    to: Address,
+    input: Bytes,
+    value: U256,
+    payload: &EvmTransactionRequest,
+) -> Result<TxEip1559, Box<dyn std::error::Error + Send + Sync>> {
+    let nonce = match (payload.nonce, rpc) {
+        (Some(nonce), _) => nonce,
+        (None, Some(rpc)) => parse_quantity(&rpc_call(rpc, "eth_getTransactionCount", serde_json::json!([from, "pending"])).await?)?,
+        (None, None) => return Err("nonce is required when HYPEREVM_RPC_URL is not configured".into()),
+    };
+    let max_fee_per_gas = match (payload.max_fee_per_gas, rpc) {
+        (Some(fee), _) => fee,
+        (None, Some(rpc)) => parse_quantity(&rpc_call(rpc, "eth_gasPrice", serde_json::json!([])).await?)? as u128,
+        (None, None) => return Err("max_fee_per_gas is required when HYPEREVM_RPC_URL is not configured".into()),
+    };
+
+    Ok(TxEip1559 {
+        chain_id,
+        nonce,
+        gas_limit: payload.gas_limit,
+        max_fee_per_gas,
+        max_priority_fee_per_gas: payload.max_priority_fee_per_gas.unwrap_or(0),
+        to: TxKind::Call(to),
+        value,
+        access_list: Default::default(),
+        input,
+    })
+}
+
+fn to_alloy_signature(signature: &ExchangeSignature) -> Result<Signature, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(Signature::new(
+        signature.r.parse()?,
+        signature.s.parse()?,
+        matches!(signature.v, 1 | 28),
+    ))
+}
+
+pub(crate) async fn rpc_call(url: &str, method: &str, params: Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+    let response: Value = reqwest::Client::new()
+        .post(url)
+        .json(&serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}))
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(error) = response.get("error") {
+        return Err(format!("{} failed: {}", method, error).into());
+    }
+    response.get("result").cloned().ok_or_else(|| format!("{} returned no result", method).into())
+}
+
+fn parse_quantity(value: &Value) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    let hex = value.as_str().ok_or("Expected hex quantity")?;
+    Ok(u64::from_str_radix(hex.trim_start_matches("0x"), 16)?)
+}
+
+fn err_response(code: ErrorCode, reason: String) -> Json<Value> {
+    Json(error_codes::err_body(code, reason))
+}
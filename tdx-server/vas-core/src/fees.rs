@@ -0,0 +1,140 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, error};
+
+use crate::auth;
+use crate::market::parse_number;
+use crate::AppState;
+
+/// Base-tier perp taker fee rate (fraction of notional)
+pub const BASE_TAKER_FEE_RATE: f64 = 0.00045;
+/// Base-tier perp maker fee rate (fraction of notional)
+pub const BASE_MAKER_FEE_RATE: f64 = 0.00015;
+
+/// Days of volume Hyperliquid uses to pick the fee tier
+const VOLUME_WINDOW_DAYS: usize = 14;
+
+/// Fee rates applied to an order, as fractions of notional
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FeeRates {
+    pub taker: f64,
+    pub maker: f64,
+}
+
+impl FeeRates {
+    pub const BASE: Self = Self { taker: BASE_TAKER_FEE_RATE, maker: BASE_MAKER_FEE_RATE };
+}
+
+/// A user's fee standing derived from `userFees`
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeSummary {
+    pub user_address: String,
+    /// Taker + maker volume over the last 14 days
+    pub volume_14d: f64,
+    /// VIP tier index (0 = base tier)
+    pub fee_tier: usize,
+    /// Effective rates after tier, staking and referral discounts
+    pub rates: FeeRates,
+    /// 14-day volume needed for the next tier, if there is one
+    pub next_tier_volume: Option<f64>,
+}
+
+impl FeeSummary {
+    pub fn from_user_fees(user_address: &str, fees: &Value) -> Self {
+        let daily = fees.get("dailyUserVlm").and_then(|d| d.as_array()).cloned().unwrap_or_default();
+        let volume_14d: f64 = daily.iter()
+            .rev()
+            .take(VOLUME_WINDOW_DAYS)
+            .map(|day| parse_number(day.get("userCross")).unwrap_or(0.0) + parse_number(day.get("userAdd")).unwrap_or(0.0))
+            .sum();
+
+        let cutoffs: Vec<f64> = fees.pointer("/feeSchedule/tiers/vip")
+            .and_then(|t| t.as_array())
+            .map(|tiers| tiers.iter().filter_map(|t| parse_number(t.get("ntlCutoff"))).collect())
+            .unwrap_or_default();
+        let fee_tier = cutoffs.iter().take_while(|cutoff| volume_14d >= **cutoff).count();
+
+        Self {
+            user_address: user_address.to_string(),
+            volume_14d,
+            fee_tier,
+            rates: FeeRates {
+                taker: parse_number(fees.get("userCrossRate")).unwrap_or(BASE_TAKER_FEE_RATE),
+                maker: parse_number(fees.get("userAddRate")).unwrap_or(BASE_MAKER_FEE_RATE),
+            },
+            next_tier_volume: cutoffs.get(fee_tier).copied(),
+        }
+    }
+}
+
+/// Estimated fees for an order action, assuming it fills in full
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeEstimate {
+    pub rates: FeeRates,
+    /// Exchange fees; non-ALO orders are assumed to take liquidity
+    pub exchange_fee: f64,
+    pub builder_fee: f64,
+    pub total: f64,
+}
+
+/// Estimate fees for an `order` action at the given rates (None for other actions)
+pub fn estimate_order_fees(action: &Value, rates: FeeRates) -> Option<FeeEstimate> {
+    let orders = action.get("orders")?.as_array()?;
+
+    let mut exchange_fee = 0.0;
+    let mut total_notional = 0.0;
+    for order in orders {
+        let notional = parse_number(order.get("p")).unwrap_or(0.0) * parse_number(order.get("s")).unwrap_or(0.0);
+        let is_maker = order.pointer("/t/limit/tif").and_then(|t| t.as_str()) == Some("Alo");
+        total_notional += notional;
+        exchange_fee += notional * if is_maker { rates.maker } else { rates.taker };
+    }
+
+    // Builder fee `f` is in tenths of a basis point
+    let builder_fee = action.pointer("/builder/f").and_then(|f| f.as_u64())
+        .map(|f| total_notional * f as f64 / 100_000.0)
+        .unwrap_or(0.0);
+
+    Some(FeeEstimate {
+        rates,
+        exchange_fee,
+        builder_fee,
+        total: exchange_fee + builder_fee,
+    })
+}
+
+/// The user's effective fee rates, falling back to the base tier when `userFees` is unavailable
+pub async fn rates_for(state: &AppState, user_address: &str) -> FeeRates {
+    match state.market.user_fees(user_address).await {
+        Ok(fees) => FeeSummary::from_user_fees(user_address, &fees).rates,
+        Err(e) => {
+            error!("❌ Failed to fetch userFees, using base rates: {:?}", e);
+            FeeRates::BASE
+        }
+    }
+}
+
+/// GET /me/fees - 14-day volume, fee tier and effective fee rates
+pub async fn me_fees(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<FeeSummary>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    info!("💸 Fee summary requested for {}", user_address);
+
+    let fees = state.market.user_fees(&user_address).await.map_err(|e| {
+        error!("❌ Failed to fetch userFees: {:?}", e);
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    Ok(Json(FeeSummary::from_user_fees(&user_address, &fees)))
+}
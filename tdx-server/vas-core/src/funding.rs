@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::error;
+
+use crate::market::parse_number;
+use crate::AppState;
+
+/// Venue name Hyperliquid uses for its own perps in `predictedFundings`
+const HL_VENUE: &str = "HlPerp";
+/// Hyperliquid settles funding every hour
+const FUNDING_INTERVAL_SECS: u64 = 3600;
+
+/// Current and predicted funding for one perp
+#[derive(Debug, Clone, Serialize)]
+pub struct FundingInfo {
+    pub asset: u64,
+    pub coin: String,
+    /// Hourly rate accruing over the current interval
+    pub current_rate: Option<f64>,
+    /// Hyperliquid's predicted rate for the next settlement
+    pub predicted_rate: Option<f64>,
+    pub next_funding_time: Option<u64>,
+}
+
+/// Funding for every perp, joined from `metaAndAssetCtxs` and `predictedFundings`
+pub async fn all_funding(state: &AppState) -> Result<Vec<FundingInfo>, Box<dyn std::error::Error + Send + Sync>> {
+    let meta_and_ctxs = state.market.meta_and_asset_ctxs().await?;
+    let predicted = state.market.predicted_fundings().await?;
+
+    let universe = meta_and_ctxs.get(0).and_then(|m| m.get("universe")).and_then(|u| u.as_array());
+    let ctxs = meta_and_ctxs.get(1).and_then(|c| c.as_array());
+
+    let mut funding = Vec::new();
+    for (index, meta) in universe.into_iter().flatten().enumerate() {
+        let Some(coin) = meta.get("name").and_then(|n| n.as_str()) else { continue };
+        let (predicted_rate, next_funding_time) = predicted_for(&predicted, coin);
+        funding.push(FundingInfo {
+            asset: index as u64,
+            coin: coin.to_string(),
+            current_rate: ctxs.and_then(|c| c.get(index)).and_then(|c| parse_number(c.get("funding"))),
+            predicted_rate,
+            next_funding_time,
+        });
+    }
+    Ok(funding)
+}
+
+/// Hyperliquid's own entry for a coin in a `predictedFundings` response:
+/// `[[coin, [[venue, {fundingRate, nextFundingTime}], ...]], ...]`
+fn predicted_for(predicted: &Value, coin: &str) -> (Option<f64>, Option<u64>) {
+    let venue = predicted.as_array()
+        .and_then(|coins| coins.iter().find(|entry| entry.get(0).and_then(|c| c.as_str()) == Some(coin)))
+        .and_then(|entry| entry.get(1))
+        .and_then(|venues| venues.as_array())
+        .and_then(|venues| venues.iter().find(|v| v.get(0).and_then(|n| n.as_str()) == Some(HL_VENUE)))
+        .and_then(|venue| venue.get(1));
+
+    match venue {
+        Some(venue) => (
+            parse_number(venue.get("fundingRate")),
+            venue.get("nextFundingTime").and_then(|t| t.as_u64()),
+        ),
+        None => (None, None),
+    }
+}
+
+/// Hourly funding cost, in bps, of holding the given side of an asset: positive means the
+/// side pays. Uses the predicted rate when available, else the current one. Also returns
+/// the next settlement time (unix secs) so callers can defer past it.
+pub async fn funding_cost_bps(state: &AppState, asset: u64, is_buy: bool) -> Option<(f64, u64)> {
+    let funding = all_funding(state).await.ok()?;
+    let info = funding.into_iter().find(|f| f.asset == asset)?;
+    let rate = info.predicted_rate.or(info.current_rate)?;
+
+    let next_funding = info.next_funding_time
+        .map(|ms| ms / 1000)
+        .unwrap_or_else(|| (now_secs() / FUNDING_INTERVAL_SECS + 1) * FUNDING_INTERVAL_SECS);
+    // Longs pay positive funding, shorts pay negative funding
+    let cost = if is_buy { rate } else { -rate };
+    Some((cost * 10_000.0, next_funding))
+}
+
+/// Query for GET /market/funding
+#[derive(Debug, Deserialize)]
+pub struct FundingQuery {
+    /// Restrict to one coin and include its last day of paid funding
+    pub coin: Option<String>,
+}
+
+/// GET /market/funding - Current and predicted funding per perp, from cached info responses
+pub async fn market_funding(
+    State(state): State<AppState>,
+    Query(query): Query<FundingQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let funding = all_funding(&state).await.map_err(|e| {
+        error!("❌ Failed to load funding: {}", e);
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    let Some(coin) = query.coin else {
+        return Ok(Json(serde_json::json!({"funding": funding})));
+    };
+
+    let info = funding.into_iter().find(|f| f.coin == coin).ok_or(StatusCode::NOT_FOUND)?;
+    let paid = state.market.funding_history(&coin).await.map_err(|e| {
+        error!("❌ Failed to load funding history for {}: {}", coin, e);
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    Ok(Json(serde_json::json!({"funding": [info], "paid": paid})))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -0,0 +1,386 @@
+use async_graphql::{ComplexObject, Context, EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use tokio::sync::OnceCell;
+
+use crate::audit::AuditEntry;
+use crate::auth;
+use crate::events::{EVENT_BACKFILL_FILLS, EVENT_BACKFILL_ORDERS, EVENT_WS_USER_FILLS};
+use crate::AppState;
+
+/// Deepest selection a query may nest
+const MAX_DEPTH: usize = 8;
+/// Most fields one query may resolve
+const MAX_COMPLEXITY: usize = 2000;
+/// Items any list field returns when no `limit` is given, and the most it returns
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 500;
+
+pub type AccountSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+fn schema() -> &'static AccountSchema {
+    static SCHEMA: OnceLock<AccountSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .limit_depth(MAX_DEPTH)
+            .limit_complexity(MAX_COMPLEXITY)
+            .finish()
+    })
+}
+
+/// Account a query runs for, plus what its fields share so nested lists don't refetch
+struct Caller {
+    user_address: String,
+    open_orders: OnceCell<Vec<Value>>,
+    fills: OnceCell<Vec<Value>>,
+}
+
+fn caller<'a>(ctx: &Context<'a>) -> &'a Caller {
+    ctx.data_unchecked::<Caller>()
+}
+
+fn app_state<'a>(ctx: &Context<'a>) -> &'a AppState {
+    ctx.data_unchecked::<AppState>()
+}
+
+/// Resting orders, fetched once per query
+async fn open_orders<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a [Value]> {
+    let caller = caller(ctx);
+    let orders = caller.open_orders.get_or_try_init(|| async {
+        let orders = app_state(ctx).proxy
+            .proxy_info_request(&serde_json::json!({"type": "frontendOpenOrders", "user": caller.user_address}))
+            .await?;
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>(orders.as_array().cloned().unwrap_or_default())
+    }).await?;
+    Ok(orders)
+}
+
+/// Recorded fills, live and backfilled, newest first and without duplicates
+async fn recorded_fills<'a>(ctx: &Context<'a>) -> &'a [Value] {
+    let caller = caller(ctx);
+    caller.fills.get_or_init(|| async {
+        let store = app_state(ctx).event_store.read().await;
+        let mut seen = HashSet::new();
+        let mut fills: Vec<Value> = [EVENT_WS_USER_FILLS, EVENT_BACKFILL_FILLS].into_iter()
+            .flat_map(|kind| store.of_kind_since(&caller.user_address, kind, 0))
+            .flat_map(|event| event.payload.get("fills").and_then(|f| f.as_array()).into_iter().flatten())
+            .filter(|fill| fill.get("tid").and_then(|t| t.as_u64()).is_none_or(|tid| seen.insert(tid)))
+            .cloned()
+            .collect();
+        fills.sort_by_key(|fill| std::cmp::Reverse(u64_field(fill, "time")));
+        fills
+    }).await
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(str::to_string)
+}
+
+fn u64_field(value: &Value, key: &str) -> u64 {
+    value.get(key).and_then(|v| v.as_u64()).unwrap_or_default()
+}
+
+fn limit(requested: Option<usize>) -> usize {
+    requested.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The account the API key trades for
+    async fn account(&self, ctx: &Context<'_>) -> Account {
+        Account { address: caller(ctx).user_address.clone() }
+    }
+}
+
+pub struct Account {
+    address: String,
+}
+
+#[Object]
+impl Account {
+    async fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Account value, margin and withdrawable balance from clearinghouseState
+    async fn margin_summary(&self, ctx: &Context<'_>) -> async_graphql::Result<async_graphql::Json<Value>> {
+        let clearinghouse = app_state(ctx).market.clearinghouse_state(&self.address).await?;
+        Ok(async_graphql::Json(serde_json::json!({
+            "marginSummary": clearinghouse.get("marginSummary"),
+            "crossMarginSummary": clearinghouse.get("crossMarginSummary"),
+            "withdrawable": clearinghouse.get("withdrawable"),
+        })))
+    }
+
+    /// Open perp positions
+    async fn positions(&self, ctx: &Context<'_>, coin: Option<String>) -> async_graphql::Result<Vec<Position>> {
+        let clearinghouse = app_state(ctx).market.clearinghouse_state(&self.address).await?;
+        Ok(clearinghouse.get("assetPositions").and_then(|p| p.as_array()).into_iter().flatten()
+            .filter_map(|entry| entry.get("position"))
+            .filter_map(Position::from_value)
+            .filter(|position| coin.as_ref().is_none_or(|c| &position.coin == c))
+            .collect())
+    }
+
+    /// Resting orders
+    async fn open_orders(&self, ctx: &Context<'_>, coin: Option<String>) -> async_graphql::Result<Vec<Order>> {
+        Ok(open_orders(ctx).await?.iter()
+            .filter_map(Order::from_value)
+            .filter(|order| coin.as_ref().is_none_or(|c| &order.coin == c))
+            .collect())
+    }
+
+    /// Order status changes imported by POST /me/backfill, newest first
+    async fn order_history(&self, ctx: &Context<'_>, coin: Option<String>, limit: Option<usize>) -> Vec<OrderUpdate> {
+        let store = app_state(ctx).event_store.read().await;
+        let mut updates: Vec<OrderUpdate> = store.of_kind_since(&self.address, EVENT_BACKFILL_ORDERS, 0)
+            .flat_map(|event| event.payload.get("orders").and_then(|o| o.as_array()).into_iter().flatten())
+            .filter_map(OrderUpdate::from_value)
+            .filter(|update| coin.as_ref().is_none_or(|c| &update.order.coin == c))
+            .collect();
+        updates.sort_by_key(|update| std::cmp::Reverse(update.status_timestamp));
+        updates.truncate(self::limit(limit));
+        updates
+    }
+
+    /// Fills from the live feed and backfills, newest first
+    async fn fills(&self, ctx: &Context<'_>, coin: Option<String>, since_ms: Option<u64>, limit: Option<usize>) -> Vec<Fill> {
+        recorded_fills(ctx).await.iter()
+            .filter_map(Fill::from_value)
+            .filter(|fill| coin.as_ref().is_none_or(|c| &fill.coin == c))
+            .filter(|fill| fill.time >= since_ms.unwrap_or_default())
+            .take(self::limit(limit))
+            .collect()
+    }
+
+    /// Audit log entries for what the agent signed for this account, newest first
+    async fn audit(
+        &self,
+        ctx: &Context<'_>,
+        kind: Option<String>,
+        from_ms: Option<u64>,
+        to_ms: Option<u64>,
+        limit: Option<usize>,
+    ) -> Vec<AuditRecord> {
+        let audit = app_state(ctx).audit.read().await;
+        audit.signatures_for(&self.address, from_ms.unwrap_or_default(), to_ms.unwrap_or(u64::MAX)).into_iter()
+            .rev()
+            .filter(|entry| kind.as_ref().is_none_or(|k| &entry.kind == k))
+            .take(self::limit(limit))
+            .map(AuditRecord::from)
+            .collect()
+    }
+}
+
+/// One perp position, as in clearinghouseState's `assetPositions`
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Position {
+    coin: String,
+    /// Signed size: negative for shorts
+    size: String,
+    entry_px: Option<String>,
+    position_value: Option<String>,
+    unrealized_pnl: Option<String>,
+    return_on_equity: Option<String>,
+    liquidation_px: Option<String>,
+    margin_used: Option<String>,
+    leverage: Option<f64>,
+}
+
+impl Position {
+    fn from_value(position: &Value) -> Option<Self> {
+        Some(Self {
+            coin: str_field(position, "coin")?,
+            size: str_field(position, "szi")?,
+            entry_px: str_field(position, "entryPx"),
+            position_value: str_field(position, "positionValue"),
+            unrealized_pnl: str_field(position, "unrealizedPnl"),
+            return_on_equity: str_field(position, "returnOnEquity"),
+            liquidation_px: str_field(position, "liquidationPx"),
+            margin_used: str_field(position, "marginUsed"),
+            leverage: position.pointer("/leverage/value").and_then(|v| v.as_f64()),
+        })
+    }
+}
+
+#[ComplexObject]
+impl Position {
+    /// Resting orders on this coin
+    async fn open_orders(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Order>> {
+        Ok(open_orders(ctx).await?.iter()
+            .filter_map(Order::from_value)
+            .filter(|order| order.coin == self.coin)
+            .collect())
+    }
+
+    /// Recorded fills on this coin, newest first
+    async fn fills(&self, ctx: &Context<'_>, limit: Option<usize>) -> Vec<Fill> {
+        recorded_fills(ctx).await.iter()
+            .filter_map(Fill::from_value)
+            .filter(|fill| fill.coin == self.coin)
+            .take(self::limit(limit))
+            .collect()
+    }
+}
+
+/// An order, as in frontendOpenOrders and historicalOrders
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Order {
+    oid: u64,
+    coin: String,
+    /// "B" for bids, "A" for asks
+    side: String,
+    limit_px: String,
+    size: String,
+    orig_size: Option<String>,
+    timestamp: u64,
+    cloid: Option<String>,
+    reduce_only: bool,
+    order_type: Option<String>,
+    trigger_px: Option<String>,
+}
+
+impl Order {
+    fn from_value(order: &Value) -> Option<Self> {
+        Some(Self {
+            oid: order.get("oid")?.as_u64()?,
+            coin: str_field(order, "coin")?,
+            side: str_field(order, "side")?,
+            limit_px: str_field(order, "limitPx")?,
+            size: str_field(order, "sz")?,
+            orig_size: str_field(order, "origSz"),
+            timestamp: u64_field(order, "timestamp"),
+            cloid: str_field(order, "cloid"),
+            reduce_only: order.get("reduceOnly").and_then(|r| r.as_bool()).unwrap_or(false),
+            order_type: str_field(order, "orderType"),
+            trigger_px: str_field(order, "triggerPx"),
+        })
+    }
+}
+
+#[ComplexObject]
+impl Order {
+    /// Recorded fills of this order, newest first
+    async fn fills(&self, ctx: &Context<'_>) -> Vec<Fill> {
+        recorded_fills(ctx).await.iter()
+            .filter_map(Fill::from_value)
+            .filter(|fill| fill.oid == self.oid)
+            .collect()
+    }
+}
+
+/// A status change of an order, as in historicalOrders
+#[derive(SimpleObject)]
+pub struct OrderUpdate {
+    order: Order,
+    status: String,
+    status_timestamp: u64,
+}
+
+impl OrderUpdate {
+    fn from_value(update: &Value) -> Option<Self> {
+        Some(Self {
+            order: Order::from_value(update.get("order")?)?,
+            status: str_field(update, "status")?,
+            status_timestamp: u64_field(update, "statusTimestamp"),
+        })
+    }
+}
+
+/// A fill, as pushed by the userFills subscription
+#[derive(SimpleObject)]
+pub struct Fill {
+    tid: u64,
+    oid: u64,
+    coin: String,
+    px: String,
+    size: String,
+    side: String,
+    time: u64,
+    dir: Option<String>,
+    closed_pnl: Option<String>,
+    fee: Option<String>,
+    fee_token: Option<String>,
+    crossed: bool,
+    hash: Option<String>,
+}
+
+impl Fill {
+    fn from_value(fill: &Value) -> Option<Self> {
+        Some(Self {
+            tid: fill.get("tid")?.as_u64()?,
+            oid: u64_field(fill, "oid"),
+            coin: str_field(fill, "coin")?,
+            px: str_field(fill, "px")?,
+            size: str_field(fill, "sz")?,
+            side: str_field(fill, "side")?,
+            time: u64_field(fill, "time"),
+            dir: str_field(fill, "dir"),
+            closed_pnl: str_field(fill, "closedPnl"),
+            fee: str_field(fill, "fee"),
+            fee_token: str_field(fill, "feeToken"),
+            crossed: fill.get("crossed").and_then(|c| c.as_bool()).unwrap_or(false),
+            hash: str_field(fill, "hash"),
+        })
+    }
+}
+
+/// An audit log entry; the client address it was signed from is left out
+#[derive(SimpleObject)]
+pub struct AuditRecord {
+    seq: u64,
+    timestamp_ms: u64,
+    kind: String,
+    subject_hash: String,
+    subject: async_graphql::Json<Value>,
+    error: Option<String>,
+    entry_hash: String,
+}
+
+impl From<&AuditEntry> for AuditRecord {
+    fn from(entry: &AuditEntry) -> Self {
+        Self {
+            seq: entry.seq,
+            timestamp_ms: entry.timestamp_ms,
+            kind: entry.kind.clone(),
+            subject_hash: entry.subject_hash.clone(),
+            subject: async_graphql::Json(entry.subject.clone()),
+            error: entry.error.clone(),
+            entry_hash: entry.entry_hash.clone(),
+        }
+    }
+}
+
+/// POST /graphql - Query the caller's positions, orders, fills and audit entries in one request.
+///
+/// Read-only; share tokens see the account they were issued for.
+pub async fn graphql(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<async_graphql::Request>,
+) -> Result<Json<async_graphql::Response>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    let caller = Caller {
+        user_address: user_address.to_lowercase(),
+        open_orders: OnceCell::new(),
+        fills: OnceCell::new(),
+    };
+
+    Ok(Json(schema().execute(request.data(caller).data(state)).await))
+}
+
+/// GET /graphql/schema - The schema in SDL, for codegen and dashboards
+pub async fn sdl() -> String {
+    schema().sdl()
+}
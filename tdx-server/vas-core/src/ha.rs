@@ -0,0 +1,555 @@
+use aes::Aes256;
+use alloy::primitives::{keccak256, B256};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Json,
+};
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use secp256k1::{ecdh::SharedSecret, PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn, error};
+
+use crate::address;
+use crate::attestation::{bound_agent_address, check_registered_key};
+use crate::config::Config;
+use crate::preset_tdx::PresetTDXData;
+use crate::signer::LocalBackend;
+use crate::universal_signing::sign_hash_with_key;
+use crate::AppState;
+
+/// Header carrying the shared peer token on /ha endpoints
+pub const HA_PEER_TOKEN_HEADER: &str = "X-HA-Peer-Token";
+
+/// TDX v4 quote: MRTD sits in the TD report body after the 48-byte header
+const MRTD_OFFSET: usize = 184;
+const MRTD_LEN: usize = 48;
+
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+
+/// This instance's place in the primary/standby pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HaRole {
+    /// No peer configured; always signs
+    Standalone,
+    /// Signs while it holds a lease renewed by the standby's heartbeats
+    Primary,
+    /// Holds an escrowed key, never signs until it takes over
+    Standby,
+}
+
+impl HaRole {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "standalone" => Some(Self::Standalone),
+            "primary" => Some(Self::Primary),
+            "standby" => Some(Self::Standby),
+            _ => None,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Primary,
+            2 => Self::Standby,
+            _ => Self::Standalone,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::Standalone => 0,
+            Self::Primary => 1,
+            Self::Standby => 2,
+        }
+    }
+}
+
+/// Fencing state checked by the signer before every signature.
+///
+/// A primary only signs while its lease is live. The standby renews the lease with each
+/// heartbeat and only promotes itself after `HA_FAILOVER_MS` without reaching the primary,
+/// which must exceed `HA_LEASE_MS`: by then a partitioned primary has already stopped
+/// signing. With no third arbiter, losing the standby also fences the primary until a
+/// standby reconnects.
+#[derive(Debug)]
+pub struct Fence {
+    role: AtomicU8,
+    epoch: AtomicU64,
+    lease_until_ms: AtomicU64,
+}
+
+impl Fence {
+    pub fn new(role: HaRole) -> Self {
+        Self {
+            role: AtomicU8::new(role.as_u8()),
+            epoch: AtomicU64::new(1),
+            // A primary starts fenced and waits for its first heartbeat
+            lease_until_ms: AtomicU64::new(0),
+        }
+    }
+
+    pub fn role(&self) -> HaRole {
+        HaRole::from_u8(self.role.load(Ordering::SeqCst))
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch.load(Ordering::SeqCst)
+    }
+
+    pub fn lease_until_ms(&self) -> u64 {
+        self.lease_until_ms.load(Ordering::SeqCst)
+    }
+
+    /// Whether this instance may produce a signature right now
+    pub fn check(&self) -> Result<(), String> {
+        match self.role() {
+            HaRole::Standalone => Ok(()),
+            HaRole::Standby => Err("Signing fenced: instance is the HA standby".to_string()),
+            HaRole::Primary if now_ms() < self.lease_until_ms() => Ok(()),
+            HaRole::Primary => Err(format!("Signing fenced: primary lease expired (epoch {})", self.epoch())),
+        }
+    }
+
+    fn renew(&self, lease_ms: u64) {
+        self.lease_until_ms.fetch_max(now_ms() + lease_ms, Ordering::SeqCst);
+    }
+
+    fn demote(&self, epoch: u64) {
+        self.role.store(HaRole::Standby.as_u8(), Ordering::SeqCst);
+        self.epoch.fetch_max(epoch, Ordering::SeqCst);
+        self.lease_until_ms.store(0, Ordering::SeqCst);
+    }
+
+    /// Take over as primary under a new epoch; signs until a standby joins and leases take over
+    fn promote(&self) -> u64 {
+        let epoch = self.epoch.fetch_add(1, Ordering::SeqCst) + 1;
+        self.lease_until_ms.store(u64::MAX, Ordering::SeqCst);
+        self.role.store(HaRole::Primary.as_u8(), Ordering::SeqCst);
+        epoch
+    }
+}
+
+/// Encrypt-then-MAC envelope (AES-256-CTR + HMAC-SHA256) used for escrow transfer, sealing and support bundles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedBox {
+    pub iv: String,
+    pub ciphertext: String,
+    pub mac: String,
+}
+
+impl SealedBox {
+    pub fn seal(secret: &[u8], plaintext: &[u8]) -> Self {
+        let (enc_key, mac_key) = derive_keys(secret);
+        let mut iv = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut iv);
+
+        let mut ciphertext = plaintext.to_vec();
+        Aes256Ctr::new(&enc_key.into(), &iv.into()).apply_keystream(&mut ciphertext);
+
+        Self {
+            iv: hex::encode(iv),
+            mac: hex::encode(mac(&mac_key, &iv, &ciphertext)),
+            ciphertext: hex::encode(ciphertext),
+        }
+    }
+
+    pub fn open(&self, secret: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        let (enc_key, mac_key) = derive_keys(secret);
+        let iv: [u8; 16] = hex::decode(&self.iv)?.try_into().map_err(|_| "Invalid IV length")?;
+        let mut ciphertext = hex::decode(&self.ciphertext)?;
+
+        let mut verifier = <Hmac<Sha256> as Mac>::new_from_slice(&mac_key)?;
+        verifier.update(&iv);
+        verifier.update(&ciphertext);
+        verifier.verify_slice(&hex::decode(&self.mac)?).map_err(|_| "Sealed box MAC mismatch")?;
+
+        Aes256Ctr::new(&enc_key.into(), &iv.into()).apply_keystream(&mut ciphertext);
+        Ok(ciphertext)
+    }
+}
+
+fn derive_keys(secret: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let enc: [u8; 32] = Sha256::new().chain_update(b"vas-escrow-enc").chain_update(secret).finalize().into();
+    let mac: [u8; 32] = Sha256::new().chain_update(b"vas-escrow-mac").chain_update(secret).finalize().into();
+    (enc, mac)
+}
+
+fn mac(mac_key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(mac_key).expect("HMAC accepts any key length");
+    mac.update(iv);
+    mac.update(ciphertext);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// MRTD (TD build measurement) from a TDX v4 quote
+pub fn quote_mrtd(quote: &[u8]) -> Option<&[u8]> {
+    quote.get(MRTD_OFFSET..MRTD_OFFSET + MRTD_LEN)
+}
+
+/// Which side of the exchange signed an ephemeral key, so one can't be replayed as the other
+const ESCROW_REQUEST: &str = "request";
+const ESCROW_RESPONSE: &str = "response";
+
+/// Digest a peer signs with its attested agent key to vouch for its ephemeral ECDH key
+fn ephemeral_key_digest(direction: &str, public_key_hex: &str) -> B256 {
+    keccak256(format!("vas-ha-escrow:{}:{}", direction, public_key_hex.to_lowercase()))
+}
+
+/// Sign our ephemeral key with this instance's attested agent key
+fn sign_ephemeral_key(agent_key: &SecretKey, direction: &str, public_key_hex: &str) -> String {
+    let signature = sign_hash_with_key(agent_key, &ephemeral_key_digest(direction, public_key_hex));
+    format!("{}{}{:02x}", signature.r, &signature.s[2..], signature.v)
+}
+
+/// Check the peer's quote carries our MRTD and that its ephemeral key is signed by the agent key
+/// the quote binds. Returns that agent address.
+fn check_peer_binding(
+    local_quote: &[u8],
+    peer_quote: &[u8],
+    direction: &str,
+    public_key_hex: &str,
+    key_signature: &str,
+) -> Result<String, String> {
+    match (quote_mrtd(local_quote), quote_mrtd(peer_quote)) {
+        (Some(local_mrtd), Some(peer_mrtd)) if local_mrtd == peer_mrtd => {}
+        (Some(_), Some(peer_mrtd)) => return Err(format!("Peer MRTD {} does not match ours", hex::encode(peer_mrtd))),
+        _ => return Err("Quote too short to carry a TD report".to_string()),
+    }
+
+    let bound = bound_agent_address(peer_quote)?;
+    let signature: alloy::primitives::Signature = key_signature.parse()
+        .map_err(|e| format!("Invalid ephemeral key signature: {}", e))?;
+    let signer = signature.recover_address_from_prehash(&ephemeral_key_digest(direction, public_key_hex))
+        .map_err(|e| format!("Invalid ephemeral key signature: {}", e))?;
+    let signer = address::Address::from(signer).to_lower_hex();
+    if signer != bound {
+        return Err(format!("Ephemeral key is signed by {}, not the agent {} the peer quote binds", signer, bound));
+    }
+    Ok(bound)
+}
+
+/// Both peers must run the same measured build, on a quote Automata DCAP verified, and prove
+/// they hold the attested key before key material crosses between them. The quote signature
+/// is verified on-chain when the peer registers it, so escrow is refused without a registry.
+async fn check_peer(
+    config: &Config,
+    peer_quote_hex: &str,
+    direction: &str,
+    public_key_hex: &str,
+    key_signature: &str,
+) -> Result<String, String> {
+    let local = PresetTDXData::get().ok_or("Preset TDX data not initialized")?;
+    let peer_quote = hex::decode(peer_quote_hex).map_err(|e| format!("Invalid peer quote hex: {}", e))?;
+    let bound = check_peer_binding(&local.tdx_quote, &peer_quote, direction, public_key_hex, key_signature)?;
+
+    let (Some(registry), Some(rpc)) = (&config.registry_address, &config.hyperevm_rpc_url) else {
+        return Err("Key escrow needs REGISTRY_ADDRESS and HYPEREVM_RPC_URL to verify the peer's quote".to_string());
+    };
+    let mrtd = quote_mrtd(&peer_quote).expect("checked above").to_vec();
+    check_registered_key(rpc, registry, &bound, &[mrtd]).await?;
+    Ok(bound)
+}
+
+/// Standby -> primary: request an escrowed copy of the agent key
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EscrowRequest {
+    pub quote_hex: String,
+    /// Ephemeral secp256k1 public key the key is encrypted to
+    pub public_key: String,
+    /// Signature over the ephemeral key by the agent key `quote_hex` binds
+    pub key_signature: String,
+}
+
+/// Primary -> standby: the agent key encrypted to the standby's ephemeral key
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EscrowResponse {
+    pub epoch: u64,
+    pub agent_address: String,
+    pub quote_hex: String,
+    /// Primary's ephemeral public key for ECDH
+    pub public_key: String,
+    /// Signature over the ephemeral key by the agent key `quote_hex` binds
+    pub key_signature: String,
+    pub sealed: SealedBox,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HeartbeatRequest {
+    pub epoch: u64,
+}
+
+/// POST /ha/escrow - Hand the agent key to an attested standby running the same build
+pub async fn escrow(
+    State(state): State<AppState>,
+    Json(payload): Json<EscrowRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    if state.ha.role() != HaRole::Primary {
+        return Err(StatusCode::CONFLICT);
+    }
+    if let Err(reason) = check_peer(&state.config, &payload.quote_hex, ESCROW_REQUEST, &payload.public_key, &payload.key_signature).await {
+        warn!("⚠️ Rejected escrow request: {}", reason);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let preset_data = PresetTDXData::get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let standby_key = PublicKey::from_slice(&hex::decode(&payload.public_key).map_err(|_| StatusCode::BAD_REQUEST)?)
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let secp = Secp256k1::new();
+    let (ephemeral_secret, ephemeral_public) = secp.generate_keypair(&mut rand::thread_rng());
+    let shared = SharedSecret::new(&standby_key, &ephemeral_secret);
+    let public_key = hex::encode(ephemeral_public.serialize());
+
+    info!("🔐 Escrowing agent key to attested standby (epoch {})", state.ha.epoch());
+
+    Ok(Json(serde_json::json!(EscrowResponse {
+        epoch: state.ha.epoch(),
+        agent_address: preset_data.agent_address.clone(),
+        quote_hex: hex::encode(&preset_data.tdx_quote),
+        key_signature: sign_ephemeral_key(&preset_data.agent_private_key, ESCROW_RESPONSE, &public_key),
+        public_key,
+        sealed: SealedBox::seal(&shared.secret_bytes(), &preset_data.agent_private_key.secret_bytes()),
+    })))
+}
+
+/// POST /ha/heartbeat - Standby liveness ping; renews the primary's signing lease
+pub async fn heartbeat(
+    State(state): State<AppState>,
+    Json(payload): Json<HeartbeatRequest>,
+) -> Json<Value> {
+    let fence = &state.ha;
+    if payload.epoch > fence.epoch() {
+        // The peer took over while we were unreachable
+        if fence.role() != HaRole::Standby {
+            warn!("🚧 Peer is at epoch {} (ours {}); stepping down to standby", payload.epoch, fence.epoch());
+        }
+        fence.demote(payload.epoch);
+    } else if fence.role() == HaRole::Primary {
+        fence.renew(state.config.ha_lease_ms);
+    }
+
+    Json(serde_json::json!({
+        "role": fence.role(),
+        "epoch": fence.epoch(),
+        "lease_until_ms": fence.lease_until_ms()
+    }))
+}
+
+/// GET /ha/status - Role, epoch and lease of this instance
+pub async fn status(State(state): State<AppState>) -> Json<Value> {
+    Json(serde_json::json!({
+        "role": state.ha.role(),
+        "epoch": state.ha.epoch(),
+        "lease_until_ms": state.ha.lease_until_ms(),
+        "signing": state.ha.check().is_ok()
+    }))
+}
+
+/// Run the standby side: fetch the escrowed key, heartbeat the primary and take over on failure
+pub fn spawn_standby(state: AppState) {
+    tokio::spawn(async move {
+        let config = state.config.clone();
+        let Some(peer_url) = config.ha_peer_url.clone() else {
+            error!("❌ HA_ROLE=standby requires HA_PEER_URL; standby loop not started");
+            return;
+        };
+
+        let client = reqwest::Client::new();
+        let mut escrowed = load_sealed_key(&config);
+        let mut last_contact = std::time::Instant::now();
+        let mut ticker = tokio::time::interval(Duration::from_millis((config.ha_lease_ms / 3).max(100)));
+
+        loop {
+            ticker.tick().await;
+
+            if escrowed.is_none() {
+                match request_escrow(&client, &config, &peer_url).await {
+                    Ok(key) => {
+                        store_sealed_key(&config, &key);
+                        escrowed = Some(key);
+                    }
+                    Err(e) => warn!("⚠️ Escrow request failed: {}", e),
+                }
+            }
+
+            let heartbeat = client.post(format!("{}/ha/heartbeat", peer_url))
+                .header(HA_PEER_TOKEN_HEADER, config.ha_peer_token.clone().unwrap_or_default())
+                .json(&HeartbeatRequest { epoch: state.ha.epoch() })
+                .timeout(Duration::from_millis(config.ha_lease_ms))
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+
+            match heartbeat {
+                Ok(_) => last_contact = std::time::Instant::now(),
+                Err(e) if last_contact.elapsed() >= Duration::from_millis(config.ha_failover_ms) => {
+                    let Some(key) = escrowed else {
+                        error!("❌ Primary unreachable ({}) but no escrowed key; cannot take over", e);
+                        continue;
+                    };
+                    take_over(&state, key).await;
+                    return;
+                }
+                Err(e) => warn!("⚠️ Primary heartbeat failed: {}", e),
+            }
+        }
+    });
+}
+
+async fn request_escrow(
+    client: &reqwest::Client,
+    config: &Config,
+    peer_url: &str,
+) -> Result<SecretKey, Box<dyn std::error::Error + Send + Sync>> {
+    let preset_data = PresetTDXData::get().ok_or("Preset TDX data not initialized")?;
+    let secp = Secp256k1::new();
+    let (ephemeral_secret, ephemeral_public) = secp.generate_keypair(&mut rand::thread_rng());
+    let public_key = hex::encode(ephemeral_public.serialize());
+
+    let response: EscrowResponse = client.post(format!("{}/ha/escrow", peer_url))
+        .header(HA_PEER_TOKEN_HEADER, config.ha_peer_token.clone().unwrap_or_default())
+        .json(&EscrowRequest {
+            quote_hex: hex::encode(&preset_data.tdx_quote),
+            key_signature: sign_ephemeral_key(&preset_data.agent_private_key, ESCROW_REQUEST, &public_key),
+            public_key,
+        })
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // Attestation is mutual: the primary must run our build and hold the key its quote binds
+    let primary_agent = check_peer(config, &response.quote_hex, ESCROW_RESPONSE, &response.public_key, &response.key_signature).await?;
+    if !address::same(&primary_agent, &response.agent_address) {
+        return Err(format!("Primary escrows {} but its quote binds {}", response.agent_address, primary_agent).into());
+    }
+
+    let primary_key = PublicKey::from_slice(&hex::decode(&response.public_key)?)?;
+    let shared = SharedSecret::new(&primary_key, &ephemeral_secret);
+    let key = SecretKey::from_slice(&response.sealed.open(&shared.secret_bytes())?)?;
+
+    let address = address::secret_key_to_address(&key);
+    if address.to_lowercase() != response.agent_address.to_lowercase() {
+        return Err(format!("Escrowed key derives {} (expected {})", address, response.agent_address).into());
+    }
+
+    info!("🔐 Received escrowed agent key for {} (primary epoch {})", address, response.epoch);
+    Ok(key)
+}
+
+/// Promote to primary: bump the epoch, load the escrowed key and claim the virtual IP
+async fn take_over(state: &AppState, key: SecretKey) {
+    let epoch = state.ha.promote();
+    warn!("🚨 Primary unreachable for {}ms; taking over at epoch {}", state.config.ha_failover_ms, epoch);
+
+    state.signer.install_backend(Arc::new(LocalBackend::new(key))).await;
+
+    if let Some(command) = &state.config.ha_takeover_command {
+        match tokio::process::Command::new("sh").arg("-c").arg(command).env("HA_EPOCH", epoch.to_string()).status().await {
+            Ok(status) if status.success() => info!("✅ Takeover command succeeded"),
+            Ok(status) => error!("❌ Takeover command exited with {}", status),
+            Err(e) => error!("❌ Failed to run takeover command: {}", e),
+        }
+    }
+}
+
+/// Sealed keys at rest are encrypted under HA_SEAL_KEY; without it the escrow lives in memory only
+fn load_sealed_key(config: &Config) -> Option<SecretKey> {
+    let seal_key = hex::decode(config.ha_seal_key.as_ref()?).ok()?;
+    let contents = std::fs::read_to_string(&config.ha_sealed_key_path).ok()?;
+
+    let sealed: SealedBox = match serde_json::from_str(&contents) {
+        Ok(sealed) => sealed,
+        Err(e) => {
+            warn!("⚠️ Unreadable sealed key at {}: {}", config.ha_sealed_key_path, e);
+            return None;
+        }
+    };
+    match sealed.open(&seal_key).map(|bytes| SecretKey::from_slice(&bytes)) {
+        Ok(Ok(key)) => {
+            info!("🔐 Loaded sealed escrow key from {}", config.ha_sealed_key_path);
+            Some(key)
+        }
+        _ => {
+            warn!("⚠️ Failed to unseal escrow key at {}", config.ha_sealed_key_path);
+            None
+        }
+    }
+}
+
+fn store_sealed_key(config: &Config, key: &SecretKey) {
+    let Some(seal_key) = config.ha_seal_key.as_ref().and_then(|k| hex::decode(k).ok()) else {
+        return;
+    };
+
+    let sealed = SealedBox::seal(&seal_key, &key.secret_bytes());
+    let path = PathBuf::from(&config.ha_sealed_key_path);
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    match serde_json::to_string(&sealed).map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()))
+    {
+        Ok(()) => info!("🔐 Sealed escrow key to {}", path.display()),
+        Err(e) => error!("❌ Failed to seal escrow key: {}", e),
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal v4 quote: an MRTD and report data binding `agent_address`
+    fn quote(mrtd: u8, agent_address: &str) -> Vec<u8> {
+        let mut quote = vec![0u8; 632];
+        quote[MRTD_OFFSET..MRTD_OFFSET + MRTD_LEN].fill(mrtd);
+        quote[600..612].copy_from_slice(b"HYPERLIQUID\0");
+        quote[612..632].copy_from_slice(&hex::decode(&agent_address[2..]).unwrap());
+        quote
+    }
+
+    #[test]
+    fn test_peer_must_sign_its_ephemeral_key_with_the_attested_key() {
+        let peer_key = SecretKey::from_slice(&[5u8; 32]).unwrap();
+        let peer_address = address::secret_key_to_address(&peer_key);
+        let local = quote(1, "0x1111111111111111111111111111111111111111");
+        let peer = quote(1, &peer_address);
+        let ephemeral = "02".to_string() + &"ab".repeat(32);
+
+        let signature = sign_ephemeral_key(&peer_key, ESCROW_REQUEST, &ephemeral);
+        assert_eq!(check_peer_binding(&local, &peer, ESCROW_REQUEST, &ephemeral, &signature).unwrap(), peer_address);
+
+        // Someone replaying the public quote with their own ephemeral key can't sign for it
+        let attacker = SecretKey::from_slice(&[6u8; 32]).unwrap();
+        let forged = sign_ephemeral_key(&attacker, ESCROW_REQUEST, &ephemeral);
+        assert!(check_peer_binding(&local, &peer, ESCROW_REQUEST, &ephemeral, &forged).unwrap_err().contains("not the agent"));
+
+        let other_ephemeral = "03".to_string() + &"cd".repeat(32);
+        assert!(check_peer_binding(&local, &peer, ESCROW_REQUEST, &other_ephemeral, &signature).is_err());
+
+        // A response signature can't stand in for a request
+        let response = sign_ephemeral_key(&peer_key, ESCROW_RESPONSE, &ephemeral);
+        assert!(check_peer_binding(&local, &peer, ESCROW_REQUEST, &ephemeral, &response).is_err());
+
+        let other_build = quote(2, &peer_address);
+        assert!(check_peer_binding(&local, &other_build, ESCROW_REQUEST, &ephemeral, &signature).unwrap_err().contains("MRTD"));
+    }
+}
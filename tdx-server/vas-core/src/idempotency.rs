@@ -0,0 +1,131 @@
+use axum::http::HeaderMap;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tokio::sync::watch;
+use tracing::info;
+
+use crate::AppState;
+
+/// Request header naming a logical /exchange request across retries
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+const MAX_KEY_LEN: usize = 128;
+
+#[derive(Debug, Clone)]
+enum Outcome {
+    InFlight,
+    Done(Value),
+    /// Failed before a response; retries sign again, with the pinned nonce
+    Failed,
+}
+
+#[derive(Debug)]
+struct Entry {
+    /// Hash of the client payload, so a reused key with a different body is refused
+    fingerprint: String,
+    /// Nonce of the first attempt; retries sign the same (action, nonce) and get the same signature
+    nonce: u64,
+    /// Closed while still in flight when the attempt was dropped (client gone, route timeout)
+    outcome: watch::Receiver<Outcome>,
+    created_at: u64,
+}
+
+/// Retried /exchange requests by (API key, idempotency key)
+#[derive(Debug)]
+pub struct IdempotencyCache {
+    entries: HashMap<(String, String), Entry>,
+    ttl_secs: u64,
+}
+
+impl IdempotencyCache {
+    pub fn new(ttl_secs: u64) -> Self {
+        Self { entries: HashMap::new(), ttl_secs }
+    }
+
+    /// Drop the remembered responses of a deleted user's key
+    pub fn remove_key(&mut self, api_key: &str) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|(key, _), _| key != api_key);
+        before - self.entries.len()
+    }
+}
+
+/// What to do with an /exchange request carrying an idempotency key
+pub enum Decision {
+    /// Sign and submit with this nonce, then report the outcome through the guard
+    Proceed { nonce: u64, guard: Guard },
+    /// An earlier attempt completed; return its response unchanged
+    Replay(Value),
+    /// The key was used for a different request
+    Conflict(String),
+}
+
+/// Held by the attempt that owns a key; dropping it unreported lets retries take over
+pub struct Guard {
+    outcome: watch::Sender<Outcome>,
+}
+
+impl Guard {
+    pub fn complete(self, response: &Value) {
+        self.outcome.send_replace(Outcome::Done(response.clone()));
+    }
+
+    pub fn fail(self) {
+        self.outcome.send_replace(Outcome::Failed);
+    }
+}
+
+/// Claim the request's idempotency key, or find the earlier attempt's outcome.
+///
+/// A retry arriving while the first attempt is in flight waits for it. Without a key, returns None.
+pub async fn begin(state: &AppState, headers: &HeaderMap, api_key: &str, payload: &Value, nonce: u64) -> Option<Decision> {
+    let idempotency_key = headers.get(IDEMPOTENCY_HEADER)?.to_str().ok()?.trim();
+    if idempotency_key.is_empty() || idempotency_key.len() > MAX_KEY_LEN {
+        return Some(Decision::Conflict(format!("{} must be 1-{} characters", IDEMPOTENCY_HEADER, MAX_KEY_LEN)));
+    }
+    let key = (api_key.to_string(), idempotency_key.to_string());
+    let fingerprint = hex::encode(Sha256::digest(payload.to_string().as_bytes()));
+
+    loop {
+        let mut in_flight = {
+            let mut cache = state.idempotency.write().await;
+            let now = now_secs();
+            let ttl_secs = cache.ttl_secs;
+            cache.entries.retain(|_, e| e.created_at + ttl_secs > now);
+
+            let Some(entry) = cache.entries.get_mut(&key) else {
+                let (guard, outcome) = watch::channel(Outcome::InFlight);
+                cache.entries.insert(key, Entry { fingerprint, nonce, outcome, created_at: now });
+                return Some(Decision::Proceed { nonce, guard: Guard { outcome: guard } });
+            };
+            if entry.fingerprint != fingerprint {
+                return Some(Decision::Conflict(format!("{} was already used for a different request", IDEMPOTENCY_HEADER)));
+            }
+
+            let current = entry.outcome.borrow().clone();
+            let abandoned = entry.outcome.has_changed().is_err();
+            match current {
+                Outcome::Done(response) => {
+                    info!("♻️ Idempotent retry answered from cache");
+                    return Some(Decision::Replay(response));
+                }
+                Outcome::InFlight if !abandoned => entry.outcome.clone(),
+                Outcome::Failed | Outcome::InFlight => {
+                    let (guard, outcome) = watch::channel(Outcome::InFlight);
+                    entry.outcome = outcome;
+                    info!("♻️ Idempotent retry re-signing with pinned nonce {}", entry.nonce);
+                    return Some(Decision::Proceed { nonce: entry.nonce, guard: Guard { outcome: guard } });
+                }
+            }
+        };
+        // Wait for the attempt in flight, then look again (a dropped attempt closes the channel)
+        let _ = in_flight.changed().await;
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
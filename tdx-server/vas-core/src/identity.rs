@@ -0,0 +1,85 @@
+use alloy::primitives::{keccak256, Address, B256};
+use alloy::sol_types::SolCall;
+use tracing::{info, warn};
+
+use crate::evm::rpc_call;
+use crate::AppState;
+
+/// ENS registry, same address on mainnet and the public testnets
+const ENS_REGISTRY: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+
+alloy::sol! {
+    function resolver(bytes32 node) external view returns (address);
+    function name(bytes32 node) external view returns (string);
+    function addr(bytes32 node) external view returns (address);
+}
+
+/// EIP-137 namehash
+pub fn namehash(name: &str) -> B256 {
+    let mut node = B256::ZERO;
+    if name.is_empty() {
+        return node;
+    }
+    for label in name.rsplit('.') {
+        let mut buf = [0u8; 64];
+        buf[..32].copy_from_slice(node.as_slice());
+        buf[32..].copy_from_slice(keccak256(label.as_bytes()).as_slice());
+        node = keccak256(buf);
+    }
+    node
+}
+
+/// Reverse-resolve `address` to its primary ENS name.
+/// Only returns names whose forward record points back at the address.
+pub async fn reverse_ens(rpc: &str, address: &str) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let address: Address = address.parse()?;
+    let reverse_node = namehash(&format!("{}.addr.reverse", hex::encode(address.as_slice())));
+
+    let Some(reverse_resolver) = resolver_for(rpc, reverse_node).await? else {
+        return Ok(None);
+    };
+    let name = nameCall::abi_decode_returns(&eth_call(rpc, reverse_resolver, nameCall { node: reverse_node }.abi_encode()).await?)?;
+    if name.is_empty() {
+        return Ok(None);
+    }
+
+    let forward_node = namehash(&name);
+    let Some(forward_resolver) = resolver_for(rpc, forward_node).await? else {
+        return Ok(None);
+    };
+    let resolved = addrCall::abi_decode_returns(&eth_call(rpc, forward_resolver, addrCall { node: forward_node }.abi_encode()).await?)?;
+
+    Ok((resolved == address).then_some(name))
+}
+
+/// Look up the ENS name for a freshly logged-in user and attach it to the session and audit log
+pub async fn enrich_session(state: AppState, api_key: String, user_address: String) {
+    let Some(rpc) = state.config.ens_rpc_url.clone() else {
+        return;
+    };
+
+    match reverse_ens(&rpc, &user_address).await {
+        Ok(Some(name)) => {
+            info!("🪪 {} resolves to {}", user_address, name);
+            state.session_manager.set_ens_name(&api_key, Some(name.clone()));
+            state.audit.write().await.set_identity(&user_address, name);
+        }
+        Ok(None) => {}
+        Err(e) => warn!("⚠️ ENS reverse lookup for {} failed: {}", user_address, e),
+    }
+}
+
+async fn resolver_for(rpc: &str, node: B256) -> Result<Option<Address>, Box<dyn std::error::Error + Send + Sync>> {
+    let registry: Address = ENS_REGISTRY.parse()?;
+    let resolver = resolverCall::abi_decode_returns(&eth_call(rpc, registry, resolverCall { node }.abi_encode()).await?)?;
+    Ok((resolver != Address::ZERO).then_some(resolver))
+}
+
+async fn eth_call(rpc: &str, to: Address, data: Vec<u8>) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let result = rpc_call(rpc, "eth_call", serde_json::json!([
+        {"to": to.to_string(), "data": format!("0x{}", hex::encode(data))},
+        "latest"
+    ])).await?;
+
+    Ok(hex::decode(result.as_str().ok_or("eth_call returned non-string")?.trim_start_matches("0x"))?)
+}
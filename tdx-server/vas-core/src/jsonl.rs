@@ -0,0 +1,71 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use tracing::warn;
+
+/// Append one record as a JSON line, creating the file (and parent directory) if needed
+pub fn append<T: Serialize>(path: &Path, record: &T) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    file.write_all(line.as_bytes())?;
+
+    Ok(())
+}
+
+/// Load all records from a JSON-lines file; a missing file yields no records.
+/// Lines that fail to parse are skipped with a warning rather than failing startup.
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, Box<dyn std::error::Error + Send + Sync>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut records = Vec::new();
+    for (line_number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => warn!("⚠️ Skipping malformed line {} in {}: {}", line_number + 1, path.display(), e),
+        }
+    }
+
+    Ok(records)
+}
+
+/// Replace the file's contents with `records`, via a temp file and rename so a crash
+/// mid-write never leaves a truncated log behind
+pub fn rewrite<T: Serialize>(path: &Path, records: &[T]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let tmp = path.with_extension("tmp");
+    {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp)?);
+        for record in records {
+            let mut line = serde_json::to_string(record)?;
+            line.push('\n');
+            file.write_all(line.as_bytes())?;
+        }
+        file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    }
+    std::fs::rename(&tmp, path)?;
+
+    Ok(())
+}
+
+/// Size of the backing file in bytes (0 when missing or not persisted)
+pub fn file_size(path: Option<&Path>) -> u64 {
+    path.and_then(|p| std::fs::metadata(p).ok()).map(|m| m.len()).unwrap_or(0)
+}
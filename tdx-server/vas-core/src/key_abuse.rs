@@ -0,0 +1,217 @@
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use tracing::warn;
+
+use crate::api_keys::API_KEY_PREFIX;
+use crate::auth;
+use crate::client_ip::ClientIp;
+use crate::config::Config;
+use crate::error_codes::{self, ErrorCode};
+use crate::notify::{Notification, NotificationKind};
+use crate::AppState;
+
+/// Findings kept for the admin API
+const MAX_FINDINGS: usize = 1000;
+
+const SUSPENDED: &str = "This API key was used from too many clients and has been suspended; sign in again to get a new key";
+
+/// A key seen from more clients than one owner plausibly runs
+#[derive(Debug, Clone, Serialize)]
+pub struct AbuseFinding {
+    pub id: u64,
+    pub user_address: Option<String>,
+    /// First bytes of sha256(api key), so findings can be matched without exposing the key
+    pub key_fingerprint: String,
+    pub detected_at_ms: u64,
+    pub window_ms: u64,
+    pub ips: Vec<String>,
+    pub user_agents: Vec<String>,
+    pub reauth_required: bool,
+}
+
+/// Clients seen for one key, by last-seen time
+#[derive(Debug, Default)]
+struct KeyUsage {
+    ips: HashMap<String, u64>,
+    user_agents: HashMap<String, u64>,
+    flagged: bool,
+}
+
+/// Counts distinct client IPs and user agents per SIWE API key over a sliding window
+#[derive(Debug)]
+pub struct KeyAbuseMonitor {
+    window_ms: u64,
+    max_ips: usize,
+    max_user_agents: usize,
+    require_reauth: bool,
+    usage: Mutex<HashMap<String, KeyUsage>>,
+    findings: Mutex<VecDeque<AbuseFinding>>,
+}
+
+impl KeyAbuseMonitor {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            window_ms: config.key_abuse_window_ms,
+            max_ips: config.key_abuse_max_ips,
+            max_user_agents: config.key_abuse_max_user_agents,
+            require_reauth: config.key_abuse_require_reauth,
+            usage: Mutex::new(HashMap::new()),
+            findings: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Record one request; returns a finding the first time the key crosses a threshold
+    fn observe(&self, api_key: &str, ip: Option<String>, user_agent: Option<String>) -> Option<AbuseFinding> {
+        let now = now_ms();
+        let cutoff = now.saturating_sub(self.window_ms);
+        let mut usage = self.usage.lock().unwrap();
+
+        // Keys idle for a whole window no longer need tracking; flagged keys stay flagged
+        if !usage.contains_key(api_key) {
+            usage.retain(|_, u| u.flagged || u.ips.values().chain(u.user_agents.values()).any(|seen| *seen >= cutoff));
+        }
+        let entry = usage.entry(api_key.to_string()).or_default();
+        if entry.flagged {
+            return None;
+        }
+
+        if let Some(ip) = ip {
+            entry.ips.insert(ip, now);
+        }
+        if let Some(user_agent) = user_agent {
+            entry.user_agents.insert(user_agent, now);
+        }
+        entry.ips.retain(|_, seen| *seen >= cutoff);
+        entry.user_agents.retain(|_, seen| *seen >= cutoff);
+        if entry.ips.len() <= self.max_ips && entry.user_agents.len() <= self.max_user_agents {
+            return None;
+        }
+        entry.flagged = true;
+
+        let mut findings = self.findings.lock().unwrap();
+        let finding = AbuseFinding {
+            id: findings.back().map(|f| f.id + 1).unwrap_or(1),
+            user_address: None,
+            key_fingerprint: hex::encode(&Sha256::digest(api_key.as_bytes())[..8]),
+            detected_at_ms: now,
+            window_ms: self.window_ms,
+            ips: entry.ips.keys().cloned().collect(),
+            user_agents: entry.user_agents.keys().cloned().collect(),
+            reauth_required: self.require_reauth,
+        };
+        findings.push_back(finding.clone());
+        if findings.len() > MAX_FINDINGS {
+            findings.pop_front();
+        }
+        Some(finding)
+    }
+
+    fn set_user(&self, id: u64, user_address: &str) {
+        if let Some(finding) = self.findings.lock().unwrap().iter_mut().find(|f| f.id == id) {
+            finding.user_address = Some(user_address.to_string());
+        }
+    }
+
+    pub fn findings(&self) -> Vec<AbuseFinding> {
+        self.findings.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    /// Forget the client IPs and user agents seen for a deleted user's keys and findings
+    pub fn remove_user(&self, user_address: &str, api_keys: &[String]) -> usize {
+        let mut usage = self.usage.lock().unwrap();
+        let before = usage.len();
+        usage.retain(|key, _| !api_keys.contains(key));
+        let mut removed = before - usage.len();
+        let mut findings = self.findings.lock().unwrap();
+        let before = findings.len();
+        findings.retain(|f| !f.user_address.as_deref().is_some_and(|u| u.eq_ignore_ascii_case(user_address)));
+        removed += before - findings.len();
+        removed
+    }
+}
+
+/// Middleware: watch SIWE keys for use from many clients. Runs after auth, so only keys that
+/// authenticated are counted; the fixed key and share tokens are exempt.
+///
+/// A flagged session keeps working unless KEY_ABUSE_REQUIRE_REAUTH is set, in which case the
+/// key is refused until the owner signs in again (which issues a new key).
+pub async fn detect(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(api_key) = auth::api_key_from_headers(request.headers()).filter(|k| k.starts_with(API_KEY_PREFIX)) else {
+        return next.run(request).await;
+    };
+    let api_key = api_key.to_string();
+
+    let flagged = state.session_manager
+        .with_session(&api_key, |session| session.abuse_flagged_at_ms.is_some())
+        .unwrap_or(false);
+    if flagged && state.key_abuse.require_reauth {
+        return Json(error_codes::err_body(ErrorCode::ReauthRequired, SUSPENDED)).into_response();
+    }
+
+    let ip = match request.extensions().get::<ClientIp>() {
+        Some(ClientIp(Some(ip))) => Some(ip.to_string()),
+        _ => None,
+    };
+    let user_agent = request.headers().get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    if let Some(finding) = state.key_abuse.observe(&api_key, ip, user_agent) {
+        let user_address = state.session_manager.flag_abuse(&api_key, finding.detected_at_ms);
+        warn!("🚨 API key {} used from {} IPs / {} user agents within {}ms (user {:?})",
+            finding.key_fingerprint, finding.ips.len(), finding.user_agents.len(), finding.window_ms, user_address);
+        if let Some(user_address) = &user_address {
+            state.key_abuse.set_user(finding.id, user_address);
+        }
+        state.notifier.notify(Notification::new(
+            NotificationKind::Alert,
+            user_address.as_deref(),
+            "API key used from too many clients",
+            serde_json::json!({
+                "ips": finding.ips,
+                "user_agents": finding.user_agents,
+                "window_ms": finding.window_ms,
+                "reauth_required": finding.reauth_required
+            }),
+        ));
+        if finding.reauth_required {
+            return Json(error_codes::err_body(ErrorCode::ReauthRequired, SUSPENDED)).into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+/// GET /admin/key-abuse - Keys flagged for use from too many clients, newest first
+pub async fn admin_key_abuse(State(state): State<AppState>) -> Json<Value> {
+    let monitor = &state.key_abuse;
+    Json(serde_json::json!({
+        "thresholds": {
+            "window_ms": monitor.window_ms,
+            "max_ips": monitor.max_ips,
+            "max_user_agents": monitor.max_user_agents,
+            "require_reauth": monitor.require_reauth
+        },
+        "findings": monitor.findings()
+    }))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
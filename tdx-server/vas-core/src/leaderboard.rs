@@ -0,0 +1,237 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::auth;
+use crate::events::{EVENT_BACKFILL_FILLS, EVENT_WS_USER_FILLS};
+use crate::market::parse_number;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+const MAX_ALIAS_LEN: usize = 32;
+const MAX_WINDOW_DAYS: u64 = 90;
+const MAX_ROWS: usize = 100;
+
+/// A user's leaderboard opt-in
+#[derive(Debug, Clone, Serialize)]
+pub struct Participant {
+    /// Public name: the chosen alias, or a random handle that can't be linked to the address
+    pub handle: String,
+    /// Publish the address next to the handle
+    pub show_address: bool,
+    pub joined_at: u64,
+}
+
+/// Opted-in users by lowercased address; users are off the board unless they opt in
+#[derive(Debug, Default)]
+pub struct Leaderboard {
+    participants: HashMap<String, Participant>,
+}
+
+impl Leaderboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn participant(&self, user_address: &str) -> Option<Participant> {
+        self.participants.get(&user_address.to_lowercase()).cloned()
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> usize {
+        usize::from(self.participants.remove(&user_address.to_lowercase()).is_some())
+    }
+}
+
+/// Trading stats over the window, from the fills recorded off the WS feed
+#[derive(Debug, Default, Serialize)]
+pub struct TraderStats {
+    pub fills: u64,
+    pub volume_usd: f64,
+    pub realized_pnl_usd: f64,
+    pub fees_usd: f64,
+    /// Closing fills with positive PnL over all closing fills
+    pub win_rate: Option<f64>,
+    #[serde(skip)]
+    wins: u64,
+    #[serde(skip)]
+    closes: u64,
+}
+
+impl TraderStats {
+    fn add(&mut self, fill: &Value) {
+        let px = parse_number(fill.get("px")).unwrap_or(0.0);
+        let sz = parse_number(fill.get("sz")).unwrap_or(0.0);
+        let closed_pnl = parse_number(fill.get("closedPnl")).unwrap_or(0.0);
+
+        self.fills += 1;
+        self.volume_usd += px * sz;
+        self.fees_usd += parse_number(fill.get("fee")).unwrap_or(0.0);
+        self.realized_pnl_usd += closed_pnl;
+        if closed_pnl != 0.0 {
+            self.closes += 1;
+            if closed_pnl > 0.0 {
+                self.wins += 1;
+            }
+        }
+        self.win_rate = (self.closes > 0).then(|| self.wins as f64 / self.closes as f64);
+    }
+
+    /// PnL after fees, the ranking key
+    fn net_pnl(&self) -> f64 {
+        self.realized_pnl_usd - self.fees_usd
+    }
+}
+
+/// Query parameters for GET /leaderboard
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    pub window_days: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+/// GET /leaderboard - Opted-in traders ranked by net realized PnL over the window.
+///
+/// Stats are computed here from recorded fills; rows carry the participant's handle and
+/// only include the address for users who chose to show it.
+pub async fn get_leaderboard(
+    State(state): State<AppState>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Json<Value> {
+    let window_days = query.window_days.unwrap_or(7).clamp(1, MAX_WINDOW_DAYS);
+    let limit = query.limit.unwrap_or(50).clamp(1, MAX_ROWS);
+    let from_ms = now_secs().saturating_sub(window_days * 24 * 3600) * 1000;
+
+    let participants = state.leaderboard.read().await.participants.clone();
+    let store = state.event_store.read().await;
+    let mut rows: Vec<(Participant, String, TraderStats)> = participants.into_iter()
+        .map(|(user, participant)| {
+            let mut stats = TraderStats::default();
+            // Imported history is recorded at import time, so window on each fill's own time
+            let fills = [EVENT_WS_USER_FILLS, EVENT_BACKFILL_FILLS].into_iter()
+                .flat_map(|kind| store.of_kind_since(&user, kind, 0))
+                .flat_map(|event| event.payload.get("fills").and_then(|f| f.as_array()).into_iter().flatten())
+                .filter(|fill| fill.get("time").and_then(|t| t.as_u64()).is_some_and(|time| time >= from_ms));
+            for fill in fills {
+                stats.add(fill);
+            }
+            (participant, user, stats)
+        })
+        .filter(|(_, _, stats)| stats.fills > 0)
+        .collect();
+    drop(store);
+
+    rows.sort_by(|a, b| b.2.net_pnl().total_cmp(&a.2.net_pnl()));
+    let total = rows.len();
+    let entries: Vec<Value> = rows.into_iter()
+        .take(limit)
+        .enumerate()
+        .map(|(i, (participant, user, stats))| serde_json::json!({
+            "rank": i + 1,
+            "handle": participant.handle,
+            "address": participant.show_address.then_some(user),
+            "net_pnl_usd": stats.net_pnl(),
+            "stats": stats
+        }))
+        .collect();
+
+    Json(serde_json::json!({
+        "window_days": window_days,
+        "participants": total,
+        "entries": entries
+    }))
+}
+
+/// Leaderboard opt-in; `enabled: false` leaves the board
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardSettingsRequest {
+    pub enabled: bool,
+    #[serde(default)]
+    pub show_address: bool,
+    pub alias: Option<String>,
+}
+
+/// PUT /me/leaderboard - Join, update or leave the public leaderboard
+pub async fn set_participation(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<LeaderboardSettingsRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_address = session_user(&state, &headers).await?;
+    let mut board = state.leaderboard.write().await;
+
+    if !payload.enabled {
+        board.participants.remove(&user_address);
+        info!("🏆 {} left the leaderboard", user_address);
+        return Ok(Json(serde_json::json!({"status": "ok", "response": "removed"})));
+    }
+    if let Some(alias) = &payload.alias {
+        let valid = !alias.is_empty()
+            && alias.len() <= MAX_ALIAS_LEN
+            && alias.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        if board.participants.iter().any(|(user, p)| *user != user_address && p.handle.eq_ignore_ascii_case(alias)) {
+            return Err(StatusCode::CONFLICT);
+        }
+    }
+
+    let participant = board.participants.entry(user_address.clone()).or_insert_with(|| Participant {
+        handle: random_handle(),
+        show_address: false,
+        joined_at: now_secs(),
+    });
+    participant.show_address = payload.show_address;
+    if let Some(alias) = payload.alias {
+        participant.handle = alias;
+    }
+
+    info!("🏆 {} on the leaderboard as {} (address {})", user_address, participant.handle,
+        if participant.show_address { "shown" } else { "hidden" });
+    Ok(Json(serde_json::json!({"status": "ok", "response": participant.clone()})))
+}
+
+/// GET /me/leaderboard - The caller's leaderboard setting
+pub async fn get_participation(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    let board = state.leaderboard.read().await;
+    let participant = board.participants.get(&user_address.to_lowercase());
+
+    Ok(Json(serde_json::json!({
+        "enabled": participant.is_some(),
+        "participant": participant
+    })))
+}
+
+/// Handles are random rather than derived from the address, so they can't be reversed
+fn random_handle() -> String {
+    format!("trader-{}", &uuid::Uuid::new_v4().simple().to_string()[..8])
+}
+
+/// Trading session behind the request; share tokens can't change the setting
+async fn session_user(state: &AppState, headers: &HeaderMap) -> Result<String, StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok(user_address.to_lowercase())
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -0,0 +1,1301 @@
+//! Core of the verifiable agent service: sessions and API-key auth, policy, the signer and
+//! the Hyperliquid proxy, plus the HTTP handlers built on them.
+//!
+//! The `vas-server` binary only loads configuration, builds an [`AppState`] and serves
+//! [`router`]; tests, `vas-ctl` and other frontends can use the same pieces without HTTP.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Extension, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{Json, Response},
+    routing::{delete, get, patch, post, put},
+    Router,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower_http::cors::CorsLayer;
+use tracing::{info, error};
+
+mod activity;
+pub mod address;
+mod admin_auth;
+mod agent;
+mod agent_notes;
+mod agent_stats;
+pub mod agents;
+mod api_keys;
+mod attestation;
+pub mod audit;
+pub mod auth;
+mod automation;
+mod backfill;
+mod backtest;
+mod book;
+mod bots;
+mod bulk_cancel;
+mod bulk_orders;
+pub mod canonical_json;
+mod client_ip;
+mod compat;
+mod compliance;
+mod conditional;
+pub mod config;
+mod confirm;
+mod cosign;
+mod dca;
+mod delegation;
+mod drawdown;
+mod drift;
+mod effective_config;
+mod equity;
+mod encrypted_orders;
+mod error_codes;
+mod escrow_orders;
+mod events;
+mod evm;
+mod fees;
+mod funding;
+#[cfg(feature = "graphql")]
+mod graphql;
+mod ha;
+mod identity;
+mod idempotency;
+pub mod jsonl;
+mod key_abuse;
+mod leaderboard;
+pub mod listener;
+mod locale;
+pub mod log_level;
+mod margin;
+mod metrics;
+mod market;
+mod market_history;
+mod notify;
+mod oco;
+mod onboarding;
+mod order_defaults;
+mod order_guard;
+pub mod policy;
+pub mod preset_tdx;
+mod prices;
+mod privacy;
+mod probe;
+pub mod proxy;
+pub mod quote_archive;
+mod ratelimit;
+mod raw_exchange;
+mod recorder;
+mod rejections;
+mod replay;
+mod retention;
+mod risk;
+mod route_timeout;
+mod safe_mode;
+pub mod sealed_config;
+mod share;
+pub mod signer;
+mod simulate;
+mod slo;
+pub mod siwe_auth;
+mod status;
+mod strategy_limits;
+mod test_drive;
+mod tls_pin;
+mod typed_data;
+pub mod universal_signing;
+mod upstream_status;
+mod user_signed;
+mod verify_page;
+mod version;
+mod webhooks;
+mod ws_feed;
+
+use agent::AgentManager;
+use agents::{AgentSessionManager, SessionLifetime};
+use admin_auth::AdminGate;
+use audit::AuditLog;
+use agent_notes::AgentNotes;
+use automation::StrategyBook;
+use book::BookService;
+use client_ip::TrustedProxies;
+use compat::SchemaWatch;
+use rejections::{RejectionReason, RejectionStats};
+use conditional::{ConditionalOrderBook, PriceFeed};
+use config::Config;
+use confirm::ConfirmationQueue;
+use cosign::CosignManager;
+use dca::DcaScheduler;
+use delegation::DelegationManager;
+use drawdown::DrawdownGuards;
+use drift::DriftTracker;
+use error_codes::ErrorCode;
+use equity::EquityHistory;
+use privacy::DeletionQueue;
+use escrow_orders::EscrowBook;
+use events::EventStore;
+use ha::{Fence, HaRole};
+use idempotency::IdempotencyCache;
+use bots::BotMonitor;
+use key_abuse::KeyAbuseMonitor;
+use leaderboard::Leaderboard;
+use log_level::LogFilter;
+use market::MarketCache;
+use market_history::MarketHistory;
+use notify::{Notification, NotificationHub, NotificationKind};
+use oco::OcoBook;
+use order_defaults::OrderDefaultsStore;
+use preset_tdx::PresetTDXData;
+use probe::ProbeStatus;
+use proxy::HyperliquidProxy;
+use policy::Policy;
+use quote_archive::QuoteArchive;
+use ratelimit::RateLimiter;
+use recorder::DebugRecorder;
+use route_timeout::{Deadline, RouteTimeouts};
+use safe_mode::SafeMode;
+use share::ShareManager;
+use slo::{LatencySample, SloTracker};
+use status::StatusBoard;
+use strategy_limits::StrategyLimits;
+use signer::{ActionRequest, LocalBackend, RemoteAttestation, RemoteBackend, SignerBackend, SignerHandle};
+use universal_signing::{create_generic_action_hash, prepare_action, SignatureChain};
+use upstream_status::UpstreamMonitor;
+use ws_feed::WsFeed;
+
+#[derive(Clone)]
+pub struct AppState {
+    proxy: Arc<HyperliquidProxy>,
+    config: Arc<Config>,
+    agent_manager: Arc<RwLock<AgentManager>>,
+    session_manager: Arc<AgentSessionManager>,
+    market: Arc<MarketCache>,
+    event_store: Arc<RwLock<EventStore>>,
+    ws_feed: Arc<WsFeed>,
+    notifier: Arc<NotificationHub>,
+    signer: SignerHandle,
+    audit: Arc<RwLock<AuditLog>>,
+    admin: Arc<AdminGate>,
+    quote_archive: Arc<RwLock<QuoteArchive>>,
+    cosign: Arc<RwLock<CosignManager>>,
+    confirmations: Arc<RwLock<ConfirmationQueue>>,
+    delegations: Arc<RwLock<DelegationManager>>,
+    shares: Arc<RwLock<ShareManager>>,
+    policy: Arc<RwLock<Policy>>,
+    slo: Arc<RwLock<SloTracker>>,
+    ha: Arc<Fence>,
+    trusted_proxies: Arc<TrustedProxies>,
+    recorder: Arc<RwLock<DebugRecorder>>,
+    route_timeouts: Arc<RouteTimeouts>,
+    rate_limiter: Arc<RateLimiter>,
+    key_abuse: Arc<KeyAbuseMonitor>,
+    bots: Arc<BotMonitor>,
+    probe: Arc<RwLock<ProbeStatus>>,
+    drift: Arc<RwLock<DriftTracker>>,
+    conditional_orders: Arc<RwLock<ConditionalOrderBook>>,
+    drawdown: Arc<RwLock<DrawdownGuards>>,
+    dca: Arc<RwLock<DcaScheduler>>,
+    oco: Arc<RwLock<OcoBook>>,
+    status: Arc<RwLock<StatusBoard>>,
+    leaderboard: Arc<RwLock<Leaderboard>>,
+    schema: Arc<SchemaWatch>,
+    rejections: Arc<RejectionStats>,
+    idempotency: Arc<RwLock<IdempotencyCache>>,
+    order_defaults: Arc<RwLock<OrderDefaultsStore>>,
+    books: Arc<BookService>,
+    log_filter: Arc<LogFilter>,
+    escrow_orders: Arc<RwLock<EscrowBook>>,
+    market_history: Arc<MarketHistory>,
+    automation: Arc<RwLock<StrategyBook>>,
+    agent_notes: Arc<RwLock<AgentNotes>>,
+    strategy_limits: Arc<RwLock<StrategyLimits>>,
+    safe_mode: Arc<SafeMode>,
+    upstream: Arc<UpstreamMonitor>,
+    equity: Arc<RwLock<EquityHistory>>,
+    deletions: Arc<RwLock<DeletionQueue>>,
+}
+
+impl AppState {
+    /// Open the stores, upstream connections and signer described by `config`.
+    ///
+    /// Expects `PresetTDXData` to be initialized. Nothing is served and no background
+    /// workers run until [`AppState::spawn_workers`], so tests and other frontends can
+    /// drive the handlers directly.
+    pub async fn from_config(config: Arc<Config>, log_filter: Arc<LogFilter>) -> Result<Self, Box<dyn std::error::Error>> {
+        let trusted_proxies = Arc::new(TrustedProxies::parse(&config.trusted_proxies)?);
+        let route_timeouts = Arc::new(RouteTimeouts::parse(&config.route_timeouts)?);
+        if !["mainnet", "testnet"].contains(&config.hyperliquid_network.as_str()) {
+            return Err(format!("Invalid HYPERLIQUID_NETWORK '{}' (expected mainnet or testnet)", config.hyperliquid_network).into());
+        }
+        let (mainnet, testnet) = config.signature_chains();
+        SignatureChain::configure(mainnet, testnet)?;
+        info!("⛓️ Signing for {}: {:?}", config.hyperliquid_network, SignatureChain::resolve(config.is_mainnet()));
+
+        let proxy = if config.upstream_tls_pins.is_empty() {
+            HyperliquidProxy::new(&config.hyperliquid_url)
+        } else {
+            let pins = config.upstream_tls_pins.iter()
+                .map(|spec| tls_pin::TlsPin::parse(spec))
+                .collect::<Result<Vec<_>, _>>()?;
+            info!("📌 Pinning Hyperliquid TLS to {} keys", pins.len());
+            HyperliquidProxy::pinned(&config.hyperliquid_url, &pins).map_err(|e| e.to_string())?
+        };
+        let proxy = Arc::new(proxy);
+        let agent_manager = Arc::new(RwLock::new(AgentManager::new()));
+        let audit = Arc::new(RwLock::new(
+            AuditLog::open(config.audit_log_path.as_ref().map(std::path::PathBuf::from))
+                .map_err(|e| format!("Failed to open audit log: {}", e))?
+        ));
+        let ha_role = HaRole::parse(&config.ha_role)
+            .ok_or_else(|| format!("Invalid HA_ROLE '{}' (expected primary, standby or standalone)", config.ha_role))?;
+        if ha_role != HaRole::Standalone && config.ha_failover_ms <= config.ha_lease_ms {
+            return Err("HA_FAILOVER_MS must exceed HA_LEASE_MS so a partitioned primary stops signing first".into());
+        }
+        let ha = Arc::new(Fence::new(ha_role));
+        let safe_mode = Arc::new(SafeMode::new());
+        let signer = SignerHandle::spawn(create_signer_backend(&config)?, proxy.clone(), audit.clone(), ha.clone(), safe_mode.clone());
+        let mut quote_archive = QuoteArchive::open(config.quote_archive_path.as_ref().map(std::path::PathBuf::from))
+            .map_err(|e| format!("Failed to open quote archive: {}", e))?;
+        if let Some(preset_data) = PresetTDXData::get() {
+            quote_archive.record_current(preset_data);
+        }
+        let quote_archive = Arc::new(RwLock::new(quote_archive));
+        let policy = Policy::from_config(&config);
+        if let Some(entry) = audit.write().await.record_policy(serde_json::json!(policy)) {
+            info!("🧾 Policy change recorded in audit entry {}", entry.seq);
+        }
+        let policy = Arc::new(RwLock::new(policy));
+        let session_manager = Arc::new(AgentSessionManager::with_lifetime(SessionLifetime {
+            default_ttl_secs: config.session_ttl_secs,
+            max_ttl_secs: config.session_max_ttl_secs,
+            sliding: config.session_sliding_expiration,
+        }));
+        let market = Arc::new(MarketCache::new(
+            proxy.clone(),
+            std::time::Duration::from_millis(config.market_cache_ttl_ms),
+            std::time::Duration::from_millis(config.price_max_staleness_ms),
+        ));
+
+        let event_store = Arc::new(RwLock::new(
+            EventStore::open(config.event_store_path.as_ref().map(std::path::PathBuf::from))
+                .map_err(|e| format!("Failed to open event store: {}", e))?
+        ));
+        let ws_feed = WsFeed::spawn(config.hyperliquid_ws_url.clone());
+        events::spawn_ws_recorder(&ws_feed, event_store.clone());
+        let books = BookService::spawn(ws_feed.clone(), &config.book_assets, config.book_max_assets);
+        let market_history = Arc::new(MarketHistory::from_config(&config)?);
+        let notifier = Arc::new(NotificationHub::from_config(&config, signer.clone(), session_manager.clone()));
+        notify::spawn_fill_notifier(&ws_feed, notifier.clone());
+        let cosign = Arc::new(RwLock::new(CosignManager::new(config.cosign_timeout_secs)));
+        let slo = Arc::new(RwLock::new(SloTracker::new(config.slo_latency_target_ms, config.slo_objective)));
+        let confirmations = Arc::new(RwLock::new(ConfirmationQueue::new(config.confirm_timeout_secs)));
+        let recorder = Arc::new(RwLock::new(DebugRecorder::new(config.debug_recorder_capacity)));
+        let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit_per_minute));
+        let key_abuse = Arc::new(KeyAbuseMonitor::from_config(&config));
+        let bots = Arc::new(BotMonitor::from_config(&config));
+        let admin = Arc::new(AdminGate::from_config(&config));
+        let idempotency = Arc::new(RwLock::new(IdempotencyCache::new(config.idempotency_ttl_secs)));
+        let oco = Arc::new(RwLock::new(
+            OcoBook::open(config.oco_store_path.as_ref().map(std::path::PathBuf::from))
+                .map_err(|e| format!("Failed to open OCO store: {}", e))?
+        ));
+        let automation = Arc::new(RwLock::new(
+            StrategyBook::open(config.strategy_store_path.as_ref().map(std::path::PathBuf::from))
+                .map_err(|e| format!("Failed to open strategy store: {}", e))?
+        ));
+        let agent_notes = Arc::new(RwLock::new(
+            AgentNotes::open(config.agent_notes_path.as_ref().map(std::path::PathBuf::from))
+                .map_err(|e| format!("Failed to open agent notes: {}", e))?
+        ));
+        let equity = Arc::new(RwLock::new(
+            EquityHistory::open(config.equity_store_path.as_ref().map(std::path::PathBuf::from))
+                .map_err(|e| format!("Failed to open equity history: {}", e))?
+        ));
+        let deletions = Arc::new(RwLock::new(
+            DeletionQueue::open(config.deletion_store_path.as_ref().map(std::path::PathBuf::from))
+                .map_err(|e| format!("Failed to open deletion queue: {}", e))?
+        ));
+
+        Ok(AppState {
+            proxy,
+            config,
+            agent_manager,
+            session_manager,
+            market,
+            event_store,
+            ws_feed,
+            notifier,
+            signer,
+            audit,
+            admin,
+            quote_archive,
+            cosign,
+            confirmations,
+            delegations: Arc::new(RwLock::new(DelegationManager::new())),
+            shares: Arc::new(RwLock::new(ShareManager::new())),
+            policy,
+            slo,
+            ha,
+            trusted_proxies,
+            recorder,
+            route_timeouts,
+            rate_limiter,
+            key_abuse,
+            bots,
+            probe: Arc::new(RwLock::new(ProbeStatus::default())),
+            drift: Arc::new(RwLock::new(DriftTracker::new())),
+            conditional_orders: Arc::new(RwLock::new(ConditionalOrderBook::new())),
+            drawdown: Arc::new(RwLock::new(DrawdownGuards::new())),
+            dca: Arc::new(RwLock::new(DcaScheduler::new())),
+            oco,
+            status: Arc::new(RwLock::new(StatusBoard::new())),
+            leaderboard: Arc::new(RwLock::new(Leaderboard::new())),
+            schema: Arc::new(SchemaWatch::new()),
+            rejections: Arc::new(RejectionStats::new()),
+            idempotency,
+            order_defaults: Arc::new(RwLock::new(OrderDefaultsStore::new())),
+            books,
+            log_filter,
+            escrow_orders: Arc::new(RwLock::new(EscrowBook::new())),
+            market_history,
+            automation,
+            agent_notes,
+            strategy_limits: Arc::new(RwLock::new(StrategyLimits::new())),
+            safe_mode,
+            upstream: Arc::new(UpstreamMonitor::new()),
+            equity,
+            deletions,
+        })
+    }
+
+    /// Start the retention, probe, trigger and scheduling workers, and HA standby if configured
+    pub fn spawn_workers(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let price_feeds = self.config.external_price_feeds.iter()
+            .map(|spec| PriceFeed::parse(spec))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        retention::spawn_compactor(self.clone());
+        probe::spawn_probe(self.clone());
+        safe_mode::spawn_monitor(self.clone());
+        bots::spawn_watchdog(self.clone());
+        conditional::spawn_trigger_engine(self.clone(), price_feeds);
+        drawdown::spawn_monitor(self.clone());
+        dca::spawn_scheduler(self.clone());
+        compat::spawn_probe(self.clone());
+        oco::spawn_fill_watcher(self.clone());
+        oco::spawn_reconciler(self.clone());
+        escrow_orders::spawn_releaser(self.clone());
+        market_history::spawn_recorder(self.clone());
+        automation::spawn_runner(self.clone());
+        upstream_status::spawn_watcher(self.clone());
+        equity::spawn_scheduler(self.clone());
+        privacy::spawn_eraser(self.clone());
+
+        let ha_role = self.ha.role();
+        if ha_role == HaRole::Standby {
+            ha::spawn_standby(self.clone());
+        }
+        info!("🫂 HA role: {:?}", ha_role);
+        Ok(())
+    }
+}
+
+/// The HTTP API, with authentication for /exchange and the other per-user endpoints
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/health", get(health_check))
+        .route("/version", get(version::version))
+        .route("/readyz", get(probe::readyz))
+        .route("/status", get(status::get_status))
+        .route("/errors", get(error_codes::catalogue))
+        .route("/leaderboard", get(leaderboard::get_leaderboard))
+        .route("/market/funding", get(funding::market_funding))
+        .route("/market/prices", get(prices::market_prices))
+        .route("/market/book/:asset", get(book::get_book))
+        .route("/market/book/:asset/stream", get(book::stream_book))
+        .route("/market/history", get(market_history::market_history))
+        .route("/webhooks/public-key", get(webhooks::public_key))
+        .route("/encryption-key", get(encrypted_orders::encryption_key))
+        .route("/info", post(proxy_info).layer(DefaultBodyLimit::max(state.config.info_max_request_bytes)))
+        .route("/exchange", post(proxy_exchange))
+        .route("/debug/agent-address", get(get_agent_address))
+        // Agents API routes
+        .route("/agents/login", post(agents_login))
+        .route("/agents/quote", get(agents_quote))
+        .route("/agents/status", get(onboarding::agents_status))
+        .route("/agents/test-drive", post(test_drive::test_drive))
+        .route("/testnet/setup", post(test_drive::testnet_setup))
+        .route("/agents/verify-key", post(api_keys::verify_key))
+        .route("/agents", get(agent_notes::list_agents))
+        .route("/agents/:name", patch(agent_notes::patch_agent))
+        .route("/agents/:name/stats", get(agent_stats::agent_stats))
+        .route("/attestation/inactivity", get(attestation::inactivity_statement))
+        .route("/attestation/history", get(attestation::quote_history))
+        .route("/attestation/measurements", get(attestation::measurements))
+        .route("/verify", get(verify_page::index))
+        .route("/verify/verify.js", get(verify_page::script))
+        .route("/verify/expected", get(verify_page::expected))
+        .route("/debug/sessions", get(debug_sessions))
+        // Operator endpoints (signed with an admin key or wallet, see admin_auth)
+        .route("/admin/sessions", get(admin_sessions))
+        .route("/admin/slo", get(slo::admin_slo))
+        .route("/admin/metrics", get(metrics::admin_metrics))
+        .route("/admin/upstream-schema", get(compat::admin_upstream_schema))
+        .route("/admin/replay", post(replay::replay))
+        .route("/admin/policy/dry-run", post(policy::admin_policy_dry_run))
+        .route("/admin/tcb-recovery", post(attestation::tcb_recovery))
+        .route("/admin/reports/compliance", get(compliance::compliance_report))
+        .route("/admin/config/seal", post(sealed_config::admin_seal_value))
+        .route("/admin/effective-config", get(effective_config::effective_config))
+        .route("/admin/support-bundle", get(recorder::support_bundle))
+        .route("/admin/key-abuse", get(key_abuse::admin_key_abuse))
+        .route("/admin/bots", get(bots::admin_bots))
+        .route("/admin/rejections", get(rejections::admin_rejections))
+        .route("/admin/log-level", get(log_level::get_log_level).put(log_level::set_log_level))
+        .route("/admin/clock-drift", get(drift::admin_clock_drift))
+        .route("/admin/status", post(status::post_status_message))
+        .route("/admin/status/:id", delete(status::delete_status_message))
+        // Warm-standby peer endpoints (X-HA-Peer-Token)
+        .route("/ha/escrow", post(ha::escrow))
+        .route("/ha/heartbeat", post(ha::heartbeat))
+        .route("/ha/status", get(ha::status))
+        // Per-user account views
+        .route("/me/margin", get(margin::me_margin))
+        .route("/me/fees", get(fees::me_fees))
+        .route("/me/referrer", put(me_referrer))
+        .route("/me/cosigner", put(cosign::set_cosigner))
+        .route("/me/debug-recorder", put(recorder::set_recording))
+        .route("/me/grants", get(delegation::list_grants).post(delegation::create_grant))
+        .route("/me/grants/:id", delete(delegation::revoke_grant))
+        .route("/me/shares", get(share::list_shares).post(share::create_share))
+        .route("/me/shares/:token", delete(share::revoke_share))
+        .route("/me/conditional-orders", get(conditional::list_conditional).post(conditional::create_conditional))
+        .route("/me/conditional-orders/:id", delete(conditional::cancel_conditional))
+        .route("/me/drawdown-guard", get(drawdown::get_guard).put(drawdown::set_guard))
+        .route("/me/drawdown-guard/reset", post(drawdown::reset_guard))
+        .route("/me/recurring-orders", get(dca::list_plans).post(dca::create_plan))
+        .route("/me/recurring-orders/:id", delete(dca::cancel_plan))
+        .route("/me/recurring-orders/:id/pause", post(dca::pause_plan))
+        .route("/me/recurring-orders/:id/resume", post(dca::resume_plan))
+        .route("/me/backfill", post(backfill::backfill))
+        .route("/me/leaderboard", get(leaderboard::get_participation).put(leaderboard::set_participation))
+        .route("/me/order-defaults", get(order_defaults::get_defaults).put(order_defaults::set_defaults))
+        .route("/me/activity", get(activity::me_activity))
+        .route("/me/equity", get(equity::me_equity))
+        .route("/me/export", get(privacy::export_user))
+        .route("/me/delete", post(privacy::request_deletion).delete(privacy::cancel_deletion))
+        .route("/me/strategies", get(automation::list_strategies).post(automation::create_strategy))
+        .route("/me/strategies/:id", put(automation::update_strategy).delete(automation::cancel_strategy))
+        .route("/me/strategies/:id/pause", post(automation::pause_strategy))
+        .route("/me/strategies/:id/resume", post(automation::resume_strategy))
+        .route("/me/bot-liveness", get(bots::get_liveness).put(bots::set_liveness))
+        .route("/me/strategy-limits", get(strategy_limits::get_limits).put(strategy_limits::set_limits))
+        .route("/strategies/grid", post(automation::create_grid))
+        .route("/strategies/grid/:id", get(automation::grid_status))
+        .route("/strategies/rebalance", post(automation::rebalance_now))
+        .route("/me/locale", get(locale::get_locale).put(locale::set_locale))
+        .route("/exchange/cosign/:id", post(cosign::complete_cosign))
+        .route("/exchange/simulate", post(simulate::simulate))
+        .route("/backtest", post(backtest::backtest))
+        .route("/exchange/raw", post(raw_exchange::raw_exchange))
+        .route("/exchange/cancel-asset", post(bulk_cancel::cancel_asset))
+        .route("/exchange/pending", get(confirm::list_pending).post(confirm::resolve_pending))
+        .route("/orders/oco", get(oco::list_oco).post(oco::create_oco))
+        .route("/orders/oco/:id", delete(oco::delete_oco))
+        .route("/orders/escrow", get(escrow_orders::list_escrow).post(escrow_orders::create_escrow))
+        .route("/orders/escrow/:id", delete(escrow_orders::cancel_escrow))
+        .route("/orders/escrow/:id/release", post(escrow_orders::release_escrow))
+        .route("/events", get(events::get_events))
+        .route("/evm/sign-transaction", post(evm::sign_transaction))
+        .route("/sign/typed-data", post(typed_data::sign_typed_data))
+        .merge(graphql_routes())
+        // Inner to auth: counts only requests whose key authenticated
+        .route_layer(middleware::from_fn_with_state(state.clone(), bots::track))
+        .route_layer(middleware::from_fn_with_state(state.clone(), ratelimit::limit))
+        .route_layer(middleware::from_fn_with_state(state.clone(), key_abuse::detect))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |State(state): State<AppState>, req: Request, next: Next| async move {
+                // Only apply auth to /exchange, /me, /orders, /events, /evm, /sign, /admin and /ha endpoints
+                let path = req.uri().path();
+                if path.starts_with("/exchange") || path.starts_with("/me/") || path.starts_with("/orders/") || path == "/events"
+                    || path.starts_with("/evm/") || path.starts_with("/sign/") || path == "/agents/status"
+                    || path == "/agents/test-drive" || path == "/testnet/setup" || path == "/backtest"
+                    || path.starts_with("/strategies/") || path == "/graphql"
+                    || (path.starts_with("/agents/") && path.ends_with("/stats"))
+                    || path == "/agents" || (path.starts_with("/agents/") && req.method() == axum::http::Method::PATCH)
+                {
+                    auth::api_key_auth(State(state), req.headers().clone(), req, next).await
+                } else if path.starts_with("/admin/") {
+                    admin_auth::admin_auth(State(state), req, next).await
+                } else if path.starts_with("/ha/") {
+                    auth::ha_peer_auth(State(state), req.headers().clone(), req, next).await
+                } else {
+                    Ok(next.run(req).await)
+                }
+            }
+        ))
+        .route_layer(middleware::from_fn(attestation::require_key_binding))
+        .layer(middleware::from_fn_with_state(state.clone(), route_timeout::enforce))
+        // Outside the timeout so 504s get a coded body too
+        .layer(middleware::from_fn(error_codes::fill_error_body))
+        .layer(middleware::from_fn_with_state(state.clone(), recorder::record_traffic))
+        // Runs before auth so every handler and the audit log see the resolved client address
+        .layer(middleware::from_fn_with_state(state.clone(), client_ip::resolve_client_ip))
+        .with_state(state)
+        .layer(CorsLayer::permissive())
+}
+
+/// GraphQL account queries, when built with the `graphql` feature
+#[cfg(feature = "graphql")]
+fn graphql_routes() -> Router<AppState> {
+    Router::new()
+        .route("/graphql", post(graphql::graphql))
+        .route("/graphql/schema", get(graphql::sdl))
+}
+
+#[cfg(not(feature = "graphql"))]
+fn graphql_routes() -> Router<AppState> {
+    Router::new()
+}
+
+/// Select the signing backend from config
+pub(crate) fn create_signer_backend(config: &Config) -> Result<Arc<dyn SignerBackend>, Box<dyn std::error::Error>> {
+    let preset_data = PresetTDXData::get().ok_or("Preset TDX data not initialized")?;
+
+    match config.signer_backend.as_str() {
+        "local" => Ok(Arc::new(LocalBackend::new(preset_data.agent_private_key))),
+        "remote" => {
+            let url = config.remote_signer_url.clone()
+                .ok_or("REMOTE_SIGNER_URL is required for the remote signer backend")?;
+            let address = config.remote_signer_address.clone()
+                .ok_or("REMOTE_SIGNER_ADDRESS is required for the remote signer backend")?;
+            if !url.starts_with("https://") {
+                return Err("REMOTE_SIGNER_URL must use https".into());
+            }
+            let (Some(registry_address), Some(rpc_url)) = (config.registry_address.clone(), config.hyperevm_rpc_url.clone()) else {
+                return Err("REGISTRY_ADDRESS and HYPEREVM_RPC_URL are required to verify the remote signer's attestation".into());
+            };
+            let mrtds = config.remote_signer_mrtds.iter()
+                .map(hex::decode)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Invalid REMOTE_SIGNER_MRTDS: {}", e))?;
+            if mrtds.is_empty() {
+                return Err("REMOTE_SIGNER_MRTDS is required for the remote signer backend".into());
+            }
+            Ok(Arc::new(RemoteBackend::new(
+                url,
+                config.remote_signer_auth_token.clone(),
+                address,
+                RemoteAttestation { rpc_url, registry_address, mrtds },
+            )))
+        }
+        other => Err(format!("Unknown SIGNER_BACKEND: {}", other).into()),
+    }
+}
+
+async fn health_check(State(state): State<AppState>) -> Json<Value> {
+    let safe_mode = state.safe_mode.status();
+    Json(serde_json::json!({
+        "status": if safe_mode.active { "safe_mode" } else { "healthy" },
+        "service": "tdx-agent-server",
+        "version": version::VERSION,
+        "safe_mode": safe_mode
+    }))
+}
+
+async fn get_agent_address(State(state): State<AppState>) -> Json<Value> {
+    let agent_manager = state.agent_manager.read().await;
+    
+    if let Some(agent) = agent_manager.get_agent("test-key") {
+        Json(serde_json::json!({
+            "agent_address": agent.address,
+            "api_key": "test-key",
+            "note": "Master wallet must approve this agent address before trading"
+        }))
+    } else {
+        Json(serde_json::json!({
+            "error": "No agent found for test-key"
+        }))
+    }
+}
+
+/// Relay /info without decoding it: the body is forwarded as sent and the upstream answer
+/// as received. Only the request's `type` is read, to pick the streamed path.
+async fn proxy_info(
+    State(state): State<AppState>,
+    deadline: Option<Extension<Deadline>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, StatusCode> {
+    let is_json = headers.get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !is_json {
+        return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+    let info_type = proxy::info_type(&body).map_err(|_| StatusCode::BAD_REQUEST)?;
+    info!("Proxying info request: {} ({} bytes)", info_type, body.len());
+
+    if state.config.streamed_info_types.iter().any(|t| *t == info_type) {
+        return stream_info(&state, body, deadline.map(|Extension(d)| d)).await;
+    }
+
+    match state.proxy.passthrough_info_request(body, state.config.info_max_response_bytes).await {
+        Ok(response) => {
+            info!("Info request successful");
+            Response::builder()
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(response))
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        Err(e) => {
+            error!("Info request failed: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Pass a large info response through chunk by chunk instead of buffering it
+async fn stream_info(state: &AppState, body: Bytes, deadline: Option<Deadline>) -> Result<Response, StatusCode> {
+    let upstream = state.proxy.stream_info_request(body).await.map_err(|e| {
+        error!("Info request failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from_stream(route_timeout::with_deadline(upstream.bytes_stream(), deadline)))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn agents_login(
+    State(state): State<AppState>,
+    Json(payload): Json<siwe_auth::SiweLoginRequest>,
+) -> Result<Json<siwe_auth::SiweLoginResponse>, (StatusCode, Json<siwe_auth::SiweLoginError>)> {
+    let response = agents::agents_login(State(state.session_manager.clone()), Json(payload)).await?;
+
+    // Follow the user's fills so the event store can replay them later
+    state.ws_feed.subscribe(serde_json::json!({
+        "type": "userFills",
+        "user": response.user_address.to_lowercase()
+    })).await;
+
+    state.event_store.write().await.append(&response.user_address, events::EVENT_SESSION_LOGIN, serde_json::json!({
+        "agent_address": response.agent_address,
+        "expires_at": response.expires_at,
+        "client_ip": client_ip::current()
+    }));
+
+    tokio::spawn(identity::enrich_session(state.clone(), response.api_key.clone(), response.user_address.clone()));
+
+    state.notifier.notify(Notification::new(
+        NotificationKind::Session,
+        Some(&response.user_address),
+        "Agent session login",
+        serde_json::json!({"agent_address": response.agent_address, "expires_at": response.expires_at}),
+    ));
+
+    Ok(response)
+}
+
+async fn agents_quote() -> Result<Json<Value>, StatusCode> {
+    agents::agents_quote().await
+}
+
+async fn me_referrer(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<agents::ReferrerPreferenceRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    agents::set_referrer_preference(State(state.session_manager), headers, Json(payload)).await
+}
+
+async fn debug_sessions(
+    State(session_manager): State<AppState>,
+) -> Json<Value> {
+    agents::debug_sessions(State(session_manager.session_manager)).await
+}
+
+async fn admin_sessions(
+    State(state): State<AppState>,
+) -> Json<Value> {
+    agents::admin_sessions(State(state.session_manager)).await
+}
+
+pub(crate) async fn proxy_exchange(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<Value>,
+) -> Result<Json<Value>, StatusCode> {
+    let mut payload = match encrypted_orders::decrypt_request(&headers, payload) {
+        Ok(payload) => payload,
+        Err(body) => return Ok(Json(body)),
+    };
+    let api_key = auth::api_key_from_headers(&headers).map(str::to_string);
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+
+    // Retries under the same Idempotency-Key reuse the first attempt's response or nonce
+    let mut idempotency_guard = None;
+    let client_nonce = payload.get("nonce").and_then(|n| n.as_u64());
+    if let Some(api_key) = &api_key {
+        match idempotency::begin(&state, &headers, api_key, &payload, client_nonce.unwrap_or(now_ms)).await {
+            Some(idempotency::Decision::Replay(mut response)) => {
+                if let Some(obj) = response.as_object_mut() {
+                    obj.insert("idempotent_replay".to_string(), Value::Bool(true));
+                }
+                return Ok(Json(response));
+            }
+            Some(idempotency::Decision::Conflict(reason)) => {
+                return Ok(Json(error_codes::err_body(ErrorCode::IdempotencyConflict, reason)));
+            }
+            Some(idempotency::Decision::Proceed { nonce, guard }) => {
+                if let Some(obj) = payload.as_object_mut() {
+                    obj.insert("nonce".to_string(), serde_json::json!(nonce));
+                }
+                idempotency_guard = Some(guard);
+            }
+            None => {}
+        }
+    }
+
+    let all_or_nothing = match bulk_orders::take_all_or_nothing(&mut payload) {
+        Ok(all_or_nothing) => all_or_nothing,
+        Err(reason) => return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, reason))),
+    };
+
+    // Fields left out of orders are only filled from defaults the key owner configured
+    let defaults_applied = match &api_key {
+        Some(api_key) => order_defaults::apply(&state, api_key, &mut payload).await,
+        None => Vec::new(),
+    };
+
+    // Client nonces are timestamps; check them against the window and track the client's clock
+    if let (Some(nonce), Some(api_key)) = (client_nonce, &api_key) {
+        if let Err(reason) = drift::check_nonce(&state, api_key, nonce).await {
+            return Ok(Json(error_codes::err_body(ErrorCode::NonceOutOfWindow, reason)));
+        }
+    }
+
+    // Cancels and modifies can be made conditional on the targeted orders' upstream state
+    match order_guard::Expectation::take(&mut payload) {
+        Ok(None) => {}
+        Ok(Some(expected)) => {
+            let account = match payload.get("vaultAddress").and_then(|v| v.as_str()) {
+                Some(vault) => Some(vault.to_string()),
+                None => match &api_key {
+                    Some(api_key) => auth::user_address_for_api_key(&state, api_key).await,
+                    None => None,
+                },
+            };
+            let Some(account) = account else {
+                return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, "Order preconditions need a session or vaultAddress")));
+            };
+            let action = payload.get("action").cloned().unwrap_or_default();
+            if let Err(body) = order_guard::check(&state, &account, &action, &expected).await {
+                return Ok(Json(body));
+            }
+        }
+        Err(reason) => return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, reason))),
+    }
+
+    // Pin the nonce up front so the reported action hash covers exactly what gets signed
+    if let Some(obj) = payload.as_object_mut() {
+        if obj.get("nonce").and_then(|n| n.as_u64()).is_none() {
+            obj.insert("nonce".to_string(), serde_json::json!(now_ms));
+        }
+    }
+
+    let envelope = exchange_envelope(&payload);
+    let user_address = match &api_key {
+        Some(api_key) => auth::user_address_for_api_key(&state, api_key).await,
+        None => None,
+    };
+    // What gets signed, for reporting (and undoing) multi-order outcomes
+    let submitted = ActionRequest {
+        action: payload.get("action").cloned().unwrap_or_default(),
+        nonce: payload.get("nonce").and_then(|n| n.as_u64()).unwrap_or(now_ms),
+        vault_address: payload.get("vaultAddress").and_then(|v| v.as_str()).map(str::to_string),
+        is_mainnet: state.config.is_mainnet(),
+        user_address: user_address.clone(),
+    };
+    let mut response = match handle_exchange(state.clone(), headers, payload).await {
+        Ok(Json(response)) => response,
+        Err(status) => {
+            if let Some(guard) = idempotency_guard {
+                guard.fail();
+            }
+            return Err(status);
+        }
+    };
+
+    bulk_orders::settle(&state, &submitted, &mut response, all_or_nothing).await;
+    if let (Some(obj), Some(envelope)) = (response.as_object_mut(), envelope) {
+        obj.extend(envelope);
+    }
+    if let (Some(obj), false) = (response.as_object_mut(), defaults_applied.is_empty()) {
+        obj.insert("defaults_applied".to_string(), Value::Array(defaults_applied));
+    }
+    if let Some(guard) = idempotency_guard {
+        guard.complete(&response);
+    }
+
+    if let Some(user_address) = user_address {
+        state.event_store.write().await.append(&user_address, events::EVENT_EXCHANGE_RESPONSE, response.clone());
+    }
+
+    Ok(Json(response))
+}
+
+/// Fields tying an /exchange response back to the enclave identity:
+/// the action hash, the signing agent and the attestation quote in force
+fn exchange_envelope(payload: &Value) -> Option<serde_json::Map<String, Value>> {
+    let preset_data = PresetTDXData::get()?;
+    let nonce = payload.get("nonce")?.as_u64()?;
+    let vault_address = payload.get("vaultAddress").and_then(|v| v.as_str());
+    // Hash what the signer submits (canonical decimals), matching the audit log entry
+    let action = prepare_action(payload.get("action")?).ok()?;
+
+    let action_hash = match create_generic_action_hash(&action, nonce, vault_address) {
+        Ok(hash) => format!("{:?}", hash),
+        Err(e) => {
+            error!("❌ Failed to compute action hash for envelope: {}", e);
+            return None;
+        }
+    };
+
+    let mut envelope = serde_json::Map::new();
+    envelope.insert("action_hash".to_string(), Value::String(action_hash));
+    envelope.insert("agent_address".to_string(), Value::String(preset_data.agent_address.clone()));
+    envelope.insert("attestation".to_string(), preset_data.attestation_reference());
+    Some(envelope)
+}
+
+async fn handle_exchange(
+    state: AppState,
+    headers: HeaderMap,
+    payload: Value,
+) -> Result<Json<Value>, StatusCode> {
+    info!("🔄 Processing exchange request with universal signing");
+    let received_at = std::time::Instant::now();
+    
+    // Extract API key (already validated by middleware)
+    let api_key = headers
+        .get("X-API-Key")
+        .and_then(|value| value.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    
+    if api_key == state.config.fixed_api_key {
+        info!("🔑 Signing with preset TDX agent for fixed API key");
+    } else {
+        info!("🔑 Signing with preset TDX agent for SIWE API key");
+    }
+    
+    // Extract action and nonce from payload
+    let action = payload.get("action")
+        .ok_or(StatusCode::BAD_REQUEST)?
+        .clone();
+    
+    let nonce = payload.get("nonce")
+        .and_then(|n| n.as_u64())
+        .unwrap_or_else(|| {
+            // Generate nonce if not provided
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64
+        });
+    
+    // Extract vault address if present
+    let vault_address = payload.get("vaultAddress")
+        .and_then(|v| v.as_str());
+    
+    // Determine if mainnet based on config
+    let is_mainnet = state.config.is_mainnet();
+    
+    info!("📋 Action: {:?}", action.get("type"));
+    info!("📋 Nonce: {}", nonce);
+    info!("📋 Vault: {:?}", vault_address);
+    info!("📋 Mainnet: {}", is_mainnet);
+    
+    // Check if this is an approveAgent request (should be forwarded as pre-signed)
+    let action_type = action.get("type").and_then(|t| t.as_str());
+    
+    if action_type == Some("approveAgent") {
+        info!("🔓 ApproveAgent detected - forwarding pre-signed master wallet request");
+        
+        // Check if request has signature (should be pre-signed by master wallet)
+        if payload.get("signature").is_some() {
+            info!("📝 ApproveAgent has signature - verifying and forwarding");
+            
+            // Validate nonce consistency (security check)
+            let request_nonce = payload.get("nonce").and_then(|n| n.as_u64());
+            let action_nonce = action.get("nonce").and_then(|n| n.as_u64());
+            
+            if request_nonce != action_nonce {
+                error!("❌ Nonce mismatch: request={:?} vs action={:?}", request_nonce, action_nonce);
+                
+                let error_response = serde_json::json!({
+                    "status": "err",
+                    "code": ErrorCode::NonceMismatch,
+                    "response": "Nonce mismatch between request body and action structure",
+                    "details": {
+                        "request_nonce": request_nonce,
+                        "action_nonce": action_nonce
+                    }
+                });
+                
+                return Ok(Json(error_response));
+            }
+            
+            // Catch wrong-wallet or wrong-domain signatures here rather than as an opaque upstream rejection
+            if let Err(error_response) = user_signed::verify_relay(&state, api_key, &payload, is_mainnet).await {
+                return Ok(Json(error_response));
+            }
+            
+            // Forward the pre-signed request directly via proxy
+            match state.proxy.proxy_exchange_request(&payload).await {
+                Ok(response) => {
+                    info!("✅ ApproveAgent forwarded successfully");
+                    info!("📊 Response: {:?}", response);
+                    if response.get("status").and_then(|s| s.as_str()) == Some("ok") {
+                        state.session_manager
+                            .advance_onboarding(api_key, onboarding::OnboardingState::AgentApproved);
+                    }
+                    Ok(Json(response))
+                }
+                Err(e) => {
+                    error!("❌ ApproveAgent forwarding failed: {:?}", e);
+                    Err(StatusCode::BAD_REQUEST)
+                }
+            }
+        } else {
+            info!("❌ ApproveAgent missing signature");
+            
+            // Return helpful error for unsigned approveAgent requests
+            let error_response = serde_json::json!({
+                "status": "err",
+                "code": ErrorCode::ApproveAgentUnsigned,
+                "response": "ApproveAgent requests must be signed by the master wallet before sending to TDX server",
+                "note": "This action approves the TDX agent and must be signed by your master wallet, not the TDX agent itself"
+            });
+            
+            Ok(Json(error_response))
+        }
+    } else {
+        // Sessions must finish onboarding before the agent signs for them (delegates act for a grantor)
+        let delegated = headers.contains_key(delegation::DELEGATED_FROM_HEADER);
+        if api_key != state.config.fixed_api_key && !delegated {
+            let current = state.session_manager.with_session(api_key, |session| session.onboarding);
+            if let Some(current) = current {
+                let onboarding = if current.can_trade() {
+                    current
+                } else {
+                    onboarding::refresh(&state, api_key).await.unwrap_or(current)
+                };
+                if !onboarding.can_trade() {
+                    error!("❌ Agent not approved for session (onboarding: {:?})", onboarding);
+                    return Ok(Json(onboarding::not_ready_response(onboarding)));
+                }
+            }
+        }
+
+        // Enforce the session scope required by this action type (the fixed key has full access)
+        let required_scope = agents::required_scope(action_type.unwrap_or_default());
+        if api_key != state.config.fixed_api_key {
+            let allowed = state.session_manager
+                .with_session(api_key, |session| session.has_scope(required_scope))
+                .unwrap_or(false);
+            if !allowed {
+                error!("❌ API key lacks '{}' scope for {:?}", required_scope, action_type);
+                return Ok(Json(error_codes::err_body(ErrorCode::ScopeNotAllowed, format!("API key is not authorized for the '{}' scope", required_scope))));
+            }
+            if let Err(body) = check_asset_allowlist(&state, api_key, &action).await {
+                return Ok(Json(body));
+            }
+        }
+
+        // Grantees act on the grantor's account, within the limits of the delegation chain
+        let user_address = match headers.get(delegation::DELEGATED_FROM_HEADER).and_then(|v| v.to_str().ok()) {
+            Some(grantor) => {
+                let grantee = auth::user_address_for_api_key(&state, api_key).await.unwrap_or_default();
+                let resolved = state.delegations.write().await.resolve(&grantee, grantor);
+                let authorized = resolved
+                    .ok_or_else(|| format!("No active delegation from {} to this session", grantor))
+                    .and_then(|d| d.authorize(&action).map(|_| d.grantor));
+                match authorized {
+                    Ok(grantor) => {
+                        info!("🎫 {} acting on behalf of {}", grantee, grantor);
+                        Some(grantor)
+                    }
+                    Err(reason) => {
+                        error!("❌ Delegation rejected: {}", reason);
+                        return Ok(Json(error_codes::err_body(ErrorCode::DelegationRejected, reason)));
+                    }
+                }
+            }
+            None => auth::user_address_for_api_key(&state, api_key).await,
+        };
+
+        // Convert the action as it will be signed and check orders against the perp universe
+        if let Err(body) = rejections::check(&state, &action, user_address.as_deref()).await {
+            return Ok(Json(body));
+        }
+
+        // Safe mode after a failed re-attestation: only cancels and reduce-only orders
+        if let Err(reason) = state.safe_mode.check_action(&action) {
+            error!("🛡️ {}", reason);
+            return Ok(Json(error_codes::err_body(ErrorCode::SafeModeRestricted, reason)));
+        }
+
+        // Policy engine: static rules (builder fees, ...) checked before any market-dependent risk checks
+        if let Err(violation) = state.policy.read().await.evaluate(&action) {
+            error!("❌ Policy rejected action: {}", violation.message);
+            state.rejections.record(RejectionReason::PolicyDenial, &action, user_address.as_deref(), &violation.message);
+            return Ok(Json(violation.to_response()));
+        }
+
+        // A tripped drawdown guard holds the account to reduce-only orders
+        if action_type == Some("order") {
+            if let Some(user_address) = &user_address {
+                if let Err(reason) = drawdown::check_reduce_only(&state, user_address, &action).await {
+                    error!("❌ {}", reason);
+                    return Ok(Json(error_codes::err_body(ErrorCode::DrawdownReduceOnly, reason)));
+                }
+            }
+        }
+
+        // Pre-sign risk checks for orders placed on behalf of a known user
+        let mut risk_warnings = Vec::new();
+        if action_type == Some("order") {
+            if let Some(user_address) = &user_address {
+                if let Err(reason) = run_risk_checks(&state, user_address, &action, &mut risk_warnings).await {
+                    error!("❌ Risk check rejected order: {}", reason);
+                    state.notifier.notify(Notification::new(
+                        NotificationKind::Alert,
+                        Some(user_address),
+                        "Order rejected by risk check",
+                        serde_json::json!({"reason": reason}),
+                    ));
+                    return Ok(Json(error_codes::err_body(ErrorCode::RiskCheckFailed, reason)));
+                }
+            }
+        }
+
+        // Per-strategy sub-limits, counted only once the order passed every other check
+        if action_type == Some("order") {
+            if let Some(user_address) = &user_address {
+                let now_ms = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as u64;
+                if let Err(violation) = state.strategy_limits.write().await.charge_request(&headers, user_address, &action, now_ms) {
+                    error!("❌ Policy rejected action: {}", violation.message);
+                    state.rejections.record(RejectionReason::PolicyDenial, &action, Some(user_address), &violation.message);
+                    return Ok(Json(violation.to_response()));
+                }
+            }
+        }
+
+        // Apply the referrer code once, before the session's first trade
+        if action_type == Some("order") {
+            apply_pending_referrer(&state, api_key, is_mainnet, user_address.clone()).await;
+        }
+
+        let request = ActionRequest {
+            action: action.clone(),
+            nonce,
+            vault_address: vault_address.map(|v| v.to_string()),
+            is_mainnet,
+            user_address,
+        };
+
+        // Large orders wait in the confirmation queue instead of being signed immediately
+        if let Some(threshold) = state.config.confirm_notional_threshold {
+            if action_type == Some("order") && confirm::order_notional(&action) > threshold {
+                let pending = state.confirmations.write().await.enqueue(api_key, request);
+                info!("⏸️ Order {} ({:.2} USD) held for confirmation", pending.id, pending.notional);
+                return Ok(Json(serde_json::json!({
+                    "status": "pending_confirmation",
+                    "response": pending,
+                    "warnings": risk_warnings
+                })));
+            }
+        }
+
+        submit_action(&state, api_key, request, risk_warnings, received_at).await
+    }
+}
+
+/// Hand a fully-checked action to the signer, or park it for the session's co-signer.
+/// This is the only place the co-signer gate applies; signing paths that skip it must
+/// refuse co-sign sessions themselves.
+pub(crate) async fn submit_action(
+    state: &AppState,
+    api_key: &str,
+    request: ActionRequest,
+    warnings: Vec<String>,
+    received_at: std::time::Instant,
+) -> Result<Json<Value>, StatusCode> {
+    // Approval gate, not a key split: park the action until the co-signer signs its digest
+    let cosigner_address = state.session_manager
+        .with_session(api_key, |session| session.cosigner_address.clone())
+        .flatten();
+    if let Some(cosigner_address) = cosigner_address {
+        let pending = state.cosign.write().await.create(api_key, &cosigner_address, request);
+        return match pending {
+            Ok(pending) => {
+                info!("🤝 Action parked for co-signature: {}", pending.id);
+                Ok(Json(serde_json::json!({
+                    "status": "pending_cosign",
+                    "response": {
+                        "cosign_id": pending.id,
+                        "digest": pending.digest,
+                        "cosigner_address": pending.cosigner_address,
+                        "expires_at": pending.expires_at,
+                        "complete_url": format!("/exchange/cosign/{}", pending.id)
+                    }
+                })))
+            }
+            Err(e) => {
+                error!("❌ Failed to create co-sign request: {}", e);
+                Err(StatusCode::BAD_REQUEST)
+            }
+        };
+    }
+
+    // While Hyperliquid is down, cancels wait for it instead of failing
+    let queueable = state.config.upstream_queue_cancels && upstream_status::is_queueable(&request.action);
+    if queueable && !state.upstream.is_available() {
+        return Ok(Json(queue_cancel(state, request)));
+    }
+    let retained = queueable.then(|| request.clone());
+
+    // Kept for the fee estimate on the response; the request itself moves into the signer
+    let is_order = request.action.get("type").and_then(|t| t.as_str()) == Some("order");
+    let fee_context = match &request.user_address {
+        Some(user) if is_order && state.config.fee_estimates_in_responses => Some((user.clone(), request.action.clone())),
+        _ => None,
+    };
+
+    let assets = slo::action_assets(&state.market, &request.action).await;
+
+    // Handle other actions with SDK (order, cancel, etc.)
+    let signing_started = std::time::Instant::now();
+    let result = state.signer.sign_action(request).await;
+    state.slo.write().await.record(LatencySample {
+        at_ms: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64,
+        assets,
+        end_to_end_ms: received_at.elapsed().as_millis() as u64,
+        upstream_ms: signing_started.elapsed().as_millis() as u64,
+        ok: result.as_ref().is_ok_and(|response| response.get("status").and_then(|s| s.as_str()) != Some("err")),
+    });
+
+    match result {
+        Ok(mut response) => {
+            info!("✅ SDK handled request completely");
+            state.upstream.observe_ok();
+            state.schema.inspect_exchange_response(&response);
+            if response.get("status").and_then(|s| s.as_str()) == Some("err") && response.get("code").is_none() {
+                let message = response.get("response").map(|r| r.to_string()).unwrap_or_default();
+                response["code"] = serde_json::json!(ErrorCode::for_upstream(&message));
+            }
+            if !warnings.is_empty() {
+                response["warnings"] = serde_json::json!(warnings);
+            }
+            if let Some((user_address, action)) = fee_context {
+                let rates = fees::rates_for(state, &user_address).await;
+                if let Some(estimate) = fees::estimate_order_fees(&action, rates) {
+                    response["estimated_fees"] = serde_json::json!(estimate);
+                }
+            }
+            Ok(Json(response))
+        }
+        Err(e) => {
+            error!("❌ SDK request handling failed: {:?}", e);
+            // Venue downtime is reported as such, so clients don't mistake it for a fault here
+            if let Some(outage) = state.upstream.observe_error(&e.to_string()) {
+                if let Some(request) = retained {
+                    return Ok(Json(queue_cancel(state, request)));
+                }
+                return Ok(Json(error_codes::err_body(
+                    ErrorCode::UpstreamUnavailable,
+                    format!("Hyperliquid is {}: {}", outage.as_str(), e),
+                )));
+            }
+            // Surface upstream throttling with its code so clients can back off
+            if ErrorCode::for_upstream(&e.to_string()) == ErrorCode::UpstreamRateLimited {
+                return Ok(Json(error_codes::err_body(ErrorCode::UpstreamRateLimited, e.to_string())));
+            }
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// Hold a checked cancel until Hyperliquid returns
+fn queue_cancel(state: &AppState, request: ActionRequest) -> Value {
+    match state.upstream.enqueue(request) {
+        Ok(id) => {
+            info!("🚧 Cancel {} queued until Hyperliquid returns", id);
+            upstream_status::queued_response(&id, &state.upstream.status())
+        }
+        Err(reason) => error_codes::err_body(ErrorCode::UpstreamUnavailable, reason),
+    }
+}
+
+/// Sign setReferrer with the agent key if the session still has a referrer pending.
+/// Failures (e.g. a referrer already set upstream) are logged and never block the order.
+pub(crate) async fn apply_pending_referrer(
+    state: &AppState,
+    api_key: &str,
+    is_mainnet: bool,
+    user_address: Option<String>,
+) {
+    let code = state.session_manager.take_pending_referrer(api_key, state.config.referrer_code.as_deref());
+
+    if let Some(code) = code {
+        match state.signer.set_referrer(code, is_mainnet, user_address).await {
+            Ok(response) => info!("🏷️ setReferrer response: {:?}", response),
+            Err(e) => error!("❌ setReferrer failed: {:?}", e),
+        }
+    }
+}
+
+/// Refuse actions on coins outside the asset allowlist signed into the session's SIWE login
+pub(crate) async fn check_asset_allowlist(state: &AppState, api_key: &str, action: &Value) -> Result<(), Value> {
+    let Some(allowed) = state.session_manager.with_session(api_key, |session| session.allowed_assets.clone()).flatten() else {
+        return Ok(());
+    };
+    let outside = agents::disallowed_assets(&state.market, &allowed, action).await;
+    if outside.is_empty() {
+        return Ok(());
+    }
+    error!("❌ API key is limited to {:?} but the action touches {:?}", allowed, outside);
+    Err(error_codes::err_body(ErrorCode::AssetNotAllowed, format!("API key is not authorized for {}", outside.join(", "))))
+}
+
+/// Run the configured pre-sign risk checks, collecting non-fatal warnings
+pub(crate) async fn run_risk_checks(
+    state: &AppState,
+    user_address: &str,
+    action: &Value,
+    warnings: &mut Vec<String>,
+) -> Result<(), String> {
+    if let Some(max_margin_usage) = state.config.max_margin_usage {
+        risk::check_order_margin(&state.market, user_address, action, max_margin_usage).await?;
+    }
+
+    if let Some(band) = state.config.liquidation_guard_band {
+        warnings.extend(risk::check_liquidation_distance(&state.market, user_address, action, band, state.config.liquidation_guard_mode).await?);
+    }
+
+    Ok(())
+}
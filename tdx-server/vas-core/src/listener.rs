@@ -0,0 +1,169 @@
+use axum::{extract::ConnectInfo, Router};
+use hyper::body::Incoming;
+use hyper_util::rt::TokioIo;
+use socket2::{Domain, Socket, Type};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_native_tls::{native_tls, TlsAcceptor};
+use tower::Service;
+use tracing::{info, warn, error};
+
+/// Where a listener binds
+#[derive(Debug, Clone)]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+/// One listener from LISTENERS: `<addr>[;tls_cert=<pem>;tls_key=<pkcs8 pem>]`.
+/// `<addr>` is `host:port`, `[v6]:port` or `unix:/path/to.sock`.
+#[derive(Debug, Clone)]
+pub struct ListenerConfig {
+    pub addr: ListenAddr,
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+}
+
+impl ListenerConfig {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut parts = spec.split(';').map(|p| p.trim());
+        let addr = parts.next().filter(|a| !a.is_empty()).ok_or("Empty listener address")?;
+
+        let addr = match addr.strip_prefix("unix:") {
+            Some(path) => ListenAddr::Unix(PathBuf::from(path)),
+            None => ListenAddr::Tcp(addr.parse().map_err(|e| format!("Invalid listener address '{}': {}", addr, e))?),
+        };
+
+        let mut config = Self { addr, tls_cert: None, tls_key: None };
+        for option in parts {
+            match option.split_once('=') {
+                Some(("tls_cert", path)) => config.tls_cert = Some(PathBuf::from(path)),
+                Some(("tls_key", path)) => config.tls_key = Some(PathBuf::from(path)),
+                _ => return Err(format!("Unknown listener option '{}'", option)),
+            }
+        }
+        if config.tls_cert.is_some() != config.tls_key.is_some() {
+            return Err(format!("Listener '{}' needs both tls_cert and tls_key", spec));
+        }
+
+        Ok(config)
+    }
+
+    fn describe(&self) -> String {
+        let scheme = if self.tls_cert.is_some() { "https" } else { "http" };
+        match &self.addr {
+            ListenAddr::Tcp(addr) => format!("{}://{}", scheme, addr),
+            ListenAddr::Unix(path) => format!("{}+unix://{}", scheme, path.display()),
+        }
+    }
+
+    fn tls_acceptor(&self) -> Result<Option<TlsAcceptor>, Box<dyn std::error::Error>> {
+        let (Some(cert), Some(key)) = (&self.tls_cert, &self.tls_key) else {
+            return Ok(None);
+        };
+        let identity = native_tls::Identity::from_pkcs8(&std::fs::read(cert)?, &std::fs::read(key)?)?;
+        Ok(Some(TlsAcceptor::from(native_tls::TlsAcceptor::new(identity)?)))
+    }
+}
+
+/// Bind every configured listener and serve `app` on all of them until one fails
+pub async fn serve_all(app: Router, listeners: Vec<ListenerConfig>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tasks = tokio::task::JoinSet::new();
+
+    for listener in listeners {
+        let tls = listener.tls_acceptor()?;
+        let description = listener.describe();
+
+        match &listener.addr {
+            ListenAddr::Tcp(addr) => {
+                let tcp = bind_tcp(*addr)?;
+                let app = app.clone();
+                tasks.spawn(async move {
+                    loop {
+                        match tcp.accept().await {
+                            Ok((stream, peer)) => {
+                                spawn_connection(stream, Some(peer), tls.clone(), app.clone());
+                            }
+                            Err(e) => warn!("⚠️ Accept failed: {}", e),
+                        }
+                    }
+                });
+            }
+            ListenAddr::Unix(path) => {
+                // A socket file left by a previous run would make bind fail
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+                let unix = tokio::net::UnixListener::bind(path)?;
+                let app = app.clone();
+                tasks.spawn(async move {
+                    loop {
+                        match unix.accept().await {
+                            Ok((stream, _)) => spawn_connection(stream, None, tls.clone(), app.clone()),
+                            Err(e) => warn!("⚠️ Accept failed: {}", e),
+                        }
+                    }
+                });
+            }
+        }
+
+        println!("🌐 TDX Agent Server listening on {}", description);
+        info!("TDX Agent Server listening on {}", description);
+    }
+
+    if tasks.join_next().await.is_some() {
+        return Err("Listener task exited".into());
+    }
+    Ok(())
+}
+
+/// IPv6 sockets are bound v6-only so `0.0.0.0:p` and `[::]:p` can be configured side by side
+fn bind_tcp(addr: SocketAddr) -> Result<tokio::net::TcpListener, Box<dyn std::error::Error>> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, None)?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(true)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+
+    Ok(tokio::net::TcpListener::from_std(socket.into())?)
+}
+
+fn spawn_connection<S>(stream: S, peer: Option<SocketAddr>, tls: Option<TlsAcceptor>, app: Router)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        match tls {
+            Some(acceptor) => match acceptor.accept(stream).await {
+                Ok(stream) => serve_connection(stream, peer, app).await,
+                Err(e) => warn!("⚠️ TLS handshake failed: {}", e),
+            },
+            None => serve_connection(stream, peer, app).await,
+        }
+    });
+}
+
+async fn serve_connection<S>(stream: S, peer: Option<SocketAddr>, app: Router)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    // Handlers see the TCP peer through the usual ConnectInfo extractor (absent on unix sockets)
+    let service = hyper::service::service_fn(move |mut request: hyper::Request<Incoming>| {
+        if let Some(peer) = peer {
+            request.extensions_mut().insert(ConnectInfo(peer));
+        }
+        app.clone().call(request)
+    });
+
+    if let Err(e) = hyper::server::conn::http1::Builder::new()
+        .serve_connection(TokioIo::new(stream), service)
+        .with_upgrades()
+        .await
+    {
+        error!("❌ Connection error: {}", e);
+    }
+}
@@ -0,0 +1,111 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+/// How a session wants human-facing times and text rendered.
+///
+/// Only ever adds display fields: machine fields (`*_ms`, `timestamp`) stay UTC unix time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Locale {
+    /// BCP 47 language tag, e.g. "en", "de-CH"
+    pub language: String,
+    /// Fixed UTC offset ("+09:00", "-05:30") or "UTC"
+    pub timezone: String,
+}
+
+impl Locale {
+    pub fn new(language: &str, timezone: &str) -> Result<Self, String> {
+        let language = language.trim();
+        let mut subtags = language.split('-');
+        let primary_ok = subtags.next().is_some_and(|p| (2..=3).contains(&p.len()) && p.chars().all(|c| c.is_ascii_alphabetic()));
+        if !primary_ok || !subtags.all(|s| (1..=8).contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphanumeric())) {
+            return Err(format!("Invalid language tag: {}", language));
+        }
+        let timezone = timezone.trim();
+        parse_offset(timezone)?;
+        Ok(Self { language: language.to_string(), timezone: timezone.to_string() })
+    }
+
+    /// Render a UTC unix-ms timestamp as RFC 3339 in the session's timezone
+    pub fn format_ms(&self, timestamp_ms: u64) -> String {
+        let offset = parse_offset(&self.timezone).unwrap_or_else(|_| FixedOffset::east_opt(0).unwrap());
+        DateTime::from_timestamp_millis(timestamp_ms as i64)
+            .map(|utc| utc.with_timezone(&offset).to_rfc3339())
+            .unwrap_or_default()
+    }
+}
+
+/// IANA zone names need a tz database this build does not carry, so only fixed offsets are accepted
+fn parse_offset(timezone: &str) -> Result<FixedOffset, String> {
+    if timezone.eq_ignore_ascii_case("UTC") || timezone == "Z" {
+        return Ok(FixedOffset::east_opt(0).unwrap());
+    }
+    let invalid = || format!("Invalid timezone '{}': use UTC or an offset like +09:00", timezone);
+    let (sign, rest) = match timezone.as_bytes().first() {
+        Some(b'+') => (1, &timezone[1..]),
+        Some(b'-') => (-1, &timezone[1..]),
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 14 || minutes >= 60 {
+        return Err(invalid());
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
+/// Locale of the user's current session, if they set one
+pub async fn for_user(state: &AppState, user_address: &str) -> Option<Locale> {
+    state.session_manager.locale_for_user(user_address)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LocaleRequest {
+    pub language: String,
+    pub timezone: String,
+}
+
+/// GET /me/locale - The session's display preferences (null when unset)
+pub async fn get_locale(State(state): State<AppState>, headers: HeaderMap) -> Result<Json<Value>, StatusCode> {
+    let api_key = session_key(&headers)?;
+    let locale = state.session_manager.with_session(api_key, |session| session.locale.clone()).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(serde_json::json!({"locale": locale})))
+}
+
+/// PUT /me/locale - Set the language and timezone used for this session's reports and notifications
+pub async fn set_locale(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<LocaleRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = session_key(&headers)?;
+    let locale = match Locale::new(&payload.language, &payload.timezone) {
+        Ok(locale) => locale,
+        Err(reason) => return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, reason))),
+    };
+
+    let session = state.session_manager.set_locale(api_key, Some(locale)).ok_or(StatusCode::NOT_FOUND)?;
+    info!("🌐 Locale for {} set to {:?}", session.user_address, session.locale);
+
+    Ok(Json(serde_json::json!({"locale": session.locale})))
+}
+
+fn session_key(headers: &HeaderMap) -> Result<&str, StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(api_key)
+}
@@ -0,0 +1,107 @@
+use axum::{
+    extract::State,
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{info, warn};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+use crate::error_codes::{self, ErrorCode};
+use crate::AppState;
+
+/// Filter the server starts with
+pub const DEFAULT_FILTER: &str = "info";
+
+/// Handle on the process-wide tracing filter
+#[derive(Debug)]
+pub struct LogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+    /// Bumped on every change, so a scheduled revert never undoes a later change
+    generation: AtomicU64,
+}
+
+impl LogFilter {
+    pub fn new(handle: reload::Handle<EnvFilter, Registry>) -> Self {
+        Self { handle, generation: AtomicU64::new(0) }
+    }
+
+    pub fn current(&self) -> String {
+        self.handle.with_current(|filter| filter.to_string()).unwrap_or_default()
+    }
+
+    fn set(&self, directives: &str) -> Result<u64, String> {
+        let filter = EnvFilter::try_new(directives).map_err(|e| format!("Invalid filter '{}': {}", directives, e))?;
+        self.handle.reload(filter).map_err(|e| e.to_string())?;
+        Ok(self.generation.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+}
+
+/// New verbosity: a default level plus per-module overrides
+#[derive(Debug, Deserialize)]
+pub struct LogLevelRequest {
+    #[serde(default = "default_level")]
+    pub default: String,
+    /// Module -> level, e.g. {"signer": "debug"}; bare names are modules of this server
+    #[serde(default)]
+    pub modules: BTreeMap<String, String>,
+    /// Restore the previous filter after this many seconds
+    pub revert_after_secs: Option<u64>,
+}
+
+fn default_level() -> String {
+    DEFAULT_FILTER.to_string()
+}
+
+/// Bare module names are targets of this crate; anything with a path is used as given
+fn directives(request: &LogLevelRequest) -> String {
+    std::iter::once(request.default.clone())
+        .chain(request.modules.iter().map(|(module, level)| {
+            if module.contains("::") {
+                format!("{}={}", module, level)
+            } else {
+                format!("{}::{}={}", env!("CARGO_CRATE_NAME"), module, level)
+            }
+        }))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// GET /admin/log-level - The tracing filter in effect
+pub async fn get_log_level(State(state): State<AppState>) -> Json<Value> {
+    Json(serde_json::json!({"filter": state.log_filter.current()}))
+}
+
+/// PUT /admin/log-level - Change tracing verbosity per module without restarting the enclave
+pub async fn set_log_level(
+    State(state): State<AppState>,
+    Json(payload): Json<LogLevelRequest>,
+) -> Json<Value> {
+    let previous = state.log_filter.current();
+    let filter = directives(&payload);
+    let generation = match state.log_filter.set(&filter) {
+        Ok(generation) => generation,
+        Err(reason) => return Json(error_codes::err_body(ErrorCode::BadRequest, reason)),
+    };
+    warn!("🔧 Log filter changed from '{}' to '{}'", previous, filter);
+
+    if let Some(secs) = payload.revert_after_secs {
+        let log_filter = state.log_filter.clone();
+        let previous = previous.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(secs)).await;
+            if log_filter.generation.load(Ordering::SeqCst) == generation && log_filter.set(&previous).is_ok() {
+                info!("🔧 Log filter reverted to '{}'", previous);
+            }
+        });
+    }
+
+    Json(serde_json::json!({
+        "filter": filter,
+        "previous": previous,
+        "revert_after_secs": payload.revert_after_secs
+    }))
+}
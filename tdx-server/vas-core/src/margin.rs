@@ -0,0 +1,79 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, error};
+
+use crate::auth;
+use crate::market::parse_number;
+use crate::AppState;
+
+/// Margin view of a user's account derived from `clearinghouseState`
+#[derive(Debug, Clone, Serialize)]
+pub struct MarginSummary {
+    pub user_address: String,
+    pub account_value: f64,
+    pub withdrawable: f64,
+    pub total_margin_used: f64,
+    /// total_margin_used / account_value (0 when the account is empty)
+    pub margin_usage: f64,
+    pub maintenance_margin_used: f64,
+    /// Cross account value left above maintenance margin before liquidation
+    pub maintenance_headroom: f64,
+}
+
+impl MarginSummary {
+    pub fn from_clearinghouse_state(user_address: &str, state: &Value) -> Self {
+        let margin = state.get("marginSummary");
+        let cross = state.get("crossMarginSummary");
+
+        let account_value = parse_number(margin.and_then(|m| m.get("accountValue"))).unwrap_or(0.0);
+        let total_margin_used = parse_number(margin.and_then(|m| m.get("totalMarginUsed"))).unwrap_or(0.0);
+        let cross_account_value = parse_number(cross.and_then(|c| c.get("accountValue"))).unwrap_or(account_value);
+        let maintenance_margin_used = parse_number(state.get("crossMaintenanceMarginUsed")).unwrap_or(0.0);
+
+        Self {
+            user_address: user_address.to_string(),
+            account_value,
+            withdrawable: parse_number(state.get("withdrawable")).unwrap_or(0.0),
+            total_margin_used,
+            margin_usage: if account_value > 0.0 { total_margin_used / account_value } else { 0.0 },
+            maintenance_margin_used,
+            maintenance_headroom: cross_account_value - maintenance_margin_used,
+        }
+    }
+}
+
+/// Leverage currently set on the user's position in `coin`, if any
+pub fn position_leverage(state: &Value, coin: &str) -> Option<f64> {
+    state.get("assetPositions")?
+        .as_array()?
+        .iter()
+        .filter_map(|p| p.get("position"))
+        .find(|p| p.get("coin").and_then(|c| c.as_str()) == Some(coin))
+        .and_then(|p| p.get("leverage"))
+        .and_then(|l| parse_number(l.get("value")))
+}
+
+/// GET /me/margin - Withdrawable balance, margin usage and maintenance headroom
+pub async fn me_margin(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<MarginSummary>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    info!("📊 Margin summary requested for {}", user_address);
+
+    let clearinghouse = state.market.clearinghouse_state(&user_address).await.map_err(|e| {
+        error!("❌ Failed to fetch clearinghouseState: {:?}", e);
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    Ok(Json(MarginSummary::from_clearinghouse_state(&user_address, &clearinghouse)))
+}
@@ -0,0 +1,236 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::proxy::HyperliquidProxy;
+
+/// Per-asset metadata and live context from `metaAndAssetCtxs`
+#[derive(Debug, Clone)]
+pub struct AssetInfo {
+    pub name: String,
+    pub max_leverage: f64,
+    pub mark_px: Option<f64>,
+    /// Decimals allowed in order sizes; prices get at most `6 - sz_decimals`
+    pub sz_decimals: u32,
+}
+
+/// Prices of one perp from a `metaAndAssetCtxs` snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetPrices {
+    pub asset: u64,
+    pub mark: Option<f64>,
+    pub oracle: Option<f64>,
+    /// None when the book is one-sided
+    pub mid: Option<f64>,
+}
+
+/// Every perp's prices as of one upstream fetch
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceSnapshot {
+    /// Unix ms when Hyperliquid served the snapshot
+    pub as_of: u64,
+    pub max_staleness_ms: u64,
+    pub prices: BTreeMap<String, AssetPrices>,
+}
+
+/// Short-lived cache over Hyperliquid info endpoints used by pre-sign checks
+#[derive(Debug)]
+pub struct MarketCache {
+    proxy: Arc<HyperliquidProxy>,
+    ttl: Duration,
+    /// Oldest `metaAndAssetCtxs` (and so mark price) ever served when a refresh fails
+    max_staleness: Duration,
+    /// Fetch time as an Instant (for the TTL) and as unix ms (for `as_of`)
+    meta_and_ctxs: RwLock<Option<(Instant, u64, Value)>>,
+    clearinghouse: RwLock<HashMap<String, (Instant, Value)>>,
+    user_fees: RwLock<HashMap<String, (Instant, Value)>>,
+    predicted_fundings: RwLock<Option<(Instant, Value)>>,
+    funding_history: RwLock<HashMap<String, (Instant, Value)>>,
+}
+
+impl MarketCache {
+    pub fn new(proxy: Arc<HyperliquidProxy>, ttl: Duration, max_staleness: Duration) -> Self {
+        Self {
+            proxy,
+            ttl,
+            max_staleness,
+            meta_and_ctxs: RwLock::new(None),
+            clearinghouse: RwLock::new(HashMap::new()),
+            user_fees: RwLock::new(HashMap::new()),
+            predicted_fundings: RwLock::new(None),
+            funding_history: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Get `metaAndAssetCtxs`, refreshing when older than the cache TTL
+    pub async fn meta_and_asset_ctxs(&self) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.meta_and_asset_ctxs_as_of().await?.1)
+    }
+
+    /// `metaAndAssetCtxs` with its fetch time (unix ms).
+    ///
+    /// When a refresh fails, the cached copy is served until it is `max_staleness` old, never after.
+    async fn meta_and_asset_ctxs_as_of(&self) -> Result<(u64, Value), Box<dyn std::error::Error + Send + Sync>> {
+        let cached = self.meta_and_ctxs.read().await.clone();
+        if let Some((fetched_at, as_of, value)) = &cached {
+            if fetched_at.elapsed() < self.ttl.min(self.max_staleness) {
+                return Ok((*as_of, value.clone()));
+            }
+        }
+
+        info!("🔄 Refreshing metaAndAssetCtxs cache");
+        let value = match self.proxy.proxy_info_request(&serde_json::json!({"type": "metaAndAssetCtxs"})).await {
+            Ok(value) => value,
+            Err(e) => match cached {
+                Some((fetched_at, as_of, value)) if fetched_at.elapsed() < self.max_staleness => {
+                    warn!("⚠️ metaAndAssetCtxs refresh failed, serving {}ms-old copy: {}", fetched_at.elapsed().as_millis(), e);
+                    return Ok((as_of, value));
+                }
+                _ => return Err(e),
+            },
+        };
+        let as_of = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as u64;
+        *self.meta_and_ctxs.write().await = Some((Instant::now(), as_of, value.clone()));
+
+        Ok((as_of, value))
+    }
+
+    /// Mark, oracle and mid price of every perp from one snapshot, no older than `max_staleness`
+    pub async fn prices(&self) -> Result<PriceSnapshot, Box<dyn std::error::Error + Send + Sync>> {
+        let (as_of, value) = self.meta_and_asset_ctxs_as_of().await?;
+        let universe = value.pointer("/0/universe").and_then(|u| u.as_array());
+        let ctxs = value.get(1).and_then(|c| c.as_array());
+
+        let mut prices = BTreeMap::new();
+        for (index, meta) in universe.into_iter().flatten().enumerate() {
+            let Some(coin) = meta.get("name").and_then(|n| n.as_str()) else { continue };
+            let ctx = ctxs.and_then(|c| c.get(index));
+            prices.insert(coin.to_string(), AssetPrices {
+                asset: index as u64,
+                mark: ctx.and_then(|c| parse_number(c.get("markPx"))),
+                oracle: ctx.and_then(|c| parse_number(c.get("oraclePx"))),
+                mid: ctx.and_then(|c| parse_number(c.get("midPx"))),
+            });
+        }
+
+        Ok(PriceSnapshot { as_of, max_staleness_ms: self.max_staleness.as_millis() as u64, prices })
+    }
+
+    /// Get `clearinghouseState` for a user, refreshing when older than the cache TTL
+    pub async fn clearinghouse_state(&self, user: &str) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let user = user.to_lowercase();
+        if let Some((fetched_at, value)) = self.clearinghouse.read().await.get(&user) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(value.clone());
+            }
+        }
+
+        info!("🔄 Refreshing clearinghouseState cache for {}", user);
+        let value = self.proxy
+            .proxy_info_request(&serde_json::json!({"type": "clearinghouseState", "user": user}))
+            .await?;
+        self.clearinghouse.write().await.insert(user, (Instant::now(), value.clone()));
+
+        Ok(value)
+    }
+
+    /// Get `userFees` for a user, refreshing when older than the cache TTL
+    pub async fn user_fees(&self, user: &str) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let user = user.to_lowercase();
+        if let Some((fetched_at, value)) = self.user_fees.read().await.get(&user) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(value.clone());
+            }
+        }
+
+        info!("🔄 Refreshing userFees cache for {}", user);
+        let value = self.proxy
+            .proxy_info_request(&serde_json::json!({"type": "userFees", "user": user}))
+            .await?;
+        self.user_fees.write().await.insert(user, (Instant::now(), value.clone()));
+
+        Ok(value)
+    }
+
+    /// Get `predictedFundings` (next rate per coin and venue), refreshing when older than the cache TTL
+    pub async fn predicted_fundings(&self) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some((fetched_at, value)) = self.predicted_fundings.read().await.as_ref() {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(value.clone());
+            }
+        }
+
+        info!("🔄 Refreshing predictedFundings cache");
+        let value = self.proxy
+            .proxy_info_request(&serde_json::json!({"type": "predictedFundings"}))
+            .await?;
+        *self.predicted_fundings.write().await = Some((Instant::now(), value.clone()));
+
+        Ok(value)
+    }
+
+    /// Get the last day of paid funding for a coin, refreshing when older than the cache TTL
+    pub async fn funding_history(&self, coin: &str) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some((fetched_at, value)) = self.funding_history.read().await.get(coin) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(value.clone());
+            }
+        }
+
+        let start_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as u64
+            - 24 * 3600 * 1000;
+        info!("🔄 Refreshing fundingHistory cache for {}", coin);
+        let value = self.proxy
+            .proxy_info_request(&serde_json::json!({"type": "fundingHistory", "coin": coin, "startTime": start_time}))
+            .await?;
+        self.funding_history.write().await.insert(coin.to_string(), (Instant::now(), value.clone()));
+
+        Ok(value)
+    }
+
+    /// Perp asset index for a coin name (the inverse of `asset`)
+    pub async fn asset_index(&self, coin: &str) -> Result<Option<u64>, Box<dyn std::error::Error + Send + Sync>> {
+        let value = self.meta_and_asset_ctxs().await?;
+        Ok(value.get(0)
+            .and_then(|meta| meta.get("universe"))
+            .and_then(|universe| universe.as_array())
+            .and_then(|universe| universe.iter().position(|asset| asset.get("name").and_then(|n| n.as_str()) == Some(coin)))
+            .map(|index| index as u64))
+    }
+
+    /// Look up metadata and live context for a perp asset index
+    pub async fn asset(&self, index: u64) -> Result<Option<AssetInfo>, Box<dyn std::error::Error + Send + Sync>> {
+        let value = self.meta_and_asset_ctxs().await?;
+        Ok(parse_asset(&value, index))
+    }
+}
+
+/// Asset `index` from a `metaAndAssetCtxs` response
+pub fn parse_asset(meta_and_ctxs: &Value, index: u64) -> Option<AssetInfo> {
+    let meta = meta_and_ctxs.get(0)?.get("universe")?.get(index as usize)?;
+    let ctx = meta_and_ctxs.get(1).and_then(|c| c.get(index as usize));
+
+    Some(AssetInfo {
+        name: meta.get("name")?.as_str()?.to_string(),
+        max_leverage: meta.get("maxLeverage").and_then(|l| l.as_f64()).unwrap_or(1.0),
+        mark_px: ctx.and_then(|c| parse_number(c.get("markPx"))),
+        sz_decimals: meta.get("szDecimals").and_then(|d| d.as_u64()).unwrap_or(0) as u32,
+    })
+}
+
+/// Parse a Hyperliquid decimal field, which is usually a string but occasionally a number
+pub fn parse_number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.as_f64(),
+        _ => None,
+    }
+}
@@ -0,0 +1,480 @@
+use arrow_array::{Array, ArrayRef, Float64Array, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::error_codes::{self, ErrorCode};
+use crate::market::parse_number;
+use crate::AppState;
+
+/// Candle intervals Hyperliquid serves
+const CANDLE_INTERVALS: &[&str] = &["1m", "3m", "5m", "15m", "30m", "1h", "2h", "4h", "8h", "12h", "1d", "3d", "1w", "1M"];
+/// activeAssetCtx pushes arrive every block; keep at most one funding sample per coin per minute
+const FUNDING_SAMPLE_MS: u64 = 60_000;
+/// Rows kept in memory per coin and kind while the store can't be written
+const MAX_PENDING_ROWS: usize = 100_000;
+const DEFAULT_LIMIT: usize = 1000;
+const MAX_LIMIT: usize = 10_000;
+
+/// What the recorder stores; each kind has its own directory and schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryKind {
+    Trades,
+    Candles,
+    Funding,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Column {
+    U64,
+    F64,
+    Str,
+}
+
+impl HistoryKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Trades => "trades",
+            Self::Candles => "candles",
+            Self::Funding => "funding",
+        }
+    }
+
+    /// Column name, type and nullability; `time` (unix ms) always comes first
+    fn columns(self) -> &'static [(&'static str, Column, bool)] {
+        match self {
+            Self::Trades => &[
+                ("time", Column::U64, false),
+                ("px", Column::F64, false),
+                ("sz", Column::F64, false),
+                ("side", Column::Str, false),
+                ("tid", Column::U64, false),
+                ("hash", Column::Str, true),
+            ],
+            // `time` is the candle's open time
+            Self::Candles => &[
+                ("time", Column::U64, false),
+                ("close_time", Column::U64, false),
+                ("interval", Column::Str, false),
+                ("open", Column::F64, false),
+                ("high", Column::F64, false),
+                ("low", Column::F64, false),
+                ("close", Column::F64, false),
+                ("volume", Column::F64, false),
+                ("trades", Column::U64, false),
+            ],
+            Self::Funding => &[
+                ("time", Column::U64, false),
+                ("funding", Column::F64, false),
+                ("premium", Column::F64, true),
+                ("mark_px", Column::F64, true),
+                ("oracle_px", Column::F64, true),
+                ("open_interest", Column::F64, true),
+            ],
+        }
+    }
+
+    fn schema(self) -> Schema {
+        Schema::new(self.columns().iter().map(|(name, column, nullable)| {
+            let data_type = match column {
+                Column::U64 => DataType::UInt64,
+                Column::F64 => DataType::Float64,
+                Column::Str => DataType::Utf8,
+            };
+            Field::new(*name, data_type, *nullable)
+        }).collect::<Vec<_>>())
+    }
+}
+
+/// Trades, closed candles and funding samples for configured coins, taken from the shared WS feed
+/// and written to parquet, so backtests read the same data path the agent trades on.
+///
+/// Rows are buffered in memory and flushed every `MARKET_HISTORY_FLUSH_SECS`, one file per coin,
+/// kind and flush: `<dir>/<kind>/<coin>/<first time>-<last time>-<written at>.parquet`.
+#[derive(Debug)]
+pub struct MarketHistory {
+    dir: PathBuf,
+    assets: Vec<String>,
+    candle_interval: String,
+    flush_interval: Duration,
+    pending: Mutex<HashMap<(HistoryKind, String), Vec<Value>>>,
+    /// Latest push of each coin's current candle; it is recorded once the next one opens
+    open_candles: Mutex<HashMap<String, Value>>,
+    last_funding: Mutex<HashMap<String, u64>>,
+}
+
+impl MarketHistory {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        if !CANDLE_INTERVALS.contains(&config.market_history_candle_interval.as_str()) {
+            return Err(format!(
+                "Invalid MARKET_HISTORY_CANDLE_INTERVAL '{}' (expected one of {})",
+                config.market_history_candle_interval,
+                CANDLE_INTERVALS.join(", ")
+            ));
+        }
+        Ok(Self {
+            dir: PathBuf::from(&config.market_history_dir),
+            assets: config.market_history_assets.clone(),
+            candle_interval: config.market_history_candle_interval.clone(),
+            flush_interval: Duration::from_secs(config.market_history_flush_secs),
+            pending: Mutex::new(HashMap::new()),
+            open_candles: Mutex::new(HashMap::new()),
+            last_funding: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.assets.is_empty()
+    }
+
+    pub fn is_recorded(&self, coin: &str) -> bool {
+        self.assets.iter().any(|a| a == coin)
+    }
+
+    /// Turn one WS message into rows for the coins being recorded
+    fn record(&self, message: &Value) {
+        let Some(data) = message.get("data") else { return };
+        match message.get("channel").and_then(|c| c.as_str()) {
+            Some("trades") => {
+                for trade in data.as_array().into_iter().flatten() {
+                    let Some(coin) = trade.get("coin").and_then(|c| c.as_str()) else { continue };
+                    if let Some(row) = trade_row(trade) {
+                        self.push(HistoryKind::Trades, coin, row);
+                    }
+                }
+            }
+            Some("candle") => {
+                let Some(coin) = data.get("s").and_then(|c| c.as_str()) else { return };
+                if data.get("i").and_then(|i| i.as_str()) != Some(self.candle_interval.as_str()) {
+                    return;
+                }
+                let Some(row) = candle_row(data) else { return };
+                let closed = {
+                    let mut open_candles = self.open_candles.lock().unwrap();
+                    match open_candles.insert(coin.to_string(), row.clone()) {
+                        Some(previous) if previous["time"].as_u64() < row["time"].as_u64() => Some(previous),
+                        _ => None,
+                    }
+                };
+                if let Some(candle) = closed {
+                    self.push(HistoryKind::Candles, coin, candle);
+                }
+            }
+            Some("activeAssetCtx") => {
+                let Some(coin) = data.get("coin").and_then(|c| c.as_str()) else { return };
+                let now = now_ms();
+                {
+                    let mut last_funding = self.last_funding.lock().unwrap();
+                    if last_funding.get(coin).is_some_and(|last| now < last + FUNDING_SAMPLE_MS) {
+                        return;
+                    }
+                    last_funding.insert(coin.to_string(), now);
+                }
+                if let Some(row) = data.get("ctx").and_then(|ctx| funding_row(ctx, now)) {
+                    self.push(HistoryKind::Funding, coin, row);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn push(&self, kind: HistoryKind, coin: &str, row: Value) {
+        if !self.is_recorded(coin) {
+            return;
+        }
+        let mut pending = self.pending.lock().unwrap();
+        let rows = pending.entry((kind, coin.to_string())).or_default();
+        if rows.len() >= MAX_PENDING_ROWS {
+            rows.remove(0);
+        }
+        rows.push(row);
+    }
+
+    /// Write everything buffered; rows that fail to write are kept for the next flush
+    async fn flush(self: &Arc<Self>) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        for ((kind, coin), mut rows) in pending {
+            rows.sort_by_key(row_time);
+            let recorder = self.clone();
+            let written = {
+                let (coin, rows) = (coin.clone(), rows.clone());
+                tokio::task::spawn_blocking(move || recorder.write(kind, &coin, &rows)).await
+            };
+            match written {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => {
+                    warn!("⚠️ Failed to write {} {} rows for {}: {}", rows.len(), kind.as_str(), coin, e);
+                    let mut pending = self.pending.lock().unwrap();
+                    let kept = pending.entry((kind, coin)).or_default();
+                    rows.append(kept);
+                    let excess = rows.len().saturating_sub(MAX_PENDING_ROWS);
+                    *kept = rows.split_off(excess);
+                }
+                Err(e) => warn!("⚠️ Market history writer panicked: {}", e),
+            }
+        }
+    }
+
+    fn write(&self, kind: HistoryKind, coin: &str, rows: &[Value]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (Some(first), Some(last)) = (rows.first(), rows.last()) else {
+            return Ok(());
+        };
+        let dir = self.coin_dir(kind, coin);
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}-{}-{}.parquet", row_time(first), row_time(last), now_ms()));
+
+        let schema = Arc::new(kind.schema());
+        let columns: Vec<ArrayRef> = kind.columns().iter().map(|(name, column, _)| match column {
+            Column::U64 => Arc::new(rows.iter().map(|r| r.get(*name).and_then(Value::as_u64)).collect::<UInt64Array>()) as ArrayRef,
+            Column::F64 => Arc::new(rows.iter().map(|r| r.get(*name).and_then(Value::as_f64)).collect::<Float64Array>()),
+            Column::Str => Arc::new(rows.iter().map(|r| r.get(*name).and_then(Value::as_str)).collect::<StringArray>()),
+        }).collect();
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+        // Written under a temporary name so queries never read a partial file
+        let partial = path.with_extension("parquet.partial");
+        let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let mut writer = ArrowWriter::try_new(std::fs::File::create(&partial)?, schema, Some(properties))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        std::fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    /// Stored and buffered rows with `start <= time <= end`, in time order
+    pub fn query(&self, kind: HistoryKind, coin: &str, start: u64, end: u64) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut rows = Vec::new();
+        let files = match std::fs::read_dir(self.coin_dir(kind, coin)) {
+            Ok(files) => files,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(self.pending_rows(kind, coin, start, end)),
+            Err(e) => return Err(e.into()),
+        };
+        for file in files {
+            let path = file?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("parquet") {
+                continue;
+            }
+            let Some((first, last)) = file_range(&path) else { continue };
+            if last < start || first > end {
+                continue;
+            }
+            rows.extend(read_file(&path, kind)?.into_iter().filter(|r| (start..=end).contains(&row_time(r))));
+        }
+        rows.extend(self.pending_rows(kind, coin, start, end));
+        rows.sort_by_key(row_time);
+        Ok(rows)
+    }
+
+    fn pending_rows(&self, kind: HistoryKind, coin: &str, start: u64, end: u64) -> Vec<Value> {
+        self.pending.lock().unwrap()
+            .get(&(kind, coin.to_string()))
+            .into_iter()
+            .flatten()
+            .filter(|r| (start..=end).contains(&row_time(r)))
+            .cloned()
+            .collect()
+    }
+
+    /// Spot coins are named like "PURR/USDC", so anything but a plain name character is replaced
+    fn coin_dir(&self, kind: HistoryKind, coin: &str) -> PathBuf {
+        let name: String = coin.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || "@_-".contains(c) { c } else { '_' })
+            .collect();
+        self.dir.join(kind.as_str()).join(name)
+    }
+}
+
+fn trade_row(trade: &Value) -> Option<Value> {
+    Some(serde_json::json!({
+        "time": trade.get("time")?.as_u64()?,
+        "px": parse_number(trade.get("px"))?,
+        "sz": parse_number(trade.get("sz"))?,
+        "side": trade.get("side")?.as_str()?,
+        "tid": trade.get("tid")?.as_u64()?,
+        "hash": trade.get("hash").and_then(|h| h.as_str()),
+    }))
+}
+
+fn candle_row(candle: &Value) -> Option<Value> {
+    Some(serde_json::json!({
+        "time": candle.get("t")?.as_u64()?,
+        "close_time": candle.get("T")?.as_u64()?,
+        "interval": candle.get("i")?.as_str()?,
+        "open": parse_number(candle.get("o"))?,
+        "high": parse_number(candle.get("h"))?,
+        "low": parse_number(candle.get("l"))?,
+        "close": parse_number(candle.get("c"))?,
+        "volume": parse_number(candle.get("v"))?,
+        "trades": candle.get("n")?.as_u64()?,
+    }))
+}
+
+fn funding_row(ctx: &Value, time: u64) -> Option<Value> {
+    Some(serde_json::json!({
+        "time": time,
+        "funding": parse_number(ctx.get("funding"))?,
+        "premium": parse_number(ctx.get("premium")),
+        "mark_px": parse_number(ctx.get("markPx")),
+        "oracle_px": parse_number(ctx.get("oraclePx")),
+        "open_interest": parse_number(ctx.get("openInterest")),
+    }))
+}
+
+pub fn row_time(row: &Value) -> u64 {
+    row.get("time").and_then(|t| t.as_u64()).unwrap_or(0)
+}
+
+/// First and last row time from a file name written by [`MarketHistory::write`]
+fn file_range(path: &Path) -> Option<(u64, u64)> {
+    let stem = path.file_stem()?.to_str()?;
+    let mut parts = stem.split('-');
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+fn read_file(path: &Path, kind: HistoryKind) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(path)?)?.build()?;
+    let mut rows = Vec::new();
+    for batch in reader {
+        let batch = batch?;
+        if batch.schema().fields() != kind.schema().fields() {
+            return Err(format!("{} does not have the {} schema", path.display(), kind.as_str()).into());
+        }
+        for i in 0..batch.num_rows() {
+            let mut row = Map::new();
+            for ((name, column, _), array) in kind.columns().iter().zip(batch.columns()) {
+                let value = if array.is_null(i) {
+                    Value::Null
+                } else {
+                    match column {
+                        Column::U64 => array.as_any().downcast_ref::<UInt64Array>().map(|a| a.value(i).into()),
+                        Column::F64 => array.as_any().downcast_ref::<Float64Array>().map(|a| a.value(i).into()),
+                        Column::Str => array.as_any().downcast_ref::<StringArray>().map(|a| a.value(i).into()),
+                    }.unwrap_or_default()
+                };
+                row.insert(name.to_string(), value);
+            }
+            rows.push(Value::Object(row));
+        }
+    }
+    Ok(rows)
+}
+
+/// Subscribe to the configured coins and record their pushes until shutdown
+pub fn spawn_recorder(state: AppState) {
+    let recorder = state.market_history.clone();
+    if !recorder.enabled() {
+        return;
+    }
+    info!("🗄️ Recording market history for {} to {}", recorder.assets.join(","), recorder.dir.display());
+
+    let mut messages = state.ws_feed.listen();
+    let listener = recorder.clone();
+    tokio::spawn(async move {
+        loop {
+            match messages.recv().await {
+                Ok(message) => listener.record(&message),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("⚠️ Market history recorder lagged, {} WS messages dropped", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        for coin in &recorder.assets {
+            state.ws_feed.subscribe(serde_json::json!({"type": "trades", "coin": coin})).await;
+            state.ws_feed.subscribe(serde_json::json!({"type": "candle", "coin": coin, "interval": recorder.candle_interval})).await;
+            state.ws_feed.subscribe(serde_json::json!({"type": "activeAssetCtx", "coin": coin})).await;
+        }
+        let mut ticker = tokio::time::interval(recorder.flush_interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            recorder.flush().await;
+        }
+    });
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub kind: HistoryKind,
+    pub coin: String,
+    /// Unix ms, inclusive
+    pub start: Option<u64>,
+    /// Unix ms, inclusive; defaults to now
+    pub end: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+/// GET /market/history - Recorded trades, closed candles or funding samples for one coin.
+///
+/// Rows come back in time order. When more than `limit` rows match, `next_start` is the
+/// `start` that continues from where this page stopped.
+pub async fn market_history(State(state): State<AppState>, Query(query): Query<HistoryQuery>) -> Json<Value> {
+    let recorder = state.market_history.clone();
+    if !recorder.enabled() {
+        return Json(error_codes::err_body(ErrorCode::ServiceUnavailable, "Market history is not recorded on this server (set MARKET_HISTORY_ASSETS)"));
+    }
+    if !recorder.is_recorded(&query.coin) {
+        return Json(error_codes::err_body(ErrorCode::UnknownAsset, format!("{} is not recorded; recorded coins: {}", query.coin, recorder.assets.join(","))));
+    }
+    let start = query.start.unwrap_or(0);
+    let end = query.end.unwrap_or_else(now_ms);
+    if start > end {
+        return Json(error_codes::err_body(ErrorCode::InvalidRange, "start must not be after end"));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let (kind, coin) = (query.kind, query.coin.clone());
+    let mut rows = match tokio::task::spawn_blocking(move || recorder.query(kind, &coin, start, end)).await {
+        Ok(Ok(rows)) => rows,
+        Ok(Err(e)) => return Json(error_codes::err_body(ErrorCode::InternalError, format!("Failed to read market history: {}", e))),
+        Err(e) => return Json(error_codes::err_body(ErrorCode::InternalError, e.to_string())),
+    };
+
+    // Pages end on a timestamp boundary so rows sharing a time aren't split across pages,
+    // unless one timestamp alone has more than `limit` rows
+    let mut next_start = rows.get(limit).map(row_time);
+    rows.truncate(limit);
+    if let Some(next) = next_start {
+        if rows.iter().any(|r| row_time(r) < next) {
+            rows.retain(|r| row_time(r) < next);
+        } else {
+            next_start = Some(next + 1);
+        }
+    }
+
+    Json(serde_json::json!({
+        "status": "ok",
+        "response": {
+            "kind": query.kind,
+            "coin": query.coin,
+            "rows": rows,
+            "next_start": next_start
+        }
+    }))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
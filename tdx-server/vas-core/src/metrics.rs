@@ -0,0 +1,17 @@
+use axum::extract::State;
+
+use crate::compat;
+use crate::probe;
+use crate::rejections;
+use crate::retention;
+use crate::AppState;
+
+/// GET /admin/metrics - Prometheus text exposition of operator metrics
+pub async fn admin_metrics(State(state): State<AppState>) -> String {
+    let mut out = state.slo.read().await.prometheus();
+    out.push_str(&retention::prometheus(&state).await);
+    out.push_str(&probe::prometheus(&state).await);
+    out.push_str(&compat::prometheus(&state));
+    out.push_str(&rejections::prometheus(&state));
+    out
+}
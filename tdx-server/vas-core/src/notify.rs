@@ -0,0 +1,304 @@
+use futures_util::future::BoxFuture;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{info, error};
+
+use crate::agents::AgentSessionManager;
+use crate::config::{Config, NotifierTransport};
+use crate::locale::Locale;
+use crate::signer::SignerHandle;
+use crate::webhooks;
+use crate::ws_feed::WsFeed;
+
+/// Event categories notifications can be routed by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    Fill,
+    Session,
+    Alert,
+}
+
+impl NotificationKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "fills" | "fill" => Some(Self::Fill),
+            "session" | "sessions" => Some(Self::Session),
+            "alerts" | "alert" => Some(Self::Alert),
+            _ => None,
+        }
+    }
+
+    pub const ALL: [NotificationKind; 3] = [Self::Fill, Self::Session, Self::Alert];
+}
+
+/// A transport-agnostic notification
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub user_address: Option<String>,
+    pub title: String,
+    pub details: Value,
+    /// Always UTC unix ms, whatever the recipient's locale
+    pub timestamp_ms: u64,
+    /// The user's session locale, for receivers that render the notification
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<Locale>,
+    /// `timestamp_ms` in the user's timezone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_time: Option<String>,
+}
+
+impl Notification {
+    pub fn new(kind: NotificationKind, user_address: Option<&str>, title: impl Into<String>, details: Value) -> Self {
+        Self {
+            kind,
+            user_address: user_address.map(|u| u.to_string()),
+            title: title.into(),
+            details,
+            timestamp_ms: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+            locale: None,
+            local_time: None,
+        }
+    }
+
+    fn localize(&mut self, locale: Locale) {
+        self.local_time = Some(locale.format_ms(self.timestamp_ms));
+        self.locale = Some(locale);
+    }
+
+    /// Single-line human-readable rendering for chat/email transports
+    pub fn summary(&self) -> String {
+        let summary = match &self.user_address {
+            Some(user) => format!("[{:?}] {} ({})", self.kind, self.title, user),
+            None => format!("[{:?}] {}", self.kind, self.title),
+        };
+        match &self.local_time {
+            Some(local_time) => format!("{} at {}", summary, local_time),
+            None => summary,
+        }
+    }
+}
+
+/// A delivery channel for notifications
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &str;
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<(), Box<dyn std::error::Error + Send + Sync>>>;
+}
+
+/// POSTs the notification as JSON to an arbitrary URL, signed with the agent key
+/// (see `webhooks` for the header scheme)
+pub struct WebhookNotifier {
+    client: Client,
+    url: String,
+    signer: SignerHandle,
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<(), Box<dyn std::error::Error + Send + Sync>>> {
+        Box::pin(async move {
+            // Sign the exact bytes sent so receivers can verify before parsing
+            let body = serde_json::to_string(notification)?;
+            let signed = webhooks::sign_body(&self.signer, notification.user_address.clone(), &body).await?;
+            let mut request = self.client.post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(webhooks::HEADER_KEY_ID, signed.key_id)
+                .header(webhooks::HEADER_TIMESTAMP, signed.timestamp_ms.to_string())
+                .header(webhooks::HEADER_SIGNATURE, signed.signature);
+            if let Some(locale) = &notification.locale {
+                request = request.header(reqwest::header::CONTENT_LANGUAGE, &locale.language);
+            }
+            request.body(body).send().await?.error_for_status()?;
+            Ok(())
+        })
+    }
+}
+
+/// Slack incoming-webhook transport
+pub struct SlackNotifier {
+    client: Client,
+    webhook_url: String,
+}
+
+impl Notifier for SlackNotifier {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<(), Box<dyn std::error::Error + Send + Sync>>> {
+        Box::pin(async move {
+            let body = serde_json::json!({"text": format!("{}\n```{}```", notification.summary(), notification.details)});
+            self.client.post(&self.webhook_url).json(&body).send().await?.error_for_status()?;
+            Ok(())
+        })
+    }
+}
+
+/// Telegram Bot API transport
+pub struct TelegramNotifier {
+    client: Client,
+    bot_token: String,
+    chat_id: String,
+}
+
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<(), Box<dyn std::error::Error + Send + Sync>>> {
+        Box::pin(async move {
+            let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+            let body = serde_json::json!({
+                "chat_id": self.chat_id,
+                "text": format!("{}\n{}", notification.summary(), notification.details)
+            });
+            self.client.post(&url).json(&body).send().await?.error_for_status()?;
+            Ok(())
+        })
+    }
+}
+
+/// Email via an HTTP mail relay accepting `{to, subject, text}`
+pub struct EmailNotifier {
+    client: Client,
+    api_url: String,
+    to: String,
+}
+
+impl Notifier for EmailNotifier {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<(), Box<dyn std::error::Error + Send + Sync>>> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "to": self.to,
+                "subject": notification.summary(),
+                "text": serde_json::to_string_pretty(&notification.details)?
+            });
+            self.client.post(&self.api_url).json(&body).send().await?.error_for_status()?;
+            Ok(())
+        })
+    }
+}
+
+/// Fans notifications out to the transports configured for each kind
+#[derive(Default)]
+pub struct NotificationHub {
+    routes: HashMap<NotificationKind, Vec<Arc<dyn Notifier>>>,
+    /// Sessions to look up each recipient's locale in
+    sessions: Option<Arc<AgentSessionManager>>,
+}
+
+impl NotificationHub {
+    pub fn from_config(config: &Config, signer: SignerHandle, sessions: Arc<AgentSessionManager>) -> Self {
+        let client = Client::new();
+        let mut hub = Self { sessions: Some(sessions), ..Self::default() };
+
+        for notifier_config in &config.notifiers {
+            let notifier: Arc<dyn Notifier> = match &notifier_config.transport {
+                NotifierTransport::Webhook { url } => Arc::new(WebhookNotifier {
+                    client: client.clone(),
+                    url: url.clone(),
+                    signer: signer.clone(),
+                }),
+                NotifierTransport::Slack { webhook_url } => Arc::new(SlackNotifier {
+                    client: client.clone(),
+                    webhook_url: webhook_url.clone(),
+                }),
+                NotifierTransport::Telegram { bot_token, chat_id } => Arc::new(TelegramNotifier {
+                    client: client.clone(),
+                    bot_token: bot_token.clone(),
+                    chat_id: chat_id.clone(),
+                }),
+                NotifierTransport::Email { api_url, to } => Arc::new(EmailNotifier {
+                    client: client.clone(),
+                    api_url: api_url.clone(),
+                    to: to.clone(),
+                }),
+            };
+
+            info!("📣 Notifier '{}' enabled for {:?}", notifier.name(), notifier_config.events);
+            for kind in &notifier_config.events {
+                hub.add_route(*kind, notifier.clone());
+            }
+        }
+
+        hub
+    }
+
+    pub fn add_route(&mut self, kind: NotificationKind, notifier: Arc<dyn Notifier>) {
+        self.routes.entry(kind).or_default().push(notifier);
+    }
+
+    /// Deliver in the background; transport failures are logged, never surfaced to callers
+    pub fn notify(&self, mut notification: Notification) {
+        let notifiers = match self.routes.get(&notification.kind) {
+            Some(notifiers) if !notifiers.is_empty() => notifiers.clone(),
+            _ => return,
+        };
+        let sessions = self.sessions.clone();
+
+        tokio::spawn(async move {
+            if let (Some(sessions), Some(user)) = (sessions, &notification.user_address) {
+                let locale = sessions.locale_for_user(user);
+                if let Some(locale) = locale {
+                    notification.localize(locale);
+                }
+            }
+            for notifier in notifiers {
+                if let Err(e) = notifier.send(&notification).await {
+                    error!("❌ Notifier '{}' failed to deliver {:?}: {}", notifier.name(), notification.kind, e);
+                }
+            }
+        });
+    }
+}
+
+/// Emit a Fill notification for every live fill pushed by the WS feed
+pub fn spawn_fill_notifier(feed: &WsFeed, hub: Arc<NotificationHub>) {
+    let mut messages = feed.listen();
+
+    tokio::spawn(async move {
+        loop {
+            let message = match messages.recv().await {
+                Ok(message) => message,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+
+            if message.get("channel").and_then(|c| c.as_str()) != Some("userFills") {
+                continue;
+            }
+            let data = match message.get("data") {
+                Some(data) if !data.get("isSnapshot").and_then(|s| s.as_bool()).unwrap_or(false) => data,
+                _ => continue,
+            };
+            let user = data.get("user").and_then(|u| u.as_str());
+
+            for fill in data.get("fills").and_then(|f| f.as_array()).into_iter().flatten() {
+                let title = format!(
+                    "Fill {} {} {} @ {}",
+                    fill.get("coin").and_then(|c| c.as_str()).unwrap_or("?"),
+                    fill.get("side").and_then(|s| s.as_str()).unwrap_or("?"),
+                    fill.get("sz").and_then(|s| s.as_str()).unwrap_or("?"),
+                    fill.get("px").and_then(|p| p.as_str()).unwrap_or("?"),
+                );
+                hub.notify(Notification::new(NotificationKind::Fill, user, title, fill.clone()));
+            }
+        }
+    });
+}
@@ -0,0 +1,392 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{info, warn, error};
+
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::jsonl;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+const MAX_ACTIVE_PER_USER: usize = 100;
+/// How often active pairs are checked against `orderStatus`, catching fills missed
+/// while the WS feed was down or the server was restarting
+const RECONCILE_INTERVAL_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OcoStatus {
+    Active,
+    /// One leg filled and the sibling was cancelled
+    Triggered,
+    /// Unlinked by the user, or both legs left the book without a fill
+    Closed,
+}
+
+/// Two resting orders where a fill on either cancels the other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcoPair {
+    pub id: String,
+    pub user_address: String,
+    /// Account the orders rest on when trading for a vault
+    pub vault_address: Option<String>,
+    /// (asset index, oid) for each leg
+    pub legs: [(u64, u64); 2],
+    pub status: OcoStatus,
+    pub created_at: u64,
+    pub triggered_by: Option<u64>,
+    /// Session that linked the pair; not persisted, so after a restart the user's
+    /// current session is used instead
+    #[serde(skip)]
+    pub api_key: String,
+}
+
+impl OcoPair {
+    /// Account whose fills and orders this pair follows
+    pub fn account(&self) -> String {
+        self.vault_address.as_deref().unwrap_or(&self.user_address).to_lowercase()
+    }
+
+    /// The leg opposite `oid`, if `oid` is one of this pair's orders
+    fn sibling_of(&self, oid: u64) -> Option<(u64, u64)> {
+        match self.legs {
+            [a, b] if a.1 == oid => Some(b),
+            [a, b] if b.1 == oid => Some(a),
+            _ => None,
+        }
+    }
+}
+
+/// OCO links by id, persisted to a JSON-lines file so they survive restarts
+#[derive(Debug)]
+pub struct OcoBook {
+    pairs: HashMap<String, OcoPair>,
+    path: Option<PathBuf>,
+}
+
+impl OcoBook {
+    /// Open the book, keeping only pairs that were still active when last written
+    pub fn open(path: Option<PathBuf>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let pairs: Vec<OcoPair> = match &path {
+            Some(path) => jsonl::load(path)?,
+            None => Vec::new(),
+        };
+        let pairs: HashMap<String, OcoPair> = pairs.into_iter()
+            .filter(|p| p.status == OcoStatus::Active)
+            .map(|p| (p.id.clone(), p))
+            .collect();
+
+        info!("🔗 OCO book opened with {} active pairs", pairs.len());
+        Ok(Self { pairs, path })
+    }
+
+    /// Rewrite the backing file with the active pairs
+    fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        let active: Vec<&OcoPair> = self.pairs.values().filter(|p| p.status == OcoStatus::Active).collect();
+        if let Err(e) = jsonl::rewrite(path, &active) {
+            error!("❌ Failed to persist OCO pairs: {}", e);
+        }
+    }
+
+    pub fn list(&self, user_address: &str) -> Vec<OcoPair> {
+        let mut pairs: Vec<_> = self.pairs.values()
+            .filter(|p| p.user_address.eq_ignore_ascii_case(user_address))
+            .cloned()
+            .collect();
+        pairs.sort_by_key(|p| p.created_at);
+        pairs
+    }
+
+    fn active(&self) -> Vec<OcoPair> {
+        self.pairs.values().filter(|p| p.status == OcoStatus::Active).cloned().collect()
+    }
+
+    /// Whether an oid is already linked on the account
+    fn is_linked(&self, account: &str, oid: u64) -> bool {
+        self.pairs.values().any(|p| p.status == OcoStatus::Active && p.account() == account && p.sibling_of(oid).is_some())
+    }
+
+    /// Mark the active pair containing `oid` on `account` as triggered and return it,
+    /// so a fill is acted on once even if both legs fill in the same push
+    fn claim_fill(&mut self, account: &str, oid: u64) -> Option<OcoPair> {
+        let pair = self.pairs.values_mut()
+            .find(|p| p.status == OcoStatus::Active && p.account() == account && p.sibling_of(oid).is_some())?;
+        pair.status = OcoStatus::Triggered;
+        pair.triggered_by = Some(oid);
+        let pair = pair.clone();
+        self.persist();
+        Some(pair)
+    }
+
+    fn close(&mut self, id: &str) {
+        if let Some(pair) = self.pairs.get_mut(id) {
+            pair.status = OcoStatus::Closed;
+            self.persist();
+        }
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> usize {
+        let before = self.pairs.len();
+        self.pairs.retain(|_, pair| !pair.user_address.eq_ignore_ascii_case(user_address));
+        let removed = before - self.pairs.len();
+        if removed > 0 {
+            self.persist();
+        }
+        removed
+    }
+}
+
+/// Cancel the sibling of every linked order that fills, as reported by userFills pushes
+pub fn spawn_fill_watcher(state: AppState) {
+    let mut messages = state.ws_feed.listen();
+
+    tokio::spawn(async move {
+        loop {
+            let message = match messages.recv().await {
+                Ok(message) => message,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    // The reconcile loop picks up any fills in the dropped messages
+                    warn!("⚠️ OCO watcher lagged, {} WS messages dropped", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+
+            if message.get("channel").and_then(|c| c.as_str()) != Some("userFills") {
+                continue;
+            }
+            let data = match message.get("data") {
+                Some(data) if !data.get("isSnapshot").and_then(|s| s.as_bool()).unwrap_or(false) => data,
+                _ => continue,
+            };
+            let Some(account) = data.get("user").and_then(|u| u.as_str()).map(|u| u.to_lowercase()) else { continue };
+
+            for fill in data.get("fills").and_then(|f| f.as_array()).into_iter().flatten() {
+                let Some(oid) = fill.get("oid").and_then(|o| o.as_u64()) else { continue };
+                let claimed = state.oco.write().await.claim_fill(&account, oid);
+                if let Some(pair) = claimed {
+                    cancel_sibling(&state, &pair, oid).await;
+                }
+            }
+        }
+    });
+}
+
+/// Resubscribe to fills for persisted pairs and periodically reconcile them against
+/// upstream order status
+pub fn spawn_reconciler(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(RECONCILE_INTERVAL_SECS));
+        loop {
+            ticker.tick().await;
+            if state.ha.check().is_err() {
+                continue;
+            }
+
+            let pairs = state.oco.read().await.active();
+            for pair in pairs {
+                let account = pair.account();
+                state.ws_feed.subscribe(serde_json::json!({"type": "userFills", "user": account})).await;
+
+                let mut statuses = Vec::with_capacity(2);
+                for (_, oid) in pair.legs {
+                    match order_status(&state, &account, oid).await {
+                        Ok(status) => statuses.push(status),
+                        Err(e) => {
+                            warn!("⚠️ OCO {} could not load status of {}: {}", pair.id, oid, e);
+                            break;
+                        }
+                    }
+                }
+                let [a, b] = statuses.as_slice() else { continue };
+
+                match (a.as_str(), b.as_str()) {
+                    ("open", "open") => {}
+                    ("filled", _) | (_, "filled") => {
+                        let filled = if a == "filled" { pair.legs[0].1 } else { pair.legs[1].1 };
+                        let claimed = state.oco.write().await.claim_fill(&account, filled);
+                        if let Some(pair) = claimed {
+                            cancel_sibling(&state, &pair, filled).await;
+                        }
+                    }
+                    ("open", _) | (_, "open") => {
+                        // One leg left the book without filling; the link no longer means anything
+                        info!("🔗 OCO {} closed: a leg was cancelled outside the pair", pair.id);
+                        state.oco.write().await.close(&pair.id);
+                    }
+                    _ => state.oco.write().await.close(&pair.id),
+                }
+            }
+        }
+    });
+}
+
+/// Upstream `orderStatus` for an oid or cloid: "open", "filled", "canceled", ... or "unknownOid"
+pub(crate) async fn order_status(state: &AppState, account: &str, oid: impl Into<Value>) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let oid = oid.into();
+    let response = state.proxy
+        .proxy_info_request(&serde_json::json!({"type": "orderStatus", "user": account, "oid": oid}))
+        .await?;
+    let status = response.pointer("/order/status")
+        .or_else(|| response.get("status"))
+        .and_then(|s| s.as_str())
+        .ok_or("orderStatus response has no status")?;
+    Ok(status.to_string())
+}
+
+/// Cancel the leg opposite `filled_oid` through the normal /exchange pipeline
+async fn cancel_sibling(state: &AppState, pair: &OcoPair, filled_oid: u64) {
+    let Some((asset, oid)) = pair.sibling_of(filled_oid) else { return };
+
+    // Prefer the user's current session; the linking session may have expired or predate a restart
+    let api_key = state.session_manager
+        .get_user_session(&pair.user_address)
+        .map(|session| session.api_key)
+        .unwrap_or_else(|| pair.api_key.clone());
+    let Ok(api_key) = HeaderValue::from_str(&api_key) else {
+        error!("❌ OCO {}: no session to cancel {} for {}", pair.id, oid, pair.user_address);
+        return;
+    };
+    let mut headers = HeaderMap::new();
+    headers.insert("X-API-Key", api_key);
+
+    let mut payload = serde_json::json!({"action": {"type": "cancel", "cancels": [{"a": asset, "o": oid}]}});
+    if let Some(vault) = &pair.vault_address {
+        payload["vaultAddress"] = serde_json::json!(vault);
+    }
+
+    match crate::proxy_exchange(State(state.clone()), headers, Json(payload)).await {
+        Ok(Json(response)) => info!("🔗 OCO {}: {} filled, cancelled {}: {}", pair.id, filled_oid, oid, response),
+        Err(status) => error!("❌ OCO {}: cancelling {} failed: {}", pair.id, oid, status),
+    }
+}
+
+/// Body of POST /orders/oco
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOcoRequest {
+    pub oids: [u64; 2],
+    pub vault_address: Option<String>,
+}
+
+/// POST /orders/oco - Link two resting orders so a fill on either cancels the other
+pub async fn create_oco(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateOcoRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let (api_key, user_address) = session_user(&state, &headers).await?;
+    if payload.oids[0] == payload.oids[1] {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let account = payload.vault_address.as_deref().unwrap_or(&user_address).to_lowercase();
+
+    // Both legs must be resting on the account; their coins give the asset indices for cancels
+    let open_orders = state.proxy
+        .proxy_info_request(&serde_json::json!({"type": "openOrders", "user": account}))
+        .await
+        .map_err(|e| {
+            error!("❌ Failed to fetch open orders: {}", e);
+            StatusCode::BAD_GATEWAY
+        })?;
+    let mut legs = [(0, 0); 2];
+    for (leg, oid) in legs.iter_mut().zip(payload.oids) {
+        let coin = open_orders.as_array()
+            .and_then(|orders| orders.iter().find(|o| o.get("oid").and_then(|v| v.as_u64()) == Some(oid)))
+            .and_then(|order| order.get("coin"))
+            .and_then(|c| c.as_str());
+        let asset = match coin {
+            Some(coin) => state.market.asset_index(coin).await.ok().flatten(),
+            None => None,
+        };
+        let Some(asset) = asset else {
+            return Ok(Json(error_codes::err_body(ErrorCode::NotFound, format!("Order {} is not resting on {}", oid, account))));
+        };
+        *leg = (asset, oid);
+    }
+
+    let mut book = state.oco.write().await;
+    if let Some(oid) = payload.oids.iter().find(|oid| book.is_linked(&account, **oid)) {
+        return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, format!("Order {} is already in an OCO pair", oid))));
+    }
+    if book.list(&user_address).iter().filter(|p| p.status == OcoStatus::Active).count() >= MAX_ACTIVE_PER_USER {
+        return Ok(Json(error_codes::err_body(ErrorCode::LimitExceeded, format!("At most {} active OCO pairs per user", MAX_ACTIVE_PER_USER))));
+    }
+
+    let pair = OcoPair {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_address,
+        vault_address: payload.vault_address.map(|v| v.to_lowercase()),
+        legs,
+        status: OcoStatus::Active,
+        created_at: now_secs(),
+        triggered_by: None,
+        api_key,
+    };
+    book.pairs.insert(pair.id.clone(), pair.clone());
+    book.persist();
+    drop(book);
+
+    state.ws_feed.subscribe(serde_json::json!({"type": "userFills", "user": account})).await;
+
+    info!("🔗 OCO {} linked {} and {} on {}", pair.id, legs[0].1, legs[1].1, account);
+    Ok(Json(serde_json::json!({"status": "ok", "response": pair})))
+}
+
+/// GET /orders/oco - The caller's OCO pairs
+pub async fn list_oco(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(serde_json::json!({"pairs": state.oco.read().await.list(&user_address)})))
+}
+
+/// DELETE /orders/oco/:id - Unlink a pair, leaving both orders resting
+pub async fn delete_oco(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let (_, user_address) = session_user(&state, &headers).await?;
+    let mut book = state.oco.write().await;
+    let owned = book.pairs.get(&id)
+        .is_some_and(|p| p.status == OcoStatus::Active && p.user_address.eq_ignore_ascii_case(&user_address));
+    if !owned {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    book.close(&id);
+
+    info!("🔗 OCO {} unlinked", id);
+    Ok(Json(serde_json::json!({"status": "ok", "response": "unlinked"})))
+}
+
+/// Trading session behind the request; share tokens can't link orders
+async fn session_user(state: &AppState, headers: &HeaderMap) -> Result<(String, String), StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok((api_key.to_string(), user_address))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -0,0 +1,132 @@
+use alloy::sol_types::SolCall;
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::auth;
+use crate::error_codes::ErrorCode;
+use crate::evm::rpc_call;
+use crate::AppState;
+
+alloy::sol! {
+    function isRegisteredAgent(address agentAddress) external view returns (bool);
+}
+
+/// Where a session is in the onboarding flow; only ever moves forward
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingState {
+    /// SIWE login done, API key issued
+    LoggedIn,
+    /// Agent quote registered in the HyperEVM registry
+    QuoteRegistered,
+    /// approveAgent relayed through this server (not yet seen upstream)
+    AgentApproved,
+    /// Hyperliquid lists the agent for the user; /exchange is open
+    TradingEnabled,
+}
+
+impl OnboardingState {
+    /// What the user needs to do next
+    pub fn next_step(&self) -> Option<&'static str> {
+        match self {
+            Self::LoggedIn => Some("Register tdx_quote_hex with the HyperEVM registry"),
+            Self::QuoteRegistered => Some("Approve the agent address on Hyperliquid (approveAgent)"),
+            Self::AgentApproved => Some("Wait for Hyperliquid to list the agent approval"),
+            Self::TradingEnabled => None,
+        }
+    }
+
+    /// Whether /exchange may sign actions for this session
+    pub fn can_trade(&self) -> bool {
+        *self >= Self::AgentApproved
+    }
+}
+
+/// Re-check upstream state and advance the session's onboarding; returns the new state
+pub async fn refresh(state: &AppState, api_key: &str) -> Option<OnboardingState> {
+    let (user_address, agent_address, mut current) = state.session_manager.with_session(api_key, |session| {
+        (session.user_address.clone(), session.agent_address.clone(), session.onboarding)
+    })?;
+
+    if current < OnboardingState::QuoteRegistered {
+        match quote_registered(state, &agent_address).await {
+            Ok(true) => current = OnboardingState::QuoteRegistered,
+            Ok(false) => {}
+            Err(e) => warn!("⚠️ Registry lookup failed: {}", e),
+        }
+    }
+
+    if current >= OnboardingState::QuoteRegistered && current < OnboardingState::TradingEnabled {
+        match agent_listed(state, &user_address, &agent_address).await {
+            Ok(true) => current = OnboardingState::TradingEnabled,
+            Ok(false) => {}
+            Err(e) => warn!("⚠️ extraAgents lookup failed: {}", e),
+        }
+    }
+
+    state.session_manager.advance_onboarding(api_key, current)
+}
+
+/// Check the on-chain registry; deployments without a registry skip this step
+async fn quote_registered(state: &AppState, agent_address: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let (registry, rpc) = match (&state.config.registry_address, &state.config.hyperevm_rpc_url) {
+        (Some(registry), Some(rpc)) => (registry, rpc),
+        _ => return Ok(true),
+    };
+
+    let call = isRegisteredAgentCall { agentAddress: agent_address.parse()? };
+    let result = rpc_call(rpc, "eth_call", serde_json::json!([
+        {"to": registry, "data": format!("0x{}", hex::encode(call.abi_encode()))},
+        "latest"
+    ])).await?;
+
+    let bytes = hex::decode(result.as_str().ok_or("eth_call returned non-string")?.trim_start_matches("0x"))?;
+    Ok(bytes.last() == Some(&1))
+}
+
+/// Whether Hyperliquid lists the agent among the user's approved agents
+async fn agent_listed(state: &AppState, user_address: &str, agent_address: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let agents = state.proxy
+        .proxy_info_request(&serde_json::json!({"type": "extraAgents", "user": user_address}))
+        .await?;
+
+    Ok(agents.as_array().is_some_and(|agents| agents.iter().any(|a| {
+        a.get("address").and_then(|addr| addr.as_str()).map(|addr| addr.to_lowercase()) == Some(agent_address.to_lowercase())
+    })))
+}
+
+/// Structured /exchange rejection for sessions that can't trade yet
+pub fn not_ready_response(onboarding: OnboardingState) -> Value {
+    serde_json::json!({
+        "status": "err",
+        "response": "Agent not approved",
+        "code": ErrorCode::AgentNotApproved,
+        "onboarding": {
+            "state": onboarding,
+            "next_step": onboarding.next_step()
+        }
+    })
+}
+
+/// GET /agents/status - Onboarding state for the caller's session
+pub async fn agents_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let onboarding = refresh(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    info!("🧭 Onboarding status: {:?}", onboarding);
+
+    Ok(Json(serde_json::json!({
+        "state": onboarding,
+        "next_step": onboarding.next_step(),
+        "can_trade": onboarding.can_trade()
+    })))
+}
@@ -0,0 +1,158 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::auth;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::universal_signing::{ORDER_FIELDS, SUPPORTED_TIFS};
+use crate::AppState;
+
+/// Values filled into orders that leave a field out; nothing is filled unless the key owner set it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrderDefaults {
+    /// Limit order time-in-force, used when an order has no `t`
+    #[serde(default)]
+    pub tif: Option<String>,
+    /// Used when an order has no `r`
+    #[serde(default)]
+    pub reduce_only: Option<bool>,
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+/// Order defaults by API key
+#[derive(Debug, Default)]
+pub struct OrderDefaultsStore {
+    by_key: HashMap<String, OrderDefaults>,
+}
+
+impl OrderDefaultsStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, api_key: &str) -> Option<OrderDefaults> {
+        self.by_key.get(api_key).cloned()
+    }
+
+    /// Drop the defaults of a deleted user's key
+    pub fn remove_key(&mut self, api_key: &str) -> usize {
+        usize::from(self.by_key.remove(api_key).is_some())
+    }
+}
+
+/// Fill the caller's defaults into orders missing `t` or `r`.
+///
+/// Returns one record per filled field, which is sent back with the response; the filled
+/// action is what gets signed, so the audit log shows the values actually used.
+pub async fn apply(state: &AppState, api_key: &str, payload: &mut Value) -> Vec<Value> {
+    let Some(defaults) = state.order_defaults.read().await.by_key.get(api_key).cloned() else {
+        return Vec::new();
+    };
+    let Some(action) = payload.get_mut("action") else {
+        return Vec::new();
+    };
+
+    let orders: Vec<(String, &mut Value)> = match action.get("type").and_then(|t| t.as_str()) {
+        Some("order") => action.get_mut("orders").and_then(|o| o.as_array_mut()).into_iter().flatten()
+            .enumerate()
+            .map(|(i, order)| (format!("orders[{}]", i), order))
+            .collect(),
+        Some("batchModify") => action.get_mut("modifies").and_then(|m| m.as_array_mut()).into_iter().flatten()
+            .enumerate()
+            .filter_map(|(i, modify)| Some((format!("modifies[{}].order", i), modify.get_mut("order")?)))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut applied = Vec::new();
+    for (path, order) in orders {
+        let Some(fields) = order.as_object_mut() else {
+            continue;
+        };
+        let mut filled = false;
+        if let (false, Some(tif)) = (fields.contains_key("t"), &defaults.tif) {
+            let value = serde_json::json!({"limit": {"tif": tif}});
+            applied.push(serde_json::json!({"field": format!("{}.t", path), "value": value}));
+            fields.insert("t".to_string(), value);
+            filled = true;
+        }
+        if let (false, Some(reduce_only)) = (fields.contains_key("r"), defaults.reduce_only) {
+            applied.push(serde_json::json!({"field": format!("{}.r", path), "value": reduce_only}));
+            fields.insert("r".to_string(), Value::Bool(reduce_only));
+            filled = true;
+        }
+        // Hyperliquid hashes order fields in a fixed order; put filled fields back in place
+        if filled {
+            let mut reordered = serde_json::Map::new();
+            for key in ORDER_FIELDS {
+                if let Some(value) = fields.remove(key) {
+                    reordered.insert(key.to_string(), value);
+                }
+            }
+            reordered.append(fields);
+            *fields = reordered;
+        }
+    }
+
+    if !applied.is_empty() {
+        info!("🧩 Filled {} order field(s) from configured defaults", applied.len());
+    }
+    applied
+}
+
+/// Trading session behind the request; share tokens can't read or change defaults
+fn session_key(headers: &HeaderMap) -> Result<&str, StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(api_key)
+}
+
+/// GET /me/order-defaults - Defaults filled into this API key's orders
+pub async fn get_defaults(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = session_key(&headers)?;
+    let defaults = state.order_defaults.read().await.by_key.get(api_key).cloned().unwrap_or_default();
+    Ok(Json(serde_json::json!({"status": "ok", "response": defaults})))
+}
+
+/// PUT /me/order-defaults - Set or clear this API key's order defaults (absent fields are cleared)
+pub async fn set_defaults(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(mut payload): Json<OrderDefaults>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = session_key(&headers)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    if payload.tif.as_deref().is_some_and(|tif| !SUPPORTED_TIFS.contains(&tif)) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    payload.updated_at = now_secs();
+    let mut store = state.order_defaults.write().await;
+    if payload.tif.is_none() && payload.reduce_only.is_none() {
+        store.by_key.remove(api_key);
+    } else {
+        store.by_key.insert(api_key.to_string(), payload.clone());
+    }
+
+    info!("🧩 Order defaults for {} set to tif={:?}, reduce_only={:?}", user_address, payload.tif, payload.reduce_only);
+    Ok(Json(serde_json::json!({"status": "ok", "response": payload})))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
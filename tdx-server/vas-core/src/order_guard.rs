@@ -0,0 +1,114 @@
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::error_codes::{self, ErrorCode};
+use crate::AppState;
+
+/// Body fields carrying the precondition; they are removed before the action is signed
+pub const EXPECTED_STATUS_FIELD: &str = "expectedStatus";
+pub const EXPECTED_REMAINING_FIELD: &str = "expectedRemaining";
+
+/// What the client believes about the orders a cancel/modify targets
+#[derive(Debug, Clone)]
+pub struct Expectation {
+    /// Upstream order status, e.g. "open"
+    pub status: Option<String>,
+    /// Unfilled size as a decimal string; only for actions targeting a single order
+    pub remaining: Option<String>,
+}
+
+impl Expectation {
+    /// Remove the precondition fields from an /exchange body, if any were sent
+    pub fn take(payload: &mut Value) -> Result<Option<Self>, String> {
+        let Some(obj) = payload.as_object_mut() else {
+            return Ok(None);
+        };
+        let status = obj.remove(EXPECTED_STATUS_FIELD);
+        let remaining = obj.remove(EXPECTED_REMAINING_FIELD);
+        if status.is_none() && remaining.is_none() {
+            return Ok(None);
+        }
+
+        let status = match status {
+            Some(Value::String(status)) => Some(status),
+            Some(_) => return Err(format!("{} must be a string", EXPECTED_STATUS_FIELD)),
+            None => None,
+        };
+        let remaining = match remaining {
+            Some(Value::String(size)) if size.parse::<f64>().is_ok() => Some(size),
+            Some(Value::Number(size)) => Some(size.to_string()),
+            Some(_) => return Err(format!("{} must be a decimal size", EXPECTED_REMAINING_FIELD)),
+            None => None,
+        };
+        Ok(Some(Self { status, remaining }))
+    }
+}
+
+/// Orders (by oid or cloid) that a cancel or modify action acts on
+fn targets(action: &Value) -> Option<Vec<Value>> {
+    let field = |items: &str, key: &str| -> Vec<Value> {
+        action.get(items).and_then(|i| i.as_array()).into_iter().flatten()
+            .filter_map(|item| item.get(key).cloned())
+            .collect()
+    };
+    match action.get("type")?.as_str()? {
+        "cancel" => Some(field("cancels", "o")),
+        "cancelByCloid" => Some(field("cancels", "cloid")),
+        "modify" => Some(action.get("oid").cloned().into_iter().collect()),
+        "batchModify" => Some(field("modifies", "oid")),
+        _ => None,
+    }
+}
+
+fn same_size(a: &str, b: &str) -> bool {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => (a - b).abs() <= 1e-9 * a.abs().max(1.0),
+        _ => false,
+    }
+}
+
+/// Check each targeted order's upstream state against the client's expectation.
+///
+/// Returns the error body to send when the precondition does not hold (or cannot be checked),
+/// so nothing is signed for a client acting on a stale book.
+pub async fn check(state: &AppState, account: &str, action: &Value, expected: &Expectation) -> Result<(), Value> {
+    let bad_request = |reason: String| error_codes::err_body(ErrorCode::BadRequest, reason);
+    let targets = targets(action)
+        .ok_or_else(|| bad_request(format!("{} / {} apply only to cancel and modify actions", EXPECTED_STATUS_FIELD, EXPECTED_REMAINING_FIELD)))?;
+    if targets.is_empty() {
+        return Err(bad_request("The action targets no orders".to_string()));
+    }
+    if expected.remaining.is_some() && targets.len() > 1 {
+        return Err(bad_request(format!("{} needs an action targeting a single order", EXPECTED_REMAINING_FIELD)));
+    }
+
+    for oid in targets {
+        let response = state.proxy
+            .proxy_info_request(&serde_json::json!({"type": "orderStatus", "user": account, "oid": oid}))
+            .await
+            .map_err(|e| error_codes::err_body(ErrorCode::UpstreamUnavailable, format!("Could not load order state: {}", e)))?;
+        // Unknown oids come back as {"status": "unknownOid"}
+        let status = response.pointer("/order/status")
+            .or_else(|| response.get("status"))
+            .and_then(|s| s.as_str())
+            .unwrap_or("unknownOid");
+        let remaining = response.pointer("/order/order/sz").and_then(|s| s.as_str());
+
+        let status_differs = expected.status.as_deref().is_some_and(|want| want != status);
+        let remaining_differs = expected.remaining.as_deref()
+            .is_some_and(|want| !remaining.is_some_and(|have| same_size(want, have)));
+        if status_differs || remaining_differs {
+            warn!("⛔ Order {} is {} with {:?} remaining; client expected {:?} / {:?}",
+                oid, status, remaining, expected.status, expected.remaining);
+            let mut body = error_codes::err_body(
+                ErrorCode::OrderStateConflict,
+                format!("Order {} is {} with {} remaining, not as expected", oid, status, remaining.unwrap_or("no size")),
+            );
+            body["order"] = serde_json::json!({"oid": oid, "status": status, "remaining": remaining});
+            return Err(body);
+        }
+    }
+
+    info!("✅ Order state precondition holds for {}", account);
+    Ok(())
+}
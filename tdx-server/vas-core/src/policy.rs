@@ -0,0 +1,185 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use crate::address;
+use crate::audit::AUDIT_EXCHANGE_ACTION;
+use crate::config::Config;
+use crate::error_codes::ErrorCode;
+use crate::AppState;
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+const MAX_DRY_RUN_DAYS: u64 = 90;
+
+/// Pre-sign rules applied to every action the agent signs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    /// Highest builder fee accepted, in tenths of a basis point (the wire unit of `builder.f`)
+    pub max_builder_fee: Option<u64>,
+    /// Builder addresses orders may route fees to (None = any builder)
+    pub builder_allowlist: Option<Vec<String>>,
+}
+
+/// Why the policy refused an action
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyViolation {
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Value,
+}
+
+impl PolicyViolation {
+    /// Exchange-style error body carrying the machine-readable code
+    pub fn to_response(&self) -> Value {
+        serde_json::json!({
+            "status": "err",
+            "response": self.message,
+            "code": self.code,
+            "details": self.details
+        })
+    }
+}
+
+impl Policy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_builder_fee: config.max_builder_fee,
+            builder_allowlist: config.builder_allowlist.as_ref()
+                .map(|list| list.iter().map(|b| address::normalize(b)).collect()),
+        }
+    }
+
+    /// Normalize a policy supplied by an operator (addresses compare lowercased)
+    fn normalized(mut self) -> Self {
+        if let Some(allowlist) = &mut self.builder_allowlist {
+            allowlist.iter_mut().for_each(|b| *b = address::normalize(b));
+        }
+        self
+    }
+
+    /// Check an action against every rule
+    pub fn evaluate(&self, action: &Value) -> Result<(), PolicyViolation> {
+        self.check_builder(action)
+    }
+
+    /// Builder codes ride on `order` actions as `{"builder": {"b": address, "f": fee}}`
+    fn check_builder(&self, action: &Value) -> Result<(), PolicyViolation> {
+        let builder = match action.get("builder") {
+            Some(builder) => builder,
+            None => return Ok(()),
+        };
+
+        let address = address::normalize(builder.get("b").and_then(|b| b.as_str()).unwrap_or_default());
+        let fee = builder.get("f").and_then(|f| f.as_u64()).unwrap_or(0);
+
+        if let Some(allowlist) = &self.builder_allowlist {
+            if !allowlist.contains(&address) {
+                return Err(PolicyViolation {
+                    code: ErrorCode::PolicyBuilderNotAllowed,
+                    message: format!("Builder {} is not in the allowlist", address),
+                    details: serde_json::json!({"builder": address}),
+                });
+            }
+        }
+
+        if let Some(max_fee) = self.max_builder_fee {
+            if fee > max_fee {
+                return Err(PolicyViolation {
+                    code: ErrorCode::PolicyBuilderFeeExceeded,
+                    message: format!("Builder fee {} exceeds maximum {} (tenths of a basis point)", fee, max_fee),
+                    details: serde_json::json!({"builder": address, "fee": fee, "max_fee": max_fee}),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Body of POST /admin/policy/dry-run
+#[derive(Debug, Deserialize)]
+pub struct DryRunRequest {
+    pub user: String,
+    /// Days of audit history to replay, counting back from now
+    #[serde(default = "default_dry_run_days")]
+    pub days: u64,
+    pub policy: Policy,
+}
+
+fn default_dry_run_days() -> u64 {
+    7
+}
+
+/// POST /admin/policy/dry-run - Evaluate a proposed policy against a user's recently signed actions.
+///
+/// Lists every action in the window the proposed policy would have refused, and whether the
+/// policy in force refuses it too, so limits can be tuned without surprising live strategies.
+/// Nothing is changed.
+pub async fn admin_policy_dry_run(
+    State(state): State<AppState>,
+    Json(request): Json<DryRunRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    if request.days == 0 || request.days > MAX_DRY_RUN_DAYS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let proposed = request.policy.normalized();
+    let current = state.policy.read().await.clone();
+    let to_ms = now_ms();
+    let from_ms = to_ms.saturating_sub(request.days * DAY_MS);
+
+    let audit = state.audit.read().await;
+    let actions: Vec<_> = audit.signatures_for(&request.user, from_ms, to_ms).into_iter()
+        .filter(|entry| entry.kind == AUDIT_EXCHANGE_ACTION)
+        .collect();
+
+    let mut blocked = Vec::new();
+    let mut newly_blocked = 0;
+    for entry in &actions {
+        let Some(action) = entry.subject.get("action") else { continue };
+        let Err(violation) = proposed.evaluate(action) else { continue };
+        let blocked_now = current.evaluate(action).is_err();
+        if !blocked_now {
+            newly_blocked += 1;
+        }
+        blocked.push(serde_json::json!({
+            "seq": entry.seq,
+            "timestamp_ms": entry.timestamp_ms,
+            "action_hash": entry.subject_hash,
+            "action_type": action.get("type"),
+            "code": violation.code,
+            "message": violation.message,
+            "details": violation.details,
+            "blocked_by_current_policy": blocked_now
+        }));
+    }
+    let evaluated = actions.len();
+    drop(audit);
+
+    info!("🧪 Policy dry run for {} over {} days: {} of {} actions blocked ({} newly)",
+        request.user, request.days, blocked.len(), evaluated, newly_blocked);
+
+    Ok(Json(serde_json::json!({
+        "user": address::normalize(&request.user),
+        "from_ms": from_ms,
+        "to_ms": to_ms,
+        "current_policy": current,
+        "proposed_policy": proposed,
+        "actions_evaluated": evaluated,
+        "blocked": blocked.len(),
+        "newly_blocked": newly_blocked,
+        "blocked_actions": blocked
+    })))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -1,8 +1,11 @@
-use std::sync::OnceLock;
+use std::sync::RwLock;
 use secp256k1::{SecretKey, PublicKey, Secp256k1};
 use hex;
 use tracing::{info, error};
 
+use crate::address;
+use crate::attestation;
+
 /// Preset TDX data for Mac development (no real TDX hardware access)
 #[derive(Debug, Clone)]
 pub struct PresetTDXData {
@@ -12,10 +15,17 @@ pub struct PresetTDXData {
     pub agent_private_key: SecretKey,
     /// Agent address derived from the private key
     pub agent_address: String,
+    /// SHA-256 of the quote, used to reference it from execution records
+    pub quote_id: String,
+    /// Why the quote's report data does not bind `agent_address`; /agents is refused while set
+    pub key_binding_error: Option<String>,
 }
 
-/// Global preset data instance
-static PRESET_TDX_DATA: OnceLock<PresetTDXData> = OnceLock::new();
+/// Global preset data instance; replaced only when a TCB recovery installs a new quote
+static PRESET_TDX_DATA: RwLock<Option<&'static PresetTDXData>> = RwLock::new(None);
+
+/// Where the quote is read from, in order
+const QUOTE_PATHS: [&str; 2] = ["agent_quote.bin", "../agent_quote.bin"];
 
 impl PresetTDXData {
     /// Initialize preset TDX data (called once on startup)
@@ -64,16 +74,36 @@ impl PresetTDXData {
         // Derive agent address from private key
         let secp = Secp256k1::new();
         let public_key = PublicKey::from_secret_key(&secp, &agent_private_key);
-        let agent_address = Self::public_key_to_address(&public_key);
+        let agent_address = address::public_key_to_address(&public_key);
+
+        let quote_id = {
+            use sha2::{Sha256, Digest};
+            hex::encode(Sha256::digest(&tdx_quote))
+        };
+
+        // agent_quote.bin and AGENT_PRIVATE_KEY are provisioned separately and can drift apart
+        let key_binding_error = attestation::check_key_binding(&tdx_quote, &agent_address).err();
+        match &key_binding_error {
+            None => info!("🔗 Quote binds the loaded agent key"),
+            Some(e) => error!("❌ {}; /agents will not be served", e),
+        }
 
         let preset_data = PresetTDXData {
             tdx_quote,
             agent_private_key,
             agent_address: agent_address.clone(),
+            quote_id,
+            key_binding_error,
         };
 
         // Store globally
-        PRESET_TDX_DATA.set(preset_data).map_err(|_| "Failed to set preset data")?;
+        {
+            let mut current = PRESET_TDX_DATA.write().map_err(|_| "Preset data lock poisoned")?;
+            if current.is_some() {
+                return Err("Failed to set preset data".into());
+            }
+            *current = Some(Box::leak(Box::new(preset_data)));
+        }
         
         info!("🤖 Preset TDX agent: {}", agent_address);
         info!("📝 Agent ready for SIWE authentication workflow");
@@ -83,30 +113,37 @@ impl PresetTDXData {
 
     /// Get the global preset TDX data
     pub fn get() -> Option<&'static PresetTDXData> {
-        PRESET_TDX_DATA.get()
+        *PRESET_TDX_DATA.read().unwrap()
     }
 
-    /// Convert public key to Ethereum address using proper Keccak256
-    fn public_key_to_address(public_key: &PublicKey) -> String {
-        use tiny_keccak::{Hasher, Keccak};
-        
-        // Get uncompressed public key (65 bytes: 0x04 + 32 bytes x + 32 bytes y)
-        let public_key_bytes = public_key.serialize_uncompressed();
-        
-        // Take last 64 bytes (skip the 0x04 prefix)
-        let public_key_hash = &public_key_bytes[1..];
-        
-        // Keccak256 hash of the public key
-        let mut keccak = Keccak::v256();
-        let mut hash = [0u8; 32];
-        keccak.update(public_key_hash);
-        keccak.finalize(&mut hash);
-        
-        // Take last 20 bytes as Ethereum address
-        let address_bytes = &hash[12..];
-        
-        // Format as 0x prefixed hex string
-        format!("0x{}", hex::encode(address_bytes))
+    /// Serve `tdx_quote` for the same agent key from now on, returning the data now in force.
+    ///
+    /// Earlier data stays valid for holders of the old reference; quotes change only on
+    /// TCB recovery, so keeping each one alive costs a few kilobytes per recovery.
+    pub fn install_quote(tdx_quote: Vec<u8>) -> Result<&'static PresetTDXData, String> {
+        let mut current = PRESET_TDX_DATA.write().unwrap();
+        let previous = current.ok_or("Preset TDX data not initialized")?;
+        let quote_id = {
+            use sha2::{Sha256, Digest};
+            hex::encode(Sha256::digest(&tdx_quote))
+        };
+        let key_binding_error = attestation::check_key_binding(&tdx_quote, &previous.agent_address).err();
+        let installed: &'static PresetTDXData = Box::leak(Box::new(PresetTDXData {
+            tdx_quote,
+            quote_id,
+            key_binding_error,
+            ..previous.clone()
+        }));
+        *current = Some(installed);
+        info!("🤖 Installed quote {} for agent {}", installed.quote_id, installed.agent_address);
+        Ok(installed)
+    }
+
+    /// Read the quote file as it is now, e.g. after it was regenerated
+    pub fn read_quote() -> Result<Vec<u8>, String> {
+        QUOTE_PATHS.iter()
+            .find_map(|path| std::fs::read(path).ok())
+            .ok_or_else(|| format!("TDX quote not found at {}", QUOTE_PATHS.join(" or ")))
     }
 }
 
@@ -139,6 +176,14 @@ impl PresetTDXData {
         }
     }
 
+    /// Reference to the attestation quote currently in force
+    pub fn attestation_reference(&self) -> serde_json::Value {
+        serde_json::json!({
+            "quote_id": self.quote_id,
+            "quote_url": "/agents/quote"
+        })
+    }
+
     /// Create TDX quote response
     pub fn create_quote_response(&self) -> TDXQuoteResponse {
         TDXQuoteResponse {
@@ -150,25 +195,6 @@ impl PresetTDXData {
     }
 }
 
-/// Generate a unique API key for a user
-pub fn generate_api_key(user_address: &str) -> String {
-    use sha2::{Sha256, Digest};
-    
-    // Create deterministic API key based on user address + timestamp
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    
-    let input = format!("{}:{}", user_address, timestamp);
-    let mut hasher = Sha256::new();
-    hasher.update(input.as_bytes());
-    let hash = hasher.finalize();
-    
-    // Take first 16 bytes and format as hex
-    format!("ak_{}", hex::encode(&hash[..16]))
-}
-
 // TODO: In production, replace with real TDX quote generation
 // TODO: Load agent key from secure TDX environment
 // TODO: Implement proper Keccak256 for address derivation
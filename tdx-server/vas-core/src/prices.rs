@@ -0,0 +1,40 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::error;
+
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct PricesQuery {
+    /// Comma-separated coins; all perps when absent
+    pub coins: Option<String>,
+}
+
+/// GET /market/prices - Mark, oracle and mid prices from the snapshot pre-sign risk checks use.
+///
+/// `as_of` is when Hyperliquid served the snapshot; it is never more than `max_staleness_ms`
+/// old, and the request fails rather than serve anything older.
+pub async fn market_prices(
+    State(state): State<AppState>,
+    Query(query): Query<PricesQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let mut snapshot = state.market.prices().await.map_err(|e| {
+        error!("❌ Failed to load prices: {}", e);
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    if let Some(coins) = query.coins {
+        let wanted: Vec<&str> = coins.split(',').map(str::trim).collect();
+        snapshot.prices.retain(|coin, _| wanted.contains(&coin.as_str()));
+        if snapshot.prices.is_empty() {
+            return Err(StatusCode::NOT_FOUND);
+        }
+    }
+
+    Ok(Json(serde_json::json!(snapshot)))
+}
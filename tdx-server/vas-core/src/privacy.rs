@@ -0,0 +1,267 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::auth;
+use crate::jsonl;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+/// Identifies the layout of GET /me/export bundles
+const EXPORT_FORMAT: &str = "vas-user-export/1";
+/// How often due deletions are carried out
+const ERASER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A user's request to have their data deleted once the grace period ends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletionRequest {
+    pub user_address: String,
+    pub requested_at_ms: u64,
+    pub execute_at_ms: u64,
+}
+
+/// Pending deletions by lowercased user, persisted so a restart doesn't drop them
+#[derive(Debug)]
+pub struct DeletionQueue {
+    pending: HashMap<String, DeletionRequest>,
+    path: Option<PathBuf>,
+}
+
+impl DeletionQueue {
+    /// Open the queue, replaying requests already persisted at `path`
+    pub fn open(path: Option<PathBuf>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let stored: Vec<DeletionRequest> = match &path {
+            Some(path) => jsonl::load(path)?,
+            None => Vec::new(),
+        };
+        let pending: HashMap<String, DeletionRequest> = stored.into_iter()
+            .map(|request| (request.user_address.clone(), request))
+            .collect();
+
+        info!("🗑️ Deletion queue opened with {} pending requests", pending.len());
+        Ok(Self { pending, path })
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        let pending: Vec<&DeletionRequest> = self.pending.values().collect();
+        if let Err(e) = jsonl::rewrite(path, &pending) {
+            error!("❌ Failed to persist deletion queue: {}", e);
+        }
+    }
+
+    /// Schedule a deletion; asking again keeps the original schedule
+    fn schedule(&mut self, user_address: &str, grace_ms: u64) -> DeletionRequest {
+        let now = now_ms();
+        let request = self.pending.entry(user_address.to_string()).or_insert_with(|| DeletionRequest {
+            user_address: user_address.to_string(),
+            requested_at_ms: now,
+            execute_at_ms: now + grace_ms,
+        }).clone();
+        self.persist();
+        request
+    }
+
+    fn cancel(&mut self, user_address: &str) -> bool {
+        let cancelled = self.pending.remove(user_address).is_some();
+        if cancelled {
+            self.persist();
+        }
+        cancelled
+    }
+
+    pub fn get(&self, user_address: &str) -> Option<DeletionRequest> {
+        self.pending.get(user_address).cloned()
+    }
+
+    fn due(&self, now_ms: u64) -> Vec<String> {
+        self.pending.values()
+            .filter(|request| request.execute_at_ms <= now_ms)
+            .map(|request| request.user_address.clone())
+            .collect()
+    }
+}
+
+/// Delete everything held about a user except what the audit chain needs.
+///
+/// Sessions end, per-user stores drop their records and audit entries are redacted to their
+/// hashes. The erasure itself is audited under the hash of the address. Returns the number of
+/// records removed per store.
+pub async fn erase_user(state: &AppState, user_address: &str) -> BTreeMap<&'static str, usize> {
+    let user_address = user_address.to_lowercase();
+    let mut erased = BTreeMap::new();
+
+    let api_keys = state.session_manager.remove_user(&user_address);
+    erased.insert("sessions", api_keys.len());
+
+    let mut order_defaults = 0;
+    let mut clock_drift = 0;
+    let mut debug_traces = 0;
+    let mut idempotency = 0;
+    for api_key in &api_keys {
+        order_defaults += state.order_defaults.write().await.remove_key(api_key);
+        clock_drift += state.drift.write().await.remove_key(api_key);
+        debug_traces += state.recorder.write().await.remove_key(api_key);
+        idempotency += state.idempotency.write().await.remove_key(api_key);
+    }
+    erased.insert("order_defaults", order_defaults);
+    erased.insert("clock_drift", clock_drift);
+    erased.insert("debug_traces", debug_traces);
+    erased.insert("idempotency_records", idempotency);
+    erased.insert("key_usage", state.key_abuse.remove_user(&user_address, &api_keys));
+    erased.insert("bot_activity", state.bots.remove_user(&user_address));
+
+    erased.insert("conditional_orders", state.conditional_orders.write().await.remove_user(&user_address));
+    erased.insert("recurring_orders", state.dca.write().await.remove_user(&user_address));
+    erased.insert("oco_pairs", state.oco.write().await.remove_user(&user_address));
+    erased.insert("strategies", state.automation.write().await.remove_user(&user_address));
+    erased.insert("escrow_orders", state.escrow_orders.write().await.remove_user(&user_address));
+    erased.insert("strategy_limits", state.strategy_limits.write().await.remove_user(&user_address));
+    erased.insert("grants", state.delegations.write().await.remove_user(&user_address));
+    erased.insert("share_links", state.shares.write().await.remove_user(&user_address));
+    erased.insert("leaderboard", state.leaderboard.write().await.remove_user(&user_address));
+    erased.insert("agent_notes", state.agent_notes.write().await.remove_user(&user_address));
+
+    let results = [
+        ("events", state.event_store.write().await.remove_user(&user_address)),
+        ("equity_points", state.equity.write().await.remove_user(&user_address)),
+        ("audit_entries_redacted", state.audit.write().await.redact_user(&user_address)),
+    ];
+    for (store, result) in results {
+        match result {
+            Ok(count) => {
+                erased.insert(store, count);
+            }
+            Err(e) => error!("❌ Failed to erase {} for a deleted user: {}", store, e),
+        }
+    }
+
+    let entry = state.audit.write().await.record_user_erasure(&user_address, serde_json::json!(erased));
+    info!("🗑️ Erased user data (audit seq {}): {:?}", entry.seq, erased);
+    erased
+}
+
+/// Carry out deletions whose grace period has ended
+pub fn spawn_eraser(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(ERASER_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let due = state.deletions.read().await.due(now_ms());
+            for user_address in due {
+                erase_user(&state, &user_address).await;
+                state.deletions.write().await.cancel(&user_address);
+            }
+        }
+    });
+}
+
+/// Trading session behind the request; share tokens can neither export nor delete
+async fn session_user(state: &AppState, headers: &HeaderMap) -> Result<(String, String), StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok((api_key.to_string(), user_address))
+}
+
+/// POST /me/delete - Schedule deletion of the caller's data after the grace period
+pub async fn request_deletion(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let (_, user_address) = session_user(&state, &headers).await?;
+    let grace_ms = state.config.user_deletion_grace_secs * 1000;
+    let request = state.deletions.write().await.schedule(&user_address.to_lowercase(), grace_ms);
+
+    info!("🗑️ Deletion of {} scheduled for {}", user_address, request.execute_at_ms);
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "response": request,
+        "note": "DELETE /me/delete cancels the request until it runs"
+    })))
+}
+
+/// DELETE /me/delete - Cancel a pending deletion
+pub async fn cancel_deletion(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let (_, user_address) = session_user(&state, &headers).await?;
+    if !state.deletions.write().await.cancel(&user_address.to_lowercase()) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("🗑️ Deletion of {} cancelled", user_address);
+    Ok(Json(serde_json::json!({"status": "ok", "response": "cancelled"})))
+}
+
+/// GET /me/export - Everything held about the caller, as one JSON bundle
+pub async fn export_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let (api_key, user_address) = session_user(&state, &headers).await?;
+    let user = user_address.to_lowercase();
+
+    let session = state.session_manager.get_session(&api_key).map(|session| serde_json::json!({
+        "agent_address": session.agent_address,
+        "created_at": session.created_at,
+        "expires_at": session.expires_at,
+        "scopes": session.scopes,
+        "allowed_assets": session.allowed_assets,
+        "referrer_code": session.referrer_code,
+        "referrer_opt_out": session.referrer_opt_out,
+        "cosigner_address": session.cosigner_address,
+        "onboarding": session.onboarding,
+        "ens_name": session.ens_name,
+        "locale": session.locale,
+    }));
+    let audit_entries: Vec<Value> = state.audit.read().await.signatures_for(&user, 0, u64::MAX)
+        .into_iter()
+        .map(|entry| serde_json::json!(entry))
+        .collect();
+    let events: Vec<Value> = state.event_store.read().await.for_user(&user)
+        .into_iter()
+        .map(|event| serde_json::json!(event))
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "format": EXPORT_FORMAT,
+        "generated_at_ms": now_ms(),
+        "user_address": user,
+        "session": session,
+        "pending_deletion": state.deletions.read().await.get(&user),
+        "audit_entries": audit_entries,
+        "events": events,
+        "agent_notes": state.agent_notes.read().await.export_user(&user),
+        "equity": state.equity.read().await.export_user(&user),
+        "order_defaults": state.order_defaults.read().await.get(&api_key),
+        "strategy_limits": state.strategy_limits.read().await.limits_for(&user),
+        "bot_liveness": state.bots.expectation(&user),
+        "leaderboard": state.leaderboard.read().await.participant(&user),
+        "conditional_orders": state.conditional_orders.read().await.list(&user),
+        "recurring_orders": state.dca.read().await.list(&user),
+        "oco_pairs": state.oco.read().await.list(&user),
+        "strategies": state.automation.read().await.list(&user),
+        "escrow_orders": state.escrow_orders.read().await.list(&user),
+        "grants": state.delegations.read().await.involving(&user),
+        "share_links": state.shares.write().await.list(&user),
+    })))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
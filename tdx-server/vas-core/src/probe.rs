@@ -0,0 +1,158 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Json,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::preset_tdx::PresetTDXData;
+use crate::signer::ActionRequest;
+use crate::AppState;
+
+/// Consecutive probe failures before /readyz reports the signing path as down
+const FAILURES_BEFORE_UNREADY: u64 = 2;
+
+/// Latest result of the end-to-end signing probe
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProbeStatus {
+    pub runs: u64,
+    pub failures: u64,
+    pub consecutive_failures: u64,
+    pub last_run_ms: Option<u64>,
+    pub last_ok_ms: Option<u64>,
+    pub last_latency_ms: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+impl ProbeStatus {
+    pub fn healthy(&self) -> bool {
+        self.last_ok_ms.is_some() && self.consecutive_failures < FAILURES_BEFORE_UNREADY
+    }
+}
+
+/// Periodically sign and submit a zero-impact action to prove the whole signing path works:
+/// signer backend, agent approval upstream, nonce handling and submission.
+///
+/// The probe cancels order id 0, which can never exist. Upstream verifies the signature and
+/// agent before looking at the order, so a top-level `ok` (with a per-order "never placed"
+/// error) means the path works, while a bad key or revoked agent comes back as `err`.
+pub fn spawn_probe(state: AppState) {
+    let Some(interval_secs) = state.config.signing_probe_interval_secs else {
+        info!("🩺 Signing probe disabled");
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs.max(10)));
+        loop {
+            ticker.tick().await;
+
+            // Fenced instances (HA standby) can't sign; that's not a signing-path failure
+            if state.ha.check().is_err() {
+                continue;
+            }
+
+            let started = Instant::now();
+            let result = run_probe(&state).await;
+            let latency_ms = started.elapsed().as_millis() as u64;
+
+            let mut status = state.probe.write().await;
+            status.runs += 1;
+            status.last_run_ms = Some(now_ms());
+            status.last_latency_ms = Some(latency_ms);
+            match result {
+                Ok(()) => {
+                    status.last_ok_ms = status.last_run_ms;
+                    status.consecutive_failures = 0;
+                    status.last_error = None;
+                }
+                Err(e) => {
+                    warn!("🩺 Signing probe failed: {}", e);
+                    status.failures += 1;
+                    status.consecutive_failures += 1;
+                    status.last_error = Some(e);
+                }
+            }
+        }
+    });
+}
+
+async fn run_probe(state: &AppState) -> Result<(), String> {
+    let request = ActionRequest {
+        action: serde_json::json!({"type": "cancel", "cancels": [{"a": 0, "o": 0}]}),
+        nonce: now_ms(),
+        vault_address: None,
+        is_mainnet: state.config.is_mainnet(),
+        user_address: None,
+    };
+
+    let response = state.signer.sign_action(request).await.map_err(|e| e.to_string())?;
+    match response.get("status").and_then(|s| s.as_str()) {
+        Some("ok") => Ok(()),
+        _ => Err(format!("Upstream rejected probe: {}", response)),
+    }
+}
+
+/// GET /readyz - Whether this instance should receive signing traffic
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let attested = PresetTDXData::get().is_some();
+    let fence = state.ha.check();
+    let probe = state.probe.read().await.clone();
+    let probe_ok = state.config.signing_probe_interval_secs.is_none() || probe.healthy();
+
+    let ready = attested && fence.is_ok() && probe_ok;
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+    (status, Json(serde_json::json!({
+        "ready": ready,
+        "checks": {
+            "attestation": attested,
+            "signing_fence": fence.err().unwrap_or_else(|| "open".to_string()),
+            "signing_probe": if state.config.signing_probe_interval_secs.is_some() {
+                serde_json::json!(probe)
+            } else {
+                serde_json::json!("disabled")
+            }
+        }
+    })))
+}
+
+/// Prometheus lines for the signing probe
+pub async fn prometheus(state: &AppState) -> String {
+    if state.config.signing_probe_interval_secs.is_none() {
+        return String::new();
+    }
+    let probe = state.probe.read().await;
+
+    let mut out = String::new();
+    out.push_str("# HELP vas_signing_probe_runs_total Signing probes attempted\n");
+    out.push_str("# TYPE vas_signing_probe_runs_total counter\n");
+    out.push_str(&format!("vas_signing_probe_runs_total {}\n", probe.runs));
+    out.push_str("# HELP vas_signing_probe_failures_total Signing probes that failed\n");
+    out.push_str("# TYPE vas_signing_probe_failures_total counter\n");
+    out.push_str(&format!("vas_signing_probe_failures_total {}\n", probe.failures));
+    out.push_str("# HELP vas_signing_probe_healthy Whether the signing path is currently passing\n");
+    out.push_str("# TYPE vas_signing_probe_healthy gauge\n");
+    out.push_str(&format!("vas_signing_probe_healthy {}\n", probe.healthy() as u8));
+    if let Some(latency_ms) = probe.last_latency_ms {
+        out.push_str("# HELP vas_signing_probe_latency_ms Latency of the last probe\n");
+        out.push_str("# TYPE vas_signing_probe_latency_ms gauge\n");
+        out.push_str(&format!("vas_signing_probe_latency_ms {}\n", latency_ms));
+    }
+    if let Some(last_ok_ms) = probe.last_ok_ms {
+        out.push_str("# HELP vas_signing_probe_last_ok_ms Unix ms of the last passing probe\n");
+        out.push_str("# TYPE vas_signing_probe_last_ok_ms gauge\n");
+        out.push_str(&format!("vas_signing_probe_last_ok_ms {}\n", last_ok_ms));
+    }
+    out
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
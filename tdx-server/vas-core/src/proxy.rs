@@ -0,0 +1,181 @@
+use axum::body::Bytes;
+use reqwest::{header, Client};
+use serde::Deserialize;
+use serde_json::Value;
+use std::borrow::Cow;
+use tracing::{info, error};
+
+use crate::tls_pin::{self, TlsPin};
+
+/// The only field of an /info body the proxy looks at; the rest is skipped unparsed
+#[derive(Deserialize)]
+struct InfoKind<'a> {
+    #[serde(borrow, rename = "type", default)]
+    kind: Option<Cow<'a, str>>,
+}
+
+/// The `type` of a raw /info body ("" when absent). Checks the body is well-formed JSON
+/// without building a `Value`, so it can be forwarded byte for byte.
+pub fn info_type(body: &[u8]) -> Result<Cow<'_, str>, serde_json::Error> {
+    let info: InfoKind = serde_json::from_slice(body)?;
+    Ok(info.kind.unwrap_or_default())
+}
+
+fn is_json(response: &reqwest::Response) -> bool {
+    response.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"))
+}
+
+#[derive(Debug)]
+pub struct HyperliquidProxy {
+    client: Client,
+    base_url: String,
+}
+
+impl HyperliquidProxy {
+    pub fn new(base_url: &str) -> Self {
+        let client = Client::new();
+        
+        Self {
+            client,
+            base_url: base_url.to_string(),
+        }
+    }
+
+    /// Proxy whose connections fail unless the server's chain matches one of `pins`
+    pub fn pinned(base_url: &str, pins: &[TlsPin]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !base_url.starts_with("https://") {
+            return Err("TLS pins require an https Hyperliquid URL".into());
+        }
+        let client = Client::builder()
+            .use_preconfigured_tls(tls_pin::client_config(pins)?)
+            .build()?;
+
+        Ok(Self {
+            client,
+            base_url: base_url.to_string(),
+        })
+    }
+
+    pub async fn proxy_info_request(&self, payload: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}/info", self.base_url);
+        
+        info!("Making request to: {}", url);
+        info!("Payload: {}", payload);
+
+        let response = self
+            .client
+            .post(&url)
+            .json(payload)
+            .send()
+            .await?;
+
+        let status = response.status();
+        info!("Response status: {}", status);
+
+        if status.is_success() {
+            let json_response: Value = response.json().await?;
+            info!("Response received successfully");
+            Ok(json_response)
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            error!("Hyperliquid API error: {} - {}", status, error_text);
+            Err(format!("API error: {} - {}", status, error_text).into())
+        }
+    }
+
+    /// Forward a raw info body and relay the upstream body as received, up to `max_response_bytes`
+    pub async fn passthrough_info_request(&self, body: Bytes, max_response_bytes: usize) -> Result<Bytes, Box<dyn std::error::Error + Send + Sync>> {
+        let mut response = self.stream_info_request(body).await?;
+        if let Some(len) = response.content_length() {
+            if len > max_response_bytes as u64 {
+                return Err(format!("Info response of {} bytes exceeds {}", len, max_response_bytes).into());
+            }
+            return Ok(response.bytes().await?);
+        }
+
+        // Chunked: cap while reading rather than after
+        let mut relayed = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if relayed.len() + chunk.len() > max_response_bytes {
+                return Err(format!("Info response exceeds {} bytes", max_response_bytes).into());
+            }
+            relayed.extend_from_slice(&chunk);
+        }
+        Ok(relayed.into())
+    }
+
+    /// Send a raw info body and hand back the upstream response unread, so large bodies
+    /// (l2Book, candleSnapshot) can be streamed to the client instead of buffered
+    pub async fn stream_info_request(&self, body: Bytes) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}/info", self.base_url);
+
+        let response = self
+            .client
+            .post(&url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            error!("Hyperliquid API error: {} - {}", status, error_text);
+            return Err(format!("API error: {} - {}", status, error_text).into());
+        }
+        if !is_json(&response) {
+            return Err(format!("Info response is not JSON ({:?})", response.headers().get(header::CONTENT_TYPE)).into());
+        }
+        Ok(response)
+    }
+
+    pub async fn proxy_exchange_request(&self, payload: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}/exchange", self.base_url);
+        
+        info!("🔄 Proxying signed exchange request to: {}", url);
+        info!("📋 Payload keys: {:?}", payload.as_object().map(|o| o.keys().collect::<Vec<_>>()));
+        
+        // Verify that the payload has required fields for signed request
+        if !payload.get("signature").is_some() {
+            error!("❌ Exchange request missing signature - this should not happen");
+            return Err("Exchange request missing signature".into());
+        }
+        
+        if !payload.get("nonce").is_some() {
+            error!("❌ Exchange request missing nonce - this should not happen");
+            return Err("Exchange request missing nonce".into());
+        }
+        
+        if !payload.get("action").is_some() {
+            error!("❌ Exchange request missing action");
+            return Err("Exchange request missing action".into());
+        }
+        
+        info!("✅ Request has all required fields (action, signature, nonce)");
+        
+        // Forward the pre-signed request directly to Hyperliquid
+        let response = self
+            .client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .json(payload)
+            .send()
+            .await?;
+
+        let status = response.status();
+        info!("📡 Hyperliquid response status: {}", status);
+
+        if status.is_success() {
+            let json_response: Value = response.json().await?;
+            info!("✅ Exchange request successful");
+            Ok(json_response)
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            error!("❌ Hyperliquid exchange error: {} - {}", status, error_text);
+            Err(format!("Exchange API error: {} - {}", status, error_text).into())
+        }
+    }
+}
\ No newline at end of file
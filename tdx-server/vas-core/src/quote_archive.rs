@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing::{info, error};
+
+use crate::jsonl;
+use crate::preset_tdx::PresetTDXData;
+
+/// An attestation quote the server has served, and the agent key it vouches for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteRecord {
+    pub quote_id: String,
+    pub tdx_quote_hex: String,
+    pub agent_address: String,
+    /// Uncompressed secp256k1 public key of the agent (hex)
+    pub agent_public_key: String,
+    /// When this quote started being served (unix ms)
+    pub active_from_ms: u64,
+    /// Set when a TCB recovery replaced this quote; verifiers should re-check against the new one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub superseded: Option<Supersession>,
+}
+
+/// Why and when a quote stopped being served
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Supersession {
+    pub at_ms: u64,
+    pub by_quote_id: String,
+    pub reason: String,
+}
+
+/// Append-only archive of every quote this deployment has served
+#[derive(Debug)]
+pub struct QuoteArchive {
+    records: Vec<QuoteRecord>,
+    path: Option<PathBuf>,
+}
+
+impl QuoteArchive {
+    pub fn open(path: Option<PathBuf>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let records: Vec<QuoteRecord> = match &path {
+            Some(path) => jsonl::load(path)?,
+            None => Vec::new(),
+        };
+
+        info!("🗄️ Quote archive opened with {} quotes", records.len());
+        Ok(Self { records, path })
+    }
+
+    /// Archive the current preset quote if it differs from the one last served
+    pub fn record_current(&mut self, preset_data: &PresetTDXData) {
+        if self.records.last().map(|r| r.quote_id.as_str()) == Some(preset_data.quote_id.as_str()) {
+            return;
+        }
+
+        let public_key = secp256k1::PublicKey::from_secret_key(secp256k1::SECP256K1, &preset_data.agent_private_key);
+        let record = QuoteRecord {
+            quote_id: preset_data.quote_id.clone(),
+            tdx_quote_hex: hex::encode(&preset_data.tdx_quote),
+            agent_address: preset_data.agent_address.clone(),
+            agent_public_key: hex::encode(public_key.serialize_uncompressed()),
+            active_from_ms: now_ms(),
+            superseded: None,
+        };
+
+        if let Some(path) = &self.path {
+            if let Err(e) = jsonl::append(path, &record) {
+                error!("❌ Failed to archive quote {}: {}", record.quote_id, e);
+            }
+        }
+
+        info!("🗄️ Archived quote {} for agent {}", record.quote_id, record.agent_address);
+        self.records.push(record);
+    }
+
+    /// Archive a regenerated quote and mark every earlier one superseded by it.
+    /// The file is rewritten, since records already written change.
+    pub fn supersede(&mut self, preset_data: &PresetTDXData, reason: &str) {
+        let at_ms = now_ms();
+        for record in self.records.iter_mut().filter(|r| r.superseded.is_none()) {
+            record.superseded = Some(Supersession {
+                at_ms,
+                by_quote_id: preset_data.quote_id.clone(),
+                reason: reason.to_string(),
+            });
+        }
+        let path = self.path.take();
+        self.record_current(preset_data);
+        self.path = path;
+
+        if let Some(path) = &self.path {
+            if let Err(e) = jsonl::rewrite(path, &self.records) {
+                error!("❌ Failed to rewrite quote archive: {}", e);
+            }
+        }
+    }
+
+    pub fn records(&self) -> &[QuoteRecord] {
+        &self.records
+    }
+
+    /// Quote that was being served at `timestamp_ms`
+    pub fn active_at(&self, timestamp_ms: u64) -> Option<&QuoteRecord> {
+        self.records.iter().rev().find(|r| r.active_from_ms <= timestamp_ms)
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
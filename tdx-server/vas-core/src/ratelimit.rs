@@ -0,0 +1,105 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::warn;
+
+use crate::auth;
+use crate::AppState;
+
+const WINDOW_MS: u64 = 60_000;
+
+/// Outcome of counting one request against its key's window
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitStatus {
+    pub limit: u64,
+    pub remaining: u64,
+    /// Seconds until the window resets
+    pub reset_secs: u64,
+    pub allowed: bool,
+}
+
+impl RateLimitStatus {
+    fn apply(&self, headers: &mut HeaderMap) {
+        headers.insert("X-RateLimit-Limit", HeaderValue::from(self.limit));
+        headers.insert("X-RateLimit-Remaining", HeaderValue::from(self.remaining));
+        headers.insert("X-RateLimit-Reset", HeaderValue::from(self.reset_secs));
+    }
+}
+
+/// Fixed one-minute windows of request counts per API key
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_minute: u64,
+    /// API key -> (window start ms, requests counted in it)
+    windows: Mutex<HashMap<String, (u64, u64)>>,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u64) -> Self {
+        Self { per_minute, windows: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn check(&self, key: &str) -> RateLimitStatus {
+        let now = now_ms();
+        let window_start = now - now % WINDOW_MS;
+        let mut windows = self.windows.lock().unwrap();
+
+        // Keys idle since an earlier window no longer need tracking
+        if !windows.contains_key(key) {
+            windows.retain(|_, (start, _)| *start == window_start);
+        }
+        let (start, count) = windows.entry(key.to_string()).or_insert((window_start, 0));
+        if *start != window_start {
+            *start = window_start;
+            *count = 0;
+        }
+
+        let allowed = *count < self.per_minute;
+        if allowed {
+            *count += 1;
+        }
+
+        RateLimitStatus {
+            limit: self.per_minute,
+            remaining: self.per_minute - *count,
+            reset_secs: (window_start + WINDOW_MS - now).div_ceil(1000),
+            allowed,
+        }
+    }
+}
+
+/// Middleware: count API-key requests and report the window on every response.
+/// Runs after auth, so only keys that authenticated are counted.
+pub async fn limit(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(api_key) = auth::api_key_from_headers(request.headers()) else {
+        return next.run(request).await;
+    };
+    let status = state.rate_limiter.check(api_key);
+
+    let mut response = if status.allowed {
+        next.run(request).await
+    } else {
+        warn!("🚦 Rate limit exceeded on {}", request.uri().path());
+        let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+        response.headers_mut().insert("Retry-After", HeaderValue::from(status.reset_secs));
+        response
+    };
+    status.apply(response.headers_mut());
+    response
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,179 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde_json::Value;
+use std::time::Instant;
+use tracing::{debug, warn};
+
+use crate::agents;
+use crate::auth;
+use crate::confirm;
+use crate::delegation;
+use crate::drawdown;
+use crate::drift;
+use crate::encrypted_orders;
+use crate::error_codes::{self, ErrorCode};
+use crate::onboarding;
+use crate::order_guard;
+use crate::rejections::{self, RejectionReason};
+use crate::signer::ActionRequest;
+use crate::slo::{self, LatencySample};
+use crate::{apply_pending_referrer, check_asset_allowlist, run_risk_checks, AppState};
+
+/// Route for latency-sensitive bots; errors point there when a feature needs the full path
+const FULL_PATH: &str = "/exchange";
+
+/// POST /exchange/raw - Sign and submit with the same auth, scope, policy and risk checks as
+/// /exchange, returning Hyperliquid's body as received.
+///
+/// Skipped on this path: the hash/attestation envelope, warnings, fee estimates, error code
+/// mapping, idempotency keys, order defaults, the event store copy and verbose request logging.
+/// Requests that need a pre-sign hold (confirmation queue, co-signature, delegation,
+/// approveAgent) or carry order state preconditions are refused rather than served
+/// differently; send those to /exchange.
+pub async fn raw_exchange(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<Value>,
+) -> Result<Json<Value>, StatusCode> {
+    let received_at = Instant::now();
+    let payload = match encrypted_orders::decrypt_request(&headers, payload) {
+        Ok(payload) => payload,
+        Err(body) => return Ok(Json(body)),
+    };
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let action = payload.get("action").ok_or(StatusCode::BAD_REQUEST)?.clone();
+    let action_type = action.get("type").and_then(|t| t.as_str()).unwrap_or_default().to_string();
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let fixed_key = api_key == state.config.fixed_api_key;
+
+    if action_type == "approveAgent" || headers.contains_key(delegation::DELEGATED_FROM_HEADER) {
+        return Ok(Json(unsupported("approveAgent and delegated requests")));
+    }
+    if payload.get(order_guard::EXPECTED_STATUS_FIELD).is_some() || payload.get(order_guard::EXPECTED_REMAINING_FIELD).is_some() {
+        return Ok(Json(unsupported("Order state preconditions")));
+    }
+
+    let nonce = match payload.get("nonce").and_then(|n| n.as_u64()) {
+        Some(nonce) => {
+            if let Err(reason) = drift::check_nonce(&state, api_key, nonce).await {
+                return Ok(Json(error_codes::err_body(ErrorCode::NonceOutOfWindow, reason)));
+            }
+            nonce
+        }
+        None => now_ms,
+    };
+
+    if !fixed_key {
+        let (onboarding, has_scope, cosigned) = {
+            match state.session_manager.get_session(api_key) {
+                Some(session) => (
+                    Some(session.onboarding),
+                    session.has_scope(agents::required_scope(&action_type)),
+                    session.cosigner_address.is_some(),
+                ),
+                None => (None, false, false),
+            }
+        };
+        if let Some(current) = onboarding.filter(|o| !o.can_trade()) {
+            let refreshed = onboarding::refresh(&state, api_key).await.unwrap_or(current);
+            if !refreshed.can_trade() {
+                return Ok(Json(onboarding::not_ready_response(refreshed)));
+            }
+        }
+        if !has_scope {
+            let scope = agents::required_scope(&action_type);
+            return Ok(Json(error_codes::err_body(ErrorCode::ScopeNotAllowed, format!("API key is not authorized for the '{}' scope", scope))));
+        }
+        if let Err(body) = check_asset_allowlist(&state, api_key, &action).await {
+            return Ok(Json(body));
+        }
+        if cosigned {
+            return Ok(Json(unsupported("Sessions in co-sign mode")));
+        }
+    }
+
+    let user_address = auth::user_address_for_api_key(&state, api_key).await;
+    if let Err(body) = rejections::check(&state, &action, user_address.as_deref()).await {
+        return Ok(Json(body));
+    }
+    if let Err(violation) = state.policy.read().await.evaluate(&action) {
+        warn!("❌ Policy rejected raw action: {}", violation.message);
+        state.rejections.record(RejectionReason::PolicyDenial, &action, user_address.as_deref(), &violation.message);
+        return Ok(Json(violation.to_response()));
+    }
+    if action_type == "order" {
+        if let Some(user_address) = &user_address {
+            if let Err(reason) = drawdown::check_reduce_only(&state, user_address, &action).await {
+                return Ok(Json(error_codes::err_body(ErrorCode::DrawdownReduceOnly, reason)));
+            }
+            // Non-fatal warnings have nowhere to go on a verbatim response
+            if let Err(reason) = run_risk_checks(&state, user_address, &action, &mut Vec::new()).await {
+                return Ok(Json(error_codes::err_body(ErrorCode::RiskCheckFailed, reason)));
+            }
+        }
+        if let Some(threshold) = state.config.confirm_notional_threshold {
+            if confirm::order_notional(&action) > threshold {
+                return Ok(Json(unsupported("Orders above the confirmation threshold")));
+            }
+        }
+        // Per-strategy sub-limits, counted only once the order passed every other check
+        if let Some(user_address) = &user_address {
+            if let Err(violation) = state.strategy_limits.write().await.charge_request(&headers, user_address, &action, now_ms) {
+                warn!("❌ Policy rejected raw action: {}", violation.message);
+                state.rejections.record(RejectionReason::PolicyDenial, &action, Some(user_address), &violation.message);
+                return Ok(Json(violation.to_response()));
+            }
+        }
+    }
+
+    let is_mainnet = state.config.is_mainnet();
+    if action_type == "order" {
+        apply_pending_referrer(&state, api_key, is_mainnet, user_address.clone()).await;
+    }
+
+    let request = ActionRequest {
+        action,
+        nonce,
+        vault_address: payload.get("vaultAddress").and_then(|v| v.as_str()).map(str::to_string),
+        is_mainnet,
+        user_address,
+    };
+    let assets = slo::action_assets(&state.market, &request.action).await;
+    let signing_started = Instant::now();
+    let result = state.signer.sign_action(request).await;
+    state.slo.write().await.record(LatencySample {
+        at_ms: now_ms,
+        assets,
+        end_to_end_ms: received_at.elapsed().as_millis() as u64,
+        upstream_ms: signing_started.elapsed().as_millis() as u64,
+        ok: result.as_ref().is_ok_and(|response| response.get("status").and_then(|s| s.as_str()) != Some("err")),
+    });
+
+    match result {
+        Ok(response) => {
+            debug!("⚡ Raw {} signed in {}ms", action_type, received_at.elapsed().as_millis());
+            state.upstream.observe_ok();
+            Ok(Json(response))
+        }
+        Err(e) => {
+            warn!("❌ Raw {} failed: {}", action_type, e);
+            if let Some(outage) = state.upstream.observe_error(&e.to_string()) {
+                return Ok(Json(error_codes::err_body(ErrorCode::UpstreamUnavailable, format!("Hyperliquid is {}: {}", outage.as_str(), e))));
+            }
+            if ErrorCode::for_upstream(&e.to_string()) == ErrorCode::UpstreamRateLimited {
+                return Ok(Json(error_codes::err_body(ErrorCode::UpstreamRateLimited, e.to_string())));
+            }
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+fn unsupported(what: &str) -> Value {
+    error_codes::err_body(ErrorCode::BadRequest, format!("{} are not supported on the raw path; use {}", what, FULL_PATH))
+}
@@ -0,0 +1,255 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{Json, Response},
+};
+use secp256k1::{ecdh::SharedSecret, PublicKey, Secp256k1};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+use tracing::{info, warn};
+
+use crate::auth;
+use crate::client_ip;
+use crate::ha::SealedBox;
+use crate::preset_tdx::PresetTDXData;
+use crate::AppState;
+
+/// Bodies larger than this are recorded by size only
+const MAX_RECORDED_BODY: usize = 64 * 1024;
+/// Headers never copied into a trace
+const REDACTED_HEADERS: &[&str] = &["x-api-key", "x-admin-token", "x-ha-peer-token", "authorization", "cookie", "set-cookie"];
+/// JSON fields whose values are replaced, matched case-insensitively as substrings
+const REDACTED_FIELDS: &[&str] = &["key", "token", "secret", "signature", "password", "private"];
+const REDACTED: &str = "[redacted]";
+
+/// One request/response exchange captured for a support escalation
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceRecord {
+    pub at_ms: u64,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub client_ip: Option<String>,
+    pub request_headers: Value,
+    pub request_body: Value,
+    pub status: u16,
+    pub response_body: Value,
+    pub duration_ms: u64,
+}
+
+/// Per-API-key ring buffers of recent traffic; keys are only recorded after opting in
+#[derive(Debug)]
+pub struct DebugRecorder {
+    capacity: usize,
+    traces: HashMap<String, VecDeque<TraceRecord>>,
+}
+
+impl DebugRecorder {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, traces: HashMap::new() }
+    }
+
+    /// Start or stop recording for `api_key`; stopping drops what was recorded
+    pub fn set_enabled(&mut self, api_key: &str, enabled: bool) {
+        if enabled {
+            self.traces.entry(api_key.to_string()).or_default();
+        } else {
+            self.traces.remove(api_key);
+        }
+    }
+
+    pub fn is_enabled(&self, api_key: &str) -> bool {
+        self.traces.contains_key(api_key)
+    }
+
+    pub fn record(&mut self, api_key: &str, trace: TraceRecord) {
+        let Some(buffer) = self.traces.get_mut(api_key) else { return };
+        if buffer.len() >= self.capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(trace);
+    }
+
+    pub fn traces(&self, api_key: &str) -> Option<Vec<TraceRecord>> {
+        self.traces.get(api_key).map(|buffer| buffer.iter().cloned().collect())
+    }
+
+    /// Drop the traces of a deleted user's key
+    pub fn remove_key(&mut self, api_key: &str) -> usize {
+        self.traces.remove(api_key).map_or(0, |buffer| buffer.len())
+    }
+}
+
+/// Middleware: capture request and response for API keys that opted in to recording
+pub async fn record_traffic(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let api_key = match auth::api_key_from_headers(request.headers()) {
+        Some(key) if state.recorder.read().await.is_enabled(key) => key.to_string(),
+        _ => return Ok(next.run(request).await),
+    };
+
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let query = request.uri().query().map(|q| q.to_string());
+    let request_headers = redact_headers(request.headers());
+
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX).await.map_err(|_| StatusCode::BAD_REQUEST)?;
+    let request_body = recorded_body(&body);
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    // Only JSON responses are buffered; anything else passes through untouched
+    let is_json = response.headers().get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    let status = response.status().as_u16();
+    let (response, response_body) = if is_json {
+        let (parts, body) = response.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let recorded = recorded_body(&body);
+        (Response::from_parts(parts, Body::from(body)), recorded)
+    } else {
+        (response, Value::Null)
+    };
+
+    state.recorder.write().await.record(&api_key, TraceRecord {
+        at_ms: now_ms(),
+        method,
+        path,
+        query,
+        client_ip: client_ip::current(),
+        request_headers,
+        request_body,
+        status,
+        response_body,
+        duration_ms: started.elapsed().as_millis() as u64,
+    });
+
+    Ok(response)
+}
+
+fn redact_headers(headers: &HeaderMap) -> Value {
+    headers.iter()
+        .filter(|(name, _)| !REDACTED_HEADERS.contains(&name.as_str()))
+        .map(|(name, value)| (name.to_string(), Value::String(value.to_str().unwrap_or("<binary>").to_string())))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+fn recorded_body(body: &Bytes) -> Value {
+    if body.is_empty() {
+        return Value::Null;
+    }
+    if body.len() > MAX_RECORDED_BODY {
+        return serde_json::json!({"truncated_bytes": body.len()});
+    }
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut value) => {
+            redact(&mut value);
+            value
+        }
+        Err(_) => serde_json::json!({"non_json_bytes": body.len()}),
+    }
+}
+
+/// Replace values of secret-looking fields anywhere in the document
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (field, value) in map.iter_mut() {
+                let field = field.to_lowercase();
+                if REDACTED_FIELDS.iter().any(|secret| field.contains(secret)) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecorderRequest {
+    pub enabled: bool,
+}
+
+/// PUT /me/debug-recorder - Opt in (or out) of recording this key's traffic for support
+pub async fn set_recording(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<RecorderRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    state.recorder.write().await.set_enabled(api_key, payload.enabled);
+
+    info!("🎙️ Debug recording {} for an API key", if payload.enabled { "enabled" } else { "disabled" });
+
+    Ok(Json(serde_json::json!({
+        "enabled": payload.enabled,
+        "capacity": state.config.debug_recorder_capacity
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SupportBundleQuery {
+    pub api_key: String,
+}
+
+/// GET /admin/support-bundle?api_key= - Recent traces for a key, encrypted to SUPPORT_BUNDLE_PUBLIC_KEY.
+///
+/// The bundle is sealed with ECDH between an ephemeral key and the support public key, so the
+/// operator transporting it cannot read it; support opens it with the matching private key.
+pub async fn support_bundle(
+    State(state): State<AppState>,
+    Query(query): Query<SupportBundleQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let Some(support_key) = state.config.support_bundle_public_key.as_deref() else {
+        warn!("⚠️ Support bundle requested but SUPPORT_BUNDLE_PUBLIC_KEY is not set");
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    };
+    let support_key = hex::decode(support_key).ok()
+        .and_then(|bytes| PublicKey::from_slice(&bytes).ok())
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let traces = state.recorder.read().await.traces(&query.api_key).ok_or(StatusCode::NOT_FOUND)?;
+    let user_address = auth::user_address_for_api_key(&state, &query.api_key).await;
+
+    let bundle = serde_json::json!({
+        "generated_at_ms": now_ms(),
+        "version": env!("CARGO_PKG_VERSION"),
+        "config_hash": hex::encode(Sha256::digest(format!("{:?}", state.config).as_bytes())),
+        "agent_address": PresetTDXData::get().map(|p| p.agent_address.clone()),
+        "ha_role": format!("{:?}", state.ha.role()),
+        "user_address": user_address,
+        "traces": traces
+    });
+    let plaintext = serde_json::to_vec(&bundle).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let (ephemeral_secret, ephemeral_public) = Secp256k1::new().generate_keypair(&mut rand::thread_rng());
+    let shared = SharedSecret::new(&support_key, &ephemeral_secret);
+
+    info!("📦 Support bundle built with {} traces", traces.len());
+
+    Ok(Json(serde_json::json!({
+        "public_key": hex::encode(ephemeral_public.serialize()),
+        "sealed": SealedBox::seal(&shared.secret_bytes(), &plaintext)
+    })))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
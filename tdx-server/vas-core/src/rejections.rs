@@ -0,0 +1,210 @@
+use axum::{extract::State, response::Json};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::error_codes::{self, ErrorCode};
+use crate::market;
+use crate::universal_signing::prepare_action;
+use crate::AppState;
+
+/// Asset indices from here up are spot pairs, which the perp universe doesn't list
+const SPOT_ASSET_OFFSET: u64 = 10_000;
+/// Hyperliquid perp prices carry at most this many decimals, less the asset's szDecimals
+const PERP_MAX_DECIMALS: u32 = 6;
+/// Non-integer perp prices carry at most this many significant figures
+const MAX_SIG_FIGS: usize = 5;
+/// Longest rejection message kept as a sample
+const MAX_SAMPLE: usize = 256;
+
+/// Why an exchange payload was refused before signing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    UnknownAsset,
+    BadTick,
+    MissingField,
+    InvalidField,
+    PolicyDenial,
+}
+
+impl RejectionReason {
+    pub const ALL: [RejectionReason; 5] = [
+        Self::UnknownAsset, Self::BadTick, Self::MissingField, Self::InvalidField, Self::PolicyDenial,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UnknownAsset => "unknown_asset",
+            Self::BadTick => "bad_tick",
+            Self::MissingField => "missing_field",
+            Self::InvalidField => "invalid_field",
+            Self::PolicyDenial => "policy_denial",
+        }
+    }
+
+    /// Reason for an error from `prepare_action`, whose messages name what is missing
+    fn for_conversion(message: &str) -> Self {
+        if message.to_lowercase().contains("missing") {
+            Self::MissingField
+        } else {
+            Self::InvalidField
+        }
+    }
+}
+
+/// Rejections of one reason since startup
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReasonCount {
+    pub count: u64,
+    pub last_seen_ms: Option<u64>,
+    /// The latest message, truncated
+    pub last_message: Option<String>,
+    pub last_user: Option<String>,
+    /// Action type -> count, to tell which client integration is misbehaving
+    pub by_action_type: BTreeMap<String, u64>,
+}
+
+/// Counters of exchange payloads refused before signing, by reason.
+///
+/// These are client mistakes rather than server faults, so they are counted instead of alerted
+/// on: a bot sending bad ticks shows up as a climbing `bad_tick` count for `order`.
+#[derive(Debug, Default)]
+pub struct RejectionStats {
+    counts: Mutex<BTreeMap<RejectionReason, ReasonCount>>,
+}
+
+impl RejectionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, reason: RejectionReason, action: &Value, user_address: Option<&str>, message: &str) {
+        let action_type = action.get("type").and_then(|t| t.as_str()).unwrap_or("unknown");
+        let mut counts = self.counts.lock().unwrap();
+        let entry = counts.entry(reason).or_default();
+        entry.count += 1;
+        entry.last_seen_ms = Some(now_ms());
+        entry.last_message = Some(message.chars().take(MAX_SAMPLE).collect());
+        entry.last_user = user_address.map(str::to_lowercase);
+        *entry.by_action_type.entry(action_type.to_string()).or_default() += 1;
+    }
+
+    fn snapshot(&self) -> BTreeMap<RejectionReason, ReasonCount> {
+        let counts = self.counts.lock().unwrap();
+        RejectionReason::ALL.iter()
+            .map(|reason| (*reason, counts.get(reason).cloned().unwrap_or_default()))
+            .collect()
+    }
+}
+
+/// Convert an action as the signer will and check its orders against the perp universe,
+/// counting any rejection. Returns the error body to send on failure.
+pub async fn check(state: &AppState, action: &Value, user_address: Option<&str>) -> Result<(), Value> {
+    let reject = |reason: RejectionReason, code: ErrorCode, message: String| {
+        debug!("🚫 Rejected {:?} before signing: {}", reason, message);
+        state.rejections.record(reason, action, user_address, &message);
+        error_codes::err_body(code, message)
+    };
+
+    let prepared = prepare_action(action).map_err(|e| {
+        let message = e.to_string();
+        reject(RejectionReason::for_conversion(&message), ErrorCode::BadRequest, message)
+    })?;
+
+    let orders: Vec<&Value> = match prepared.get("type").and_then(|t| t.as_str()) {
+        Some("order") => prepared["orders"].as_array().into_iter().flatten().collect(),
+        Some("batchModify") => prepared["modifies"].as_array().into_iter().flatten()
+            .filter_map(|m| m.get("order"))
+            .collect(),
+        _ => return Ok(()),
+    };
+    if orders.iter().all(|order| order["a"].as_u64().is_none_or(|a| a >= SPOT_ASSET_OFFSET)) {
+        return Ok(());
+    }
+
+    // Without metadata the orders go to upstream unchecked rather than being refused here
+    let meta_and_ctxs = match state.market.meta_and_asset_ctxs().await {
+        Ok(meta_and_ctxs) => meta_and_ctxs,
+        Err(e) => {
+            warn!("⚠️ Skipping asset checks, metaAndAssetCtxs unavailable: {}", e);
+            return Ok(());
+        }
+    };
+    for order in orders {
+        let Some(index) = order["a"].as_u64().filter(|a| *a < SPOT_ASSET_OFFSET) else { continue };
+        let Some(asset) = market::parse_asset(&meta_and_ctxs, index) else {
+            return Err(reject(RejectionReason::UnknownAsset, ErrorCode::UnknownAsset, format!("Unknown asset index {}", index)));
+        };
+        if let Err(message) = check_precision(order, &asset.name, asset.sz_decimals) {
+            return Err(reject(RejectionReason::BadTick, ErrorCode::InvalidTick, message));
+        }
+    }
+    Ok(())
+}
+
+/// Check a canonicalized perp order's price and size against the asset's tick and lot size
+fn check_precision(order: &Value, coin: &str, sz_decimals: u32) -> Result<(), String> {
+    let max_px_decimals = PERP_MAX_DECIMALS.saturating_sub(sz_decimals);
+    for (pointer, name) in [("/p", "Price"), ("/t/trigger/triggerPx", "Trigger price")] {
+        let Some(px) = order.pointer(pointer).and_then(|p| p.as_str()) else { continue };
+        if decimals(px) > max_px_decimals {
+            return Err(format!("{} {} has more than {} decimals for {}", name, px, max_px_decimals, coin));
+        }
+        if decimals(px) > 0 && significant_figures(px) > MAX_SIG_FIGS {
+            return Err(format!("{} {} has more than {} significant figures", name, px, MAX_SIG_FIGS));
+        }
+    }
+    if let Some(size) = order.get("s").and_then(|s| s.as_str()) {
+        if decimals(size) > sz_decimals {
+            return Err(format!("Size {} has more than {} decimals for {}", size, sz_decimals, coin));
+        }
+    }
+    Ok(())
+}
+
+fn decimals(value: &str) -> u32 {
+    value.split_once('.').map_or(0, |(_, fraction)| fraction.len() as u32)
+}
+
+fn significant_figures(value: &str) -> usize {
+    let digits: String = value.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits.trim_start_matches('0').len()
+}
+
+/// GET /admin/rejections - Exchange payloads refused before signing, by reason
+pub async fn admin_rejections(State(state): State<AppState>) -> Json<Value> {
+    let reasons = state.rejections.snapshot();
+    let total: u64 = reasons.values().map(|r| r.count).sum();
+    let reasons: serde_json::Map<String, Value> = reasons.into_iter()
+        .map(|(reason, count)| (reason.as_str().to_string(), serde_json::json!(count)))
+        .collect();
+    Json(serde_json::json!({"total": total, "reasons": reasons}))
+}
+
+/// Prometheus lines for /admin/metrics
+pub fn prometheus(state: &AppState) -> String {
+    let mut out = String::new();
+    out.push_str("# HELP vas_exchange_rejections_total Exchange payloads rejected before signing\n");
+    out.push_str("# TYPE vas_exchange_rejections_total counter\n");
+    for (reason, count) in state.rejections.snapshot() {
+        for (action_type, n) in &count.by_action_type {
+            out.push_str(&format!(
+                "vas_exchange_rejections_total{{reason=\"{}\",action_type=\"{}\"}} {}\n",
+                reason.as_str(),
+                action_type.replace(['"', '\\', '\n'], "_"),
+                n
+            ));
+        }
+    }
+    out
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,115 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{info, error};
+
+use crate::audit::AUDIT_REPLAY;
+use crate::market::parse_asset;
+use crate::universal_signing::{create_generic_action_hash, prepare_action, signing_digest};
+use crate::AppState;
+
+/// A recorded /exchange request to re-run through the signing pipeline
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayBundle {
+    pub action: Value,
+    pub nonce: u64,
+    pub vault_address: Option<String>,
+    /// Defaults to the recorded entry's network, then the configured upstream
+    pub is_mainnet: Option<bool>,
+    /// `metaAndAssetCtxs` (or bare `meta`) snapshot taken when the request was made
+    pub meta: Option<Value>,
+}
+
+/// POST /admin/replay - Deterministically re-run conversion, hashing and signing for a recorded request.
+///
+/// Nothing is submitted upstream. A signature is only produced when the action hash matches an
+/// entry already in the audit log, so replay never signs anything the agent hadn't signed before.
+pub async fn replay(
+    State(state): State<AppState>,
+    Json(bundle): Json<ReplayBundle>,
+) -> Result<Json<Value>, StatusCode> {
+    info!("🔁 Replaying recorded request (nonce {})", bundle.nonce);
+
+    let assets = bundle.meta.as_ref().map(|meta| asset_mapping(&bundle.action, meta));
+
+    // Hash and sign what the signer would have submitted, not the raw client action
+    let prepared = prepare_action(&bundle.action).map_err(|e| {
+        error!("❌ Replay could not prepare action: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    let msgpack = rmp_serde::to_vec_named(&prepared).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let action_hash = create_generic_action_hash(&prepared, bundle.nonce, bundle.vault_address.as_deref())
+        .map_err(|e| {
+            error!("❌ Replay could not hash action: {}", e);
+            StatusCode::BAD_REQUEST
+        })?;
+    let action_hash_hex = format!("{:?}", action_hash);
+
+    let recorded = state.audit.read().await.find_action(&action_hash_hex).cloned();
+    let is_mainnet = bundle.is_mainnet
+        .or_else(|| recorded.as_ref().and_then(|e| e.subject.get("isMainnet")).and_then(|m| m.as_bool()))
+        .unwrap_or_else(|| state.config.is_mainnet());
+    let digest = signing_digest(&prepared, bundle.nonce, bundle.vault_address.as_deref(), is_mainnet)
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let signature = match &recorded {
+        Some(entry) => {
+            let subject = serde_json::json!({
+                "action": prepared,
+                "nonce": bundle.nonce,
+                "vaultAddress": bundle.vault_address,
+                "isMainnet": is_mainnet,
+                "replayOf": entry.seq
+            });
+            match state.signer.sign_digest(digest, entry.user_address.clone(), AUDIT_REPLAY, subject).await {
+                Ok(signature) => serde_json::json!({
+                    "signature": signature.to_json(),
+                    "recorded_signature": entry.signature,
+                    "matches_recorded": entry.signature.as_ref().map(|recorded| *recorded == signature.to_json())
+                }),
+                Err(e) => serde_json::json!({"error": e.to_string()}),
+            }
+        }
+        None => serde_json::json!({"error": "No audit entry for this action hash; refusing to sign"}),
+    };
+
+    Ok(Json(serde_json::json!({
+        "normalized_action": prepared,
+        "assets": assets,
+        "msgpack_hex": hex::encode(&msgpack),
+        "action_hash": action_hash_hex,
+        "is_mainnet": is_mainnet,
+        "signing_digest": format!("{:?}", digest),
+        "recorded_entry": recorded.as_ref().map(|e| serde_json::json!({
+            "seq": e.seq,
+            "timestamp_ms": e.timestamp_ms,
+            "user_address": e.user_address,
+            "error": e.error
+        })),
+        "signature": signature
+    })))
+}
+
+/// Resolve each asset index against the meta snapshot; indexes are signed as sent
+fn asset_mapping(action: &Value, meta: &Value) -> Vec<Value> {
+    // Accept either a metaAndAssetCtxs pair or a bare meta object
+    let meta_and_ctxs = if meta.is_array() { meta.clone() } else { serde_json::json!([meta]) };
+
+    action.get("orders").or_else(|| action.get("cancels"))
+        .and_then(|e| e.as_array())
+        .map(|entries| entries.iter().map(|entry| {
+            let index = entry.get("a").and_then(|a| a.as_u64()).unwrap_or(0);
+            let coin = parse_asset(&meta_and_ctxs, index).map(|asset| asset.name);
+            serde_json::json!({
+                "index": index,
+                "coin": coin,
+                "consistent": coin.is_some()
+            })
+        }).collect())
+        .unwrap_or_default()
+}
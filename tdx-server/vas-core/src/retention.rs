@@ -0,0 +1,65 @@
+use std::time::Duration;
+use tracing::{info, error};
+
+use crate::AppState;
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Periodically prune events and audit entries past their retention window.
+/// Audit entries are folded into Merkle-root checkpoints that are never pruned.
+pub fn spawn_compactor(state: AppState) {
+    let config = state.config.clone();
+    if config.event_retention_days.is_none() && config.audit_retention_days.is_none() {
+        info!("🗜️ Retention disabled; events and audit entries are kept indefinitely");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.retention_interval_secs.max(60)));
+        loop {
+            ticker.tick().await;
+            let now = now_ms();
+
+            if let Some(days) = config.event_retention_days {
+                if let Err(e) = state.event_store.write().await.prune_before(now.saturating_sub(days * DAY_MS)) {
+                    error!("❌ Event retention failed: {}", e);
+                }
+            }
+
+            if let Some(days) = config.audit_retention_days {
+                if let Err(e) = state.audit.write().await.prune_before(now.saturating_sub(days * DAY_MS)) {
+                    error!("❌ Audit retention failed: {}", e);
+                }
+            }
+        }
+    });
+}
+
+/// Prometheus lines for store sizes and retention activity
+pub async fn prometheus(state: &AppState) -> String {
+    let (events, events_pruned, events_bytes) = state.event_store.read().await.storage_stats();
+    let (entries, checkpoints, entries_pruned, audit_bytes) = state.audit.read().await.storage_stats();
+
+    let mut out = String::new();
+    out.push_str("# HELP vas_store_records Records currently retained per store\n");
+    out.push_str("# TYPE vas_store_records gauge\n");
+    out.push_str(&format!("vas_store_records{{store=\"events\"}} {}\n", events));
+    out.push_str(&format!("vas_store_records{{store=\"audit\"}} {}\n", entries));
+    out.push_str(&format!("vas_store_records{{store=\"audit_checkpoints\"}} {}\n", checkpoints));
+    out.push_str("# HELP vas_store_bytes On-disk size per store\n");
+    out.push_str("# TYPE vas_store_bytes gauge\n");
+    out.push_str(&format!("vas_store_bytes{{store=\"events\"}} {}\n", events_bytes));
+    out.push_str(&format!("vas_store_bytes{{store=\"audit\"}} {}\n", audit_bytes));
+    out.push_str("# HELP vas_store_pruned_total Records removed by retention since startup\n");
+    out.push_str("# TYPE vas_store_pruned_total counter\n");
+    out.push_str(&format!("vas_store_pruned_total{{store=\"events\"}} {}\n", events_pruned));
+    out.push_str(&format!("vas_store_pruned_total{{store=\"audit\"}} {}\n", entries_pruned));
+    out
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,196 @@
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::margin::{position_leverage, MarginSummary};
+use crate::market::{parse_number, MarketCache};
+
+/// Pre-sign risk check for `order` actions.
+///
+/// Returns `Ok(())` when the order may be signed, or `Err(reason)` when it must be rejected.
+/// Reduce-only orders are never blocked since they can only lower margin usage.
+pub async fn check_order_margin(
+    market: &MarketCache,
+    user_address: &str,
+    action: &Value,
+    max_margin_usage: f64,
+) -> Result<(), String> {
+    let orders = match action.get("orders").and_then(|o| o.as_array()) {
+        Some(orders) => orders,
+        None => return Ok(()),
+    };
+
+    let clearinghouse = market.clearinghouse_state(user_address).await
+        .map_err(|e| format!("Risk check unavailable: failed to fetch clearinghouseState: {}", e))?;
+    let summary = MarginSummary::from_clearinghouse_state(user_address, &clearinghouse);
+
+    let mut additional_margin = 0.0;
+    for order in orders {
+        if order.get("r").and_then(|r| r.as_bool()).unwrap_or(false) {
+            continue;
+        }
+
+        let notional = parse_number(order.get("p")).unwrap_or(0.0) * parse_number(order.get("s")).unwrap_or(0.0);
+        let asset_index = order.get("a").and_then(|a| a.as_u64()).unwrap_or(0);
+
+        // Use the leverage already set on the position; assume 1x for new positions (conservative)
+        let leverage = match market.asset(asset_index).await {
+            Ok(Some(asset)) => position_leverage(&clearinghouse, &asset.name).unwrap_or(1.0),
+            _ => 1.0,
+        };
+
+        additional_margin += notional / leverage.max(1.0);
+    }
+
+    if summary.account_value <= 0.0 {
+        warn!("⚠️ Margin check: account {} has no equity", user_address);
+        return Err("Order rejected: account has no equity to margin new positions".to_string());
+    }
+
+    let projected_usage = (summary.total_margin_used + additional_margin) / summary.account_value;
+    info!("📐 Margin check: current usage {:.4}, projected {:.4}, limit {:.4}",
+        summary.margin_usage, projected_usage, max_margin_usage);
+
+    if projected_usage > max_margin_usage {
+        return Err(format!(
+            "Order rejected: projected margin usage {:.2}% exceeds limit {:.2}%",
+            projected_usage * 100.0,
+            max_margin_usage * 100.0
+        ));
+    }
+
+    Ok(())
+}
+
+/// What the liquidation guard does when an order lands too close to liquidation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiquidationGuardMode {
+    Reject,
+    Warn,
+}
+
+impl LiquidationGuardMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "reject" => Some(Self::Reject),
+            "warn" => Some(Self::Warn),
+            _ => None,
+        }
+    }
+}
+
+/// Estimate each touched position's post-trade liquidation price and compare it to the mark.
+///
+/// Uses Hyperliquid's cross-margin formula
+/// `liq_px = px - side * margin_available / |size| / (1 - l * side)` with `l = 1 / (2 * max_leverage)`.
+/// Returns warnings in `Warn` mode, or `Err(reason)` in `Reject` mode when a position would
+/// end up within `band` (fraction of mark) of liquidation.
+pub async fn check_liquidation_distance(
+    market: &MarketCache,
+    user_address: &str,
+    action: &Value,
+    band: f64,
+    mode: LiquidationGuardMode,
+) -> Result<Vec<String>, String> {
+    let orders = match action.get("orders").and_then(|o| o.as_array()) {
+        Some(orders) => orders,
+        None => return Ok(Vec::new()),
+    };
+
+    let clearinghouse = market.clearinghouse_state(user_address).await
+        .map_err(|e| format!("Liquidation guard unavailable: failed to fetch clearinghouseState: {}", e))?;
+    let summary = MarginSummary::from_clearinghouse_state(user_address, &clearinghouse);
+
+    // Net signed size change per asset across the whole batch
+    let mut size_deltas: Vec<(u64, f64)> = Vec::new();
+    for order in orders {
+        let asset_index = order.get("a").and_then(|a| a.as_u64()).unwrap_or(0);
+        let size = parse_number(order.get("s")).unwrap_or(0.0);
+        let signed = if order.get("b").and_then(|b| b.as_bool()).unwrap_or(true) { size } else { -size };
+
+        match size_deltas.iter_mut().find(|(a, _)| *a == asset_index) {
+            Some((_, delta)) => *delta += signed,
+            None => size_deltas.push((asset_index, signed)),
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for (asset_index, delta) in size_deltas {
+        let asset = match market.asset(asset_index).await {
+            Ok(Some(asset)) => asset,
+            _ => continue,
+        };
+        let mark_px = match asset.mark_px {
+            Some(px) if px > 0.0 => px,
+            _ => continue,
+        };
+
+        let current_size = position_size(&clearinghouse, &asset.name);
+        let post_size = current_size + delta;
+        if post_size == 0.0 {
+            continue;
+        }
+
+        let liq_px = match estimate_liquidation_px(&summary, asset.max_leverage, mark_px, current_size, post_size) {
+            Some(px) => px,
+            None => continue,
+        };
+
+        let distance = (mark_px - liq_px).abs() / mark_px;
+        info!("📐 Liquidation guard: {} post-trade size {}, est. liq px {:.4}, mark {:.4}, distance {:.2}%",
+            asset.name, post_size, liq_px, mark_px, distance * 100.0);
+
+        if distance < band {
+            let message = format!(
+                "{} estimated liquidation price {:.4} is within {:.2}% of mark {:.4} (guard band {:.2}%)",
+                asset.name, liq_px, distance * 100.0, mark_px, band * 100.0
+            );
+            match mode {
+                LiquidationGuardMode::Reject => return Err(format!("Order rejected: {}", message)),
+                LiquidationGuardMode::Warn => {
+                    warn!("⚠️ {}", message);
+                    warnings.push(message);
+                }
+            }
+        }
+    }
+
+    Ok(warnings)
+}
+
+/// Post-trade cross-margin liquidation price for one asset, or None when it can't be liquidated.
+///
+/// Maintenance margin held by the user's other positions stays reserved.
+pub fn estimate_liquidation_px(
+    summary: &MarginSummary,
+    max_leverage: f64,
+    mark_px: f64,
+    current_size: f64,
+    post_size: f64,
+) -> Option<f64> {
+    if post_size == 0.0 {
+        return None;
+    }
+
+    let l = 1.0 / (2.0 * max_leverage.max(1.0));
+    let side = if post_size > 0.0 { 1.0 } else { -1.0 };
+
+    let own_maintenance = current_size.abs() * mark_px * l;
+    let other_maintenance = (summary.maintenance_margin_used - own_maintenance).max(0.0);
+    let margin_available = summary.account_value - other_maintenance;
+
+    let liq_px = mark_px - side * margin_available / post_size.abs() / (1.0 - l * side);
+    (liq_px > 0.0).then_some(liq_px)
+}
+
+/// Signed size of the user's current position in `coin` (0 when flat)
+pub fn position_size(state: &Value, coin: &str) -> f64 {
+    state.get("assetPositions")
+        .and_then(|p| p.as_array())
+        .and_then(|positions| positions.iter()
+            .filter_map(|p| p.get("position"))
+            .find(|p| p.get("coin").and_then(|c| c.as_str()) == Some(coin)))
+        .and_then(|p| parse_number(p.get("szi")))
+        .unwrap_or(0.0)
+}
@@ -0,0 +1,90 @@
+use axum::{
+    body::Bytes,
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use futures_util::{Stream, StreamExt};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::AppState;
+
+/// Response-time limits by path prefix, from ROUTE_TIMEOUTS (`/info=10000,/exchange=30000`).
+/// The longest matching prefix wins; unmatched routes are unbounded.
+#[derive(Debug, Clone, Default)]
+pub struct RouteTimeouts {
+    routes: Vec<(String, Duration)>,
+}
+
+impl RouteTimeouts {
+    pub fn parse(entries: &[String]) -> Result<Self, String> {
+        let mut routes = entries.iter()
+            .map(|entry| {
+                let (prefix, ms) = entry.split_once('=').ok_or_else(|| format!("Invalid route timeout '{}'", entry))?;
+                let ms: u64 = ms.trim().parse().map_err(|_| format!("Invalid milliseconds in '{}'", entry))?;
+                Ok((prefix.trim().to_string(), Duration::from_millis(ms)))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        routes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+
+        Ok(Self { routes })
+    }
+
+    pub fn limit_for(&self, path: &str) -> Option<Duration> {
+        self.routes.iter()
+            .find(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .map(|(_, limit)| *limit)
+    }
+}
+
+/// When the current request's time limit runs out; set as a request extension by `enforce`
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline(pub Instant);
+
+/// Middleware: answer 504 when a handler runs past its route's limit.
+/// The limit covers producing the response head; streamed bodies use `with_deadline`.
+pub async fn enforce(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let Some(limit) = state.route_timeouts.limit_for(request.uri().path()) else {
+        return Ok(next.run(request).await);
+    };
+    request.extensions_mut().insert(Deadline(Instant::now() + limit));
+
+    let path = request.uri().path().to_string();
+    tokio::time::timeout(limit, next.run(request)).await.map_err(|_| {
+        warn!("⏱️ {} exceeded its {}ms limit", path, limit.as_millis());
+        StatusCode::GATEWAY_TIMEOUT
+    })
+}
+
+/// Forward an upstream byte stream, failing the body (and closing the connection)
+/// if it is still running at `deadline`
+pub fn with_deadline<S, E>(stream: S, deadline: Option<Deadline>) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    futures_util::stream::unfold(Some(Box::pin(stream)), move |stream| async move {
+        let mut stream = stream?;
+        let next = match deadline {
+            Some(Deadline(deadline)) => tokio::time::timeout_at(deadline, stream.next()).await,
+            None => Ok(stream.next().await),
+        };
+
+        match next {
+            Ok(Some(Ok(chunk))) => Some((Ok(chunk), Some(stream))),
+            Ok(Some(Err(e))) => Some((Err(std::io::Error::other(e)), None)),
+            Ok(None) => None,
+            Err(_) => {
+                warn!("⏱️ Streamed response cut off at its route limit");
+                Some((Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "Route time limit exceeded")), None))
+            }
+        }
+    })
+}
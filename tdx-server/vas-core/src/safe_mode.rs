@@ -0,0 +1,189 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::RwLock;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::audit::{AUDIT_EVM_TRANSACTION, AUDIT_TYPED_DATA};
+use crate::config::Config;
+use crate::ha::quote_mrtd;
+use crate::notify::{Notification, NotificationKind};
+use crate::preset_tdx::PresetTDXData;
+use crate::AppState;
+
+/// Longest a quote regeneration command may run
+const REGENERATE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Action types that only take risk off: cancels, plus orders when every one is reduce-only
+const RISK_REDUCING_ACTIONS: &[&str] = &["cancel", "cancelByCloid", "scheduleCancel"];
+
+/// Outcome of the periodic re-attestation
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SafeModeStatus {
+    pub active: bool,
+    /// Why the last re-attestation failed, while safe mode is on
+    pub reason: Option<String>,
+    pub since_ms: Option<u64>,
+    pub last_check_ms: Option<u64>,
+    pub last_ok_ms: Option<u64>,
+    pub failures: u64,
+}
+
+/// Restricts signing to risk-reducing actions while the attestation can't be renewed.
+///
+/// Shared by the signer actor and the handlers, so it uses a std lock: it is never held
+/// across an await.
+#[derive(Debug, Default)]
+pub struct SafeMode {
+    status: RwLock<SafeModeStatus>,
+}
+
+impl SafeMode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> SafeModeStatus {
+        self.status.read().unwrap().clone()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status.read().unwrap().active
+    }
+
+    /// Record a failed re-attestation; returns true when this turned safe mode on
+    pub(crate) fn enter(&self, reason: String) -> bool {
+        let mut status = self.status.write().unwrap();
+        let now = now_ms();
+        status.last_check_ms = Some(now);
+        status.failures += 1;
+        status.reason = Some(reason);
+        let entered = !status.active;
+        if entered {
+            status.active = true;
+            status.since_ms = Some(now);
+        }
+        entered
+    }
+
+    /// Record a successful re-attestation; returns true when this turned safe mode off
+    pub(crate) fn clear(&self) -> bool {
+        let mut status = self.status.write().unwrap();
+        let now = now_ms();
+        status.last_check_ms = Some(now);
+        status.last_ok_ms = Some(now);
+        let cleared = status.active;
+        status.active = false;
+        status.reason = None;
+        status.since_ms = None;
+        cleared
+    }
+
+    /// Refuse exchange actions that could add risk while safe mode is on
+    pub fn check_action(&self, action: &Value) -> Result<(), String> {
+        if !self.is_active() {
+            return Ok(());
+        }
+        let action_type = action.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+        let reduce_only_orders = action_type == "order" && action.get("orders")
+            .and_then(|orders| orders.as_array())
+            .is_some_and(|orders| orders.iter().all(|order| order.get("r").and_then(|r| r.as_bool()) == Some(true)));
+        if RISK_REDUCING_ACTIONS.contains(&action_type) || reduce_only_orders {
+            Ok(())
+        } else {
+            Err(format!("Safe mode: attestation could not be renewed, so only cancels and reduce-only orders are signed (refused '{}')", action_type))
+        }
+    }
+
+    /// Refuse EVM transactions and typed data while safe mode is on; statements and webhooks move nothing
+    pub fn check_digest(&self, kind: &str) -> Result<(), String> {
+        if self.is_active() && [AUDIT_EVM_TRANSACTION, AUDIT_TYPED_DATA].contains(&kind) {
+            return Err(format!("Safe mode: attestation could not be renewed, so {} signatures are refused", kind));
+        }
+        Ok(())
+    }
+}
+
+/// Periodically regenerate the quote and check it still attests this build.
+///
+/// A failure (the quoting tool rejecting an out-of-date TCB, a platform error, a quote for
+/// another measurement) puts the server in safe mode until a later re-attestation succeeds.
+pub fn spawn_monitor(state: AppState) {
+    let Some(interval_secs) = state.config.reattest_interval_secs else {
+        info!("🛡️ Re-attestation disabled");
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs.max(60)));
+        loop {
+            ticker.tick().await;
+
+            match reattest(&state.config).await {
+                Ok(()) => {
+                    if state.safe_mode.clear() {
+                        info!("🛡️ Re-attestation succeeded; leaving safe mode");
+                        state.notifier.notify(Notification::new(
+                            NotificationKind::Alert,
+                            None,
+                            "Safe mode lifted: attestation renewed",
+                            serde_json::json!({}),
+                        ));
+                    }
+                }
+                Err(reason) => {
+                    if state.safe_mode.enter(reason.clone()) {
+                        error!("🛡️ Re-attestation failed; entering safe mode: {}", reason);
+                        state.notifier.notify(Notification::new(
+                            NotificationKind::Alert,
+                            None,
+                            "Safe mode: attestation could not be renewed",
+                            serde_json::json!({"reason": reason}),
+                        ));
+                    } else {
+                        warn!("🛡️ Re-attestation still failing: {}", reason);
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Run the regeneration command (if any), then check the quote on disk against the one served
+async fn reattest(config: &Config) -> Result<(), String> {
+    regenerate_quote(config).await?;
+    let quote = PresetTDXData::read_quote()?;
+    check_measurement(&quote)
+}
+
+/// Run `REATTEST_COMMAND`, which rewrites agent_quote.bin; a no-op when unset
+pub async fn regenerate_quote(config: &Config) -> Result<(), String> {
+    if let Some(command) = &config.reattest_command {
+        let output = tokio::time::timeout(REGENERATE_TIMEOUT, tokio::process::Command::new("sh").arg("-c").arg(command).output())
+            .await
+            .map_err(|_| "Quote regeneration timed out".to_string())?
+            .map_err(|e| format!("Failed to run quote regeneration: {}", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Quote regeneration exited with {}: {}", output.status, stderr.trim()));
+        }
+    }
+    Ok(())
+}
+
+/// A regenerated quote must attest the build being served
+pub fn check_measurement(quote: &[u8]) -> Result<(), String> {
+    let served = PresetTDXData::get().ok_or("Preset TDX data not initialized")?;
+    match (quote_mrtd(quote), quote_mrtd(&served.tdx_quote)) {
+        (Some(fresh), Some(current)) if fresh == current => Ok(()),
+        (Some(fresh), Some(_)) => Err(format!("Regenerated quote has MRTD {}, not the measurement being served", hex::encode(fresh))),
+        _ => Err("Quote too short to carry a TD report".to_string()),
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,119 @@
+use axum::{
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+use crate::ha::SealedBox;
+use crate::preset_tdx::PresetTDXData;
+
+/// Prefix marking an encrypted configuration value: `enc:<iv>:<ciphertext>:<mac>`, all hex
+pub const ENC_PREFIX: &str = "enc:";
+
+/// Key the config values were decrypted with, kept so /admin/config/seal can encrypt new ones
+static CONFIG_KEY: OnceLock<[u8; 32]> = OnceLock::new();
+/// Variables that were decrypted at startup
+static SEALED_NAMES: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Decrypt every `enc:` environment variable in place, before Config::from_env reads them.
+///
+/// The key comes from CONFIG_SEAL_KEY (hex, provisioned by the platform's TEE sealing) or is
+/// released by the KMS at CONFIG_KMS_URL against this enclave's attestation quote. A value that
+/// does not decrypt stops startup rather than leave e.g. an admin token set to ciphertext.
+/// AGENT_PRIVATE_KEY and the key settings themselves are read before this runs and stay plaintext.
+pub async fn decrypt_env() -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let sealed: Vec<(String, String)> = std::env::vars()
+        .filter(|(_, value)| value.starts_with(ENC_PREFIX))
+        .collect();
+    let _ = SEALED_NAMES.set(sealed.iter().map(|(name, _)| name.clone()).collect());
+
+    let Some(key) = load_key().await? else {
+        if !sealed.is_empty() {
+            let names: Vec<&str> = sealed.iter().map(|(name, _)| name.as_str()).collect();
+            return Err(format!("{} are encrypted but neither CONFIG_SEAL_KEY nor CONFIG_KMS_URL is set", names.join(", ")).into());
+        }
+        return Ok(0);
+    };
+    let _ = CONFIG_KEY.set(key);
+
+    for (name, value) in &sealed {
+        let plaintext = open(&key, value).map_err(|e| format!("Failed to decrypt {}: {}", name, e))?;
+        // Startup is still single-threaded in effect: no task has been spawned yet
+        std::env::set_var(name, plaintext);
+    }
+    if !sealed.is_empty() {
+        info!("🔐 Decrypted {} sealed configuration values", sealed.len());
+    }
+    Ok(sealed.len())
+}
+
+/// Whether `name` held an `enc:` value that was decrypted at startup
+pub fn was_sealed(name: &str) -> bool {
+    SEALED_NAMES.get().is_some_and(|names| names.iter().any(|n| n == name))
+}
+
+async fn load_key() -> Result<Option<[u8; 32]>, Box<dyn std::error::Error + Send + Sync>> {
+    if let Ok(key) = std::env::var("CONFIG_SEAL_KEY") {
+        return Ok(Some(derive_key(&hex::decode(key.trim_start_matches("0x"))?)));
+    }
+    let Ok(kms_url) = std::env::var("CONFIG_KMS_URL") else {
+        return Ok(None);
+    };
+
+    // The KMS releases the key only to a quote whose measurement matches this build
+    let preset_data = PresetTDXData::get().ok_or("Preset TDX data is not initialized")?;
+    let response: Value = reqwest::Client::new()
+        .post(&kms_url)
+        .json(&serde_json::json!({
+            "quote_hex": hex::encode(&preset_data.tdx_quote),
+            "agent_address": preset_data.agent_address
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let key = response.get("key").and_then(|k| k.as_str()).ok_or("KMS response has no key")?;
+    info!("🔑 Configuration key released by KMS at {}", kms_url);
+    Ok(Some(derive_key(&hex::decode(key.trim_start_matches("0x"))?)))
+}
+
+/// Separate the config key from other uses of the same sealing secret
+fn derive_key(secret: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update(b"vas-config").chain_update(secret).finalize().into()
+}
+
+fn open(key: &[u8], value: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let mut parts = value.trim_start_matches(ENC_PREFIX).split(':');
+    let (Some(iv), Some(ciphertext), Some(mac), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return Err("expected enc:<iv>:<ciphertext>:<mac>".into());
+    };
+    let sealed = SealedBox { iv: iv.to_string(), ciphertext: ciphertext.to_string(), mac: mac.to_string() };
+    Ok(String::from_utf8(sealed.open(key)?)?)
+}
+
+fn seal(key: &[u8], plaintext: &str) -> String {
+    let sealed = SealedBox::seal(key, plaintext.as_bytes());
+    format!("{}{}:{}:{}", ENC_PREFIX, sealed.iv, sealed.ciphertext, sealed.mac)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SealRequest {
+    pub value: String,
+}
+
+/// POST /admin/config/seal - Encrypt a value under this deployment's config key.
+///
+/// The result can be committed in place of the plaintext; only an enclave holding the same
+/// sealing key (or passing the same KMS policy) can decrypt it.
+pub async fn admin_seal_value(Json(payload): Json<SealRequest>) -> Result<Json<Value>, StatusCode> {
+    let Some(key) = CONFIG_KEY.get() else {
+        warn!("⚠️ Config sealing requested but no config key is configured");
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    };
+    Ok(Json(serde_json::json!({"status": "ok", "value": seal(key, &payload.value)})))
+}
@@ -0,0 +1,187 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, Method, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::auth;
+use crate::AppState;
+
+/// Prefix distinguishing share tokens from session API keys
+pub const SHARE_TOKEN_PREFIX: &str = "share_";
+
+/// Default and maximum lifetime of a share link
+const DEFAULT_SHARE_TTL_SECS: u64 = 7 * 24 * 3600;
+const MAX_SHARE_TTL_SECS: u64 = 90 * 24 * 3600;
+
+/// Read-only token exposing a user's positions, fills and orders to a third party
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareLink {
+    pub token: String,
+    pub user_address: String,
+    pub label: Option<String>,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+/// Share links by token; independent of the session that minted them
+#[derive(Debug, Default)]
+pub struct ShareManager {
+    links: HashMap<String, ShareLink>,
+}
+
+impl ShareManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create(&mut self, user_address: &str, label: Option<String>, ttl_secs: u64) -> ShareLink {
+        let created_at = now_secs();
+        let link = ShareLink {
+            token: format!("{}{}", SHARE_TOKEN_PREFIX, uuid::Uuid::new_v4().simple()),
+            user_address: user_address.to_lowercase(),
+            label,
+            created_at,
+            expires_at: created_at + ttl_secs,
+        };
+        self.links.insert(link.token.clone(), link.clone());
+        link
+    }
+
+    /// User behind a live share token
+    pub fn resolve(&self, token: &str) -> Option<&str> {
+        self.links.get(token)
+            .filter(|link| link.expires_at > now_secs())
+            .map(|link| link.user_address.as_str())
+    }
+
+    pub fn list(&mut self, user_address: &str) -> Vec<ShareLink> {
+        let now = now_secs();
+        self.links.retain(|_, link| link.expires_at > now);
+
+        let user_address = user_address.to_lowercase();
+        self.links.values().filter(|link| link.user_address == user_address).cloned().collect()
+    }
+
+    /// Revoke a link owned by `user_address`
+    pub fn revoke(&mut self, token: &str, user_address: &str) -> bool {
+        match self.links.get(token) {
+            Some(link) if link.user_address == user_address.to_lowercase() => {
+                self.links.remove(token);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> usize {
+        let before = self.links.len();
+        self.links.retain(|_, link| !link.user_address.eq_ignore_ascii_case(user_address));
+        before - self.links.len()
+    }
+}
+
+/// The views a share token may read: the owner's positions, fills and orders
+const SHARED_VIEWS: [&str; 4] = [
+    "/me/margin",
+    "/me/activity",
+    "/me/conditional-orders",
+    "/me/recurring-orders",
+];
+
+/// Share tokens may only read the views in `SHARED_VIEWS`; every other route is refused,
+/// including ones added later
+pub fn share_token_allows(method: &Method, path: &str) -> bool {
+    method == Method::GET && SHARED_VIEWS.contains(&path)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Share link parameters
+#[derive(Debug, Deserialize)]
+pub struct CreateShareRequest {
+    pub label: Option<String>,
+    pub ttl_secs: Option<u64>,
+}
+
+/// Resolve the session user for share management; share tokens can't manage shares
+async fn session_user(state: &AppState, headers: &HeaderMap) -> Result<String, StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)
+}
+
+/// POST /me/shares - Mint a read-only share token for the caller's positions, fills and orders
+pub async fn create_share(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateShareRequest>,
+) -> Result<Json<ShareLink>, StatusCode> {
+    let user_address = session_user(&state, &headers).await?;
+    let ttl_secs = payload.ttl_secs.unwrap_or(DEFAULT_SHARE_TTL_SECS).min(MAX_SHARE_TTL_SECS);
+
+    let link = state.shares.write().await.create(&user_address, payload.label, ttl_secs);
+    info!("🔗 Share link created for {} (expires {})", user_address, link.expires_at);
+
+    Ok(Json(link))
+}
+
+/// GET /me/shares - Live share links minted by the caller
+pub async fn list_shares(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_address = session_user(&state, &headers).await?;
+    let links = state.shares.write().await.list(&user_address);
+
+    Ok(Json(serde_json::json!({ "shares": links })))
+}
+
+/// DELETE /me/shares/:token - Revoke a share link
+pub async fn revoke_share(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(token): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_address = session_user(&state, &headers).await?;
+    if !state.shares.write().await.revoke(&token, &user_address) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("🔗 Share link revoked by {}", user_address);
+    Ok(Json(serde_json::json!({"status": "ok", "response": "revoked"})))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_only_shared_views() {
+        for path in SHARED_VIEWS {
+            assert!(share_token_allows(&Method::GET, path), "{}", path);
+        }
+        for path in ["/me/grants", "/me/order-defaults", "/me/strategies", "/me/bot-liveness", "/me/locale", "/me/export", "/me/shares", "/me/margin/", "/graphql"] {
+            assert!(!share_token_allows(&Method::GET, path), "{}", path);
+        }
+    }
+
+    #[test]
+    fn refuses_writes_to_shared_views() {
+        for method in [Method::POST, Method::PUT, Method::DELETE] {
+            assert!(!share_token_allows(&method, "/me/conditional-orders"));
+        }
+    }
+}
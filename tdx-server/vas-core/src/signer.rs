@@ -0,0 +1,477 @@
+use alloy::primitives::B256;
+use futures_util::future::BoxFuture;
+use reqwest::Client;
+use secp256k1::SecretKey;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, OnceCell, RwLock};
+use tracing::{info, error};
+
+use crate::attestation::check_registered_key;
+use crate::audit::{AuditLog, Requester, AUDIT_EXCHANGE_ACTION, AUDIT_SET_REFERRER};
+use crate::client_ip;
+use crate::ha::Fence;
+use crate::proxy::HyperliquidProxy;
+use crate::safe_mode::SafeMode;
+use crate::universal_signing::{
+    build_exchange_payload, create_generic_action_hash, is_user_signed, prepare_action,
+    sign_hash_with_key, signing_digest, ExchangeSignature,
+};
+
+type SignResult = Result<Value, String>;
+
+/// Where signatures are produced.
+///
+/// The local backend holds the key in enclave memory; the remote backend only
+/// ever sends 32-byte digests and gets signatures back.
+pub trait SignerBackend: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Sign an already-computed EIP-712 digest
+    fn sign_hash<'a>(&'a self, hash: B256) -> BoxFuture<'a, Result<ExchangeSignature, Box<dyn std::error::Error + Send + Sync>>>;
+}
+
+impl std::fmt::Debug for dyn SignerBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SignerBackend({})", self.name())
+    }
+}
+
+/// Key held in enclave memory
+pub struct LocalBackend {
+    private_key: SecretKey,
+}
+
+impl LocalBackend {
+    pub fn new(private_key: SecretKey) -> Self {
+        Self { private_key }
+    }
+}
+
+impl SignerBackend for LocalBackend {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn sign_hash<'a>(&'a self, hash: B256) -> BoxFuture<'a, Result<ExchangeSignature, Box<dyn std::error::Error + Send + Sync>>> {
+        Box::pin(async move { Ok(sign_hash_with_key(&self.private_key, &hash)) })
+    }
+}
+
+/// Remote signer service reached over TLS, itself running in an attested TD.
+///
+/// The service signs with `expected_address`, a key the HyperEVM registry has verified a quote
+/// for (Automata DCAP) from one of the allowed builds. That is checked once before the first
+/// request, and every returned signature must recover to that key, so responses are bound to
+/// the attested build rather than to anything the service says about itself.
+pub struct RemoteBackend {
+    client: Client,
+    url: String,
+    auth_token: Option<String>,
+    expected_address: String,
+    registry: RemoteAttestation,
+    attested: OnceCell<()>,
+}
+
+/// Where and against which builds the remote signer's key is checked
+pub struct RemoteAttestation {
+    pub rpc_url: String,
+    pub registry_address: String,
+    /// Allowed MRTDs of the remote signer's build (48 bytes each)
+    pub mrtds: Vec<Vec<u8>>,
+}
+
+impl RemoteBackend {
+    pub fn new(
+        url: String,
+        auth_token: Option<String>,
+        expected_address: String,
+        registry: RemoteAttestation,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            url,
+            auth_token,
+            expected_address: expected_address.to_lowercase(),
+            registry,
+            attested: OnceCell::new(),
+        }
+    }
+}
+
+impl SignerBackend for RemoteBackend {
+    fn name(&self) -> &str {
+        "remote"
+    }
+
+    fn sign_hash<'a>(&'a self, hash: B256) -> BoxFuture<'a, Result<ExchangeSignature, Box<dyn std::error::Error + Send + Sync>>> {
+        Box::pin(async move {
+            self.attested.get_or_try_init(|| check_registered_key(
+                &self.registry.rpc_url,
+                &self.registry.registry_address,
+                &self.expected_address,
+                &self.registry.mrtds,
+            )).await.map_err(|e| format!("Remote signer is not attested: {}", e))?;
+
+            let mut request = self.client
+                .post(&self.url)
+                .json(&serde_json::json!({
+                    "hash": format!("{:?}", hash),
+                    "address": self.expected_address
+                }));
+            if let Some(token) = &self.auth_token {
+                request = request.bearer_auth(token);
+            }
+
+            let response = request.send().await?.error_for_status()?;
+
+            let body: Value = response.json().await?;
+            let signature = ExchangeSignature {
+                r: body.get("r").and_then(|r| r.as_str()).ok_or("Remote signer response missing r")?.to_string(),
+                s: body.get("s").and_then(|s| s.as_str()).ok_or("Remote signer response missing s")?.to_string(),
+                v: body.get("v").and_then(|v| v.as_u64()).ok_or("Remote signer response missing v")?,
+            };
+
+            let recovered = signature.recover_address(&hash)?;
+            if recovered != self.expected_address {
+                return Err(format!("Remote signer returned signature from {} (expected {})", recovered, self.expected_address).into());
+            }
+
+            Ok(signature)
+        })
+    }
+}
+
+/// An L1 exchange action ready for signing, with the account it is attributed to
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionRequest {
+    pub action: Value,
+    pub nonce: u64,
+    pub vault_address: Option<String>,
+    #[serde(skip)]
+    pub is_mainnet: bool,
+    /// Account the agent acts for (grantor for delegated actions); recorded in the audit log
+    pub user_address: Option<String>,
+}
+
+/// A fully-validated, policy-approved request for the signer.
+///
+/// Handlers must finish auth, scope and risk checks before constructing one;
+/// the signer only signs and submits.
+#[derive(Debug)]
+pub enum SignRequest {
+    /// Sign and submit an L1 exchange action
+    Action {
+        request: ActionRequest,
+        reply: oneshot::Sender<SignResult>,
+    },
+    /// Sign an L1 exchange action and return the signed body instead of submitting it
+    Hold {
+        request: ActionRequest,
+        reply: oneshot::Sender<SignResult>,
+    },
+    /// Sign and submit setReferrer for the agent's account
+    SetReferrer {
+        code: String,
+        is_mainnet: bool,
+        user_address: Option<String>,
+        reply: oneshot::Sender<SignResult>,
+    },
+    /// Sign a digest whose preimage has already passed an allowlist policy
+    Digest {
+        hash: B256,
+        user_address: Option<String>,
+        /// Audit kind and a description of the preimage for the transparency log
+        kind: &'static str,
+        subject: Value,
+        reply: oneshot::Sender<Result<ExchangeSignature, String>>,
+    },
+    /// Replace the signing backend (HA takeover with an escrowed key)
+    InstallBackend {
+        backend: Arc<dyn SignerBackend>,
+    },
+}
+
+/// Cloneable capability to request signatures; never exposes key material
+#[derive(Debug, Clone)]
+pub struct SignerHandle {
+    /// Requests paired with the client address of the HTTP request that caused them
+    requests: mpsc::Sender<(SignRequest, Option<String>)>,
+}
+
+impl SignerHandle {
+    /// Start the signer actor, moving the backend (and any key it holds) into it.
+    /// Every request it handles is recorded in the audit log; requests are refused while `fence` is closed,
+    /// and limited to risk-reducing ones while `safe_mode` is on.
+    pub fn spawn(
+        backend: Arc<dyn SignerBackend>,
+        proxy: Arc<HyperliquidProxy>,
+        audit: Arc<RwLock<AuditLog>>,
+        fence: Arc<Fence>,
+        safe_mode: Arc<SafeMode>,
+    ) -> Self {
+        let (requests, request_rx) = mpsc::channel(256);
+        info!("🔏 Signer actor started with '{}' backend", backend.name());
+        tokio::spawn(run_signer(backend, proxy, audit, fence, safe_mode, request_rx));
+
+        Self { requests }
+    }
+
+    /// Sign and submit an exchange action, returning the normalized response
+    pub async fn sign_action(&self, request: ActionRequest) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let (reply, response) = oneshot::channel();
+        self.send(SignRequest::Action { request, reply }).await?;
+        Ok(response.await.map_err(|_| "Signer dropped request")??)
+    }
+
+    /// Sign an exchange action for later submission, returning the signed /exchange body
+    pub async fn sign_and_hold(&self, request: ActionRequest) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let (reply, response) = oneshot::channel();
+        self.send(SignRequest::Hold { request, reply }).await?;
+        Ok(response.await.map_err(|_| "Signer dropped request")??)
+    }
+
+    /// Sign and submit setReferrer
+    pub async fn set_referrer(
+        &self,
+        code: String,
+        is_mainnet: bool,
+        user_address: Option<String>,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let (reply, response) = oneshot::channel();
+        self.send(SignRequest::SetReferrer { code, is_mainnet, user_address, reply }).await?;
+        Ok(response.await.map_err(|_| "Signer dropped request")??)
+    }
+
+    /// Sign a policy-checked digest without submitting anything
+    pub async fn sign_digest(
+        &self,
+        hash: B256,
+        user_address: Option<String>,
+        kind: &'static str,
+        subject: Value,
+    ) -> Result<ExchangeSignature, Box<dyn std::error::Error + Send + Sync>> {
+        let (reply, response) = oneshot::channel();
+        self.send(SignRequest::Digest { hash, user_address, kind, subject, reply }).await?;
+        Ok(response.await.map_err(|_| "Signer dropped request")??)
+    }
+
+    /// Swap the backend used for all subsequent requests
+    pub async fn install_backend(&self, backend: Arc<dyn SignerBackend>) {
+        if self.send(SignRequest::InstallBackend { backend }).await.is_err() {
+            error!("❌ Signer actor is not running; backend not installed");
+        }
+    }
+
+    async fn send(&self, request: SignRequest) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.requests.send((request, client_ip::current())).await.map_err(|_| "Signer actor is not running".into())
+    }
+}
+
+/// Actor loop: the only place the signing backend lives after startup.
+/// Each request runs in its own task so slow upstream calls don't serialize signing.
+async fn run_signer(
+    mut backend: Arc<dyn SignerBackend>,
+    proxy: Arc<HyperliquidProxy>,
+    audit: Arc<RwLock<AuditLog>>,
+    fence: Arc<Fence>,
+    safe_mode: Arc<SafeMode>,
+    mut requests: mpsc::Receiver<(SignRequest, Option<String>)>,
+) {
+    while let Some((request, client_ip)) = requests.recv().await {
+        if let SignRequest::InstallBackend { backend: installed } = request {
+            info!("🔏 Signer backend replaced with '{}'", installed.name());
+            backend = installed;
+            continue;
+        }
+
+        // Fenced instances refuse to sign; refusals are not audited since nothing was signed
+        if let Err(reason) = fence.check() {
+            error!("🚧 {}", reason);
+            match request {
+                SignRequest::Action { reply, .. } | SignRequest::Hold { reply, .. } | SignRequest::SetReferrer { reply, .. } => { let _ = reply.send(Err(reason)); }
+                SignRequest::Digest { reply, .. } => { let _ = reply.send(Err(reason)); }
+                SignRequest::InstallBackend { .. } => {}
+            }
+            continue;
+        }
+
+        // Safe mode: the handlers refuse risk-adding requests with a coded error; this is the backstop
+        let refused = match &request {
+            SignRequest::Action { request, .. } | SignRequest::Hold { request, .. } => safe_mode.check_action(&request.action).err(),
+            SignRequest::SetReferrer { .. } => safe_mode.check_action(&serde_json::json!({"type": "setReferrer"})).err(),
+            SignRequest::Digest { kind, .. } => safe_mode.check_digest(kind).err(),
+            SignRequest::InstallBackend { .. } => None,
+        };
+        if let Some(reason) = refused {
+            error!("🛡️ {}", reason);
+            match request {
+                SignRequest::Action { reply, .. } | SignRequest::Hold { reply, .. } | SignRequest::SetReferrer { reply, .. } => { let _ = reply.send(Err(reason)); }
+                SignRequest::Digest { reply, .. } => { let _ = reply.send(Err(reason)); }
+                SignRequest::InstallBackend { .. } => {}
+            }
+            continue;
+        }
+
+        let backend = backend.clone();
+        let proxy = proxy.clone();
+        let audit = audit.clone();
+
+        tokio::spawn(async move {
+            match request {
+                SignRequest::Action { request, reply } => {
+                    let requester = Requester { user_address: request.user_address.clone(), client_ip };
+                    let _ = reply.send(sign_action_request(backend.as_ref(), Some(&proxy), &audit, &requester, request).await);
+                }
+                SignRequest::Hold { request, reply } => {
+                    let requester = Requester { user_address: request.user_address.clone(), client_ip };
+                    let _ = reply.send(sign_action_request(backend.as_ref(), None, &audit, &requester, request).await);
+                }
+                SignRequest::SetReferrer { code, is_mainnet, user_address, reply } => {
+                    let action = serde_json::json!({"type": "setReferrer", "code": code});
+                    let nonce = now_ms();
+                    info!("🏷️ Setting referrer code: {}", code);
+                    let result = sign_and_submit(backend.as_ref(), &proxy, &action, nonce, None, is_mainnet)
+                        .await
+                        .map(|(response, signature)| (response, Some(signature)))
+                        .map_err(|e| e.to_string());
+
+                    let subject_hash = create_generic_action_hash(&action, nonce, None)
+                        .map(|h| format!("{:?}", h))
+                        .unwrap_or_default();
+                    let subject = audit_subject(&action, nonce, None, is_mainnet);
+                    let requester = Requester { user_address, client_ip };
+                    record(&audit, &requester, AUDIT_SET_REFERRER, subject_hash, subject, &result).await;
+
+                    let _ = reply.send(result.map(|(response, _)| response));
+                }
+                SignRequest::Digest { hash, user_address, kind, subject, reply } => {
+                    let result = backend.sign_hash(hash).await.map_err(|e| e.to_string());
+
+                    let (signature, error) = match &result {
+                        Ok(signature) => (Some(signature.to_json()), None),
+                        Err(e) => (None, Some(e.clone())),
+                    };
+                    let entry = audit.write().await.append(
+                        &Requester { user_address, client_ip },
+                        kind,
+                        format!("{:?}", hash),
+                        subject,
+                        signature,
+                        error,
+                    );
+                    info!("🧾 Audit entry {} ({})", entry.seq, kind);
+
+                    let _ = reply.send(result);
+                }
+                SignRequest::InstallBackend { .. } => {}
+            }
+        });
+    }
+
+    info!("🔏 Signer actor stopped");
+}
+
+/// Prepare and sign an exchange action, audit it, and submit it through `proxy` if given;
+/// without a proxy the signed body is returned for the caller to submit later
+async fn sign_action_request(
+    backend: &dyn SignerBackend,
+    proxy: Option<&HyperliquidProxy>,
+    audit: &RwLock<AuditLog>,
+    requester: &Requester,
+    request: ActionRequest,
+) -> SignResult {
+    let ActionRequest { action, nonce, vault_address, is_mainnet, .. } = request;
+    // Audit what was actually signed; unpreparable actions are recorded as received
+    let (action, result) = match prepare_action(&action) {
+        Ok(prepared) => {
+            let result = match proxy {
+                Some(proxy) => sign_and_submit(backend, proxy, &prepared, nonce, vault_address.as_deref(), is_mainnet).await,
+                None => sign_payload(backend, &prepared, nonce, vault_address.as_deref(), is_mainnet).await,
+            };
+            (prepared, result)
+        }
+        Err(e) => (action, Err(e)),
+    };
+    let result = result
+        .map(|(response, signature)| (response, Some(signature)))
+        .map_err(|e| e.to_string());
+
+    if let Err(e) = &result {
+        error!("❌ Signer failed action: {}", e);
+    }
+
+    let subject_hash = create_generic_action_hash(&action, nonce, vault_address.as_deref())
+        .map(|h| format!("{:?}", h))
+        .unwrap_or_default();
+    let subject = audit_subject(&action, nonce, vault_address.as_deref(), is_mainnet);
+    record(audit, requester, AUDIT_EXCHANGE_ACTION, subject_hash, subject, &result).await;
+
+    result.map(|(response, _)| response)
+}
+
+/// Everything needed to recompute an L1 action's hash and signing digest offline
+fn audit_subject(action: &Value, nonce: u64, vault_address: Option<&str>, is_mainnet: bool) -> Value {
+    serde_json::json!({
+        "action": action,
+        "nonce": nonce,
+        "vaultAddress": vault_address,
+        "isMainnet": is_mainnet
+    })
+}
+
+/// Append the outcome of an exchange signing request to the audit log
+async fn record(
+    audit: &RwLock<AuditLog>,
+    requester: &Requester,
+    kind: &str,
+    subject_hash: String,
+    subject: Value,
+    result: &Result<(Value, Option<ExchangeSignature>), String>,
+) {
+    let (signature, error) = match result {
+        Ok((_, signature)) => (signature.as_ref().map(|s| s.to_json()), None),
+        Err(e) => (None, Some(e.clone())),
+    };
+    let entry = audit.write().await.append(requester, kind, subject_hash, subject, signature, error);
+    info!("🧾 Audit entry {} ({})", entry.seq, kind);
+}
+
+/// Native signing path for every backend: compute the digest of the prepared action
+/// (phantom agent for L1 actions, typed data for user-signed ones), sign it and forward
+/// the signed body upstream. The upstream response is passed through as-is.
+async fn sign_and_submit(
+    backend: &dyn SignerBackend,
+    proxy: &HyperliquidProxy,
+    action: &Value,
+    nonce: u64,
+    vault_address: Option<&str>,
+    is_mainnet: bool,
+) -> Result<(Value, ExchangeSignature), Box<dyn std::error::Error + Send + Sync>> {
+    let (payload, signature) = sign_payload(backend, action, nonce, vault_address, is_mainnet).await?;
+    let response = proxy.proxy_exchange_request(&payload).await?;
+    Ok((response, signature))
+}
+
+/// Sign a prepared action and build the /exchange body carrying the signature
+async fn sign_payload(
+    backend: &dyn SignerBackend,
+    action: &Value,
+    nonce: u64,
+    vault_address: Option<&str>,
+    is_mainnet: bool,
+) -> Result<(Value, ExchangeSignature), Box<dyn std::error::Error + Send + Sync>> {
+    // User-signed actions act on the signer's own account and carry no vault
+    let vault_address = vault_address.filter(|_| !is_user_signed(action));
+    let digest = signing_digest(action, nonce, vault_address, is_mainnet)?;
+    let signature = backend.sign_hash(digest).await?;
+    Ok((build_exchange_payload(action, nonce, vault_address, &signature), signature))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
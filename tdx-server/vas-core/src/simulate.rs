@@ -0,0 +1,147 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, error};
+
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::fees::{self, FeeEstimate, FeeRates};
+use crate::margin::{position_leverage, MarginSummary};
+use crate::market::parse_number;
+use crate::risk::{estimate_liquidation_px, position_size};
+use crate::AppState;
+
+/// Projected effect of an order on one asset
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionProjection {
+    pub coin: String,
+    pub current_size: f64,
+    pub projected_size: f64,
+    pub mark_px: Option<f64>,
+    pub projected_notional: Option<f64>,
+    pub leverage: f64,
+    /// Additional initial margin the order ties up (0 for reduce-only)
+    pub margin_required: f64,
+    pub estimated_liquidation_px: Option<f64>,
+}
+
+/// Result of POST /exchange/simulate
+#[derive(Debug, Clone, Serialize)]
+pub struct Simulation {
+    pub user_address: String,
+    pub account_value: f64,
+    pub current_margin_usage: f64,
+    pub projected_margin_usage: Option<f64>,
+    pub positions: Vec<PositionProjection>,
+    pub fees: FeeEstimate,
+}
+
+/// Project an order action against the user's cached account state. Nothing is signed.
+pub async fn simulate_order(
+    state: &AppState,
+    user_address: &str,
+    action: &Value,
+    rates: FeeRates,
+) -> Result<Simulation, String> {
+    let orders = action.get("orders").and_then(|o| o.as_array())
+        .ok_or("Only order actions can be simulated")?;
+
+    let clearinghouse = state.market.clearinghouse_state(user_address).await
+        .map_err(|e| format!("Failed to fetch clearinghouseState: {}", e))?;
+    let summary = MarginSummary::from_clearinghouse_state(user_address, &clearinghouse);
+
+    let mut positions: Vec<PositionProjection> = Vec::new();
+    let mut additional_margin = 0.0;
+
+    for order in orders {
+        let asset_index = order.get("a").and_then(|a| a.as_u64()).unwrap_or(0);
+        let asset = state.market.asset(asset_index).await
+            .map_err(|e| format!("Failed to fetch metaAndAssetCtxs: {}", e))?
+            .ok_or_else(|| format!("Unknown asset index {}", asset_index))?;
+
+        let size = parse_number(order.get("s")).unwrap_or(0.0);
+        let px = parse_number(order.get("p")).unwrap_or(0.0);
+        let signed = if order.get("b").and_then(|b| b.as_bool()).unwrap_or(true) { size } else { -size };
+        let reduce_only = order.get("r").and_then(|r| r.as_bool()).unwrap_or(false);
+        let notional = px * size;
+
+        // Same leverage assumption as the margin risk check: position leverage, else 1x
+        let leverage = position_leverage(&clearinghouse, &asset.name).unwrap_or(1.0).max(1.0);
+        let margin_required = if reduce_only { 0.0 } else { notional / leverage };
+        additional_margin += margin_required;
+
+        match positions.iter_mut().find(|p| p.coin == asset.name) {
+            Some(projection) => {
+                projection.projected_size += signed;
+                projection.margin_required += margin_required;
+            }
+            None => {
+                let current_size = position_size(&clearinghouse, &asset.name);
+                positions.push(PositionProjection {
+                    coin: asset.name.clone(),
+                    current_size,
+                    projected_size: current_size + signed,
+                    mark_px: asset.mark_px,
+                    projected_notional: None,
+                    leverage,
+                    margin_required,
+                    estimated_liquidation_px: None,
+                });
+            }
+        }
+
+        if let (Some(projection), Some(mark_px)) = (positions.iter_mut().find(|p| p.coin == asset.name), asset.mark_px) {
+            projection.projected_notional = Some(projection.projected_size.abs() * mark_px);
+            projection.estimated_liquidation_px = estimate_liquidation_px(
+                &summary, asset.max_leverage, mark_px, projection.current_size, projection.projected_size,
+            );
+        }
+    }
+
+    let fees = fees::estimate_order_fees(action, rates).ok_or("Only order actions can be simulated")?;
+
+    let projected_margin_usage = (summary.account_value > 0.0)
+        .then(|| (summary.total_margin_used + additional_margin) / summary.account_value);
+
+    Ok(Simulation {
+        user_address: user_address.to_string(),
+        account_value: summary.account_value,
+        current_margin_usage: summary.margin_usage,
+        projected_margin_usage,
+        positions,
+        fees,
+    })
+}
+
+/// POST /exchange/simulate - Projected position, margin usage, liquidation price and fees for a candidate order
+pub async fn simulate(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<Value>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_address = auth::user_address_for_api_key(&state, api_key)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let action = payload.get("action").ok_or(StatusCode::BAD_REQUEST)?;
+
+    info!("🧪 Simulating order for {}", user_address);
+
+    let rates = fees::rates_for(&state, &user_address).await;
+
+    match simulate_order(&state, &user_address, action, rates).await {
+        Ok(simulation) => Ok(Json(serde_json::json!({
+            "status": "ok",
+            "simulation": simulation
+        }))),
+        Err(e) => {
+            error!("❌ Simulation failed: {}", e);
+            Ok(Json(error_codes::err_body(ErrorCode::SimulationFailed, e)))
+        }
+    }
+}
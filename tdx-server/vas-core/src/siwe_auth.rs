@@ -0,0 +1,230 @@
+use siwe::{Message, VerificationOpts};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn, error};
+use chrono::{Utc, Duration};
+
+use crate::address::Address;
+
+/// SIWE login request
+#[derive(Debug, Deserialize)]
+pub struct SiweLoginRequest {
+    pub message: String,
+    pub signature: String,
+    /// Scopes requested for the issued API key (defaults to trade only)
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+    /// Session lifetime, up to the deployment's SESSION_MAX_TTL_SECS (defaults to SESSION_TTL_SECS)
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+/// SIWE login response
+#[derive(Debug, Serialize)]
+pub struct SiweLoginResponse {
+    pub success: bool,
+    pub user_address: String,
+    pub api_key: String,
+    pub agent_address: String,
+    pub tdx_quote_hex: String,
+    pub message: String,
+    pub expires_at: String,
+    /// Latest the session can last; later than `expires_at` only when sessions slide
+    pub max_expires_at: String,
+    pub scopes: Vec<String>,
+    /// Coins the key may trade, present when the SIWE message signed an asset allowlist
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_assets: Option<Vec<String>>,
+}
+
+/// SIWE login error response
+#[derive(Debug, Serialize)]
+pub struct SiweLoginError {
+    pub success: bool,
+    pub error: String,
+    pub code: u16,
+}
+
+/// Validate SIWE message and signature
+pub async fn validate_siwe_signature(
+    message: &str, 
+    signature: &str
+) -> Result<Address, Box<dyn std::error::Error + Send + Sync>> {
+    verify_siwe_message(message, signature).await
+        .map(|siwe_message| Address::from(siwe_message.address))
+}
+
+/// Validate SIWE message and signature, returning the parsed message for its signed fields
+pub async fn verify_siwe_message(
+    message: &str,
+    signature: &str
+) -> Result<Message, Box<dyn std::error::Error + Send + Sync>> {
+    info!("🔐 Validating SIWE signature...");
+    
+    // Parse the SIWE message
+    let siwe_message: Message = message.parse()
+        .map_err(|e| format!("Invalid SIWE message format: {}", e))?;
+    
+    info!("📋 SIWE message parsed successfully");
+    let address = Address::from(siwe_message.address);
+    info!("   Address: {}", address);
+    info!("   Domain: {}", siwe_message.domain);
+    info!("   URI: {}", siwe_message.uri);
+    
+    // Verify the signature
+    let verification_opts = VerificationOpts {
+        domain: Some(siwe_message.domain.clone()),
+        nonce: Some(siwe_message.nonce.clone()),
+        timestamp: None, // Use default timestamp handling
+        ..Default::default()
+    };
+    
+    // Convert signature to the format expected by SIWE
+    let signature_bytes = if signature.starts_with("0x") {
+        hex::decode(&signature[2..])
+            .map_err(|e| format!("Invalid signature hex: {}", e))?
+    } else {
+        hex::decode(signature)
+            .map_err(|e| format!("Invalid signature hex: {}", e))?
+    };
+    
+    // Verify the signature (async call)
+    match siwe_message.verify(&signature_bytes, &verification_opts).await {
+        Ok(_) => {
+            info!("✅ SIWE signature valid for address: {}", address);
+            Ok(siwe_message)
+        }
+        Err(e) => {
+            warn!("❌ SIWE signature verification failed: {}", e);
+            Err(format!("SIWE verification failed: {}", e).into())
+        }
+    }
+}
+
+/// SIWE resource granting the issued key a scope, e.g. `urn:vas:scope:trade`
+pub const SCOPE_RESOURCE_PREFIX: &str = "urn:vas:scope:";
+/// SIWE resource limiting the issued key to an asset, e.g. `urn:vas:asset:ETH`
+pub const ASSET_RESOURCE_PREFIX: &str = "urn:vas:asset:";
+/// Namespace of the resources above; anything else under it is refused rather than ignored
+const RESOURCE_NAMESPACE: &str = "urn:vas:";
+
+/// What the `resources` of a signed SIWE message grant the issued API key
+#[derive(Debug, Default, PartialEq)]
+pub struct ResourceGrants {
+    /// Scopes named by `urn:vas:scope:` resources, if any were signed
+    pub scopes: Option<Vec<String>>,
+    /// Coins named by `urn:vas:asset:` resources, if any were signed
+    pub assets: Option<Vec<String>>,
+}
+
+impl ResourceGrants {
+    /// Read the `urn:vas:` resources of a message; resources outside that namespace are ignored
+    pub fn from_resources<'a>(resources: impl IntoIterator<Item = &'a str>) -> Result<Self, String> {
+        let mut grants = Self::default();
+        for resource in resources {
+            let (values, value) = if let Some(scope) = resource.strip_prefix(SCOPE_RESOURCE_PREFIX) {
+                (grants.scopes.get_or_insert_with(Vec::new), scope)
+            } else if let Some(coin) = resource.strip_prefix(ASSET_RESOURCE_PREFIX) {
+                (grants.assets.get_or_insert_with(Vec::new), coin)
+            } else if resource.starts_with(RESOURCE_NAMESPACE) {
+                return Err(format!("Unsupported SIWE resource: {}", resource));
+            } else {
+                continue;
+            };
+            if value.is_empty() {
+                return Err(format!("SIWE resource {} names nothing", resource));
+            }
+            if !values.iter().any(|v| v == value) {
+                values.push(value.to_string());
+            }
+        }
+        Ok(grants)
+    }
+
+    /// Grants signed into a verified SIWE message
+    pub fn from_message(message: &Message) -> Result<Self, String> {
+        Self::from_resources(message.resources.iter().map(|r| r.as_str()))
+    }
+}
+
+/// Generate a SIWE message for testing
+pub fn generate_siwe_message(
+    user_address: &str,
+    domain: &str,
+    uri: &str,
+    nonce: &str,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now();
+    let expires = now + Duration::hours(24); // 24 hour expiry
+    
+    let message = format!(
+        "{} wants you to sign in with your Ethereum account:\n{}\n\nGenerate agent wallet for TEE-secured trading.\n\nURI: {}\nVersion: 1\nChain ID: 1\nNonce: {}\nIssued At: {}\nExpiration Time: {}",
+        domain,
+        user_address,
+        uri,
+        nonce,
+        now.to_rfc3339(),
+        expires.to_rfc3339()
+    );
+    
+    Ok(message)
+}
+
+/// Generate a secure nonce for SIWE
+pub fn generate_nonce() -> String {
+    use rand::Rng;
+    
+    // Generate 16 random bytes and encode as hex
+    let mut rng = rand::thread_rng();
+    let bytes: [u8; 16] = rng.gen();
+    hex::encode(bytes)
+}
+
+/// Validate that a SIWE message is not expired (simplified)
+pub fn is_siwe_message_valid(message: &str) -> bool {
+    match message.parse::<Message>() {
+        Ok(_siwe_message) => {
+            // For now, just check that message parses correctly
+            // TODO: Implement proper timestamp validation with SIWE TimeStamp types
+            info!("📋 SIWE message validation: parsed successfully");
+            true
+        }
+        Err(e) => {
+            error!("Failed to parse SIWE message for validation: {}", e);
+            false
+        }
+    }
+}
+
+// TODO: Add session management for API keys
+// TODO: Implement proper nonce tracking for replay protection  
+// TODO: Add rate limiting for SIWE authentication
+// TODO: Add API key expiration and renewal
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_vas_resources_to_grants() {
+        let grants = ResourceGrants::from_resources([
+            "urn:vas:scope:trade",
+            "urn:vas:asset:ETH",
+            "https://example.com/terms",
+            "urn:vas:asset:BTC",
+            "urn:vas:asset:ETH",
+        ]).unwrap();
+        assert_eq!(grants.scopes, Some(vec!["trade".to_string()]));
+        assert_eq!(grants.assets, Some(vec!["ETH".to_string(), "BTC".to_string()]));
+    }
+
+    #[test]
+    fn no_vas_resources_grant_nothing() {
+        assert_eq!(ResourceGrants::from_resources(["ipfs://bafy"]).unwrap(), ResourceGrants::default());
+    }
+
+    #[test]
+    fn rejects_unknown_or_empty_vas_resources() {
+        assert!(ResourceGrants::from_resources(["urn:vas:vault:0xabc"]).is_err());
+        assert!(ResourceGrants::from_resources(["urn:vas:scope:"]).is_err());
+        assert!(ResourceGrants::from_resources(["urn:vas:asset:"]).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use axum::{extract::State, response::Json};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use tracing::info;
+
+use crate::market::MarketCache;
+use crate::AppState;
+
+/// Burn-rate windows reported by the tracker (label, length in ms)
+const WINDOWS: [(&str, u64); 3] = [("5m", 5 * 60_000), ("1h", 60 * 60_000), ("6h", 6 * 60 * 60_000)];
+/// Samples older than the longest window are dropped
+const RETENTION_MS: u64 = 6 * 60 * 60_000;
+/// Hard cap so a burst can't grow the buffer without bound
+const MAX_SAMPLES: usize = 100_000;
+
+/// Latency of one signed exchange action
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySample {
+    pub at_ms: u64,
+    /// Coins the action touched (empty for actions without an asset)
+    pub assets: Vec<String>,
+    /// From request receipt to response
+    pub end_to_end_ms: u64,
+    /// Time inside the signer: signing plus the upstream /exchange round trip
+    pub upstream_ms: u64,
+    pub ok: bool,
+}
+
+/// Latency stats and burn rate over one window
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowStats {
+    pub window: String,
+    pub count: usize,
+    pub p50_ms: Option<u64>,
+    pub p99_ms: Option<u64>,
+    pub upstream_p50_ms: Option<u64>,
+    pub upstream_p99_ms: Option<u64>,
+    /// Fraction of requests that succeeded within the latency target
+    pub good_ratio: Option<f64>,
+    /// Error-budget burn rate: bad fraction / (1 - objective); 1.0 spends the budget exactly
+    pub burn_rate: Option<f64>,
+}
+
+/// Result of GET /admin/slo
+#[derive(Debug, Clone, Serialize)]
+pub struct SloSummary {
+    pub target_ms: u64,
+    pub objective: f64,
+    pub windows: Vec<WindowStats>,
+    /// Per-coin stats over the 1h window
+    pub assets: BTreeMap<String, WindowStats>,
+}
+
+/// Rolling record of exchange latency against a latency SLO
+#[derive(Debug)]
+pub struct SloTracker {
+    target_ms: u64,
+    objective: f64,
+    samples: VecDeque<LatencySample>,
+}
+
+impl SloTracker {
+    pub fn new(target_ms: u64, objective: f64) -> Self {
+        Self {
+            target_ms,
+            objective: objective.clamp(0.0, 0.9999),
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, sample: LatencySample) {
+        let cutoff = sample.at_ms.saturating_sub(RETENTION_MS);
+        while self.samples.front().is_some_and(|s| s.at_ms < cutoff) || self.samples.len() >= MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn summary(&self) -> SloSummary {
+        let now = now_ms();
+
+        let windows = WINDOWS.iter()
+            .map(|(label, len)| self.stats(label, self.samples.iter().filter(|s| s.at_ms >= now.saturating_sub(*len))))
+            .collect();
+
+        let hour_ago = now.saturating_sub(WINDOWS[1].1);
+        let mut coins: Vec<&str> = self.samples.iter()
+            .filter(|s| s.at_ms >= hour_ago)
+            .flat_map(|s| s.assets.iter().map(|a| a.as_str()))
+            .collect();
+        coins.sort();
+        coins.dedup();
+
+        let assets = coins.into_iter()
+            .map(|coin| {
+                let samples = self.samples.iter().filter(|s| s.at_ms >= hour_ago && s.assets.iter().any(|a| a == coin));
+                (coin.to_string(), self.stats(WINDOWS[1].0, samples))
+            })
+            .collect();
+
+        SloSummary {
+            target_ms: self.target_ms,
+            objective: self.objective,
+            windows,
+            assets,
+        }
+    }
+
+    /// Prometheus exposition of the current burn rates and latency quantiles
+    pub fn prometheus(&self) -> String {
+        let summary = self.summary();
+        let mut out = String::new();
+
+        out.push_str("# HELP vas_exchange_slo_burn_rate Error-budget burn rate of the exchange latency SLO\n");
+        out.push_str("# TYPE vas_exchange_slo_burn_rate gauge\n");
+        for window in &summary.windows {
+            if let Some(burn_rate) = window.burn_rate {
+                out.push_str(&format!("vas_exchange_slo_burn_rate{{window=\"{}\"}} {}\n", window.window, burn_rate));
+            }
+        }
+        for (coin, stats) in &summary.assets {
+            if let Some(burn_rate) = stats.burn_rate {
+                out.push_str(&format!("vas_exchange_slo_burn_rate{{window=\"{}\",asset=\"{}\"}} {}\n", stats.window, coin, burn_rate));
+            }
+        }
+
+        out.push_str("# HELP vas_exchange_latency_ms Exchange latency quantiles over the window\n");
+        out.push_str("# TYPE vas_exchange_latency_ms gauge\n");
+        for window in &summary.windows {
+            let series = [
+                ("end_to_end", "0.5", window.p50_ms),
+                ("end_to_end", "0.99", window.p99_ms),
+                ("upstream", "0.5", window.upstream_p50_ms),
+                ("upstream", "0.99", window.upstream_p99_ms),
+            ];
+            for (stage, quantile, value) in series {
+                if let Some(value) = value {
+                    out.push_str(&format!(
+                        "vas_exchange_latency_ms{{window=\"{}\",stage=\"{}\",quantile=\"{}\"}} {}\n",
+                        window.window, stage, quantile, value
+                    ));
+                }
+            }
+        }
+
+        out.push_str(&format!("vas_exchange_slo_target_ms {}\n", self.target_ms));
+        out
+    }
+
+    fn stats<'a>(&self, window: &str, samples: impl Iterator<Item = &'a LatencySample>) -> WindowStats {
+        let samples: Vec<&LatencySample> = samples.collect();
+        let mut end_to_end: Vec<u64> = samples.iter().map(|s| s.end_to_end_ms).collect();
+        let mut upstream: Vec<u64> = samples.iter().map(|s| s.upstream_ms).collect();
+        end_to_end.sort_unstable();
+        upstream.sort_unstable();
+
+        let good = samples.iter().filter(|s| s.ok && s.end_to_end_ms <= self.target_ms).count();
+        let good_ratio = (!samples.is_empty()).then(|| good as f64 / samples.len() as f64);
+
+        WindowStats {
+            window: window.to_string(),
+            count: samples.len(),
+            p50_ms: percentile(&end_to_end, 0.5),
+            p99_ms: percentile(&end_to_end, 0.99),
+            upstream_p50_ms: percentile(&upstream, 0.5),
+            upstream_p99_ms: percentile(&upstream, 0.99),
+            good_ratio,
+            burn_rate: good_ratio.map(|ratio| (1.0 - ratio) / (1.0 - self.objective)),
+        }
+    }
+}
+
+/// Nearest-rank percentile of an ascending slice
+fn percentile(sorted: &[u64], q: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((q * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+    Some(sorted[rank - 1])
+}
+
+/// Coins referenced by an order, cancel or modify action, for the per-asset breakdown
+pub async fn action_assets(market: &MarketCache, action: &Value) -> Vec<String> {
+    let mut indices: Vec<u64> = ["orders", "cancels", "modifies"].iter()
+        .filter_map(|field| action.get(*field).and_then(|v| v.as_array()))
+        .flatten()
+        .filter_map(|entry| entry.get("a").or_else(|| entry.pointer("/order/a")).and_then(|a| a.as_u64()))
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+
+    let mut assets = Vec::new();
+    for index in indices {
+        match market.asset(index).await {
+            Ok(Some(asset)) => assets.push(asset.name),
+            _ => assets.push(format!("#{}", index)),
+        }
+    }
+    assets
+}
+
+/// GET /admin/slo - Latency SLO summary with burn rates and per-asset breakdown
+pub async fn admin_slo(State(state): State<AppState>) -> Json<SloSummary> {
+    let summary = state.slo.read().await.summary();
+    info!("⏱️ SLO summary requested ({} samples in 6h)", summary.windows.last().map(|w| w.count).unwrap_or(0));
+    Json(summary)
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
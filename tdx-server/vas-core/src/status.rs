@@ -0,0 +1,172 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use crate::error_codes::{self, ErrorCode};
+use crate::preset_tdx::PresetTDXData;
+use crate::upstream_status::UpstreamState;
+use crate::AppState;
+
+const MAX_MESSAGES: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusLevel {
+    Info,
+    Degraded,
+    Maintenance,
+}
+
+/// An operator-set banner, optionally bounded to a time window (unix secs)
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusMessage {
+    pub id: String,
+    pub level: StatusLevel,
+    pub message: String,
+    pub starts_at: Option<u64>,
+    pub ends_at: Option<u64>,
+    pub created_at: u64,
+}
+
+impl StatusMessage {
+    fn is_expired(&self, now: u64) -> bool {
+        self.ends_at.is_some_and(|ends_at| ends_at <= now)
+    }
+
+    fn is_in_effect(&self, now: u64) -> bool {
+        !self.is_expired(now) && self.starts_at.is_none_or(|starts_at| starts_at <= now)
+    }
+}
+
+/// Operator status messages shown by GET /status
+#[derive(Debug, Default)]
+pub struct StatusBoard {
+    messages: Vec<StatusMessage>,
+}
+
+impl StatusBoard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Messages in effect or scheduled, dropping expired ones
+    fn current(&mut self, now: u64) -> Vec<StatusMessage> {
+        self.messages.retain(|m| !m.is_expired(now));
+        self.messages.clone()
+    }
+}
+
+/// GET /status - Overall state, operator messages and component health for frontends.
+/// `upstream` tells Hyperliquid downtime apart from problems of this service
+pub async fn get_status(State(state): State<AppState>) -> Json<Value> {
+    let now = now_secs();
+    let messages = state.status.write().await.current(now);
+
+    // A quote that doesn't bind the signing key attests nothing about it
+    let attested = PresetTDXData::get().is_some_and(|data| data.key_binding_error.is_none());
+    let fence = state.ha.check();
+    let probe_ok = state.config.signing_probe_interval_secs.is_none() || state.probe.read().await.healthy();
+    if let Err(e) = state.market.meta_and_asset_ctxs().await {
+        state.upstream.observe_error(&e.to_string());
+    }
+    let upstream = state.upstream.status();
+    let upstream_ok = upstream.state == UpstreamState::Operational;
+
+    let components = serde_json::json!({
+        "attestation": if attested { "operational" } else { "down" },
+        "signing": match (fence.is_ok(), probe_ok) {
+            (true, true) => "operational",
+            (true, false) => "degraded",
+            (false, _) => "standby",
+        },
+        "upstream": upstream.state,
+    });
+
+    let in_effect = |level: StatusLevel| messages.iter().any(|m| m.level == level && m.is_in_effect(now));
+    let overall = if in_effect(StatusLevel::Maintenance) || upstream.state == UpstreamState::Maintenance {
+        "maintenance"
+    } else if !(attested && probe_ok && upstream_ok) || in_effect(StatusLevel::Degraded) {
+        "degraded"
+    } else {
+        "operational"
+    };
+
+    Json(serde_json::json!({
+        "status": overall,
+        "messages": messages,
+        "components": components,
+        "upstream": upstream,
+        "timestamp": now
+    }))
+}
+
+/// Body of POST /admin/status
+#[derive(Debug, Deserialize)]
+pub struct StatusMessageRequest {
+    pub level: StatusLevel,
+    pub message: String,
+    pub starts_at: Option<u64>,
+    pub ends_at: Option<u64>,
+}
+
+/// POST /admin/status - Publish a status message (e.g. scheduled maintenance)
+pub async fn post_status_message(
+    State(state): State<AppState>,
+    Json(payload): Json<StatusMessageRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let now = now_secs();
+    if payload.message.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if let (Some(starts_at), Some(ends_at)) = (payload.starts_at, payload.ends_at) {
+        if ends_at <= starts_at {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    let mut board = state.status.write().await;
+    if board.current(now).len() >= MAX_MESSAGES {
+        return Ok(Json(error_codes::err_body(ErrorCode::LimitExceeded, format!("At most {} status messages", MAX_MESSAGES))));
+    }
+
+    let message = StatusMessage {
+        id: uuid::Uuid::new_v4().to_string(),
+        level: payload.level,
+        message: payload.message,
+        starts_at: payload.starts_at,
+        ends_at: payload.ends_at,
+        created_at: now,
+    };
+    board.messages.push(message.clone());
+
+    info!("📢 Status message {} published ({:?}): {}", message.id, message.level, message.message);
+    Ok(Json(serde_json::json!({"status": "ok", "response": message})))
+}
+
+/// DELETE /admin/status/:id - Withdraw a status message
+pub async fn delete_status_message(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let mut board = state.status.write().await;
+    let before = board.messages.len();
+    board.messages.retain(|m| m.id != id);
+    if board.messages.len() == before {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("📢 Status message {} withdrawn", id);
+    Ok(Json(serde_json::json!({"status": "ok", "response": "deleted"})))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -0,0 +1,309 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use tracing::info;
+
+use crate::auth;
+use crate::confirm::order_notional;
+use crate::error_codes::{self, ErrorCode};
+use crate::policy::PolicyViolation;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::AppState;
+
+/// Header naming the strategy an /exchange order belongs to; automated strategies send their id
+pub const STRATEGY_TAG_HEADER: &str = "X-Strategy-Tag";
+
+const MAX_TAGS: usize = 50;
+const MAX_TAG_LEN: usize = 64;
+const MINUTE_MS: u64 = 60 * 1000;
+const HOUR_MS: u64 = 60 * MINUTE_MS;
+
+/// Caps on what one strategy, or the whole account, may place
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubLimit {
+    /// Order notional placed in any rolling hour, in USD
+    pub max_notional_per_hour: Option<f64>,
+    /// Orders placed in any rolling minute
+    pub max_orders_per_minute: Option<u64>,
+}
+
+impl SubLimit {
+    fn is_empty(&self) -> bool {
+        self.max_notional_per_hour.is_none() && self.max_orders_per_minute.is_none()
+    }
+}
+
+/// A user's budget and its per-strategy shares.
+///
+/// Orders tagged with a configured strategy count against that strategy's caps and the total;
+/// untagged orders and unknown tags count against the total only.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserLimits {
+    pub total: SubLimit,
+    pub strategies: BTreeMap<String, SubLimit>,
+    pub updated_at: u64,
+}
+
+impl UserLimits {
+    fn validate(&self) -> Result<(), String> {
+        if self.strategies.len() > MAX_TAGS {
+            return Err(format!("At most {} strategy sub-limits", MAX_TAGS));
+        }
+        for (tag, limit) in std::iter::once(("total", &self.total)).chain(self.strategies.iter().map(|(t, l)| (t.as_str(), l))) {
+            if tag.is_empty() || tag.len() > MAX_TAG_LEN {
+                return Err(format!("Strategy tags must be 1 to {} characters", MAX_TAG_LEN));
+            }
+            if limit.max_notional_per_hour.is_some_and(|n| !(n.is_finite() && n > 0.0)) || limit.max_orders_per_minute == Some(0) {
+                return Err(format!("Limits for '{}' must be positive", tag));
+            }
+        }
+
+        // Sub-limits carve up the total; they can't add up to more than it
+        if let Some(total) = self.total.max_notional_per_hour {
+            let carved: f64 = self.strategies.values().filter_map(|l| l.max_notional_per_hour).sum();
+            if carved > total {
+                return Err(format!("Strategy notional caps add up to {:.2} USD, above the total of {:.2} USD", carved, total));
+            }
+        }
+        if let Some(total) = self.total.max_orders_per_minute {
+            let carved: u64 = self.strategies.values().filter_map(|l| l.max_orders_per_minute).sum();
+            if carved > total {
+                return Err(format!("Strategy order rates add up to {}/min, above the total of {}/min", carved, total));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An order action counted against a user's limits
+#[derive(Debug, Clone)]
+struct Usage {
+    at_ms: u64,
+    tag: Option<String>,
+    orders: u64,
+    notional: f64,
+}
+
+/// Per-user strategy sub-limits and the last hour of usage
+#[derive(Debug, Default)]
+pub struct StrategyLimits {
+    limits: HashMap<String, UserLimits>,
+    usage: HashMap<String, Vec<Usage>>,
+}
+
+impl StrategyLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Orders and notional placed in the window, for one tag or (None) the whole account
+    fn used(&self, user_address: &str, tag: Option<&str>, now_ms: u64) -> (u64, f64) {
+        let entries = self.usage.get(user_address).into_iter().flatten()
+            .filter(|u| tag.is_none() || u.tag.as_deref() == tag);
+        let mut orders = 0;
+        let mut notional = 0.0;
+        for usage in entries {
+            if usage.at_ms + MINUTE_MS > now_ms {
+                orders += usage.orders;
+            }
+            if usage.at_ms + HOUR_MS > now_ms {
+                notional += usage.notional;
+            }
+        }
+        (orders, notional)
+    }
+
+    fn check_limit(&self, user_address: &str, tag: Option<&str>, limit: &SubLimit, orders: u64, notional: f64, now_ms: u64) -> Result<(), PolicyViolation> {
+        let scope = tag.unwrap_or("total");
+        let (used_orders, used_notional) = self.used(user_address, tag, now_ms);
+        if let Some(max) = limit.max_orders_per_minute {
+            if used_orders + orders > max {
+                return Err(PolicyViolation {
+                    code: ErrorCode::PolicyStrategyLimitExceeded,
+                    message: format!("Order rate limit of {}/min for '{}' reached", max, scope),
+                    details: serde_json::json!({"strategy": scope, "max_orders_per_minute": max, "used": used_orders, "requested": orders}),
+                });
+            }
+        }
+        if let Some(max) = limit.max_notional_per_hour {
+            if used_notional + notional > max {
+                return Err(PolicyViolation {
+                    code: ErrorCode::PolicyStrategyLimitExceeded,
+                    message: format!("Hourly notional limit of {:.2} USD for '{}' would be exceeded", max, scope),
+                    details: serde_json::json!({"strategy": scope, "max_notional_per_hour": max, "used": used_notional, "requested": notional}),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check an order action against the tag's sub-limit and the user's total, without counting it
+    pub fn check(&self, user_address: &str, tag: Option<&str>, action: &Value, now_ms: u64) -> Result<(), PolicyViolation> {
+        let Some(limits) = self.limits.get(user_address) else {
+            return Ok(());
+        };
+        let orders = action.get("orders").and_then(|o| o.as_array()).map_or(0, |o| o.len()) as u64;
+        let notional = order_notional(action);
+
+        self.check_limit(user_address, None, &limits.total, orders, notional, now_ms)?;
+        if let Some((tag, limit)) = tag.and_then(|t| limits.strategies.get_key_value(t)) {
+            self.check_limit(user_address, Some(tag), limit, orders, notional, now_ms)?;
+        }
+        Ok(())
+    }
+
+    /// Check an order action and, if it fits, count it against the user's limits
+    pub fn charge(&mut self, user_address: &str, tag: Option<&str>, action: &Value, now_ms: u64) -> Result<(), PolicyViolation> {
+        self.check(user_address, tag, action, now_ms)?;
+        let Some(limits) = self.limits.get(user_address) else {
+            return Ok(());
+        };
+
+        let tag = tag.filter(|t| limits.strategies.contains_key(*t)).map(str::to_string);
+        let usage = self.usage.entry(user_address.to_string()).or_default();
+        usage.retain(|u| u.at_ms + HOUR_MS > now_ms);
+        usage.push(Usage {
+            at_ms: now_ms,
+            tag,
+            orders: action.get("orders").and_then(|o| o.as_array()).map_or(0, |o| o.len()) as u64,
+            notional: order_notional(action),
+        });
+        Ok(())
+    }
+
+    /// Charge an order action to the strategy its request names in [`STRATEGY_TAG_HEADER`].
+    /// Every signing path (/exchange, /exchange/raw, escrow) counts orders through here.
+    pub fn charge_request(&mut self, headers: &HeaderMap, user_address: &str, action: &Value, now_ms: u64) -> Result<(), PolicyViolation> {
+        let tag = headers.get(STRATEGY_TAG_HEADER).and_then(|v| v.to_str().ok());
+        self.charge(&user_address.to_lowercase(), tag, action, now_ms)
+    }
+
+    fn usage_report(&self, user_address: &str, limits: &UserLimits, now_ms: u64) -> Value {
+        let report = |tag: Option<&str>| {
+            let (orders, notional) = self.used(user_address, tag, now_ms);
+            serde_json::json!({"orders_last_minute": orders, "notional_last_hour": notional})
+        };
+        let strategies: serde_json::Map<String, Value> = limits.strategies.keys()
+            .map(|tag| (tag.clone(), report(Some(tag))))
+            .collect();
+        serde_json::json!({"total": report(None), "strategies": strategies})
+    }
+
+    pub fn limits_for(&self, user_address: &str) -> Option<UserLimits> {
+        self.limits.get(&user_address.to_lowercase()).cloned()
+    }
+
+    /// Drop everything held for a user, for data deletion; returns how many records went
+    pub fn remove_user(&mut self, user_address: &str) -> usize {
+        let user_address = user_address.to_lowercase();
+        let usage = self.usage.remove(&user_address).map_or(0, |u| u.len());
+        usage + usize::from(self.limits.remove(&user_address).is_some())
+    }
+}
+
+/// Trading session behind the request; share tokens can't read or change limits
+async fn session_user(state: &AppState, headers: &HeaderMap) -> Result<String, StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok(user_address.to_lowercase())
+}
+
+/// GET /me/strategy-limits - The caller's sub-limits and what each strategy used recently
+pub async fn get_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_address = session_user(&state, &headers).await?;
+    let book = state.strategy_limits.read().await;
+    let limits = book.limits.get(&user_address).cloned().unwrap_or_default();
+    let usage = book.usage_report(&user_address, &limits, now_ms());
+
+    Ok(Json(serde_json::json!({"status": "ok", "response": {"limits": limits, "usage": usage}})))
+}
+
+/// PUT /me/strategy-limits - Replace the caller's total and per-strategy limits (an empty body clears them)
+pub async fn set_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(mut payload): Json<UserLimits>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_address = session_user(&state, &headers).await?;
+    payload.strategies.retain(|_, limit| !limit.is_empty());
+    if let Err(e) = payload.validate() {
+        return Ok(Json(error_codes::err_body(ErrorCode::BadRequest, e)));
+    }
+
+    payload.updated_at = now_ms() / 1000;
+    let mut book = state.strategy_limits.write().await;
+    if payload.total.is_empty() && payload.strategies.is_empty() {
+        book.limits.remove(&user_address);
+        book.usage.remove(&user_address);
+    } else {
+        book.limits.insert(user_address.clone(), payload.clone());
+    }
+
+    info!("🧮 Strategy limits for {} set: total {:?}, {} strategies", user_address, payload.total, payload.strategies.len());
+    Ok(Json(serde_json::json!({"status": "ok", "response": payload})))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const USER: &str = "0x1111111111111111111111111111111111111111";
+
+    fn order(px: &str, sz: &str) -> Value {
+        json!({"type": "order", "orders": [{"a": 0, "b": true, "p": px, "s": sz, "r": false, "t": {"limit": {"tif": "Gtc"}}}], "grouping": "na"})
+    }
+
+    fn tagged(tag: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(STRATEGY_TAG_HEADER, tag.parse().unwrap());
+        headers
+    }
+
+    fn book() -> StrategyLimits {
+        let mut book = StrategyLimits::new();
+        let mut strategies = BTreeMap::new();
+        strategies.insert("grid".to_string(), SubLimit { max_notional_per_hour: Some(1000.0), max_orders_per_minute: Some(2) });
+        book.limits.insert(USER.to_string(), UserLimits { total: SubLimit::default(), strategies, updated_at: 0 });
+        book
+    }
+
+    #[test]
+    fn test_tagged_order_over_its_limit_is_rejected() {
+        let mut book = book();
+        let now = 1_700_000_000_000;
+
+        book.charge_request(&tagged("grid"), USER, &order("100", "5"), now).unwrap();
+        let violation = book.charge_request(&tagged("grid"), USER, &order("100", "6"), now).unwrap_err();
+        assert_eq!(violation.code, ErrorCode::PolicyStrategyLimitExceeded);
+        assert!(violation.message.contains("'grid'"), "{}", violation.message);
+
+        // The refused order wasn't counted; the rate cap still has room for a small one
+        book.charge_request(&tagged("grid"), USER, &order("100", "1"), now).unwrap();
+        let violation = book.charge_request(&tagged("grid"), USER, &order("1", "1"), now).unwrap_err();
+        assert!(violation.message.contains("2/min"), "{}", violation.message);
+
+        // Untagged orders only count against the (unlimited) total
+        book.charge_request(&HeaderMap::new(), USER, &order("100", "50"), now).unwrap();
+    }
+}
@@ -0,0 +1,294 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use secp256k1::SecretKey;
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::address;
+use crate::auth;
+use crate::dca::format_px;
+use crate::error_codes::{self, ErrorCode};
+use crate::market::parse_number;
+use crate::onboarding;
+use crate::preset_tdx::PresetTDXData;
+use crate::proxy::HyperliquidProxy;
+use crate::share::SHARE_TOKEN_PREFIX;
+use crate::signer::ActionRequest;
+use crate::universal_signing::{build_exchange_payload, prepare_action, sign_exchange_request};
+use crate::AppState;
+
+/// Testnet faucet (requires a mainnet deposit on the same address)
+const FAUCET_URL: &str = "https://app.hyperliquid-testnet.xyz/drip";
+/// Asset used for the sample order (BTC perp)
+const SAMPLE_ASSET: u64 = 0;
+/// Hyperliquid's minimum order value is $10; stay a little above it
+const SAMPLE_NOTIONAL_USD: f64 = 12.0;
+
+/// Body of POST /agents/test-drive
+#[derive(Debug, Default, Deserialize)]
+pub struct TestDriveRequest {
+    /// Rest a far-from-market post-only order and cancel it once the agent is approved and funded
+    #[serde(default)]
+    pub place_sample_order: bool,
+}
+
+/// Testnet-only agent key, derived from the enclave agent key so it never exists outside it
+/// and the mainnet agent is never used on testnet
+fn testnet_agent(preset_data: &PresetTDXData) -> Result<(SecretKey, String), Box<dyn std::error::Error + Send + Sync>> {
+    let seed: [u8; 32] = Sha256::new()
+        .chain_update(b"vas-testnet-agent")
+        .chain_update(preset_data.agent_private_key.secret_bytes())
+        .finalize()
+        .into();
+    let secret_key = SecretKey::from_slice(&seed)?;
+    let address = address::secret_key_to_address(&secret_key);
+    Ok((secret_key, address))
+}
+
+fn step(name: &str, done: bool, detail: Value) -> Value {
+    serde_json::json!({"step": name, "done": done, "detail": detail})
+}
+
+/// POST /agents/test-drive - Walk the caller's onboarding on testnet before approving a mainnet agent.
+///
+/// Reports each step (testnet agent approval, funding, sample order) with what to do next.
+/// The approveAgent action is returned unsigned: the user signs it with their own wallet for
+/// the Testnet chain and submits it to testnet directly, so this server never relays it.
+pub async fn test_drive(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Option<Json<TestDriveRequest>>,
+) -> Result<Json<Value>, StatusCode> {
+    let (_, user_address) = session(&state, &headers).await?;
+    let Some(testnet_url) = state.config.testnet_url.as_deref() else {
+        return Ok(Json(error_codes::err_body(ErrorCode::ServiceUnavailable, "Test drive is disabled on this deployment")));
+    };
+    let Json(request) = payload.unwrap_or_default();
+
+    let preset_data = PresetTDXData::get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let (agent_key, agent_address) = testnet_agent(preset_data).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let testnet = HyperliquidProxy::new(testnet_url);
+    let walk = Walk {
+        testnet: &testnet,
+        user_address: &user_address,
+        agent_address: &agent_address,
+        agent_name: "vas-test-drive",
+        signer: SampleSigner::Derived(&agent_key),
+    };
+    let (steps, ready) = walk.run(request.place_sample_order).await?;
+    info!("🧪 Test drive for {}: ready={}", user_address, ready);
+
+    Ok(Json(serde_json::json!({
+        "network": "testnet",
+        "testnet_url": testnet_url,
+        "steps": steps,
+        "ready_for_mainnet": ready
+    })))
+}
+
+/// POST /testnet/setup - Walk a session on a testnet deployment through agent approval, funding
+/// and a verification order, with the session's own enclave agent.
+///
+/// Mainnet deployments answer with a pointer to /agents/test-drive instead.
+pub async fn testnet_setup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Option<Json<TestDriveRequest>>,
+) -> Result<Json<Value>, StatusCode> {
+    let (api_key, user_address) = session(&state, &headers).await?;
+    if state.config.is_mainnet() {
+        return Ok(Json(error_codes::err_body(
+            ErrorCode::BadRequest,
+            "This deployment trades on mainnet; rehearse on testnet with POST /agents/test-drive",
+        )));
+    }
+    let Json(request) = payload.unwrap_or_default();
+
+    let agent_address = match state.session_manager.get_session(&api_key) {
+        Some(session) => session.agent_address,
+        None => PresetTDXData::get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?.agent_address.clone(),
+    };
+    let walk = Walk {
+        testnet: &state.proxy,
+        user_address: &user_address,
+        agent_address: &agent_address,
+        agent_name: "vas-agent",
+        signer: SampleSigner::Enclave(&state),
+    };
+    let (steps, ready) = walk.run(request.place_sample_order).await?;
+
+    // An approval made outside /exchange still has to move the session's onboarding along
+    let onboarding = onboarding::refresh(&state, &api_key).await;
+    info!("🧪 Testnet setup for {}: ready={}, onboarding={:?}", user_address, ready, onboarding);
+
+    Ok(Json(serde_json::json!({
+        "network": "testnet",
+        "steps": steps,
+        "onboarding": onboarding,
+        "ready": ready
+    })))
+}
+
+/// Trading session behind the request; share tokens can't rehearse orders
+async fn session(state: &AppState, headers: &HeaderMap) -> Result<(String, String), StatusCode> {
+    let api_key = auth::api_key_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if api_key.starts_with(SHARE_TOKEN_PREFIX) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let user_address = auth::user_address_for_api_key(state, api_key).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok((api_key.to_string(), user_address))
+}
+
+/// Who signs the sample order
+enum SampleSigner<'a> {
+    /// The derived testnet agent, signing locally (test drive from a mainnet deployment)
+    Derived(&'a SecretKey),
+    /// The enclave agent through the audited signer (testnet deployments)
+    Enclave(&'a AppState),
+}
+
+/// Onboarding steps against a testnet API for one user and agent
+struct Walk<'a> {
+    testnet: &'a HyperliquidProxy,
+    user_address: &'a str,
+    agent_address: &'a str,
+    agent_name: &'a str,
+    signer: SampleSigner<'a>,
+}
+
+impl Walk<'_> {
+    /// Check each step in turn; returns the step reports and whether all are done
+    async fn run(&self, place_sample_order: bool) -> Result<(Vec<Value>, bool), StatusCode> {
+        let upstream_err = |e: Box<dyn std::error::Error + Send + Sync>| {
+            warn!("⚠️ Testnet lookup failed: {}", e);
+            StatusCode::BAD_GATEWAY
+        };
+
+        // 1. The agent must be approved by the user's wallet on testnet
+        let agents = self.testnet
+            .proxy_info_request(&serde_json::json!({"type": "extraAgents", "user": self.user_address}))
+            .await
+            .map_err(upstream_err)?;
+        let approved = agents.as_array().is_some_and(|agents| agents.iter().any(|a| {
+            a.get("address").and_then(|addr| addr.as_str()).is_some_and(|addr| addr.eq_ignore_ascii_case(self.agent_address))
+        }));
+        let approve_detail = if approved {
+            serde_json::json!({"agent_address": self.agent_address})
+        } else {
+            serde_json::json!({
+                "agent_address": self.agent_address,
+                "next_step": "Sign this approveAgent action with your wallet and submit it to the testnet /exchange",
+                "action": {
+                    "type": "approveAgent",
+                    "hyperliquidChain": "Testnet",
+                    "signatureChainId": "0x66eee",
+                    "agentAddress": self.agent_address,
+                    "agentName": self.agent_name,
+                    "nonce": now_ms()
+                }
+            })
+        };
+
+        // 2. The account needs testnet USDC to place the sample order
+        let summary = self.testnet
+            .proxy_info_request(&serde_json::json!({"type": "clearinghouseState", "user": self.user_address}))
+            .await
+            .map_err(upstream_err)?;
+        let account_value = parse_number(summary.pointer("/marginSummary/accountValue")).unwrap_or(0.0);
+        let funded = account_value >= SAMPLE_NOTIONAL_USD;
+        let fund_detail = if funded {
+            serde_json::json!({"account_value": account_value})
+        } else {
+            serde_json::json!({
+                "account_value": account_value,
+                "next_step": "Claim testnet USDC from the faucet",
+                "faucet_url": FAUCET_URL
+            })
+        };
+
+        // 3. Sample order: rests far below the market (post-only) and is cancelled right away
+        let sample = if !place_sample_order {
+            step("sample_order", false, serde_json::json!({"next_step": "Call again with place_sample_order: true"}))
+        } else if !(approved && funded) {
+            step("sample_order", false, serde_json::json!({"next_step": "Approve the testnet agent and fund the account first"}))
+        } else {
+            match self.place_sample_order().await {
+                Ok(detail) => step("sample_order", true, detail),
+                Err(e) => step("sample_order", false, serde_json::json!({"error": e.to_string()})),
+            }
+        };
+
+        let steps = vec![
+            step("approve_agent", approved, approve_detail),
+            step("fund_account", funded, fund_detail),
+            sample,
+        ];
+        let ready = steps.iter().all(|s| s.get("done").and_then(|d| d.as_bool()) == Some(true));
+        Ok((steps, ready))
+    }
+
+    /// Rest a post-only buy at half the mark price, then cancel it
+    async fn place_sample_order(&self) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let meta = self.testnet.proxy_info_request(&serde_json::json!({"type": "metaAndAssetCtxs"})).await?;
+        let sz_decimals = meta.pointer(&format!("/0/universe/{}/szDecimals", SAMPLE_ASSET))
+            .and_then(|d| d.as_u64())
+            .ok_or("Testnet meta has no sample asset")? as u32;
+        let mark_px = parse_number(meta.pointer(&format!("/1/{}/markPx", SAMPLE_ASSET))).ok_or("Testnet mark price unavailable")?;
+
+        let px = mark_px * 0.5;
+        let size_scale = 10f64.powi(sz_decimals as i32);
+        let size = (SAMPLE_NOTIONAL_USD / px * size_scale).ceil() / size_scale;
+        let order = serde_json::json!({"type": "order", "orders": [{
+            "a": SAMPLE_ASSET,
+            "b": true,
+            "p": format_px(px, sz_decimals),
+            "s": format!("{:.*}", sz_decimals as usize, size),
+            "r": false,
+            "t": {"limit": {"tif": "Alo"}}
+        }], "grouping": "na"});
+
+        let placed = self.submit(&order).await?;
+        let oid = placed.pointer("/response/data/statuses/0/resting/oid")
+            .and_then(|o| o.as_u64())
+            .ok_or_else(|| format!("Sample order did not rest: {}", placed))?;
+
+        let cancel = serde_json::json!({"type": "cancel", "cancels": [{"a": SAMPLE_ASSET, "o": oid}]});
+        let cancelled = self.submit(&cancel).await?;
+
+        info!("🧪 Sample order {} placed and cancelled for {}", oid, self.user_address);
+        Ok(serde_json::json!({"oid": oid, "order": placed, "cancel": cancelled}))
+    }
+
+    /// Sign with the walk's agent and submit to testnet
+    async fn submit(&self, action: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let nonce = now_ms();
+        match self.signer {
+            SampleSigner::Derived(agent_key) => {
+                let action = prepare_action(action)?;
+                let signature = sign_exchange_request(&action, nonce, agent_key, None, false)?;
+                self.testnet.proxy_exchange_request(&build_exchange_payload(&action, nonce, None, &signature)).await
+            }
+            SampleSigner::Enclave(state) => {
+                state.signer.sign_action(ActionRequest {
+                    action: action.clone(),
+                    nonce,
+                    vault_address: None,
+                    is_mainnet: false,
+                    user_address: Some(self.user_address.to_string()),
+                }).await
+            }
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,121 @@
+use base64::Engine;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::warn;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// SHA-256 of a certificate's SubjectPublicKeyInfo, accepted for upstream TLS
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsPin {
+    pub spki_sha256: [u8; 32],
+    /// Unix seconds after which a retiring pin stops being accepted
+    pub retire_at: Option<u64>,
+}
+
+impl TlsPin {
+    /// Parse `sha256/<base64>`, optionally suffixed `@<unix secs>` to give a rotated-out pin a grace period
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (pin, retire_at) = match spec.trim().split_once('@') {
+            Some((pin, at)) => (pin, Some(at.parse::<u64>().map_err(|_| format!("Invalid pin retirement time in '{}'", spec))?)),
+            None => (spec.trim(), None),
+        };
+        let encoded = pin.strip_prefix("sha256/").ok_or_else(|| format!("Pin '{}' must start with sha256/", spec))?;
+        let spki_sha256 = base64::engine::general_purpose::STANDARD.decode(encoded)
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| format!("Pin '{}' is not a base64 SHA-256 digest", spec))?;
+        Ok(Self { spki_sha256, retire_at })
+    }
+
+    fn active(&self, now_secs: u64) -> bool {
+        self.retire_at.is_none_or(|at| now_secs < at)
+    }
+}
+
+/// Normal WebPKI validation, then require some certificate in the chain to match an active pin.
+///
+/// Pinning intermediates as well as the leaf lets operators pin the issuing CA's key and
+/// survive routine leaf renewals.
+#[derive(Debug)]
+struct PinningVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    pins: Vec<TlsPin>,
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)?;
+
+        let chain: Vec<[u8; 32]> = std::iter::once(end_entity).chain(intermediates)
+            .filter_map(|cert| spki_sha256(cert))
+            .collect();
+        let matched = self.pins.iter()
+            .filter(|pin| pin.active(now.as_secs()))
+            .find(|pin| chain.contains(&pin.spki_sha256));
+
+        match matched {
+            Some(pin) => {
+                if let Some(at) = pin.retire_at {
+                    warn!("⚠️ {:?} matched only a retiring TLS pin (accepted until {}); update UPSTREAM_TLS_PINS", server_name, at);
+                }
+                Ok(verified)
+            }
+            None => {
+                warn!("🚨 TLS pin mismatch for {:?}; refusing the connection", server_name);
+                Err(rustls::Error::General("Upstream certificate does not match any configured pin".to_string()))
+            }
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+fn spki_sha256(cert: &CertificateDer<'_>) -> Option<[u8; 32]> {
+    let (_, parsed) = X509Certificate::from_der(cert.as_ref()).ok()?;
+    Some(Sha256::digest(parsed.public_key().raw).into())
+}
+
+/// TLS config for reqwest that only completes handshakes with pinned upstream keys
+pub fn client_config(pins: &[TlsPin]) -> Result<ClientConfig, Box<dyn std::error::Error + Send + Sync>> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let roots = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+    let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone()).build()?;
+
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(PinningVerifier { inner, pins: pins.to_vec() }))
+        .with_no_client_auth();
+    Ok(config)
+}
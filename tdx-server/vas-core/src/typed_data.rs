@@ -0,0 +1,134 @@
+use alloy::dyn_abi::TypedData;
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{info, warn, error};
+
+use crate::agents::SCOPE_TYPED_DATA;
+use crate::audit::AUDIT_TYPED_DATA;
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::preset_tdx::PresetTDXData;
+use crate::AppState;
+
+/// Hyperliquid's own signing domains; these only go through /exchange and its policy checks
+const RESERVED_DOMAINS: [&str; 2] = ["Exchange", "HyperliquidSignTransaction"];
+
+/// One allowlisted (domain, primary type) combination.
+///
+/// Parsed from `name:chainId:verifyingContract:PrimaryType`, where any field may be `*`.
+#[derive(Debug, Clone)]
+struct TypedDataRule {
+    name: Option<String>,
+    chain_id: Option<u64>,
+    verifying_contract: Option<String>,
+    primary_type: Option<String>,
+}
+
+impl TypedDataRule {
+    fn parse(entry: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = entry.split(':').collect();
+        if fields.len() != 4 {
+            return Err(format!("Invalid typed-data allowlist entry (want name:chainId:contract:PrimaryType): {}", entry));
+        }
+        let wildcard = |f: &str| if f == "*" { None } else { Some(f.to_string()) };
+
+        Ok(Self {
+            name: wildcard(fields[0]),
+            chain_id: match fields[1] {
+                "*" => None,
+                id => Some(id.parse().map_err(|_| format!("Invalid chain id in typed-data allowlist: {}", id))?),
+            },
+            verifying_contract: wildcard(fields[2]).map(|c| c.to_lowercase()),
+            primary_type: wildcard(fields[3]),
+        })
+    }
+
+    fn matches(&self, typed_data: &TypedData) -> bool {
+        let domain = &typed_data.domain;
+        let name = domain.name.as_deref();
+        let chain_id = domain.chain_id.and_then(|id| u64::try_from(id).ok());
+        let contract = domain.verifying_contract.map(|c| format!("{:?}", c).to_lowercase());
+
+        self.name.as_deref().is_none_or(|n| Some(n) == name)
+            && self.chain_id.is_none_or(|id| Some(id) == chain_id)
+            && self.verifying_contract.as_ref().is_none_or(|c| Some(c) == contract.as_ref())
+            && self.primary_type.as_ref().is_none_or(|t| *t == typed_data.primary_type)
+    }
+}
+
+/// Check typed data against the configured allowlist
+fn check_policy(allowlist: &[String], typed_data: &TypedData) -> Result<(), String> {
+    if let Some(name) = typed_data.domain.name.as_deref() {
+        if RESERVED_DOMAINS.contains(&name) {
+            return Err(format!("Domain '{}' is reserved for /exchange", name));
+        }
+    }
+
+    for entry in allowlist {
+        if TypedDataRule::parse(entry)?.matches(typed_data) {
+            return Ok(());
+        }
+    }
+
+    Err(format!(
+        "Typed data (domain {:?}, primary type {}) is not in the allowlist",
+        typed_data.domain.name, typed_data.primary_type
+    ))
+}
+
+/// EIP-712 payload in the standard `eth_signTypedData_v4` shape
+#[derive(Debug, Deserialize)]
+pub struct TypedDataRequest {
+    #[serde(rename = "typedData")]
+    pub typed_data: TypedData,
+}
+
+/// POST /sign/typed-data - Sign an allowlisted EIP-712 payload with the agent key
+pub async fn sign_typed_data(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<TypedDataRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let api_key = auth::api_key_from_headers(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    if !auth::api_key_has_scope(&state, api_key, SCOPE_TYPED_DATA).await {
+        return Ok(Json(error_codes::err_body(ErrorCode::ScopeNotAllowed, format!("API key is not authorized for the '{}' scope", SCOPE_TYPED_DATA))));
+    }
+
+    let typed_data = payload.typed_data;
+    if let Err(reason) = check_policy(&state.config.typed_data_allowlist, &typed_data) {
+        warn!("❌ Typed-data signing rejected: {}", reason);
+        return Ok(Json(error_codes::err_body(ErrorCode::TypedDataNotAllowed, reason)));
+    }
+
+    let hash = typed_data.eip712_signing_hash().map_err(|e| {
+        warn!("❌ Invalid typed data: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    if let Err(reason) = state.safe_mode.check_digest(AUDIT_TYPED_DATA) {
+        return Ok(Json(error_codes::err_body(ErrorCode::SafeModeRestricted, reason)));
+    }
+
+    let user_address = auth::user_address_for_api_key(&state, api_key).await;
+    let subject = serde_json::to_value(&typed_data).unwrap_or(Value::Null);
+    let signature = state.signer.sign_digest(hash, user_address, AUDIT_TYPED_DATA, subject).await.map_err(|e| {
+        error!("❌ Typed-data signing failed: {:?}", e);
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    info!("✍️ Signed typed data {} for domain {:?}", typed_data.primary_type, typed_data.domain.name);
+
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "response": {
+            "hash": format!("{:?}", hash),
+            "signature": signature.to_json(),
+            "signer": PresetTDXData::get().map(|p| p.agent_address.clone())
+        }
+    })))
+}
@@ -0,0 +1,246 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::error_codes::{self, ErrorCode};
+use crate::events;
+use crate::notify::{Notification, NotificationKind};
+use crate::signer::ActionRequest;
+use crate::AppState;
+
+/// Most cancels held while Hyperliquid is unavailable; later ones are refused
+const MAX_QUEUED_CANCELS: usize = 1000;
+/// Longest upstream error kept for /status
+const MAX_ERROR_LEN: usize = 256;
+
+/// Action types held for submission while the venue is unavailable
+const QUEUEABLE_ACTIONS: &[&str] = &["cancel", "cancelByCloid"];
+
+/// What the last upstream call said about Hyperliquid itself
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpstreamState {
+    #[default]
+    Operational,
+    /// Hyperliquid answered that it is in maintenance
+    Maintenance,
+    /// Hyperliquid could not be reached, or its gateway returned 502/503/504
+    Down,
+}
+
+impl UpstreamState {
+    /// Venue state an upstream error message implies; None for ordinary rejections,
+    /// which show the venue is up
+    pub fn from_error(message: &str) -> Option<Self> {
+        let message = message.to_lowercase();
+        if message.contains("maintenance") {
+            Some(Self::Maintenance)
+        } else if ["502", "503", "504", "bad gateway", "service unavailable", "gateway timeout",
+            "error sending request", "connection refused", "connection reset", "timed out", "dns error"]
+            .iter()
+            .any(|marker| message.contains(marker))
+        {
+            Some(Self::Down)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Operational => "operational",
+            Self::Maintenance => "maintenance",
+            Self::Down => "down",
+        }
+    }
+}
+
+/// Detected Hyperliquid availability, as shown by GET /status
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UpstreamStatus {
+    pub state: UpstreamState,
+    /// When the current outage was first seen (unix ms)
+    pub since_ms: Option<u64>,
+    /// The error that last showed the outage, truncated
+    pub last_error: Option<String>,
+    pub last_ok_ms: Option<u64>,
+    /// Cancels waiting for the venue to come back
+    pub queued_cancels: usize,
+}
+
+/// A cancel that passed every check and waits for Hyperliquid to return
+#[derive(Debug)]
+struct QueuedCancel {
+    id: String,
+    request: ActionRequest,
+    queued_at_ms: u64,
+}
+
+/// Hyperliquid availability, inferred from the errors of upstream calls, and the cancels
+/// held until it returns.
+///
+/// Clients see venue downtime as `upstream: maintenance|down` in /status rather than as
+/// failures of this service. Shared by handlers and the recheck worker, so it uses std locks
+/// that are never held across an await.
+#[derive(Debug, Default)]
+pub struct UpstreamMonitor {
+    status: RwLock<UpstreamStatus>,
+    queue: Mutex<VecDeque<QueuedCancel>>,
+}
+
+impl UpstreamMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> UpstreamStatus {
+        let mut status = self.status.read().unwrap().clone();
+        status.queued_cancels = self.queue.lock().unwrap().len();
+        status
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.status.read().unwrap().state == UpstreamState::Operational
+    }
+
+    /// Record an upstream error; returns the outage it shows, if any
+    pub fn observe_error(&self, message: &str) -> Option<UpstreamState> {
+        let state = UpstreamState::from_error(message)?;
+        let mut status = self.status.write().unwrap();
+        if status.state == UpstreamState::Operational {
+            warn!("🚧 Hyperliquid looks {}: {}", state.as_str(), message);
+            status.since_ms = Some(now_ms());
+        }
+        status.state = state;
+        status.last_error = Some(message.chars().take(MAX_ERROR_LEN).collect());
+        Some(state)
+    }
+
+    /// Record a call Hyperliquid answered; returns true when this ended an outage
+    pub fn observe_ok(&self) -> bool {
+        let mut status = self.status.write().unwrap();
+        let recovered = status.state != UpstreamState::Operational;
+        status.state = UpstreamState::Operational;
+        status.since_ms = None;
+        status.last_error = None;
+        status.last_ok_ms = Some(now_ms());
+        recovered
+    }
+
+    /// Hold a checked cancel until the venue is back; returns its queue id
+    pub fn enqueue(&self, request: ActionRequest) -> Result<String, String> {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.len() >= MAX_QUEUED_CANCELS {
+            return Err(format!("At most {} cancels are held while Hyperliquid is unavailable", MAX_QUEUED_CANCELS));
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        queue.push_back(QueuedCancel { id: id.clone(), request, queued_at_ms: now_ms() });
+        Ok(id)
+    }
+
+    fn take_next(&self) -> Option<QueuedCancel> {
+        self.queue.lock().unwrap().pop_front()
+    }
+
+    fn put_back(&self, cancel: QueuedCancel) {
+        self.queue.lock().unwrap().push_front(cancel);
+    }
+}
+
+/// Whether an action is held, rather than refused, while the venue is unavailable
+pub fn is_queueable(action: &Value) -> bool {
+    action.get("type").and_then(|t| t.as_str()).is_some_and(|t| QUEUEABLE_ACTIONS.contains(&t))
+}
+
+/// Response for a cancel held until Hyperliquid returns
+pub fn queued_response(id: &str, status: &UpstreamStatus) -> Value {
+    serde_json::json!({
+        "status": "queued",
+        "response": {
+            "queue_id": id,
+            "position": status.queued_cancels,
+            "upstream": status.state,
+            "note": "Hyperliquid is unavailable; the cancel is submitted as soon as it returns"
+        }
+    })
+}
+
+/// While Hyperliquid is unavailable, poll it and submit held cancels the moment it answers
+pub fn spawn_watcher(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(state.config.upstream_recheck_secs.max(1)));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            if state.upstream.is_available() {
+                continue;
+            }
+
+            match state.proxy.proxy_info_request(&serde_json::json!({"type": "meta"})).await {
+                Ok(_) => {
+                    if state.upstream.observe_ok() {
+                        info!("✅ Hyperliquid is back");
+                        state.notifier.notify(Notification::new(
+                            NotificationKind::Alert,
+                            None,
+                            "Hyperliquid is reachable again",
+                            serde_json::json!({"queued_cancels": state.upstream.status().queued_cancels}),
+                        ));
+                    }
+                    drain(&state).await;
+                }
+                Err(e) => {
+                    // Anything other than an outage means the venue answered
+                    if state.upstream.observe_error(&e.to_string()).is_none() && state.upstream.observe_ok() {
+                        drain(&state).await;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Submit held cancels in arrival order, stopping if the venue goes away again
+async fn drain(state: &AppState) {
+    while let Some(mut cancel) = state.upstream.take_next() {
+        // The original nonce may have aged out during a long outage
+        cancel.request.nonce = now_ms().max(cancel.request.nonce + 1);
+        let user_address = cancel.request.user_address.clone();
+
+        match state.signer.sign_action(cancel.request.clone()).await {
+            Ok(response) => {
+                info!("📤 Submitted queued cancel {} (held {}ms)", cancel.id, now_ms().saturating_sub(cancel.queued_at_ms));
+                if let Some(user_address) = user_address {
+                    let mut event = response;
+                    if let Some(obj) = event.as_object_mut() {
+                        obj.insert("queue_id".to_string(), Value::String(cancel.id.clone()));
+                    }
+                    state.event_store.write().await.append(&user_address, events::EVENT_EXCHANGE_RESPONSE, event);
+                }
+            }
+            Err(e) => {
+                if state.upstream.observe_error(&e.to_string()).is_some() {
+                    warn!("🚧 Hyperliquid unavailable again; {} stays queued", cancel.id);
+                    state.upstream.put_back(cancel);
+                    return;
+                }
+                error!("❌ Queued cancel {} failed: {}", cancel.id, e);
+                if let Some(user_address) = user_address {
+                    let mut event = error_codes::err_body(ErrorCode::for_upstream(&e.to_string()), e.to_string());
+                    event["queue_id"] = Value::String(cancel.id.clone());
+                    state.event_store.write().await.append(&user_address, events::EVENT_EXCHANGE_RESPONSE, event);
+                }
+            }
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
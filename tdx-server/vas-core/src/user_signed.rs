@@ -0,0 +1,80 @@
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::auth;
+use crate::error_codes::{self, ErrorCode};
+use crate::universal_signing::{approve_agent_digest, signature_chain_id, ExchangeSignature, SignatureChain};
+use crate::AppState;
+
+/// Left-pad an r/s component; wallets and SDKs often drop leading zero bytes
+fn signature_component(value: &Value) -> Option<String> {
+    let hex = value.as_str()?.trim_start_matches("0x");
+    (hex.len() <= 64 && hex.chars().all(|c| c.is_ascii_hexdigit())).then(|| format!("0x{:0>64}", hex))
+}
+
+fn reject(reason: String, details: Value) -> Value {
+    warn!("❌ Relayed user signature rejected: {}", reason);
+    let mut body = error_codes::err_body(ErrorCode::InvalidSignature, reason);
+    body["details"] = details;
+    body
+}
+
+/// Verify the master wallet's EIP-712 signature on a relayed `approveAgent` before forwarding it.
+///
+/// Checks the network the action names, that the signature recovers under the
+/// `HyperliquidSignTransaction` domain for its `signatureChainId`, and that the signer is the
+/// session's user. Returns the recovered address, or the error body explaining what to fix, so
+/// clients get more than upstream's bare "invalid signature".
+pub async fn verify_relay(state: &AppState, api_key: &str, payload: &Value, is_mainnet: bool) -> Result<String, Value> {
+    let action = payload.get("action").cloned().unwrap_or_default();
+    let signature = payload.get("signature").cloned().unwrap_or_default();
+
+    let expected_chain = SignatureChain::resolve(is_mainnet).hyperliquid_chain;
+    let chain = action.get("hyperliquidChain").and_then(|c| c.as_str());
+    if chain != Some(expected_chain) {
+        return Err(reject(
+            format!("hyperliquidChain is {:?}, but this server relays to {}", chain, expected_chain),
+            serde_json::json!({"expected_hyperliquid_chain": expected_chain}),
+        ));
+    }
+    let chain_id = signature_chain_id(&action)
+        .map_err(|e| reject(e.to_string(), serde_json::json!({"field": "action.signatureChainId"})))?;
+
+    let (Some(r), Some(s), Some(v)) = (
+        signature.get("r").and_then(signature_component),
+        signature.get("s").and_then(signature_component),
+        signature.get("v").and_then(|v| v.as_u64()),
+    ) else {
+        return Err(reject("signature must have hex r, s and numeric v".to_string(), serde_json::json!({"field": "signature"})));
+    };
+    let digest = approve_agent_digest(&action)
+        .map_err(|e| reject(e.to_string(), serde_json::json!({"field": "action"})))?;
+    let signer = ExchangeSignature { r, s, v }
+        .recover_address(&digest)
+        .map_err(|e| reject(format!("Signature does not recover: {}", e), serde_json::json!({"digest": format!("{:?}", digest)})))?;
+
+    let domain = serde_json::json!({
+        "name": "HyperliquidSignTransaction",
+        "version": "1",
+        "chainId": chain_id,
+        "verifyingContract": "0x0000000000000000000000000000000000000000"
+    });
+    // The fixed development key has no session user to compare against
+    if let Some(user_address) = auth::user_address_for_api_key(state, api_key).await {
+        if !signer.eq_ignore_ascii_case(&user_address) {
+            return Err(reject(
+                format!("Signed by {}, but this session belongs to {}; sign with the session's wallet and check the EIP-712 domain and primary type", signer, user_address),
+                serde_json::json!({
+                    "recovered_address": signer,
+                    "expected_address": user_address,
+                    "domain": domain,
+                    "primary_type": "HyperliquidTransaction:ApproveAgent",
+                    "digest": format!("{:?}", digest)
+                }),
+            ));
+        }
+    }
+
+    info!("✅ Relayed approveAgent signed by {} (signatureChainId {})", signer, chain_id);
+    Ok(signer)
+}
@@ -0,0 +1,33 @@
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Json},
+};
+use serde_json::Value;
+
+use crate::AppState;
+
+const INDEX_HTML: &str = include_str!("../assets/verify/index.html");
+const VERIFY_JS: &str = include_str!("../assets/verify/verify.js");
+
+/// Scripts load only from this server and the page can't be framed
+const CSP: &str = "default-src 'none'; script-src 'self'; style-src 'unsafe-inline'; connect-src 'self'; frame-ancestors 'none'";
+
+/// GET /verify - Static page that parses a quote in the browser and checks its measurements
+/// and agent binding, for users without their own tooling
+pub async fn index() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/html; charset=utf-8"), (header::CONTENT_SECURITY_POLICY, CSP)],
+        INDEX_HTML,
+    )
+}
+
+/// GET /verify/verify.js
+pub async fn script() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "text/javascript; charset=utf-8")], VERIFY_JS)
+}
+
+/// GET /verify/expected - MRTD values the operator published for its builds
+pub async fn expected(State(state): State<AppState>) -> Json<Value> {
+    Json(serde_json::json!({"mrtd": state.config.expected_mrtds}))
+}
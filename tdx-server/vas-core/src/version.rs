@@ -0,0 +1,65 @@
+use alloy::primitives::eip191_hash_message;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Json,
+};
+use serde_json::Value;
+use tokio::sync::OnceCell;
+use tracing::{info, error};
+
+use crate::audit::AUDIT_STATEMENT;
+use crate::canonical_json;
+use crate::preset_tdx::PresetTDXData;
+use crate::AppState;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Set by build.rs
+pub const GIT_COMMIT: &str = env!("VAS_GIT_COMMIT");
+pub const BUILD_TIMESTAMP: &str = env!("VAS_BUILD_TIMESTAMP");
+
+/// The provenance statement never changes for a running process, so it is signed (and audited) once
+static SIGNED_VERSION: OnceCell<Value> = OnceCell::const_new();
+
+/// GET /version - Semver, commit and build time, signed by the attested agent key.
+///
+/// The signature is EIP-191 over `statement`, which also names the agent address and quote,
+/// so a client pinning either can tell when a different build starts answering.
+pub async fn version(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    let signed = SIGNED_VERSION.get_or_try_init(|| sign_version(&state)).await?;
+    Ok(Json(signed.clone()))
+}
+
+async fn sign_version(state: &AppState) -> Result<Value, StatusCode> {
+    let preset_data = PresetTDXData::get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let build_timestamp: u64 = BUILD_TIMESTAMP.parse().unwrap_or(0);
+    let statement = serde_json::json!({
+        "type": "version",
+        "version": VERSION,
+        "git_commit": GIT_COMMIT,
+        "build_timestamp": build_timestamp,
+        "agent_address": preset_data.agent_address,
+        "quote_id": preset_data.quote_id
+    });
+
+    let message = canonical_json::to_string(&statement);
+    let hash = eip191_hash_message(message.as_bytes());
+    let signature = state.signer
+        .sign_digest(hash, None, AUDIT_STATEMENT, statement)
+        .await
+        .map_err(|e| {
+            error!("❌ Failed to sign version statement: {:?}", e);
+            StatusCode::BAD_GATEWAY
+        })?;
+
+    info!("🏷️ Signed version statement for {} ({})", VERSION, GIT_COMMIT);
+
+    Ok(serde_json::json!({
+        "version": VERSION,
+        "git_commit": GIT_COMMIT,
+        "build_timestamp": build_timestamp,
+        "agent_address": preset_data.agent_address,
+        "statement": message,
+        "signature": signature.to_json()
+    }))
+}
@@ -0,0 +1,112 @@
+use alloy::primitives::eip191_hash_message;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Json,
+};
+use serde_json::Value;
+
+use crate::audit::AUDIT_WEBHOOK;
+use crate::preset_tdx::PresetTDXData;
+use crate::signer::SignerHandle;
+use crate::AppState;
+
+/// Quote id of the agent key that signed the callback
+pub const HEADER_KEY_ID: &str = "X-VAS-Key-Id";
+/// Unix ms at signing; receivers should reject stale callbacks
+pub const HEADER_TIMESTAMP: &str = "X-VAS-Timestamp";
+/// 65-byte `r || s || v` hex signature
+pub const HEADER_SIGNATURE: &str = "X-VAS-Signature";
+
+/// Callbacks older than this should be rejected by receivers
+const MAX_AGE_MS: u64 = 5 * 60 * 1000;
+
+/// Headers authenticating one webhook body
+#[derive(Debug)]
+pub struct SignedHeaders {
+    pub key_id: String,
+    pub timestamp_ms: u64,
+    pub signature: String,
+}
+
+/// Exact bytes covered by the signature: `vas-webhook:<timestamp_ms>:<body>`
+pub fn signing_message(timestamp_ms: u64, body: &str) -> String {
+    format!("vas-webhook:{}:{}", timestamp_ms, body)
+}
+
+/// Sign a serialized webhook body with the attested agent key (EIP-191)
+pub async fn sign_body(
+    signer: &SignerHandle,
+    user_address: Option<String>,
+    body: &str,
+) -> Result<SignedHeaders, Box<dyn std::error::Error + Send + Sync>> {
+    let preset_data = PresetTDXData::get().ok_or("Preset TDX data not initialized")?;
+    let timestamp_ms = now_ms();
+    let message = signing_message(timestamp_ms, body);
+
+    let subject = serde_json::json!({
+        "type": "webhook",
+        "key_id": preset_data.quote_id,
+        "timestamp_ms": timestamp_ms,
+        "body": body
+    });
+    let signature = signer
+        .sign_digest(eip191_hash_message(message.as_bytes()), user_address, AUDIT_WEBHOOK, subject)
+        .await?;
+
+    Ok(SignedHeaders {
+        key_id: preset_data.quote_id.clone(),
+        timestamp_ms,
+        signature: format!(
+            "0x{}{}{:02x}",
+            signature.r.trim_start_matches("0x"),
+            signature.s.trim_start_matches("0x"),
+            signature.v
+        ),
+    })
+}
+
+/// GET /webhooks/public-key - Keys that sign webhook callbacks, by key id.
+///
+/// The key id is the quote id of the enclave generation holding the key, so a callback
+/// whose `X-VAS-Key-Id` is unknown means the key rotated: refetch this endpoint and check
+/// the new key's quote before trusting it. Retired keys stay listed so callbacks signed
+/// shortly before a rotation still verify.
+pub async fn public_key(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    let preset_data = PresetTDXData::get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let archive = state.quote_archive.read().await;
+    let records = archive.records();
+
+    let keys: Vec<Value> = records.iter().enumerate()
+        .map(|(i, record)| serde_json::json!({
+            "key_id": record.quote_id,
+            "agent_address": record.agent_address,
+            "public_key": record.agent_public_key,
+            "active_from_ms": record.active_from_ms,
+            "retired_at_ms": records.get(i + 1).map(|next| next.active_from_ms),
+        }))
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "current_key_id": preset_data.quote_id,
+        "agent_address": preset_data.agent_address,
+        "keys": keys,
+        "scheme": {
+            "headers": {
+                "key_id": HEADER_KEY_ID,
+                "timestamp": HEADER_TIMESTAMP,
+                "signature": HEADER_SIGNATURE
+            },
+            "message": "vas-webhook:<X-VAS-Timestamp>:<raw request body>",
+            "signature": "EIP-191 personal_sign over message; recover and compare to the key's agent_address",
+            "max_age_ms": MAX_AGE_MS
+        }
+    })))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
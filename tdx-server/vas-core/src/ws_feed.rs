@@ -0,0 +1,119 @@
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn, error};
+
+/// Shared connection to the Hyperliquid WebSocket API.
+///
+/// Subscriptions are remembered and replayed on reconnect; every inbound
+/// message is fanned out to consumers through a broadcast channel.
+#[derive(Debug)]
+pub struct WsFeed {
+    subscriptions: Mutex<Vec<Value>>,
+    commands: mpsc::UnboundedSender<Value>,
+    messages: broadcast::Sender<Value>,
+}
+
+impl WsFeed {
+    /// Start the background connection task
+    pub fn spawn(ws_url: String) -> Arc<Self> {
+        let (commands, command_rx) = mpsc::unbounded_channel();
+        let (messages, _) = broadcast::channel(1024);
+
+        let feed = Arc::new(Self {
+            subscriptions: Mutex::new(Vec::new()),
+            commands,
+            messages,
+        });
+
+        tokio::spawn(run_connection(ws_url, feed.clone(), command_rx));
+        feed
+    }
+
+    /// Receive every message pushed by the upstream feed
+    pub fn listen(&self) -> broadcast::Receiver<Value> {
+        self.messages.subscribe()
+    }
+
+    /// Add a subscription (idempotent), e.g. `{"type": "userFills", "user": "0x..."}`
+    pub async fn subscribe(&self, subscription: Value) {
+        let mut subscriptions = self.subscriptions.lock().await;
+        if subscriptions.contains(&subscription) {
+            return;
+        }
+        subscriptions.push(subscription.clone());
+
+        // Delivered immediately if connected, otherwise replayed on the next connect
+        let _ = self.commands.send(subscription);
+    }
+}
+
+async fn run_connection(
+    ws_url: String,
+    feed: Arc<WsFeed>,
+    mut command_rx: mpsc::UnboundedReceiver<Value>,
+) {
+    let mut backoff = Duration::from_secs(1);
+
+    loop {
+        info!("🔌 Connecting to Hyperliquid WS feed: {}", ws_url);
+
+        match tokio_tungstenite::connect_async(ws_url.as_str()).await {
+            Ok((stream, _)) => {
+                info!("✅ WS feed connected");
+                backoff = Duration::from_secs(1);
+                let (mut write, mut read) = stream.split();
+
+                // Drop queued commands; the full subscription list is replayed below
+                while command_rx.try_recv().is_ok() {}
+                let subscriptions = feed.subscriptions.lock().await.clone();
+                for subscription in subscriptions {
+                    let msg = serde_json::json!({"method": "subscribe", "subscription": subscription});
+                    if write.send(Message::Text(msg.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+
+                loop {
+                    tokio::select! {
+                        incoming = read.next() => match incoming {
+                            Some(Ok(Message::Text(text))) => match serde_json::from_str::<Value>(&text) {
+                                Ok(value) => { let _ = feed.messages.send(value); }
+                                Err(e) => warn!("⚠️ Unparseable WS message: {}", e),
+                            },
+                            Some(Ok(Message::Ping(payload))) => { let _ = write.send(Message::Pong(payload)).await; }
+                            Some(Ok(_)) => {}
+                            Some(Err(e)) => { error!("❌ WS feed error: {}", e); break; }
+                            None => { warn!("⚠️ WS feed closed by upstream"); break; }
+                        },
+                        command = command_rx.recv() => match command {
+                            Some(subscription) => {
+                                let msg = serde_json::json!({"method": "subscribe", "subscription": subscription});
+                                if write.send(Message::Text(msg.to_string())).await.is_err() {
+                                    break;
+                                }
+                            }
+                            None => return,
+                        },
+                    }
+                }
+            }
+            Err(e) => error!("❌ WS feed connect failed: {}", e),
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(Duration::from_secs(60));
+    }
+}
+
+/// Derive the WS endpoint from the REST base URL (https://host -> wss://host/ws)
+pub fn ws_url_for(api_url: &str) -> String {
+    let host = api_url
+        .trim_end_matches('/')
+        .replacen("https://", "wss://", 1)
+        .replacen("http://", "ws://", 1);
+    format!("{}/ws", host)
+}
@@ -0,0 +1,216 @@
+//! Operator CLI for offline checks against the server's data files.
+//!
+//! ```text
+//! vas-ctl audit verify [--audit data/audit.jsonl] [--quotes data/quotes.jsonl]
+//! ```
+//!
+//! Signature chain overrides (`MAINNET_AGENT_SOURCE`, ...) are read from the environment, as the
+//! server reads them, so digests are recomputed with the parameters the entries were signed with.
+
+use alloy::dyn_abi::TypedData;
+use alloy::primitives::{eip191_hash_message, B256};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use vas_core::address;
+use vas_core::audit::{self, AuditCheckpoint, AuditEntry, AUDIT_ADMIN_ACTION, AUDIT_EXCHANGE_ACTION, AUDIT_POLICY, AUDIT_REPLAY, AUDIT_SET_REFERRER, AUDIT_STATEMENT, AUDIT_STRATEGY, AUDIT_TYPED_DATA, AUDIT_USER_ERASURE};
+use vas_core::canonical_json;
+use vas_core::config::Config;
+use vas_core::jsonl;
+use vas_core::quote_archive::QuoteRecord;
+use vas_core::universal_signing::{agent_signing_hash, create_generic_action_hash, signing_digest, ExchangeSignature, SignatureChain};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (mainnet, testnet) = match Config::from_env() {
+        Ok(config) => config.signature_chains(),
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    if let Err(e) = SignatureChain::configure(mainnet, testnet) {
+        eprintln!("{}", e);
+        return ExitCode::from(2);
+    }
+
+    match args.iter().map(|a| a.as_str()).collect::<Vec<_>>().as_slice() {
+        ["audit", "verify", rest @ ..] => {
+            let audit_path = flag(rest, "--audit").unwrap_or_else(|| "data/audit.jsonl".to_string());
+            let quotes_path = flag(rest, "--quotes").unwrap_or_else(|| "data/quotes.jsonl".to_string());
+            audit_verify(PathBuf::from(audit_path), PathBuf::from(quotes_path))
+        }
+        _ => {
+            eprintln!("usage: vas-ctl audit verify [--audit <audit.jsonl>] [--quotes <quotes.jsonl>]");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn flag(args: &[&str], name: &str) -> Option<String> {
+    args.iter()
+        .position(|a| *a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(|v| v.to_string())
+}
+
+/// Replay the audit log: check the hash chain, recompute every recorded digest,
+/// and recover each signature against the agent key archived for that time
+fn audit_verify(audit_path: PathBuf, quotes_path: PathBuf) -> ExitCode {
+    let entries: Vec<AuditEntry> = match jsonl::load(&audit_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("failed to read {}: {}", audit_path.display(), e);
+            return ExitCode::from(2);
+        }
+    };
+    let quotes: Vec<QuoteRecord> = match jsonl::load(&quotes_path) {
+        Ok(quotes) => quotes,
+        Err(e) => {
+            eprintln!("failed to read {}: {}", quotes_path.display(), e);
+            return ExitCode::from(2);
+        }
+    };
+
+    // Entries pruned by retention survive as Merkle-root checkpoints next to the log
+    let checkpoints: Vec<AuditCheckpoint> = match jsonl::load(&audit::checkpoints_path(&audit_path)) {
+        Ok(checkpoints) => checkpoints,
+        Err(e) => {
+            eprintln!("failed to read checkpoints for {}: {}", audit_path.display(), e);
+            return ExitCode::from(2);
+        }
+    };
+
+    println!("audit entries: {}", entries.len());
+    println!("audit checkpoints: {}", checkpoints.len());
+    println!("archived quotes: {}", quotes.len());
+
+    let mut mismatches = 0;
+
+    if let Err(e) = audit::verify_checkpoints(&checkpoints) {
+        println!("MISMATCH checkpoints: {}", e);
+        mismatches += 1;
+    }
+    if let Err(e) = audit::verify_entries_from(&entries, &audit::chain_anchor(&checkpoints)) {
+        println!("MISMATCH chain: {}", e);
+        mismatches += 1;
+    }
+
+    let mut signatures_checked = 0;
+    let mut redacted = 0;
+    let mut links_only = 0;
+    for entry in &entries {
+        // Deleted users' entries keep only hashes, the personal commitment and links, checked with the chain above
+        if entry.redacted {
+            redacted += 1;
+            if entry.personal_commitment.is_none() {
+                links_only += 1;
+            }
+            continue;
+        }
+        if let Err(e) = verify_subject_hash(entry) {
+            println!("MISMATCH entry {} ({}): {}", entry.seq, entry.kind, e);
+            mismatches += 1;
+            continue;
+        }
+
+        let Some(signature) = &entry.signature else { continue };
+        match verify_signature(entry, signature, &quotes) {
+            Ok(()) => signatures_checked += 1,
+            Err(e) => {
+                println!("MISMATCH entry {} ({}): {}", entry.seq, entry.kind, e);
+                mismatches += 1;
+            }
+        }
+    }
+
+    println!("signatures verified: {}", signatures_checked);
+    println!("redacted entries: {} ({} checked by links only)", redacted, links_only);
+    if mismatches > 0 {
+        println!("FAILED: {} mismatches", mismatches);
+        ExitCode::FAILURE
+    } else {
+        println!("OK");
+        ExitCode::SUCCESS
+    }
+}
+
+/// Recompute `subject_hash` from the recorded subject where the preimage is fully recorded
+fn verify_subject_hash(entry: &AuditEntry) -> Result<(), String> {
+    let recomputed = match entry.kind.as_str() {
+        AUDIT_EXCHANGE_ACTION | AUDIT_SET_REFERRER => {
+            let action = entry.subject.get("action").ok_or("subject missing action")?;
+            let nonce = entry.subject.get("nonce").and_then(|n| n.as_u64()).ok_or("subject missing nonce")?;
+            let vault_address = entry.subject.get("vaultAddress").and_then(|v| v.as_str());
+            create_generic_action_hash(action, nonce, vault_address).map_err(|e| e.to_string())?
+        }
+        // Replays go through the digest path, so the recorded hash is the phantom-agent digest
+        AUDIT_REPLAY => {
+            let action = entry.subject.get("action").ok_or("subject missing action")?;
+            let nonce = entry.subject.get("nonce").and_then(|n| n.as_u64()).ok_or("subject missing nonce")?;
+            let vault_address = entry.subject.get("vaultAddress").and_then(|v| v.as_str());
+            let is_mainnet = entry.subject.get("isMainnet").and_then(|m| m.as_bool()).ok_or("subject missing isMainnet")?;
+            let action_hash = create_generic_action_hash(action, nonce, vault_address).map_err(|e| e.to_string())?;
+            agent_signing_hash(action_hash, is_mainnet)
+        }
+        AUDIT_TYPED_DATA => {
+            let typed_data: TypedData = serde_json::from_value(entry.subject.clone()).map_err(|e| e.to_string())?;
+            typed_data.eip712_signing_hash().map_err(|e| e.to_string())?
+        }
+        AUDIT_STATEMENT => eip191_hash_message(subject_json(entry)?),
+        AUDIT_POLICY | AUDIT_STRATEGY | AUDIT_ADMIN_ACTION | AUDIT_USER_ERASURE => B256::from_slice(&Sha256::digest(subject_json(entry)?)),
+        // EVM transactions record only the call, not the full fee fields; trust subject_hash
+        _ => return Ok(()),
+    };
+
+    if format!("{:?}", recomputed) != entry.subject_hash {
+        return Err(format!("subject hash {} != recomputed {:?}", entry.subject_hash, recomputed));
+    }
+    Ok(())
+}
+
+/// The subject serialized as it was when hashed: canonical JSON from `HASH_FORMAT_CANONICAL` on
+fn subject_json(entry: &AuditEntry) -> Result<Vec<u8>, String> {
+    if entry.hash_format == audit::HASH_FORMAT_LEGACY {
+        serde_json::to_vec(&entry.subject).map_err(|e| e.to_string())
+    } else {
+        Ok(canonical_json::to_vec(&entry.subject))
+    }
+}
+
+/// Recover the signer of the entry's digest and compare it to the archived agent key
+fn verify_signature(entry: &AuditEntry, signature: &Value, quotes: &[QuoteRecord]) -> Result<(), String> {
+    let subject_hash: B256 = entry.subject_hash.parse().map_err(|_| "unparseable subject hash")?;
+    let digest = match entry.kind.as_str() {
+        // L1 actions are signed through the phantom agent wrapper, user-signed ones as typed data
+        AUDIT_EXCHANGE_ACTION | AUDIT_SET_REFERRER => {
+            let action = entry.subject.get("action").ok_or("subject missing action")?;
+            let nonce = entry.subject.get("nonce").and_then(|n| n.as_u64()).ok_or("subject missing nonce")?;
+            let vault_address = entry.subject.get("vaultAddress").and_then(|v| v.as_str());
+            let is_mainnet = entry.subject.get("isMainnet").and_then(|m| m.as_bool()).ok_or("subject missing isMainnet")?;
+            signing_digest(action, nonce, vault_address, is_mainnet).map_err(|e| e.to_string())?
+        }
+        _ => subject_hash,
+    };
+
+    let signature = ExchangeSignature {
+        r: signature.get("r").and_then(|r| r.as_str()).ok_or("signature missing r")?.to_string(),
+        s: signature.get("s").and_then(|s| s.as_str()).ok_or("signature missing s")?.to_string(),
+        v: signature.get("v").and_then(|v| v.as_u64()).ok_or("signature missing v")?,
+    };
+    let recovered = signature.recover_address(&digest).map_err(|e| e.to_string())?;
+
+    let quote = quotes.iter().rev()
+        .find(|q| q.active_from_ms <= entry.timestamp_ms)
+        .ok_or("no archived quote active at entry time")?;
+    let public_key = secp256k1::PublicKey::from_slice(&hex::decode(&quote.agent_public_key).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    let archived_address = address::public_key_to_address(&public_key);
+
+    if recovered != archived_address.to_lowercase() {
+        return Err(format!("signed by {} but quote {} binds {}", recovered, quote.quote_id, archived_address));
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+use tracing::info;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use vas_core::config::Config;
+use vas_core::listener::{self, ListenerConfig};
+use vas_core::log_level::{self, LogFilter};
+use vas_core::preset_tdx::PresetTDXData;
+use vas_core::{sealed_config, AppState};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Load environment variables
+    dotenvy::dotenv().ok();
+
+    // Initialize tracing; the filter can be changed at runtime via /admin/log-level
+    let (filter, filter_handle) = tracing_subscriber::reload::Layer::new(
+        tracing_subscriber::EnvFilter::new(log_level::DEFAULT_FILTER),
+    );
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+    let log_filter = Arc::new(LogFilter::new(filter_handle));
+
+    println!("🚀 Starting TDX Agent Server...");
+    info!("Starting TDX Agent Server");
+
+    // Initialize preset TDX data
+    PresetTDXData::initialize()?;
+    info!("✅ Preset TDX data initialized");
+
+    // Decrypt enc: values before the configuration reads them
+    sealed_config::decrypt_env().await.map_err(|e| e.to_string())?;
+
+    // Load configuration
+    let config = Arc::new(Config::from_env()?);
+    let listeners = config.listeners.iter()
+        .map(|spec| ListenerConfig::parse(spec))
+        .collect::<Result<Vec<_>, _>>()?;
+    if listeners.is_empty() {
+        return Err("LISTENERS must name at least one address".into());
+    }
+
+    let state = AppState::from_config(config, log_filter).await?;
+    state.spawn_workers()?;
+
+    listener::serve_all(vas_core::router(state), listeners).await?;
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+"""
+Latency benchmark: /exchange vs /exchange/raw.
+
+Both paths sign and submit the same action; the raw path skips response reshaping
+(envelope hashing, fee estimates, event store copy) and verbose logging. The action is a
+cancel of an order id that does not exist, so Hyperliquid rejects it and nothing trades.
+
+Run against a live server:
+    pytest test_exchange_raw_latency.py -m slow -s
+"""
+
+import statistics
+import time
+
+import pytest
+
+ROUNDS = 50
+MISSING_OID = 1
+
+
+def cancel_payload():
+    return {
+        "action": {"type": "cancel", "cancels": [{"a": 0, "o": MISSING_OID}]},
+        "nonce": int(time.time() * 1000),
+    }
+
+
+def measure(client, endpoint):
+    """Client-side round trips in milliseconds"""
+    samples = []
+    for _ in range(ROUNDS):
+        started = time.perf_counter()
+        response = client.post(endpoint, cancel_payload())
+        samples.append((time.perf_counter() - started) * 1000)
+        assert response.status_code == 200, f"{endpoint}: {response.status_code} {response.text}"
+        # Stay clear of the per-key rate limit and of nonce reuse
+        time.sleep(0.05)
+    return samples
+
+
+def percentile(samples, q):
+    ordered = sorted(samples)
+    return ordered[min(len(ordered) - 1, int(len(ordered) * q))]
+
+
+class TestRawExchangeLatency:
+    """Compare round trips of the full and raw exchange paths."""
+
+    @pytest.mark.integration
+    @pytest.mark.slow
+    def test_raw_path_is_not_slower(self, tdx_server_client):
+        if not tdx_server_client.health_check():
+            pytest.skip("TDX server is not running or not healthy")
+
+        # Warm up connections and caches on both paths
+        for endpoint in ("/exchange", "/exchange/raw"):
+            tdx_server_client.post(endpoint, cancel_payload())
+
+        # Interleave so upstream latency drift affects both paths alike
+        full, raw = [], []
+        for _ in range(2):
+            full += measure(tdx_server_client, "/exchange")
+            raw += measure(tdx_server_client, "/exchange/raw")
+
+        rows = [
+            ("p50", statistics.median(full), statistics.median(raw)),
+            ("p95", percentile(full, 0.95), percentile(raw, 0.95)),
+            ("mean", statistics.mean(full), statistics.mean(raw)),
+        ]
+        print(f"\n{'':6}{'/exchange':>12}{'/exchange/raw':>16}{'saved':>10}")
+        for name, full_ms, raw_ms in rows:
+            print(f"{name:6}{full_ms:10.2f}ms{raw_ms:14.2f}ms{full_ms - raw_ms:8.2f}ms")
+
+        # Round trips are dominated by Hyperliquid; only guard against the raw path regressing
+        assert statistics.median(raw) <= statistics.median(full) * 1.1
+
+    @pytest.mark.integration
+    def test_raw_response_is_upstream_body(self, tdx_server_client):
+        if not tdx_server_client.health_check():
+            pytest.skip("TDX server is not running or not healthy")
+
+        body = tdx_server_client.post("/exchange/raw", cancel_payload()).json()
+        for reshaped in ("action_hash", "attestation", "warnings", "estimated_fees", "code"):
+            assert reshaped not in body, f"raw response carries '{reshaped}': {body}"